@@ -18,6 +18,7 @@ use move_core_types::{
     vm_status::StatusCode,
 };
 use move_vm_runtime::{
+    native_extensions::RestorableExtension,
     native_functions,
     native_functions::{NativeContext, NativeFunction, NativeFunctionTable},
 };
@@ -210,7 +211,31 @@ impl<'a> NativeTableContext<'a> {
     }
 }
 
+/// Tables are restored when a session rolls back to a savepoint, if the context was added with
+/// `NativeContextExtensions::add_restorable`.
+impl<'a> RestorableExtension<'a> for NativeTableContext<'a> {
+    fn copy_state(&self) -> PartialVMResult<Self> {
+        Ok(Self {
+            resolver: self.resolver,
+            txn_hash: self.txn_hash,
+            table_data: RefCell::new(self.table_data.borrow().copy()?),
+        })
+    }
+}
+
 impl TableData {
+    fn copy(&self) -> PartialVMResult<Self> {
+        let mut tables = BTreeMap::new();
+        for (handle, table) in &self.tables {
+            tables.insert(*handle, table.copy()?);
+        }
+        Ok(Self {
+            new_tables: self.new_tables.clone(),
+            removed_tables: self.removed_tables.clone(),
+            tables,
+        })
+    }
+
     /// Gets or creates a new table in the TableData. This initializes information about
     /// the table, like the type layout for keys and values.
     fn get_or_create_table(
@@ -238,6 +263,19 @@ impl TableData {
 }
 
 impl Table {
+    fn copy(&self) -> PartialVMResult<Self> {
+        let mut content = BTreeMap::new();
+        for (key, gv) in &self.content {
+            content.insert(key.clone(), gv.copy_value()?);
+        }
+        Ok(Self {
+            handle: self.handle,
+            key_layout: self.key_layout.clone(),
+            value_layout: self.value_layout.clone(),
+            content,
+        })
+    }
+
     fn get_or_create_global_value(
         &mut self,
         context: &NativeTableContext,
@@ -2,15 +2,15 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use std::collections::BTreeSet;
+use std::{collections::BTreeSet, fmt};
 
 use crate::{
     errors::{PartialVMError, PartialVMResult},
-    file_format::{AbilitySet, StructTypeParameter, Visibility},
+    file_format::{Ability, AbilitySet, StructTypeParameter, Visibility},
     file_format_common::VERSION_5,
-    normalized::Module,
+    normalized::{Function, Module, Struct, Type},
 };
-use move_core_types::vm_status::StatusCode;
+use move_core_types::{identifier::Identifier, language_storage::ModuleId, vm_status::StatusCode};
 
 /// The result of a linking and layout compatibility check. Here is what the different combinations. NOTE that if `check_struct_layout` is false, type safety over a series of upgrades cannot be guaranteed.
 /// mean:
@@ -72,36 +72,100 @@ impl Compatibility {
 
     /// Check compatibility for `new_module` relative to old module `old_module`.
     pub fn check(&self, old_module: &Module, new_module: &Module) -> PartialVMResult<()> {
-        let mut struct_and_pub_function_linking = true;
-        let mut struct_layout = true;
-        let mut friend_linking = true;
+        for incompatibility in Self::incompatibilities(old_module, new_module) {
+            let checked = match incompatibility.check {
+                CompatibilityCheck::Linking
+                | CompatibilityCheck::Abilities
+                | CompatibilityCheck::TypeParameterConstraints => {
+                    self.check_struct_and_pub_function_linking
+                }
+                CompatibilityCheck::StructLayout => self.check_struct_layout,
+                CompatibilityCheck::FriendLinking => self.check_friend_linking,
+            };
+            if checked {
+                return Err(PartialVMError::new(
+                    StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns every incompatibility of `new_module` relative to old module `old_module`, for
+    /// all the checks. Each check is performed independently of the others, so the same change
+    /// may fail several checks.
+    pub fn incompatibilities(old_module: &Module, new_module: &Module) -> Vec<Incompatibility> {
+        let mut incompatibilities = vec![];
+        let mut report = |check, item, reason: &str, old, new| {
+            incompatibilities.push(Incompatibility {
+                check,
+                item,
+                reason: reason.to_string(),
+                old,
+                new,
+            })
+        };
 
         // module's name and address are unchanged
         if old_module.address != new_module.address || old_module.name != new_module.name {
-            struct_and_pub_function_linking = false;
+            report(
+                CompatibilityCheck::Linking,
+                UpgradeItem::Module,
+                "module address or name changed",
+                Some(module_id_to_string(&old_module.module_id())),
+                Some(module_id_to_string(&new_module.module_id())),
+            );
         }
 
         // old module's structs are a subset of the new module's structs
         for (name, old_struct) in &old_module.structs {
+            let item = || UpgradeItem::Struct(name.clone());
+            let old_decl = || Some(struct_declaration(name, old_struct));
             let new_struct = match new_module.structs.get(name) {
                 Some(new_struct) => new_struct,
                 None => {
                     // Struct not present in new . Existing modules that depend on this struct will fail to link with the new version of the module.
                     // Also, struct layout cannot be guaranteed transitively, because after
                     // removing the struct, it could be re-added later with a different layout.
-                    struct_and_pub_function_linking = false;
-                    struct_layout = false;
-                    break;
+                    report(
+                        CompatibilityCheck::Linking,
+                        item(),
+                        "struct removed",
+                        old_decl(),
+                        None,
+                    );
+                    report(
+                        CompatibilityCheck::StructLayout,
+                        item(),
+                        "struct removed",
+                        old_decl(),
+                        None,
+                    );
+                    continue;
                 }
             };
+            let new_decl = || Some(struct_declaration(name, new_struct));
 
-            if !struct_abilities_compatibile(old_struct.abilities, new_struct.abilities)
-                || !struct_type_parameters_compatibile(
-                    &old_struct.type_parameters,
-                    &new_struct.type_parameters,
-                )
-            {
-                struct_and_pub_function_linking = false;
+            if !struct_abilities_compatibile(old_struct.abilities, new_struct.abilities) {
+                report(
+                    CompatibilityCheck::Abilities,
+                    item(),
+                    "abilities removed",
+                    old_decl(),
+                    new_decl(),
+                );
+            }
+            if !struct_type_parameters_compatibile(
+                &old_struct.type_parameters,
+                &new_struct.type_parameters,
+            ) {
+                report(
+                    CompatibilityCheck::TypeParameterConstraints,
+                    item(),
+                    "type parameters changed incompatibly",
+                    old_decl(),
+                    new_decl(),
+                );
             }
             if new_struct.fields != old_struct.fields {
                 // Fields changed. Code in this module will fail at runtime if it tries to
@@ -110,7 +174,13 @@ impl Compatibility {
                 // choose that changing the name (but not position or type) of a field is
                 // compatible. The VM does not care about the name of a field
                 // (it's purely informational), but clients presumably do.
-                struct_layout = false
+                report(
+                    CompatibilityCheck::StructLayout,
+                    item(),
+                    "fields changed",
+                    old_decl(),
+                    new_decl(),
+                );
             }
         }
 
@@ -129,17 +199,29 @@ impl Compatibility {
         // friend list. But for simplicity, we decided to go to the more restrictive form now and
         // we may revisit this in the future.
         for (name, old_func) in &old_module.exposed_functions {
+            let item = || UpgradeItem::Function(name.clone());
+            let old_decl = || Some(function_declaration(name, old_func));
+            // Changes to a friend function only break the linking of friend modules
+            let is_friend = matches!(old_func.visibility, Visibility::Friend);
+            let linking = if is_friend {
+                CompatibilityCheck::FriendLinking
+            } else {
+                CompatibilityCheck::Linking
+            };
             let new_func = match new_module.exposed_functions.get(name) {
                 Some(new_func) => new_func,
                 None => {
-                    if matches!(old_func.visibility, Visibility::Friend) {
-                        friend_linking = false;
-                    } else {
-                        struct_and_pub_function_linking = false;
-                    }
+                    report(
+                        linking,
+                        item(),
+                        "function removed or made private",
+                        old_decl(),
+                        None,
+                    );
                     continue;
                 }
             };
+            let new_decl = || Some(function_declaration(name, new_func));
             let is_vis_compatible = match (old_func.visibility, new_func.visibility) {
                 // public must remain public
                 (Visibility::Public, Visibility::Public) => true,
@@ -162,20 +244,48 @@ impl Compatibility {
                 // If it was not an entry function, it is allowed to become one.
                 !old_func.is_entry || new_func.is_entry
             };
-            if !is_vis_compatible
-                || !is_entry_compatible
-                || old_func.parameters != new_func.parameters
-                || old_func.return_ != new_func.return_
-                || !fun_type_parameters_compatibile(
-                    &old_func.type_parameters,
-                    &new_func.type_parameters,
-                )
-            {
-                if matches!(old_func.visibility, Visibility::Friend) {
-                    friend_linking = false;
-                } else {
-                    struct_and_pub_function_linking = false;
-                }
+            if !is_vis_compatible {
+                report(
+                    linking,
+                    item(),
+                    "visibility reduced",
+                    old_decl(),
+                    new_decl(),
+                );
+            }
+            if !is_entry_compatible {
+                report(
+                    linking,
+                    item(),
+                    "entry declaration changed",
+                    old_decl(),
+                    new_decl(),
+                );
+            }
+            if old_func.parameters != new_func.parameters || old_func.return_ != new_func.return_ {
+                report(
+                    linking,
+                    item(),
+                    "parameter or return types changed",
+                    old_decl(),
+                    new_decl(),
+                );
+            }
+            if !fun_type_parameters_compatibile(
+                &old_func.type_parameters,
+                &new_func.type_parameters,
+            ) {
+                report(
+                    if is_friend {
+                        CompatibilityCheck::FriendLinking
+                    } else {
+                        CompatibilityCheck::TypeParameterConstraints
+                    },
+                    item(),
+                    "type parameters changed incompatibly",
+                    old_decl(),
+                    new_decl(),
+                );
             }
         }
 
@@ -184,30 +294,162 @@ impl Compatibility {
         // - additions to the list are allowed
         // - removals are not allowed
         //
-        let old_friend_module_ids: BTreeSet<_> = old_module.friends.iter().cloned().collect();
         let new_friend_module_ids: BTreeSet<_> = new_module.friends.iter().cloned().collect();
-        if !old_friend_module_ids.is_subset(&new_friend_module_ids) {
-            friend_linking = false;
+        for friend in &old_module.friends {
+            if !new_friend_module_ids.contains(friend) {
+                report(
+                    CompatibilityCheck::FriendLinking,
+                    UpgradeItem::Friend(friend.clone()),
+                    "friend declaration removed",
+                    Some(format!("friend {}", module_id_to_string(friend))),
+                    None,
+                );
+            }
         }
 
-        if self.check_struct_and_pub_function_linking && !struct_and_pub_function_linking {
-            return Err(PartialVMError::new(
-                StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE,
-            ));
-        }
-        if self.check_struct_layout && !struct_layout {
-            return Err(PartialVMError::new(
-                StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE,
-            ));
-        }
-        if self.check_friend_linking && !friend_linking {
-            return Err(PartialVMError::new(
-                StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE,
-            ));
+        incompatibilities
+    }
+}
+
+/// The checks performed when upgrading a module
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CompatibilityCheck {
+    /// Modules that use the structs and the public and entry functions of the module still link
+    Linking,
+    /// Struct values published under the old module can be read by the new one
+    StructLayout,
+    /// Friend modules still link against the friend functions of the module
+    FriendLinking,
+    /// Structs keep all their abilities
+    Abilities,
+    /// The type parameters of structs and of public and entry functions keep compatible
+    /// constraints and phantom declarations
+    TypeParameterConstraints,
+}
+
+/// The part of a module an incompatibility is about
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum UpgradeItem {
+    Module,
+    Struct(Identifier),
+    Function(Identifier),
+    Friend(ModuleId),
+}
+
+/// A change to a module which fails one of the compatibility checks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Incompatibility {
+    pub check: CompatibilityCheck,
+    pub item: UpgradeItem,
+    /// What changed about `item`
+    pub reason: String,
+    /// The declaration of `item` in the old module
+    pub old: Option<String>,
+    /// The declaration of `item` in the new module, if it still exists
+    pub new: Option<String>,
+}
+
+impl fmt::Display for CompatibilityCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Linking => "linking",
+            Self::StructLayout => "struct layout",
+            Self::FriendLinking => "friend linking",
+            Self::Abilities => "abilities",
+            Self::TypeParameterConstraints => "type parameter constraints",
+        };
+        f.write_str(name)
+    }
+}
+
+impl fmt::Display for UpgradeItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Module => write!(f, "module"),
+            Self::Struct(name) => write!(f, "struct `{}`", name),
+            Self::Function(name) => write!(f, "function `{}`", name),
+            Self::Friend(id) => write!(f, "friend `{}`", module_id_to_string(id)),
         }
+    }
+}
 
-        Ok(())
+fn module_id_to_string(id: &ModuleId) -> String {
+    format!("0x{}::{}", id.address().short_str_lossless(), id.name())
+}
+
+fn abilities_to_string(abilities: AbilitySet) -> String {
+    abilities
+        .into_iter()
+        .map(|ability| match ability {
+            Ability::Copy => "copy",
+            Ability::Drop => "drop",
+            Ability::Store => "store",
+            Ability::Key => "key",
+        })
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+fn type_parameter_to_string(idx: usize, constraints: AbilitySet, is_phantom: bool) -> String {
+    let mut s = format!("{}T{}", if is_phantom { "phantom " } else { "" }, idx);
+    if constraints != AbilitySet::EMPTY {
+        s.push_str(": ");
+        s.push_str(&abilities_to_string(constraints));
+    }
+    s
+}
+
+fn struct_declaration(name: &Identifier, struct_: &Struct) -> String {
+    let mut s = format!("struct {}", name);
+    if !struct_.type_parameters.is_empty() {
+        let type_parameters: Vec<_> = struct_
+            .type_parameters
+            .iter()
+            .enumerate()
+            .map(|(idx, tp)| type_parameter_to_string(idx, tp.constraints, tp.is_phantom))
+            .collect();
+        s.push_str(&format!("<{}>", type_parameters.join(", ")));
+    }
+    if struct_.abilities != AbilitySet::EMPTY {
+        s.push_str(&format!(
+            " has {}",
+            abilities_to_string(struct_.abilities).replace(" + ", ", ")
+        ));
+    }
+    let fields: Vec<_> = struct_
+        .fields
+        .iter()
+        .map(|field| format!("{}: {}", field.name, field.type_))
+        .collect();
+    s.push_str(&format!(" {{ {} }}", fields.join(", ")));
+    s
+}
+
+fn function_declaration(name: &Identifier, function: &Function) -> String {
+    let visibility = match function.visibility {
+        Visibility::Public => "public ",
+        Visibility::Friend => "public(friend) ",
+        Visibility::Private => "",
+    };
+    let entry = if function.is_entry { "entry " } else { "" };
+    let mut s = format!("{}{}fun {}", visibility, entry, name);
+    if !function.type_parameters.is_empty() {
+        let type_parameters: Vec<_> = function
+            .type_parameters
+            .iter()
+            .enumerate()
+            .map(|(idx, constraints)| type_parameter_to_string(idx, *constraints, false))
+            .collect();
+        s.push_str(&format!("<{}>", type_parameters.join(", ")));
+    }
+    let types = |types: &[Type]| types.iter().map(Type::to_string).collect::<Vec<_>>();
+    s.push_str(&format!("({})", types(&function.parameters).join(", ")));
+    match function.return_.as_slice() {
+        [] => (),
+        [ty] => s.push_str(&format!(": {}", ty)),
+        tys => s.push_str(&format!(": ({})", types(tys).join(", "))),
     }
+    s
 }
 
 // When upgrading, the new abilities must be a superset of the old abilities.
@@ -3,7 +3,11 @@
 
 use std::convert::TryFrom;
 
-use crate::{compatibility::Compatibility, file_format::*, normalized};
+use crate::{
+    compatibility::{Compatibility, CompatibilityCheck},
+    file_format::*,
+    normalized,
+};
 use move_core_types::{account_address::AccountAddress, identifier::Identifier};
 
 fn mk_module(vis: u8) -> normalized::Module {
@@ -115,3 +119,172 @@ fn deprecated_add_script_visibility() {
         .check(&friend_module, &script_module)
         .is_err());
 }
+
+fn mk_normalized_module(
+    structs: Vec<(&str, normalized::Struct)>,
+    functions: Vec<(&str, normalized::Function)>,
+) -> normalized::Module {
+    normalized::Module {
+        file_format_version: crate::file_format_common::VERSION_6,
+        address: AccountAddress::ONE,
+        name: Identifier::new("M").unwrap(),
+        friends: vec![],
+        structs: structs
+            .into_iter()
+            .map(|(name, s)| (Identifier::new(name).unwrap(), s))
+            .collect(),
+        exposed_functions: functions
+            .into_iter()
+            .map(|(name, f)| (Identifier::new(name).unwrap(), f))
+            .collect(),
+    }
+}
+
+fn mk_struct(abilities: AbilitySet, fields: Vec<(&str, normalized::Type)>) -> normalized::Struct {
+    normalized::Struct {
+        abilities,
+        type_parameters: vec![StructTypeParameter {
+            constraints: AbilitySet::singleton(Ability::Store),
+            is_phantom: true,
+        }],
+        fields: fields
+            .into_iter()
+            .map(|(name, type_)| normalized::Field {
+                name: Identifier::new(name).unwrap(),
+                type_,
+            })
+            .collect(),
+    }
+}
+
+fn mk_function(visibility: Visibility, parameters: Vec<normalized::Type>) -> normalized::Function {
+    normalized::Function {
+        visibility,
+        is_entry: false,
+        type_parameters: vec![AbilitySet::singleton(Ability::Copy)],
+        parameters,
+        return_: vec![normalized::Type::U64, normalized::Type::Bool],
+    }
+}
+
+#[test]
+fn incompatibilities_of_compatible_upgrade() {
+    let old = mk_normalized_module(
+        vec![("S", mk_struct(AbilitySet::EMPTY, vec![]))],
+        vec![("f", mk_function(Visibility::Friend, vec![]))],
+    );
+    // adding abilities, structs and functions, and making a friend function public is fine
+    let new = mk_normalized_module(
+        vec![
+            ("S", mk_struct(AbilitySet::PRIMITIVES, vec![])),
+            ("T", mk_struct(AbilitySet::EMPTY, vec![])),
+        ],
+        vec![
+            ("f", mk_function(Visibility::Public, vec![])),
+            ("g", mk_function(Visibility::Public, vec![])),
+        ],
+    );
+    assert!(Compatibility::incompatibilities(&old, &new).is_empty());
+    assert!(Compatibility::full_check().check(&old, &new).is_ok());
+}
+
+#[test]
+fn incompatibilities_are_reported_per_check() {
+    let old = mk_normalized_module(
+        vec![
+            (
+                "S",
+                mk_struct(AbilitySet::PRIMITIVES, vec![("x", normalized::Type::U64)]),
+            ),
+            ("R", mk_struct(AbilitySet::EMPTY, vec![])),
+        ],
+        vec![
+            ("f", mk_function(Visibility::Friend, vec![])),
+            ("g", mk_function(Visibility::Public, vec![])),
+        ],
+    );
+    let new = mk_normalized_module(
+        vec![(
+            "S",
+            mk_struct(AbilitySet::EMPTY, vec![("x", normalized::Type::U128)]),
+        )],
+        vec![
+            (
+                "f",
+                mk_function(Visibility::Friend, vec![normalized::Type::U8]),
+            ),
+            ("g", mk_function(Visibility::Public, vec![])),
+        ],
+    );
+    let incompatibilities: Vec<_> = Compatibility::incompatibilities(&old, &new)
+        .into_iter()
+        .map(|i| (i.check, i.item.to_string()))
+        .collect();
+    assert_eq!(
+        incompatibilities,
+        vec![
+            (CompatibilityCheck::Linking, "struct `R`".to_string()),
+            (CompatibilityCheck::StructLayout, "struct `R`".to_string()),
+            (CompatibilityCheck::Abilities, "struct `S`".to_string()),
+            (CompatibilityCheck::StructLayout, "struct `S`".to_string()),
+            (
+                CompatibilityCheck::FriendLinking,
+                "function `f`".to_string()
+            ),
+        ]
+    );
+
+    assert!(Compatibility::full_check().check(&old, &new).is_err());
+    assert!(Compatibility::new(true, false, false)
+        .check(&old, &new)
+        .is_err());
+    assert!(Compatibility::new(false, true, false)
+        .check(&old, &new)
+        .is_err());
+    assert!(Compatibility::new(false, false, true)
+        .check(&old, &new)
+        .is_err());
+    assert!(Compatibility::no_check().check(&old, &new).is_ok());
+}
+
+#[test]
+fn incompatibilities_show_declarations() {
+    let old = mk_normalized_module(
+        vec![(
+            "S",
+            mk_struct(
+                AbilitySet::singleton(Ability::Key),
+                vec![(
+                    "x",
+                    normalized::Type::Vector(Box::new(normalized::Type::U8)),
+                )],
+            ),
+        )],
+        vec![("f", mk_function(Visibility::Public, vec![]))],
+    );
+    let new = mk_normalized_module(
+        vec![],
+        vec![(
+            "f",
+            mk_function(Visibility::Public, vec![normalized::Type::Address]),
+        )],
+    );
+    let incompatibilities = Compatibility::incompatibilities(&old, &new);
+    assert_eq!(incompatibilities.len(), 3);
+    assert_eq!(
+        incompatibilities[0].old.as_deref(),
+        Some("struct S<phantom T0: store> has key { x: vector<u8> }")
+    );
+    assert_eq!(incompatibilities[0].new, None);
+    let function = &incompatibilities[2];
+    assert_eq!(function.check, CompatibilityCheck::Linking);
+    assert_eq!(function.reason, "parameter or return types changed");
+    assert_eq!(
+        function.old.as_deref(),
+        Some("public fun f<T0: copy>(): (u64, bool)")
+    );
+    assert_eq!(
+        function.new.as_deref(),
+        Some("public fun f<T0: copy>(address): (u64, bool)")
+    );
+}
@@ -0,0 +1,198 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    compatibility::Compatibility,
+    file_format::*,
+    upgrade_policy::{check_upgrade_policy, upgrade_policy},
+};
+use move_core_types::{identifier::Identifier, metadata::UpgradePolicy, vm_status::StatusCode};
+
+fn mk_module(policy: Option<UpgradePolicy>) -> CompiledModule {
+    let mut m = basic_test_module();
+    m.metadata = policy.into_iter().map(UpgradePolicy::to_metadata).collect();
+    m
+}
+
+/// Adds a private function `name` returning immediately.
+fn add_function(m: &mut CompiledModule, name: &str) {
+    m.function_handles.push(FunctionHandle {
+        module: ModuleHandleIndex(0),
+        name: IdentifierIndex(m.identifiers.len() as u16),
+        parameters: SignatureIndex(0),
+        return_: SignatureIndex(0),
+        type_parameters: vec![],
+    });
+    m.identifiers.push(Identifier::new(name).unwrap());
+    m.function_defs.push(FunctionDefinition {
+        function: FunctionHandleIndex(m.function_handles.len() as u16 - 1),
+        visibility: Visibility::Private,
+        is_entry: false,
+        acquires_global_resources: vec![],
+        code: Some(CodeUnit {
+            locals: SignatureIndex(0),
+            code: vec![Bytecode::Ret],
+        }),
+    });
+}
+
+fn set_body(m: &mut CompiledModule, code: Vec<Bytecode>) {
+    m.function_defs[0].code.as_mut().unwrap().code = code;
+}
+
+fn check(old: &CompiledModule, new: &CompiledModule) -> Result<(), StatusCode> {
+    check_upgrade_policy(Compatibility::full_check(), old, new).map_err(|e| e.major_status())
+}
+
+#[test]
+fn modules_without_policy_are_compatible() {
+    let old = mk_module(None);
+    assert_eq!(upgrade_policy(&old).unwrap(), UpgradePolicy::Compatible);
+
+    let mut new = mk_module(None);
+    set_body(&mut new, vec![Bytecode::Nop, Bytecode::Ret]);
+    add_function(&mut new, "bar");
+    assert_eq!(check(&old, &new), Ok(()));
+
+    let mut new = mk_module(None);
+    new.function_defs.clear();
+    new.function_handles.clear();
+    assert_eq!(check(&old, &new), Ok(()));
+}
+
+#[test]
+fn policy_cannot_be_relaxed() {
+    let old = mk_module(Some(UpgradePolicy::AdditiveOnly));
+    assert_eq!(
+        check(&old, &mk_module(None)),
+        Err(StatusCode::UPGRADE_POLICY_VIOLATION)
+    );
+    assert_eq!(
+        check(&old, &mk_module(Some(UpgradePolicy::Compatible))),
+        Err(StatusCode::UPGRADE_POLICY_VIOLATION)
+    );
+    assert_eq!(
+        check(&old, &mk_module(Some(UpgradePolicy::DependencyOnly))),
+        Ok(())
+    );
+}
+
+#[test]
+fn additive_only_allows_new_functions() {
+    let old = mk_module(Some(UpgradePolicy::AdditiveOnly));
+    let mut new = old.clone();
+    add_function(&mut new, "bar");
+    assert_eq!(check(&old, &new), Ok(()));
+}
+
+#[test]
+fn additive_only_rejects_changed_bodies() {
+    let old = mk_module(Some(UpgradePolicy::AdditiveOnly));
+    let mut new = old.clone();
+    set_body(&mut new, vec![Bytecode::Nop, Bytecode::Ret]);
+    assert_eq!(check(&old, &new), Err(StatusCode::UPGRADE_POLICY_VIOLATION));
+}
+
+#[test]
+fn additive_only_ignores_pool_layout() {
+    let mut old = mk_module(Some(UpgradePolicy::AdditiveOnly));
+    old.constant_pool.push(Constant {
+        type_: SignatureToken::U64,
+        data: 7u64.to_le_bytes().to_vec(),
+    });
+    set_body(
+        &mut old,
+        vec![
+            Bytecode::LdConst(ConstantPoolIndex(0)),
+            Bytecode::Pop,
+            Bytecode::Ret,
+        ],
+    );
+
+    // The same function, with its constant moved to another index of the pool
+    let mut new = old.clone();
+    new.constant_pool.insert(
+        0,
+        Constant {
+            type_: SignatureToken::U64,
+            data: 8u64.to_le_bytes().to_vec(),
+        },
+    );
+    set_body(
+        &mut new,
+        vec![
+            Bytecode::LdConst(ConstantPoolIndex(1)),
+            Bytecode::Pop,
+            Bytecode::Ret,
+        ],
+    );
+    assert_eq!(check(&old, &new), Ok(()));
+
+    // A different constant is a change to the body
+    set_body(
+        &mut new,
+        vec![
+            Bytecode::LdConst(ConstantPoolIndex(0)),
+            Bytecode::Pop,
+            Bytecode::Ret,
+        ],
+    );
+    assert_eq!(check(&old, &new), Err(StatusCode::UPGRADE_POLICY_VIOLATION));
+}
+
+#[test]
+fn additive_only_rejects_added_abilities() {
+    let old = mk_module(Some(UpgradePolicy::AdditiveOnly));
+    let mut new = old.clone();
+    new.struct_handles[0].abilities = AbilitySet::EMPTY | Ability::Copy;
+    assert_eq!(check(&old, &new), Err(StatusCode::UPGRADE_POLICY_VIOLATION));
+    // Under the compatible policy, adding abilities is fine
+    let old = mk_module(None);
+    let mut new = old.clone();
+    new.struct_handles[0].abilities = AbilitySet::EMPTY | Ability::Copy;
+    assert_eq!(check(&old, &new), Ok(()));
+}
+
+#[test]
+fn additive_only_requires_compatibility() {
+    let old = mk_module(Some(UpgradePolicy::AdditiveOnly));
+    let mut new = old.clone();
+    new.struct_defs[0].field_information = StructFieldInformation::Declared(vec![]);
+    assert_eq!(
+        check(&old, &new),
+        Err(StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE)
+    );
+}
+
+#[test]
+fn dependency_only_rejects_new_declarations() {
+    let old = mk_module(Some(UpgradePolicy::DependencyOnly));
+    assert_eq!(check(&old, &old.clone()), Ok(()));
+
+    let mut new = old.clone();
+    add_function(&mut new, "bar");
+    assert_eq!(check(&old, &new), Err(StatusCode::UPGRADE_POLICY_VIOLATION));
+}
+
+#[test]
+fn immutable_rejects_any_change() {
+    let old = mk_module(Some(UpgradePolicy::Immutable));
+    assert_eq!(check(&old, &old.clone()), Ok(()));
+
+    let mut new = old.clone();
+    new.identifiers.push(Identifier::new("unused").unwrap());
+    assert_eq!(check(&old, &new), Err(StatusCode::UPGRADE_POLICY_VIOLATION));
+}
+
+#[test]
+fn malformed_policy() {
+    let mut m = basic_test_module();
+    m.metadata.push(move_core_types::metadata::Metadata {
+        key: move_core_types::metadata::UPGRADE_POLICY_METADATA_KEY.to_vec(),
+        value: b"frozen".to_vec(),
+    });
+    assert_eq!(
+        upgrade_policy(&m).unwrap_err().major_status(),
+        StatusCode::MALFORMED
+    );
+}
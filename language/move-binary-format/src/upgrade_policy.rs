@@ -0,0 +1,276 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Enforcement of the upgrade policy recorded in the metadata of a published module.
+//!
+//! The policy of the published module governs the upgrade:
+//! - `compatible`: the upgrade must pass the configured compatibility check.
+//! - `additive-only`: the upgrade must be fully compatible, must not change the body of any
+//!   existing function and must not add abilities to existing structs.
+//! - `dependency-only`: the declarations and code of the module must not change. Only the
+//!   modules it depends on may have been upgraded.
+//! - `immutable`: the module must not change at all.
+//!
+//! An upgrade can also never relax the policy of a module.
+
+use std::collections::BTreeMap;
+
+use crate::{
+    access::ModuleAccess,
+    compatibility::Compatibility,
+    errors::{PartialVMError, PartialVMResult},
+    file_format::{
+        Bytecode, CompiledModule, FieldHandleIndex, FunctionDefinition, FunctionHandleIndex,
+        SignatureIndex, StructDefInstantiationIndex, StructDefinitionIndex, Visibility,
+    },
+    normalized::{self, Type},
+};
+use move_core_types::{identifier::Identifier, metadata::UpgradePolicy, vm_status::StatusCode};
+
+/// Returns the upgrade policy of `module`. Modules without a policy are `Compatible`.
+pub fn upgrade_policy(module: &CompiledModule) -> PartialVMResult<UpgradePolicy> {
+    UpgradePolicy::from_metadata(&module.metadata)
+        .map(|policy| policy.unwrap_or(UpgradePolicy::Compatible))
+        .map_err(|msg| PartialVMError::new(StatusCode::MALFORMED).with_message(msg))
+}
+
+/// Check that `new_module` is a valid upgrade of `old_module` under the upgrade policy of
+/// `old_module`. `compat` is the compatibility check used for `compatible` modules; stricter
+/// policies always require full compatibility.
+pub fn check_upgrade_policy(
+    compat: Compatibility,
+    old_module: &CompiledModule,
+    new_module: &CompiledModule,
+) -> PartialVMResult<()> {
+    let old_policy = upgrade_policy(old_module)?;
+    let new_policy = upgrade_policy(new_module)?;
+    if new_policy < old_policy {
+        return Err(violation(format!(
+            "the upgrade policy cannot be relaxed from '{}' to '{}'",
+            old_policy, new_policy
+        )));
+    }
+
+    // Modules are only normalized by the checks which compare them
+    let old_normalized = || normalized::Module::new(old_module);
+    let new_normalized = || normalized::Module::new(new_module);
+    match old_policy {
+        UpgradePolicy::Compatible if !compat.need_check_compat() => Ok(()),
+        UpgradePolicy::Compatible => compat.check(&old_normalized(), &new_normalized()),
+        UpgradePolicy::AdditiveOnly => {
+            let old_normalized = old_normalized();
+            let new_normalized = new_normalized();
+            Compatibility::full_check().check(&old_normalized, &new_normalized)?;
+            let new_functions = functions(new_module);
+            for (name, old_function) in functions(old_module) {
+                if new_functions.get(&name) != Some(&old_function) {
+                    return Err(violation(format!(
+                        "function `{}` cannot be changed by an additive-only upgrade",
+                        name
+                    )));
+                }
+            }
+            for (name, old_struct) in &old_normalized.structs {
+                let new_abilities = new_normalized.structs[name].abilities;
+                if new_abilities != old_struct.abilities {
+                    return Err(violation(format!(
+                        "abilities of struct `{}` cannot be changed by an additive-only upgrade",
+                        name
+                    )));
+                }
+            }
+            Ok(())
+        }
+        UpgradePolicy::DependencyOnly => {
+            let old_normalized = old_normalized();
+            let new_normalized = new_normalized();
+            if old_normalized.friends != new_normalized.friends
+                || old_normalized.structs != new_normalized.structs
+                || functions(old_module) != functions(new_module)
+            {
+                return Err(violation(
+                    "a dependency-only upgrade cannot change the module".to_string(),
+                ));
+            }
+            Ok(())
+        }
+        UpgradePolicy::Immutable => {
+            if old_module != new_module {
+                return Err(violation(
+                    "an immutable module cannot be changed".to_string(),
+                ));
+            }
+            Ok(())
+        }
+    }
+}
+
+fn violation(msg: String) -> PartialVMError {
+    PartialVMError::new(StatusCode::UPGRADE_POLICY_VIOLATION).with_message(msg)
+}
+
+/// A function definition with every pool index resolved, so that functions of two versions of
+/// a module can be compared.
+#[derive(Debug, PartialEq, Eq)]
+struct NormalizedFunction {
+    visibility: Visibility,
+    is_entry: bool,
+    type_parameters: Vec<String>,
+    parameters: Vec<Type>,
+    return_: Vec<Type>,
+    acquires: Vec<String>,
+    locals: Option<Vec<Type>>,
+    code: Option<Vec<String>>,
+}
+
+fn functions(module: &CompiledModule) -> BTreeMap<Identifier, NormalizedFunction> {
+    module
+        .function_defs()
+        .iter()
+        .map(|def| {
+            let handle = module.function_handle_at(def.function);
+            let name = module.identifier_at(handle.name).to_owned();
+            (name, normalize_function(module, def))
+        })
+        .collect()
+}
+
+fn normalize_function(module: &CompiledModule, def: &FunctionDefinition) -> NormalizedFunction {
+    let handle = module.function_handle_at(def.function);
+    NormalizedFunction {
+        visibility: def.visibility,
+        is_entry: def.is_entry,
+        type_parameters: handle
+            .type_parameters
+            .iter()
+            .map(|abilities| format!("{:?}", abilities))
+            .collect(),
+        parameters: types(module, handle.parameters),
+        return_: types(module, handle.return_),
+        acquires: def
+            .acquires_global_resources
+            .iter()
+            .map(|idx| struct_def_name(module, *idx))
+            .collect(),
+        locals: def.code.as_ref().map(|code| types(module, code.locals)),
+        code: def.code.as_ref().map(|code| {
+            code.code
+                .iter()
+                .map(|instr| normalize_instruction(module, instr))
+                .collect()
+        }),
+    }
+}
+
+fn types(module: &CompiledModule, idx: SignatureIndex) -> Vec<Type> {
+    module
+        .signature_at(idx)
+        .0
+        .iter()
+        .map(|token| Type::new(module, token))
+        .collect()
+}
+
+fn type_arguments(module: &CompiledModule, idx: SignatureIndex) -> String {
+    types(module, idx)
+        .iter()
+        .map(|ty| ty.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn struct_def_name(module: &CompiledModule, idx: StructDefinitionIndex) -> String {
+    let handle = module.struct_handle_at(module.struct_def_at(idx).struct_handle);
+    module.identifier_at(handle.name).to_string()
+}
+
+fn struct_instantiation(module: &CompiledModule, idx: StructDefInstantiationIndex) -> String {
+    let inst = module.struct_instantiation_at(idx);
+    format!(
+        "{}<{}>",
+        struct_def_name(module, inst.def),
+        type_arguments(module, inst.type_parameters)
+    )
+}
+
+fn field(module: &CompiledModule, idx: FieldHandleIndex) -> String {
+    let handle = module.field_handle_at(idx);
+    format!("{}.{}", struct_def_name(module, handle.owner), handle.field)
+}
+
+fn function(module: &CompiledModule, idx: FunctionHandleIndex) -> String {
+    let handle = module.function_handle_at(idx);
+    let module_id = module.module_id_for_handle(module.module_handle_at(handle.module));
+    format!("{}::{}", module_id, module.identifier_at(handle.name))
+}
+
+/// Renders `instr` with its pool indices replaced by what they refer to.
+fn normalize_instruction(module: &CompiledModule, instr: &Bytecode) -> String {
+    use Bytecode::*;
+    match instr {
+        LdConst(idx) => {
+            let constant = module.constant_at(*idx);
+            format!(
+                "LdConst({}, {:?})",
+                Type::new(module, &constant.type_),
+                constant.data
+            )
+        }
+        Call(idx) => format!("Call({})", function(module, *idx)),
+        CallGeneric(idx) => {
+            let inst = module.function_instantiation_at(*idx);
+            format!(
+                "CallGeneric({}<{}>)",
+                function(module, inst.handle),
+                type_arguments(module, inst.type_parameters)
+            )
+        }
+        Pack(idx) => format!("Pack({})", struct_def_name(module, *idx)),
+        Unpack(idx) => format!("Unpack({})", struct_def_name(module, *idx)),
+        MutBorrowGlobal(idx) => format!("MutBorrowGlobal({})", struct_def_name(module, *idx)),
+        ImmBorrowGlobal(idx) => format!("ImmBorrowGlobal({})", struct_def_name(module, *idx)),
+        Exists(idx) => format!("Exists({})", struct_def_name(module, *idx)),
+        MoveFrom(idx) => format!("MoveFrom({})", struct_def_name(module, *idx)),
+        MoveTo(idx) => format!("MoveTo({})", struct_def_name(module, *idx)),
+        PackGeneric(idx) => format!("PackGeneric({})", struct_instantiation(module, *idx)),
+        UnpackGeneric(idx) => format!("UnpackGeneric({})", struct_instantiation(module, *idx)),
+        MutBorrowGlobalGeneric(idx) => format!(
+            "MutBorrowGlobalGeneric({})",
+            struct_instantiation(module, *idx)
+        ),
+        ImmBorrowGlobalGeneric(idx) => format!(
+            "ImmBorrowGlobalGeneric({})",
+            struct_instantiation(module, *idx)
+        ),
+        ExistsGeneric(idx) => format!("ExistsGeneric({})", struct_instantiation(module, *idx)),
+        MoveFromGeneric(idx) => {
+            format!("MoveFromGeneric({})", struct_instantiation(module, *idx))
+        }
+        MoveToGeneric(idx) => format!("MoveToGeneric({})", struct_instantiation(module, *idx)),
+        MutBorrowField(idx) => format!("MutBorrowField({})", field(module, *idx)),
+        ImmBorrowField(idx) => format!("ImmBorrowField({})", field(module, *idx)),
+        MutBorrowFieldGeneric(idx) | ImmBorrowFieldGeneric(idx) => {
+            let inst = module.field_instantiation_at(*idx);
+            let name = if matches!(instr, MutBorrowFieldGeneric(_)) {
+                "MutBorrowFieldGeneric"
+            } else {
+                "ImmBorrowFieldGeneric"
+            };
+            format!(
+                "{}({}<{}>)",
+                name,
+                field(module, inst.handle),
+                type_arguments(module, inst.type_parameters)
+            )
+        }
+        VecPack(idx, n) => format!("VecPack({}, {})", type_arguments(module, *idx), n),
+        VecUnpack(idx, n) => format!("VecUnpack({}, {})", type_arguments(module, *idx), n),
+        VecLen(idx) => format!("VecLen({})", type_arguments(module, *idx)),
+        VecImmBorrow(idx) => format!("VecImmBorrow({})", type_arguments(module, *idx)),
+        VecMutBorrow(idx) => format!("VecMutBorrow({})", type_arguments(module, *idx)),
+        VecPushBack(idx) => format!("VecPushBack({})", type_arguments(module, *idx)),
+        VecPopBack(idx) => format!("VecPopBack({})", type_arguments(module, *idx)),
+        VecSwap(idx) => format!("VecSwap({})", type_arguments(module, *idx)),
+        _ => format!("{:?}", instr),
+    }
+}
@@ -36,7 +36,7 @@ pub use signature::SignatureChecker;
 pub use struct_defs::RecursiveStructDefChecker;
 pub use verifier::{
     verify_module, verify_module_with_config, verify_script, verify_script_with_config,
-    VerifierConfig,
+    VerifierConfig, VERIFIER_VERSION,
 };
 
 mod acquires_list_verifier;
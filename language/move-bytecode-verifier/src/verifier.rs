@@ -17,6 +17,11 @@ use move_binary_format::{
     file_format::{CompiledModule, CompiledScript},
 };
 
+/// Version of the verification rules. It must be bumped whenever a change to the verifier can
+/// cause a module to be accepted or rejected differently, so that caches of verification results
+/// are invalidated.
+pub const VERIFIER_VERSION: u32 = 1;
+
 #[derive(Debug, Clone)]
 pub struct VerifierConfig {
     pub max_loop_depth: Option<usize>,
@@ -30,6 +30,8 @@ pub const FLAVOR: &str = "flavor";
 
 pub const BYTECODE_VERSION: &str = "bytecode-version";
 
+pub const FEATURES: &str = "features";
+
 pub const COLOR_MODE_ENV_VAR: &str = "COLOR_MODE";
 
 pub const MOVE_COMPILED_INTERFACES_DIR: &str = "mv_interfaces";
@@ -21,7 +21,7 @@ use move_command_line_common::{env::read_env_var, files::FileHash};
 use move_ir_types::location::*;
 use move_symbol_pool::Symbol;
 use std::{
-    collections::{BTreeMap, HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     iter::FromIterator,
     ops::Range,
 };
@@ -232,6 +232,14 @@ impl Diagnostics {
         self.diagnostics
     }
 
+    /// The files in which the diagnostics are reported
+    pub fn file_hashes(&self) -> BTreeSet<FileHash> {
+        self.diagnostics
+            .iter()
+            .map(|diag| diag.primary_label.0.file_hash())
+            .collect()
+    }
+
     pub fn into_codespan_format(
         self,
     ) -> Vec<(
@@ -314,6 +322,10 @@ impl Diagnostic {
     pub fn add_note(&mut self, msg: impl ToString) {
         self.notes.push(msg.to_string())
     }
+
+    pub fn info(&self) -> &DiagnosticInfo {
+        &self.info
+    }
 }
 
 #[macro_export]
@@ -557,11 +557,21 @@ fn flatten_attributes(
     let all_attrs = attributes
         .into_iter()
         .flat_map(|attrs| attrs.value)
+        // `cfg` attributes have already been evaluated by `features::ast_filter`
+        .filter(|attr| !is_cfg_attribute(attr))
         .flat_map(|attr| attribute(context, attr_position, attr))
         .collect::<Vec<_>>();
     unique_attributes(context, attr_position, false, all_attrs)
 }
 
+fn is_cfg_attribute(attr: &P::Attribute) -> bool {
+    use known_attributes::{ConditionalAttribute, KnownAttribute};
+    matches!(
+        KnownAttribute::resolve(attr.value.attribute_name().value),
+        Some(KnownAttribute::Conditional(ConditionalAttribute::Cfg))
+    )
+}
+
 fn unique_attributes(
     context: &mut Context,
     attr_position: AttributePosition,
@@ -0,0 +1,153 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_symbol_pool::Symbol;
+
+use crate::{
+    diag,
+    parser::{
+        ast as P,
+        filter::{filter_program, FilterContext},
+    },
+    shared::{known_attributes, CompilationEnv},
+};
+
+struct Context<'env> {
+    env: &'env mut CompilationEnv,
+    current_package: Option<Symbol>,
+}
+
+impl<'env> Context<'env> {
+    fn new(compilation_env: &'env mut CompilationEnv) -> Self {
+        Self {
+            env: compilation_env,
+            current_package: None,
+        }
+    }
+}
+
+impl FilterContext for Context<'_> {
+    fn set_current_package(&mut self, package: Option<Symbol>) {
+        self.current_package = package;
+    }
+
+    fn should_remove_by_attributes(
+        &mut self,
+        attrs: &[P::Attributes],
+        _is_source_def: bool,
+    ) -> bool {
+        should_remove_node(self, attrs)
+    }
+}
+
+//***************************************************************************
+// Filtering of cfg-annotated module members
+//***************************************************************************
+
+// This filters out all AST elements annotated with a `#[cfg(<predicate>)]` attribute whose
+// predicate does not hold for the features enabled for the package the element belongs to.
+pub fn program(compilation_env: &mut CompilationEnv, prog: P::Program) -> P::Program {
+    let mut context = Context::new(compilation_env);
+    filter_program(&mut context, prog)
+}
+
+// An AST element should be removed if any of its `#[cfg(..)]` predicates does not hold. Malformed
+// predicates are reported and treated as holding, so that the element is still checked.
+fn should_remove_node(context: &mut Context, attrs: &[P::Attributes]) -> bool {
+    use known_attributes::{ConditionalAttribute, KnownAttribute};
+    let mut remove = false;
+    for attr in attrs.iter().flat_map(|attrs| &attrs.value) {
+        match (
+            KnownAttribute::resolve(attr.value.attribute_name().value),
+            &attr.value,
+        ) {
+            (
+                Some(KnownAttribute::Conditional(ConditionalAttribute::Cfg)),
+                P::Attribute_::Parameterized(_, sp!(_, predicates)),
+            ) if predicates.len() == 1 => {
+                if let Some(holds) = evaluate_predicate(context, &predicates[0]) {
+                    remove |= !holds;
+                }
+            }
+            (Some(KnownAttribute::Conditional(ConditionalAttribute::Cfg)), _) => {
+                let msg = format!(
+                    "Expected a single predicate, e.g. '#[{}({} = <name>)]'",
+                    ConditionalAttribute::CFG,
+                    ConditionalAttribute::FEATURE_NAME,
+                );
+                context
+                    .env
+                    .add_diag(diag!(Attributes::InvalidUsage, (attr.loc, msg)));
+            }
+            _ => (),
+        }
+    }
+    remove
+}
+
+// Evaluates a cfg predicate. Returns `None` if the predicate is malformed.
+fn evaluate_predicate(context: &mut Context, sp!(loc, predicate): &P::Attribute) -> Option<bool> {
+    use known_attributes::ConditionalAttribute as C;
+    use P::Attribute_ as PA;
+    match predicate {
+        PA::Assigned(n, value) if n.value.as_str() == C::FEATURE_NAME => {
+            let feature = match &value.value {
+                P::AttributeValue_::Value(sp!(_, P::Value_::ByteString(s))) => *s,
+                P::AttributeValue_::ModuleAccess(sp!(_, P::NameAccessChain_::One(n))) => n.value,
+                _ => {
+                    let msg = format!(
+                        "Expected a feature name, e.g. '{} = b\"<name>\"' or '{} = <name>'",
+                        C::FEATURE_NAME,
+                        C::FEATURE_NAME,
+                    );
+                    context
+                        .env
+                        .add_diag(diag!(Attributes::InvalidValue, (value.loc, msg)));
+                    return None;
+                }
+            };
+            Some(
+                context
+                    .env
+                    .flags()
+                    .is_feature_enabled(context.current_package, feature.as_str()),
+            )
+        }
+        PA::Parameterized(n, sp!(_, args)) if n.value.as_str() == C::NOT_NAME => {
+            if args.len() != 1 {
+                let msg = format!("'{}' expects exactly one predicate", C::NOT_NAME);
+                context
+                    .env
+                    .add_diag(diag!(Attributes::InvalidValue, (*loc, msg)));
+                return None;
+            }
+            evaluate_predicate(context, &args[0]).map(|holds| !holds)
+        }
+        PA::Parameterized(n, sp!(_, args))
+            if n.value.as_str() == C::ALL_NAME || n.value.as_str() == C::ANY_NAME =>
+        {
+            let results = args
+                .iter()
+                .map(|arg| evaluate_predicate(context, arg))
+                .collect::<Option<Vec<_>>>()?;
+            Some(if n.value.as_str() == C::ALL_NAME {
+                results.into_iter().all(|holds| holds)
+            } else {
+                results.into_iter().any(|holds| holds)
+            })
+        }
+        _ => {
+            let msg = format!(
+                "Unknown predicate. Expected one of '{} = <name>', '{}(..)', '{}(..)' or '{}(..)'",
+                C::FEATURE_NAME,
+                C::NOT_NAME,
+                C::ALL_NAME,
+                C::ANY_NAME,
+            );
+            context
+                .env
+                .add_diag(diag!(Attributes::InvalidValue, (*loc, msg)));
+            None
+        }
+    }
+}
@@ -13,6 +13,7 @@ pub mod command_line;
 pub mod compiled_unit;
 pub mod diagnostics;
 pub mod expansion;
+pub mod features;
 pub mod hlir;
 pub mod interface_generator;
 pub mod ir_translation;
@@ -3,11 +3,15 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use move_ir_types::location::sp;
+use move_symbol_pool::Symbol;
 
 use crate::parser::ast as P;
 
 /// A trait that decides whether to include a parsed element in the compilation
 pub trait FilterContext {
+    /// Called before the definitions of each package are filtered
+    fn set_current_package(&mut self, _package: Option<Symbol>) {}
+
     /// Attribute-based node removal
     fn should_remove_by_attributes(
         &mut self,
@@ -138,6 +142,7 @@ pub fn filter_program<T: FilterContext>(context: &mut T, prog: P::Program) -> P:
                  named_address_map,
                  def,
              }| {
+                context.set_current_package(package);
                 Some(P::PackageDefinition {
                     package,
                     named_address_map,
@@ -155,6 +160,7 @@ pub fn filter_program<T: FilterContext>(context: &mut T, prog: P::Program) -> P:
                  named_address_map,
                  def,
              }| {
+                context.set_current_package(package);
                 Some(P::PackageDefinition {
                     package,
                     named_address_map,
@@ -12,7 +12,7 @@ use move_ir_types::location::*;
 use move_symbol_pool::Symbol;
 use petgraph::{algo::astar as petgraph_astar, graphmap::DiGraphMap};
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     fmt,
     hash::Hash,
     sync::atomic::{AtomicUsize, Ordering as AtomicOrdering},
@@ -312,6 +312,19 @@ pub struct Flags {
     )]
     shadow: bool,
 
+    /// Features enabled for conditional compilation with `#[cfg(feature = ...)]`. Applies to all
+    /// packages that have no package specific feature set.
+    #[clap(
+        long = cli::FEATURES,
+        use_value_delimiter = true,
+    )]
+    features: Vec<String>,
+
+    /// Internal map from package names to their enabled features, as determined by the package
+    /// system.
+    #[clap(skip)]
+    package_features: BTreeMap<Symbol, BTreeSet<Symbol>>,
+
     /// Internal flag used by the model builder to maintain functions which would be otherwise
     /// included only in tests, without creating the unit test code regular tests do.
     #[clap(skip)]
@@ -326,6 +339,8 @@ impl Flags {
             shadow: false,
             flavor: "".to_string(),
             bytecode_version: None,
+            features: vec![],
+            package_features: BTreeMap::new(),
             keep_testing_functions: false,
         }
     }
@@ -337,6 +352,8 @@ impl Flags {
             shadow: false,
             flavor: "".to_string(),
             bytecode_version: None,
+            features: vec![],
+            package_features: BTreeMap::new(),
             keep_testing_functions: false,
         }
     }
@@ -348,6 +365,8 @@ impl Flags {
             shadow: true, // allows overlapping between sources and deps
             flavor: "".to_string(),
             bytecode_version: None,
+            features: vec![],
+            package_features: BTreeMap::new(),
             keep_testing_functions: false,
         }
     }
@@ -373,6 +392,23 @@ impl Flags {
         }
     }
 
+    pub fn set_features(self, features: impl IntoIterator<Item = impl ToString>) -> Self {
+        Self {
+            features: features.into_iter().map(|f| f.to_string()).collect(),
+            ..self
+        }
+    }
+
+    pub fn set_package_features(
+        self,
+        package_features: BTreeMap<Symbol, BTreeSet<Symbol>>,
+    ) -> Self {
+        Self {
+            package_features,
+            ..self
+        }
+    }
+
     pub fn is_empty(&self) -> bool {
         self == &Self::empty()
     }
@@ -400,6 +436,15 @@ impl Flags {
     pub fn bytecode_version(&self) -> Option<u32> {
         self.bytecode_version
     }
+
+    /// Returns true if `feature` is enabled for code in `package`. Packages without a package
+    /// specific feature set fall back to the globally enabled features.
+    pub fn is_feature_enabled(&self, package: Option<Symbol>, feature: &str) -> bool {
+        match package.and_then(|p| self.package_features.get(&p)) {
+            Some(features) => features.contains(&Symbol::from(feature)),
+            None => self.features.iter().any(|f| f == feature),
+        }
+    }
 }
 
 //**************************************************************************************************
@@ -428,6 +473,7 @@ pub mod known_attributes {
         Testing(TestingAttribute),
         Verification(VerificationAttribute),
         Native(NativeAttribute),
+        Conditional(ConditionalAttribute),
     }
 
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -446,6 +492,12 @@ pub mod known_attributes {
         VerifyOnly,
     }
 
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    pub enum ConditionalAttribute {
+        // The associated AST node will be included in the compilation only if the predicate holds
+        Cfg,
+    }
+
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
     pub enum NativeAttribute {
         // It is a fake native function that actually compiles to a bytecode instruction
@@ -482,6 +534,7 @@ pub mod known_attributes {
                 NativeAttribute::BYTECODE_INSTRUCTION => {
                     Self::Native(NativeAttribute::BytecodeInstruction)
                 }
+                ConditionalAttribute::CFG => Self::Conditional(ConditionalAttribute::Cfg),
                 _ => return None,
             })
         }
@@ -491,6 +544,7 @@ pub mod known_attributes {
                 Self::Testing(a) => a.name(),
                 Self::Verification(a) => a.name(),
                 Self::Native(a) => a.name(),
+                Self::Conditional(a) => a.name(),
             }
         }
 
@@ -499,6 +553,7 @@ pub mod known_attributes {
                 Self::Testing(a) => a.expected_positions(),
                 Self::Verification(a) => a.expected_positions(),
                 Self::Native(a) => a.expected_positions(),
+                Self::Conditional(a) => a.expected_positions(),
             }
         }
     }
@@ -586,6 +641,40 @@ pub mod known_attributes {
         }
     }
 
+    impl ConditionalAttribute {
+        pub const CFG: &'static str = "cfg";
+        pub const FEATURE_NAME: &'static str = "feature";
+        pub const NOT_NAME: &'static str = "not";
+        pub const ALL_NAME: &'static str = "all";
+        pub const ANY_NAME: &'static str = "any";
+
+        pub const fn name(&self) -> &str {
+            match self {
+                Self::Cfg => Self::CFG,
+            }
+        }
+
+        pub fn expected_positions(&self) -> &'static BTreeSet<AttributePosition> {
+            static CFG_POSITIONS: Lazy<BTreeSet<AttributePosition>> = Lazy::new(|| {
+                IntoIterator::into_iter([
+                    AttributePosition::AddressBlock,
+                    AttributePosition::Module,
+                    AttributePosition::Script,
+                    AttributePosition::Use,
+                    AttributePosition::Friend,
+                    AttributePosition::Constant,
+                    AttributePosition::Struct,
+                    AttributePosition::Function,
+                    AttributePosition::Spec,
+                ])
+                .collect()
+            });
+            match self {
+                Self::Cfg => &CFG_POSITIONS,
+            }
+        }
+    }
+
     impl NativeAttribute {
         pub const BYTECODE_INSTRUCTION: &'static str = "bytecode_instruction";
 
@@ -0,0 +1,19 @@
+// Check that members whose cfg predicate does not hold are filtered out
+address 0x1 {
+module A {
+    #[cfg(feature = disabled)]
+    struct Foo has drop {}
+
+    #[cfg(not(feature = enabled))]
+    public fun build_foo(): Foo { Foo {} }
+}
+
+module B {
+    #[cfg(any(feature = enabled, feature = disabled))]
+    use 0x1::A;
+
+    fun tester() {
+        A::build_foo();
+    }
+}
+}
@@ -0,0 +1,27 @@
+// Check that cfg filtering and calling is supported across modules and
+// different types of module members
+address 0x1 {
+module A {
+    #[cfg(feature = enabled)]
+    struct Foo has drop {}
+
+    #[cfg(feature = b"enabled")]
+    public fun build_foo(): Foo { Foo {} }
+
+    #[cfg(feature = disabled)]
+    public fun build_foo(): u64 { 0 }
+}
+
+module B {
+    #[cfg(all(feature = enabled, not(feature = disabled)))]
+    use 0x1::A::{Self, Foo};
+
+    #[cfg(any(feature = disabled, feature = enabled))]
+    fun x(_: Foo) { }
+
+    #[cfg(feature = enabled)]
+    fun tester() {
+        x(A::build_foo())
+    }
+}
+}
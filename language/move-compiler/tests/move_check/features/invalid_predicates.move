@@ -0,0 +1,18 @@
+address 0x1 {
+module M {
+    #[cfg]
+    fun no_predicate() {}
+
+    #[cfg(feature = enabled, feature = disabled)]
+    fun two_predicates() {}
+
+    #[cfg(feature = 0)]
+    fun not_a_name() {}
+
+    #[cfg(not(feature = enabled, feature = disabled))]
+    fun not_with_two_predicates() {}
+
+    #[cfg(unknown)]
+    fun unknown_predicate() {}
+}
+}
@@ -24,6 +24,10 @@ const VERIFICATION_EXT: &str = "verification";
 /// Root of tests which require to set flavor flags.
 const FLAVOR_PATH: &str = "flavors/";
 
+/// Root of tests which are compiled with the `TEST_FEATURES` enabled.
+const FEATURES_PATH: &str = "features/";
+const TEST_FEATURES: &[&str] = &["enabled"];
+
 fn default_testing_addresses() -> BTreeMap<String, NumericalAddress> {
     let mapping = [
         ("std", "0x1"),
@@ -98,6 +102,7 @@ fn move_check_testsuite(path: &Path) -> datatest_stable::Result<()> {
                 .to_string();
             flags = flags.set_flavor(flavor)
         }
+        Some(p) if p.contains(FEATURES_PATH) => flags = flags.set_features(TEST_FEATURES),
         _ => {}
     };
     run_test(path, &exp_path, &out_path, flags)?;
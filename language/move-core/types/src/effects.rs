@@ -5,10 +5,13 @@
 use crate::{
     account_address::AccountAddress,
     identifier::Identifier,
-    language_storage::{ModuleId, StructTag, TypeTag},
+    language_storage::{ModuleId, ResourceKey, StructTag, TypeTag},
 };
 use anyhow::{bail, Result};
-use std::collections::btree_map::{self, BTreeMap};
+use std::collections::{
+    btree_map::{self, BTreeMap},
+    BTreeSet,
+};
 
 /// A storage operation.
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
@@ -296,3 +299,88 @@ impl ChangeSet {
 }
 
 pub type Event = (Vec<u8>, u64, TypeTag, Vec<u8>);
+
+/// An instruction accessing a resource in global storage.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum ResourceAccess {
+    Exists,
+    BorrowGlobal,
+    BorrowGlobalMut,
+    MoveFrom,
+    MoveTo,
+}
+
+impl ResourceAccess {
+    /// Whether the access reads the resource, following the convention of the static read/write
+    /// set analysis. A mutable borrow is considered both a read and a write.
+    pub fn is_read(self) -> bool {
+        matches!(
+            self,
+            Self::Exists | Self::BorrowGlobal | Self::BorrowGlobalMut
+        )
+    }
+
+    /// Whether the access may write the resource.
+    pub fn is_write(self) -> bool {
+        matches!(self, Self::BorrowGlobalMut | Self::MoveFrom | Self::MoveTo)
+    }
+}
+
+/// The resources and modules accessed during the execution of a session, including accesses
+/// which failed, for instance a `move_from` of a resource which does not exist.
+///
+/// Only the accesses performed by the VM are recorded. State which natives access through their
+/// extensions, e.g. the items of tables, is not part of the set.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct AccessSet {
+    resources: BTreeMap<ResourceKey, BTreeSet<ResourceAccess>>,
+    modules: BTreeSet<ModuleId>,
+}
+
+impl AccessSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_resource_access(&mut self, key: ResourceKey, access: ResourceAccess) {
+        self.resources.entry(key).or_default().insert(access);
+    }
+
+    /// Add the accesses recorded in `other` to this set.
+    pub fn extend(&mut self, other: Self) {
+        for (key, accesses) in other.resources {
+            self.resources.entry(key).or_default().extend(accesses);
+        }
+        self.modules.extend(other.modules);
+    }
+
+    pub fn add_module_access(&mut self, module_id: ModuleId) {
+        self.modules.insert(module_id);
+    }
+
+    /// The kinds of accesses to each resource.
+    pub fn resources(&self) -> &BTreeMap<ResourceKey, BTreeSet<ResourceAccess>> {
+        &self.resources
+    }
+
+    /// The modules loaded, including the dependencies of the modules used.
+    pub fn modules(&self) -> &BTreeSet<ModuleId> {
+        &self.modules
+    }
+
+    pub fn keys_read(&self) -> BTreeSet<ResourceKey> {
+        self.keys_with(ResourceAccess::is_read)
+    }
+
+    pub fn keys_written(&self) -> BTreeSet<ResourceKey> {
+        self.keys_with(ResourceAccess::is_write)
+    }
+
+    fn keys_with(&self, pred: fn(ResourceAccess) -> bool) -> BTreeSet<ResourceKey> {
+        self.resources
+            .iter()
+            .filter(|(_, accesses)| accesses.iter().any(|access| pred(*access)))
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+}
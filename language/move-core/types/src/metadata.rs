@@ -10,3 +10,84 @@ pub struct Metadata {
     /// The value of the metadata.
     pub value: Vec<u8>,
 }
+
+/// The key of the metadata entry holding the upgrade policy of a module.
+pub const UPGRADE_POLICY_METADATA_KEY: &[u8] = b"upgrade_policy";
+
+/// The policy restricting how a published module can be upgraded. Policies are ordered from the
+/// least to the most restrictive, and an upgrade cannot make the policy of a module less
+/// restrictive. Modules without an upgrade policy are `Compatible`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub enum UpgradePolicy {
+    /// The module can be upgraded in any backward compatible way
+    Compatible,
+    /// The module can be upgraded in a backward compatible way, by adding new functions and
+    /// structs only: the bodies of existing functions and the abilities of existing structs
+    /// cannot change
+    AdditiveOnly,
+    /// The module can only be upgraded to depend on upgraded versions of its dependencies: its
+    /// own declarations and code cannot change
+    DependencyOnly,
+    /// The module cannot be upgraded
+    Immutable,
+}
+
+impl UpgradePolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Compatible => "compatible",
+            Self::AdditiveOnly => "additive-only",
+            Self::DependencyOnly => "dependency-only",
+            Self::Immutable => "immutable",
+        }
+    }
+
+    /// The metadata entry recording this policy
+    pub fn to_metadata(self) -> Metadata {
+        Metadata {
+            key: UPGRADE_POLICY_METADATA_KEY.to_vec(),
+            value: self.as_str().as_bytes().to_vec(),
+        }
+    }
+
+    /// The upgrade policy recorded in `metadata`, if any. Returns an error if the policy entry is
+    /// malformed.
+    pub fn from_metadata(metadata: &[Metadata]) -> Result<Option<Self>, String> {
+        match metadata
+            .iter()
+            .find(|entry| entry.key == UPGRADE_POLICY_METADATA_KEY)
+        {
+            None => Ok(None),
+            Some(entry) => std::str::from_utf8(&entry.value)
+                .map_err(|_| "upgrade policy is not valid UTF-8".to_string())?
+                .parse()
+                .map(Some),
+        }
+    }
+}
+
+impl std::str::FromStr for UpgradePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "compatible" => Self::Compatible,
+            "additive-only" => Self::AdditiveOnly,
+            "dependency-only" => Self::DependencyOnly,
+            "immutable" => Self::Immutable,
+            _ => {
+                return Err(format!(
+                    "unknown upgrade policy '{}', expected one of 'compatible', 'additive-only', \
+                     'dependency-only' or 'immutable'",
+                    s
+                ))
+            }
+        })
+    }
+}
+
+impl std::fmt::Display for UpgradePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -7,6 +7,7 @@ use crate::{
     identifier::{self, Identifier},
     language_storage::{StructTag, TypeTag},
     transaction_argument::TransactionArgument,
+    value::{MoveStruct, MoveStructLayout, MoveTypeLayout, MoveValue},
 };
 use anyhow::{bail, format_err, Result};
 use std::iter::Peekable;
@@ -32,14 +33,23 @@ enum Token {
     U64(String),
     U128(String),
     U256(String),
-
+    /// An integer literal without type suffix
+    Num(String),
     Bytes(String),
+    Str(String),
     True,
     False,
     ColonColon,
+    Colon,
     Lt,
     Gt,
     Comma,
+    LBracket,
+    RBracket,
+    LBrace,
+    RBrace,
+    LParen,
+    RParen,
     EOF,
 }
 
@@ -97,7 +107,7 @@ fn next_number(initial: char, mut it: impl Iterator<Item = char>) -> Result<(Tok
             }
             _ => {
                 let len = num.len();
-                return Ok((Token::U64(num), len));
+                return Ok((Token::Num(num), len));
             }
         }
     }
@@ -112,10 +122,39 @@ fn next_token(s: &str) -> Result<Option<(Token, usize)>> {
             '<' => (Token::Lt, 1),
             '>' => (Token::Gt, 1),
             ',' => (Token::Comma, 1),
+            '[' => (Token::LBracket, 1),
+            ']' => (Token::RBracket, 1),
+            '{' => (Token::LBrace, 1),
+            '}' => (Token::RBrace, 1),
+            '(' => (Token::LParen, 1),
+            ')' => (Token::RParen, 1),
             ':' => match it.next() {
                 Some(':') => (Token::ColonColon, 2),
-                _ => bail!("unrecognized token"),
+                _ => (Token::Colon, 1),
             },
+            '"' => {
+                let mut r = String::new();
+                let mut len = 1;
+                loop {
+                    let c = match it.next() {
+                        Some('"') => break,
+                        Some('\\') => {
+                            len += 1;
+                            match it.next() {
+                                Some(c @ ('"' | '\\')) => c,
+                                Some('n') => '\n',
+                                Some('t') => '\t',
+                                _ => bail!("unrecognized escape sequence"),
+                            }
+                        }
+                        Some(c) => c,
+                        None => bail!("unterminated string"),
+                    };
+                    len += c.len_utf8();
+                    r.push(c);
+                }
+                (Token::Str(r), len + 1)
+            }
             '0' if it.peek() == Some(&'x') || it.peek() == Some(&'X') => {
                 it.next().unwrap();
                 match it.next() {
@@ -325,7 +364,7 @@ impl<I: Iterator<Item = Token>> Parser<I> {
             Token::U8(s) => TransactionArgument::U8(s.replace('_', "").parse()?),
             Token::U16(s) => TransactionArgument::U16(s.replace('_', "").parse()?),
             Token::U32(s) => TransactionArgument::U32(s.replace('_', "").parse()?),
-            Token::U64(s) => TransactionArgument::U64(s.replace('_', "").parse()?),
+            Token::U64(s) | Token::Num(s) => TransactionArgument::U64(s.replace('_', "").parse()?),
             Token::U128(s) => TransactionArgument::U128(s.replace('_', "").parse()?),
             Token::U256(s) => TransactionArgument::U256(s.replace('_', "").parse()?),
             Token::True => TransactionArgument::Bool(true),
@@ -337,6 +376,163 @@ impl<I: Iterator<Item = Token>> Parser<I> {
             tok => bail!("unexpected token {:?}, expected transaction argument", tok),
         })
     }
+
+    fn parse_value(&mut self, layout: &MoveTypeLayout) -> Result<MoveValue> {
+        use MoveTypeLayout as L;
+        Ok(match layout {
+            L::Bool => match self.next()? {
+                Token::True => MoveValue::Bool(true),
+                Token::False => MoveValue::Bool(false),
+                tok => bail!("unexpected token {:?}, expected bool", tok),
+            },
+            L::U8 => MoveValue::U8(self.parse_number(layout)?.parse()?),
+            L::U16 => MoveValue::U16(self.parse_number(layout)?.parse()?),
+            L::U32 => MoveValue::U32(self.parse_number(layout)?.parse()?),
+            L::U64 => MoveValue::U64(self.parse_number(layout)?.parse()?),
+            L::U128 => MoveValue::U128(self.parse_number(layout)?.parse()?),
+            L::U256 => MoveValue::U256(self.parse_number(layout)?.parse()?),
+            L::Address => match self.next()? {
+                Token::Address(addr) => {
+                    MoveValue::Address(AccountAddress::from_hex_literal(&addr)?)
+                }
+                tok => bail!("unexpected token {:?}, expected address", tok),
+            },
+            L::Signer => bail!("signer values cannot be parsed"),
+            L::Vector(elem) => match (elem.as_ref(), self.peek()) {
+                (L::U8, Some(Token::Bytes(_))) => match self.next()? {
+                    Token::Bytes(s) => MoveValue::vector_u8(hex::decode(s)?),
+                    _ => unreachable!(),
+                },
+                _ => {
+                    self.consume(Token::LBracket)?;
+                    let elems = self.parse_comma_list(
+                        |parser| parser.parse_value(elem),
+                        Token::RBracket,
+                        true,
+                    )?;
+                    self.consume(Token::RBracket)?;
+                    MoveValue::Vector(elems)
+                }
+            },
+            L::Struct(MoveStructLayout::WithTypes { type_, fields })
+                if is_std_struct(type_, "string", "String")
+                    || is_std_struct(type_, "ascii", "String") =>
+            {
+                let bytes = match self.next()? {
+                    Token::Str(s) => s.into_bytes(),
+                    Token::Bytes(s) => hex::decode(s)?,
+                    tok => bail!("unexpected token {:?}, expected string", tok),
+                };
+                if type_.module.as_str() == "string" && std::str::from_utf8(&bytes).is_err() {
+                    bail!("invalid UTF-8 string")
+                }
+                if type_.module.as_str() == "ascii" && !bytes.is_ascii() {
+                    bail!("invalid ASCII string")
+                }
+                debug_assert_eq!(fields.len(), 1);
+                MoveValue::Struct(MoveStruct::Runtime(vec![MoveValue::vector_u8(bytes)]))
+            }
+            L::Struct(MoveStructLayout::WithTypes { type_, fields })
+                if is_std_struct(type_, "option", "Option") =>
+            {
+                let elem = match &fields[0].layout {
+                    L::Vector(elem) => elem,
+                    _ => bail!("unexpected layout for {}", type_),
+                };
+                let value = match self.peek() {
+                    Some(Token::Name(name)) if name == "none" => {
+                        self.next()?;
+                        None
+                    }
+                    Some(Token::Name(name)) if name == "some" => {
+                        self.next()?;
+                        self.consume(Token::LParen)?;
+                        let value = self.parse_value(elem)?;
+                        self.consume(Token::RParen)?;
+                        Some(value)
+                    }
+                    _ => Some(self.parse_value(elem)?),
+                };
+                MoveValue::Struct(MoveStruct::Runtime(vec![MoveValue::Vector(
+                    value.into_iter().collect(),
+                )]))
+            }
+            L::Struct(MoveStructLayout::WithTypes { fields, .. })
+            | L::Struct(MoveStructLayout::WithFields(fields)) => {
+                self.consume(Token::LBrace)?;
+                let mut values = self.parse_comma_list(
+                    |parser| {
+                        let name = parser.parse_field_name()?;
+                        let field = match fields.iter().find(|field| field.name.as_str() == name) {
+                            Some(field) => field,
+                            None => bail!("unknown field `{}`", name),
+                        };
+                        parser.consume(Token::Colon)?;
+                        Ok((name, parser.parse_value(&field.layout)?))
+                    },
+                    Token::RBrace,
+                    true,
+                )?;
+                self.consume(Token::RBrace)?;
+                // Fields can be given in any order, but each of them exactly once
+                let mut ordered = vec![];
+                for field in fields {
+                    let mut given = values
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, (name, _))| name == field.name.as_str());
+                    let idx = match (given.next(), given.next()) {
+                        (Some((idx, _)), None) => idx,
+                        (None, _) => bail!("missing field `{}`", field.name),
+                        (Some(_), Some(_)) => bail!("duplicate field `{}`", field.name),
+                    };
+                    ordered.push(values.remove(idx).1);
+                }
+                MoveValue::Struct(MoveStruct::Runtime(ordered))
+            }
+            L::Struct(MoveStructLayout::Runtime(_)) => {
+                bail!("struct values can only be parsed with a layout that has field names")
+            }
+        })
+    }
+
+    /// Parses an integer literal for the integer type `layout`. A literal without suffix can be
+    /// used for any integer type.
+    fn parse_number(&mut self, layout: &MoveTypeLayout) -> Result<String> {
+        use MoveTypeLayout as L;
+        let (num, matches) = match self.next()? {
+            Token::U8(s) => (s, matches!(layout, L::U8)),
+            Token::U16(s) => (s, matches!(layout, L::U16)),
+            Token::U32(s) => (s, matches!(layout, L::U32)),
+            Token::U64(s) => (s, matches!(layout, L::U64)),
+            Token::U128(s) => (s, matches!(layout, L::U128)),
+            Token::U256(s) => (s, matches!(layout, L::U256)),
+            Token::Num(s) => (s, true),
+            tok => bail!("unexpected token {:?}, expected {}", tok, layout),
+        };
+        if !matches {
+            bail!("integer literal {} does not have type {}", num, layout)
+        }
+        Ok(num.replace('_', ""))
+    }
+
+    /// Parses a field name, which can be any identifier, including the names of builtin types
+    fn parse_field_name(&mut self) -> Result<String> {
+        Ok(match self.next()? {
+            Token::Name(s) => s,
+            Token::U8Type => "u8".to_string(),
+            Token::U16Type => "u16".to_string(),
+            Token::U32Type => "u32".to_string(),
+            Token::U64Type => "u64".to_string(),
+            Token::U128Type => "u128".to_string(),
+            Token::U256Type => "u256".to_string(),
+            Token::BoolType => "bool".to_string(),
+            Token::AddressType => "address".to_string(),
+            Token::VectorType => "vector".to_string(),
+            Token::SignerType => "signer".to_string(),
+            tok => bail!("unexpected token {:?}, expected field name", tok),
+        })
+    }
 }
 
 fn parse<F, T>(s: &str, f: F) -> Result<T>
@@ -384,6 +580,24 @@ pub fn parse_transaction_argument(s: &str) -> Result<TransactionArgument> {
     parse(s, |parser| parser.parse_transaction_argument())
 }
 
+/// Parses a value of the type described by `layout`. Struct layouts must include field names,
+/// and the layouts of `0x1::string::String`, `0x1::ascii::String` and `0x1::option::Option`
+/// must include their types:
+/// - integers, booleans and addresses are written as transaction arguments, e.g. `1`, `1u8`,
+///   `true` or `0x1`
+/// - vectors are written as lists, e.g. `[1, 2]`, and `vector<u8>` can also be written `x"0102"`
+///   or `b"ab"`
+/// - strings are written as quoted literals, e.g. `"hello"`
+/// - options are written `none`, `some(<value>)` or just `<value>`
+/// - structs are written as lists of fields, in any order, e.g. `{ x: 1, y: true }`
+pub fn parse_value(s: &str, layout: &MoveTypeLayout) -> Result<MoveValue> {
+    parse(s, |parser| parser.parse_value(layout))
+}
+
+fn is_std_struct(tag: &StructTag, module: &str, name: &str) -> bool {
+    tag.address == AccountAddress::ONE && tag.module.as_str() == module && tag.name.as_str() == name
+}
+
 pub fn parse_struct_tag(s: &str) -> Result<StructTag> {
     let type_tag = parse(s, |parser| parser.parse_type_tag())
         .map_err(|e| format_err!("invalid struct tag: {}, {}", s, e))?;
@@ -400,9 +614,12 @@ mod tests {
 
     use crate::{
         account_address::AccountAddress,
-        parser::{parse_struct_tag, parse_transaction_argument, parse_type_tag},
+        identifier::Identifier,
+        language_storage::StructTag,
+        parser::{parse_struct_tag, parse_transaction_argument, parse_type_tag, parse_value},
         transaction_argument::TransactionArgument,
         u256,
+        value::{MoveFieldLayout, MoveStruct, MoveStructLayout, MoveTypeLayout, MoveValue},
     };
 
     #[allow(clippy::unreadable_literal)]
@@ -614,4 +831,148 @@ mod tests {
             );
         }
     }
+
+    fn std_struct(module: &str, name: &str, fields: Vec<(&str, MoveTypeLayout)>) -> MoveTypeLayout {
+        MoveTypeLayout::Struct(MoveStructLayout::WithTypes {
+            type_: StructTag {
+                address: AccountAddress::ONE,
+                module: Identifier::new(module).unwrap(),
+                name: Identifier::new(name).unwrap(),
+                type_params: vec![],
+            },
+            fields: fields
+                .into_iter()
+                .map(|(name, layout)| MoveFieldLayout::new(Identifier::new(name).unwrap(), layout))
+                .collect(),
+        })
+    }
+
+    fn string_layout() -> MoveTypeLayout {
+        std_struct(
+            "string",
+            "String",
+            vec![(
+                "bytes",
+                MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U8)),
+            )],
+        )
+    }
+
+    fn string_value(s: &str) -> MoveValue {
+        MoveValue::Struct(MoveStruct::Runtime(vec![MoveValue::vector_u8(
+            s.as_bytes().to_vec(),
+        )]))
+    }
+
+    #[test]
+    fn test_parse_value_positive() {
+        use MoveTypeLayout as L;
+        use MoveValue as V;
+        let vec = |l| L::Vector(Box::new(l));
+        let option = |l| std_struct("option", "Option", vec![("vec", L::Vector(Box::new(l)))]);
+        let point = L::Struct(MoveStructLayout::WithFields(vec![
+            MoveFieldLayout::new(Identifier::new("x").unwrap(), L::U64),
+            MoveFieldLayout::new(Identifier::new("address").unwrap(), L::Address),
+        ]));
+        let cases = vec![
+            ("true", L::Bool, V::Bool(true)),
+            ("1", L::U8, V::U8(1)),
+            ("1u8", L::U8, V::U8(1)),
+            ("1_000", L::U32, V::U32(1000)),
+            ("1u128", L::U128, V::U128(1)),
+            ("1u64", L::U64, V::U64(1)),
+            (
+                "0x42",
+                L::Address,
+                V::Address(AccountAddress::from_hex_literal("0x42").unwrap()),
+            ),
+            ("[]", vec(L::U64), V::Vector(vec![])),
+            (
+                "[1, 2,]",
+                vec(L::U16),
+                V::Vector(vec![V::U16(1), V::U16(2)]),
+            ),
+            ("x\"0102\"", vec(L::U8), V::vector_u8(vec![1, 2])),
+            ("b\"ab\"", vec(L::U8), V::vector_u8(b"ab".to_vec())),
+            (
+                "[[true], []]",
+                vec(vec(L::Bool)),
+                V::Vector(vec![V::Vector(vec![V::Bool(true)]), V::Vector(vec![])]),
+            ),
+            ("\"hello\"", string_layout(), string_value("hello")),
+            ("\"h\\\"é\\n\"", string_layout(), string_value("h\"é\n")),
+            (
+                "[\"a\", \"b\"]",
+                vec(string_layout()),
+                V::Vector(vec![string_value("a"), string_value("b")]),
+            ),
+            (
+                "none",
+                option(L::U64),
+                V::Struct(MoveStruct::Runtime(vec![V::Vector(vec![])])),
+            ),
+            (
+                "some(3)",
+                option(L::U64),
+                V::Struct(MoveStruct::Runtime(vec![V::Vector(vec![V::U64(3)])])),
+            ),
+            (
+                "3",
+                option(L::U64),
+                V::Struct(MoveStruct::Runtime(vec![V::Vector(vec![V::U64(3)])])),
+            ),
+            (
+                "{ address: 0x1, x: 5 }",
+                point.clone(),
+                V::Struct(MoveStruct::Runtime(vec![
+                    V::U64(5),
+                    V::Address(AccountAddress::ONE),
+                ])),
+            ),
+        ];
+        for (s, layout, expected) in cases {
+            assert_eq!(
+                parse_value(s, &layout).unwrap_or_else(|e| panic!("{}: {}", s, e)),
+                expected,
+                "{}",
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_value_negative() {
+        use MoveTypeLayout as L;
+        let point = L::Struct(MoveStructLayout::WithFields(vec![
+            MoveFieldLayout::new(Identifier::new("x").unwrap(), L::U64),
+            MoveFieldLayout::new(Identifier::new("y").unwrap(), L::U64),
+        ]));
+        let runtime = L::Struct(MoveStructLayout::Runtime(vec![L::U64]));
+        let cases = vec![
+            ("1u8", L::U64),
+            ("1u64", L::U128),
+            ("1u64", L::U8),
+            ("256", L::U8),
+            ("true", L::U64),
+            ("1", L::Bool),
+            ("1", L::Signer),
+            ("[1, 2", L::Vector(Box::new(L::U64))),
+            ("[1 2]", L::Vector(Box::new(L::U64))),
+            ("\"abc", string_layout()),
+            ("\"a\\q\"", string_layout()),
+            ("x\"ff\"", string_layout()),
+            ("{ x: 1 }", point.clone()),
+            ("{ x: 1, y: 2, z: 3 }", point.clone()),
+            ("{ x: 1, x: 2 }", point.clone()),
+            ("{ x 1, y: 2 }", point),
+            ("{}", runtime),
+        ];
+        for (s, layout) in cases {
+            assert!(
+                parse_value(s, &layout).is_err(),
+                "test case unexpectedly succeeded: {}",
+                s
+            );
+        }
+    }
 }
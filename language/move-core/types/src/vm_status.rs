@@ -604,6 +604,10 @@ pub enum StatusCode {
     VALUE_STACK_OVERFLOW = 1115,
     TOO_MANY_TYPE_NODES = 1116,
     VALUE_STACK_PUSH_OVERFLOW = 1117,
+    // The updated module violates the upgrade policy of the published module
+    UPGRADE_POLICY_VIOLATION = 1118,
+    // The signature a native function was registered with differs from its declaration
+    NATIVE_FUNCTION_SIGNATURE_MISMATCH = 1119,
 
     // These are errors that the VM might raise if a violation of internal
     // invariants takes place.
@@ -676,6 +680,7 @@ pub enum StatusCode {
     MEMORY_LIMIT_EXCEEDED = 4028,
     VM_MAX_TYPE_NODES_REACHED = 4029,
     VM_MAX_DEPENDENCY_DEPTH_REACHED = 4030,
+    VM_MAX_LOCALS_MEMORY_REACHED = 4031,
 
     // A reserved status to represent an unknown vm status.
     // this is std::u64::MAX, but we can't pattern match on that, so put the hardcoded value in
@@ -7,4 +7,5 @@
 pub mod mapping;
 pub mod marking;
 pub mod source_map;
+pub mod stack_trace;
 pub mod utils;
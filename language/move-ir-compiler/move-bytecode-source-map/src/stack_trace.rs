@@ -0,0 +1,233 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Symbolization of the stack traces carried by VM errors. A frame of an `ExecutionState` is a
+//! function definition index and a code offset; with the source maps and source files of the
+//! code executed, it can be turned into a function name, a file and line range, and the names of
+//! the function's locals.
+
+use crate::source_map::SourceMap;
+use move_binary_format::{
+    access::ModuleAccess,
+    errors::{ExecutionState, Location, VMError},
+    file_format::{CodeOffset, FunctionDefinitionIndex},
+    CompiledModule,
+};
+use move_command_line_common::files::FileHash;
+use move_core_types::language_storage::ModuleId;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+/// A range of lines in a source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub file_name: String,
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+/// A frame of a stack trace, with the information found for it in the source maps. Fields are
+/// `None` or empty when the code of the frame or its sources are unknown to the symbolizer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolizedFrame {
+    /// The module of the function, or `None` for a script.
+    pub module_id: Option<ModuleId>,
+    pub function_index: FunctionDefinitionIndex,
+    pub code_offset: CodeOffset,
+    pub function_name: Option<String>,
+    pub location: Option<SourceLocation>,
+    /// The names of the parameters and locals of the function, by local index.
+    pub locals: Vec<String>,
+}
+
+/// A symbolized stack trace, innermost frame first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StackTrace {
+    frames: Vec<SymbolizedFrame>,
+}
+
+impl StackTrace {
+    pub fn frames(&self) -> &[SymbolizedFrame] {
+        &self.frames
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn into_frames(self) -> Vec<SymbolizedFrame> {
+        self.frames
+    }
+}
+
+/// Symbolizes stack traces against the modules, scripts and source files added to it.
+#[derive(Default)]
+pub struct StackTraceSymbolizer<'a> {
+    modules: BTreeMap<ModuleId, (&'a CompiledModule, &'a SourceMap)>,
+    script: Option<(String, &'a SourceMap)>,
+    // file hash -> (file name, contents)
+    files: HashMap<FileHash, (String, String)>,
+}
+
+impl<'a> StackTraceSymbolizer<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_module(&mut self, module: &'a CompiledModule, source_map: &'a SourceMap) {
+        self.modules.insert(module.self_id(), (module, source_map));
+    }
+
+    /// Set the script frames without a module belong to. Scripts have a single function, named
+    /// `name` in the stack trace.
+    pub fn set_script(&mut self, name: impl Into<String>, source_map: &'a SourceMap) {
+        self.script = Some((name.into(), source_map));
+    }
+
+    /// Add a source file, which locations in the source maps refer to by the hash of its contents.
+    pub fn add_file(&mut self, file_name: impl Into<String>, contents: impl Into<String>) {
+        let contents = contents.into();
+        self.files
+            .insert(FileHash::new(&contents), (file_name.into(), contents));
+    }
+
+    /// Symbolize the frames of `exec_state`.
+    pub fn symbolize(&self, exec_state: &ExecutionState) -> StackTrace {
+        StackTrace {
+            frames: exec_state
+                .stack_trace()
+                .iter()
+                .map(|(module_id, function_index, code_offset)| {
+                    self.symbolize_frame(module_id.clone(), *function_index, *code_offset)
+                })
+                .collect(),
+        }
+    }
+
+    /// Symbolize the full call chain of `error`: the frame the error was raised in, followed by
+    /// the frames of its execution state, if any.
+    pub fn symbolize_error(&self, error: &VMError) -> StackTrace {
+        let mut frames = vec![];
+        let module_id = match error.location() {
+            Location::Module(module_id) => Some(Some(module_id.clone())),
+            Location::Script => Some(None),
+            Location::Undefined => None,
+        };
+        if let (Some(module_id), Some((function_index, code_offset))) =
+            (module_id, error.offsets().first())
+        {
+            frames.push(self.symbolize_frame(module_id, *function_index, *code_offset));
+        }
+        if let Some(exec_state) = error.exec_state() {
+            frames.extend(self.symbolize(exec_state).into_frames());
+        }
+        StackTrace { frames }
+    }
+
+    fn symbolize_frame(
+        &self,
+        module_id: Option<ModuleId>,
+        function_index: FunctionDefinitionIndex,
+        code_offset: CodeOffset,
+    ) -> SymbolizedFrame {
+        let (function_name, source_map) = match &module_id {
+            Some(module_id) => match self.modules.get(module_id) {
+                Some((module, source_map)) => {
+                    let function_name =
+                        module
+                            .function_defs()
+                            .get(function_index.0 as usize)
+                            .map(|def| {
+                                let handle = module.function_handle_at(def.function);
+                                module.identifier_at(handle.name).to_string()
+                            });
+                    (function_name, Some(*source_map))
+                }
+                None => (None, None),
+            },
+            None => match &self.script {
+                Some((name, source_map)) => (Some(name.clone()), Some(*source_map)),
+                None => (None, None),
+            },
+        };
+        let function_source_map = source_map
+            .and_then(|source_map| source_map.get_function_source_map(function_index).ok());
+        let location = function_source_map
+            .and_then(|function_source_map| function_source_map.get_code_location(code_offset))
+            .and_then(|loc| {
+                let (file_name, contents) = self.files.get(&loc.file_hash())?;
+                Some(SourceLocation {
+                    file_name: file_name.clone(),
+                    start_line: line_number(contents, loc.start())?,
+                    end_line: line_number(contents, loc.end())?,
+                })
+            });
+        let locals = function_source_map
+            .map(|function_source_map| {
+                function_source_map
+                    .parameters
+                    .iter()
+                    .chain(function_source_map.locals.iter())
+                    .map(|(name, _)| name.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+        SymbolizedFrame {
+            module_id,
+            function_index,
+            code_offset,
+            function_name,
+            location,
+            locals,
+        }
+    }
+}
+
+/// The 1-based number of the line containing the byte at `offset`.
+fn line_number(contents: &str, offset: u32) -> Option<usize> {
+    let prefix = contents.as_bytes().get(..offset as usize)?;
+    Some(prefix.iter().filter(|b| **b == b'\n').count() + 1)
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.start_line == self.end_line {
+            write!(f, "{}:{}", self.file_name, self.start_line)
+        } else {
+            write!(
+                f,
+                "{}:{}-{}",
+                self.file_name, self.start_line, self.end_line
+            )
+        }
+    }
+}
+
+impl fmt::Display for SymbolizedFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.module_id {
+            Some(module_id) => write!(f, "{}::", module_id.name())?,
+            None => write!(f, "script::")?,
+        }
+        match &self.function_name {
+            Some(name) => write!(f, "{}", name)?,
+            None => write!(f, "<function {}>", self.function_index.0)?,
+        }
+        match &self.location {
+            Some(location) => write!(f, "({})", location),
+            None => write!(f, "(at code offset {})", self.code_offset),
+        }
+    }
+}
+
+/// One frame per line, each indented with a tab.
+impl fmt::Display for StackTrace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for frame in &self.frames {
+            writeln!(f, "\t{}", frame)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_binary_format::{
+    errors::{ExecutionState, Location, PartialVMError},
+    file_format::{basic_test_module, FunctionDefinitionIndex},
+};
+use move_bytecode_source_map::{
+    source_map::SourceMap,
+    stack_trace::{SourceLocation, StackTraceSymbolizer},
+};
+use move_command_line_common::files::FileHash;
+use move_core_types::{
+    account_address::AccountAddress, identifier::Identifier, language_storage::ModuleId,
+    vm_status::StatusCode,
+};
+use move_ir_types::location::Loc;
+
+const SOURCE: &str =
+    "module M {\n    fun foo(x: u64) {\n        let y = x;\n        abort y\n    }\n}\n";
+
+fn source_map() -> SourceMap {
+    let file_hash = FileHash::new(SOURCE);
+    let loc = |start: usize, end: usize| Loc::new(file_hash, start as u32, end as u32);
+    let fdef_idx = FunctionDefinitionIndex(0);
+    let fun_start = SOURCE.find("fun").unwrap();
+    let abort_start = SOURCE.find("abort").unwrap();
+
+    let mut source_map = SourceMap::new(loc(0, SOURCE.len()), None);
+    source_map
+        .add_top_level_function_mapping(fdef_idx, loc(fun_start, SOURCE.len() - 2), false)
+        .unwrap();
+    source_map
+        .add_code_mapping(fdef_idx, 0, loc(fun_start, abort_start))
+        .unwrap();
+    source_map
+        .add_code_mapping(fdef_idx, 2, loc(abort_start, abort_start + 7))
+        .unwrap();
+    source_map
+        .add_parameter_mapping(fdef_idx, ("x".to_string(), loc(0, 0)))
+        .unwrap();
+    source_map
+        .add_local_mapping(fdef_idx, ("y".to_string(), loc(0, 0)))
+        .unwrap();
+    source_map
+}
+
+#[test]
+fn symbolize_frames() {
+    let module = basic_test_module();
+    let source_map = source_map();
+    let mut symbolizer = StackTraceSymbolizer::new();
+    symbolizer.add_module(&module, &source_map);
+    symbolizer.add_file("M.move", SOURCE);
+
+    let unknown = ModuleId::new(AccountAddress::ONE, Identifier::new("N").unwrap());
+    let stack_trace = symbolizer.symbolize(&ExecutionState::new(vec![
+        (Some(module.self_id()), FunctionDefinitionIndex(0), 3),
+        (Some(module.self_id()), FunctionDefinitionIndex(0), 1),
+        (Some(unknown), FunctionDefinitionIndex(2), 5),
+    ]));
+
+    let frames = stack_trace.frames();
+    assert_eq!(frames.len(), 3);
+    assert_eq!(frames[0].function_name.as_deref(), Some("foo"));
+    assert_eq!(
+        frames[0].location,
+        Some(SourceLocation {
+            file_name: "M.move".to_string(),
+            start_line: 4,
+            end_line: 4,
+        })
+    );
+    assert_eq!(frames[0].locals, vec!["x", "y"]);
+    assert_eq!(frames[2].function_name, None);
+    assert!(frames[2].locals.is_empty());
+    assert_eq!(
+        stack_trace.to_string(),
+        "\t<SELF>::foo(M.move:4)\n\
+         \t<SELF>::foo(M.move:2-4)\n\
+         \tN::<function 2>(at code offset 5)\n"
+    );
+}
+
+#[test]
+fn symbolize_error() {
+    let module = basic_test_module();
+    let source_map = source_map();
+    let mut symbolizer = StackTraceSymbolizer::new();
+    symbolizer.add_module(&module, &source_map);
+
+    // without the source file, frames have no location
+    let error = PartialVMError::new(StatusCode::ABORTED)
+        .with_exec_state(ExecutionState::new(vec![(
+            Some(module.self_id()),
+            FunctionDefinitionIndex(0),
+            1,
+        )]))
+        .at_code_offset(FunctionDefinitionIndex(0), 3)
+        .finish(Location::Module(module.self_id()));
+    assert_eq!(
+        symbolizer.symbolize_error(&error).to_string(),
+        "\t<SELF>::foo(at code offset 3)\n\t<SELF>::foo(at code offset 1)\n"
+    );
+
+    let error = PartialVMError::new(StatusCode::ABORTED).finish(Location::Undefined);
+    assert!(symbolizer.symbolize_error(&error).is_empty());
+}
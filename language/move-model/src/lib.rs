@@ -307,10 +307,15 @@ pub fn run_bytecode_model_builder<'a>(
         // add functions
         for (i, def) in m.function_defs().iter().enumerate() {
             let def_idx = FunctionDefinitionIndex(i as u16);
-            let name = m.identifier_at(m.function_handle_at(def.function).name);
+            let handle = m.function_handle_at(def.function);
+            let name = m.identifier_at(handle.name);
             let symbol = env.symbol_pool().make(name.as_str());
             let fun_id = FunId::new(symbol);
-            let data = FunctionData::stub(symbol, def_idx, def.function);
+            // without sources, parameters are named like other locals
+            let arg_names = (0..m.signature_at(handle.parameters).len())
+                .map(|idx| env.symbol_pool().make(&format!("$t{}", idx)))
+                .collect();
+            let data = FunctionData::stub(symbol, def_idx, def.function, arg_names);
             module_data.function_data.insert(fun_id, data);
             module_data.function_idx_to_id.insert(def_idx, fun_id);
         }
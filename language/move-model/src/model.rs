@@ -2959,6 +2959,7 @@ impl FunctionData {
         name: Symbol,
         def_idx: FunctionDefinitionIndex,
         handle_idx: FunctionHandleIndex,
+        arg_names: Vec<Symbol>,
     ) -> Self {
         FunctionData {
             name,
@@ -2966,7 +2967,7 @@ impl FunctionData {
             attributes: Vec::default(),
             def_idx,
             handle_idx,
-            arg_names: vec![],
+            arg_names,
             type_arg_names: vec![],
             spec: Spec::default(),
             called_funs: Default::default(),
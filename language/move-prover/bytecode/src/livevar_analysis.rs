@@ -275,6 +275,13 @@ impl<'a> LiveVarAnalysis<'a> {
                 {
                     // Drop this load/assign as it is not used.
                 }
+                Bytecode::Call(_, _, Operation::Destroy, srcs, _)
+                    if annotation_at.after.contains(&srcs[0])
+                        && !self.func_target.get_local_type(srcs[0]).is_reference() =>
+                {
+                    // Drop this destroy as the value is still used. This happens when copy
+                    // propagation replaced the destroyed local by the one it is an alias of.
+                }
                 Bytecode::Call(attr_id, dests, oper, srcs, aa)
                     if code_offset + 1 < code.len()
                         && dests.len() == 1
@@ -230,6 +230,8 @@ impl ReachingDefState {
 
     fn kill(&mut self, dest: TempIndex) {
         self.map.remove(&dest);
+        // Locals aliasing `dest` do not hold its new value.
+        self.map.retain(|_, defs| !defs.contains(&Def::Alias(dest)));
     }
 }
 
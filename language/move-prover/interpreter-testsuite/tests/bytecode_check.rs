@@ -0,0 +1,69 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Checks the stackless bytecode interpreter on modules written in Move IR, for bytecode patterns
+//! the Move compiler optimizes away.
+
+use move_binary_format::errors::VMResult;
+use move_core_types::{identifier::Identifier, value::MoveValue};
+use move_ir_compiler::Compiler;
+use move_model::run_bytecode_model_builder;
+use move_stackless_bytecode_interpreter::{
+    concrete::{settings::InterpreterSettings, value::GlobalState},
+    StacklessBytecodeInterpreter,
+};
+
+fn interpret(code: &str, function: &str, args: &[MoveValue]) -> VMResult<Vec<Vec<u8>>> {
+    let module = Compiler::new(vec![]).into_compiled_module(code).unwrap();
+    let env = run_bytecode_model_builder(vec![&module]).unwrap();
+    let interpreter = StacklessBytecodeInterpreter::new(&env, None, InterpreterSettings::default());
+    let (result, _, _) = interpreter.interpret(
+        &module.self_id(),
+        &Identifier::new(function).unwrap(),
+        &[],
+        args,
+        &GlobalState::default(),
+    );
+    result
+}
+
+#[test]
+fn move_and_store_back() {
+    let code = r#"
+        module 0x2.A {
+            public f(x: u64): u64 {
+            label b0:
+                x = move(x);
+                x = move(x);
+                _ = move(x);
+                return 7;
+            }
+        }
+    "#;
+    assert_eq!(
+        interpret(code, "f", &[MoveValue::U64(7)]),
+        Ok(vec![MoveValue::U64(7).simple_serialize().unwrap()])
+    );
+}
+
+#[test]
+fn destroy_aliased_temporary() {
+    let code = r#"
+        module 0x2.A {
+            public f(x: u64): u64 {
+                let y: u64;
+                let z: u64;
+            label b0:
+                y = copy(x);
+                z = copy(x);
+                _ = move(y);
+                _ = move(z);
+                return move(x);
+            }
+        }
+    "#;
+    assert_eq!(
+        interpret(code, "f", &[MoveValue::U64(7)]),
+        Ok(vec![MoveValue::U64(7).simple_serialize().unwrap()])
+    );
+}
@@ -0,0 +1,99 @@
+module 0x2::A {
+    fun add_u16(a: u16, b: u16): u16 {
+        a + b
+    }
+
+    fun mul_u32(a: u32, b: u32): u32 {
+        a * b
+    }
+
+    fun sub_u256(a: u256, b: u256): u256 {
+        a - b
+    }
+
+    fun add_u256(a: u256, b: u256): u256 {
+        a + b
+    }
+
+    fun xor_u32(a: u32, b: u32): u32 {
+        a ^ b
+    }
+
+    fun shl_u8(a: u8, n: u8): u8 {
+        a << n
+    }
+
+    fun shr_u64(a: u64, n: u8): u64 {
+        a >> n
+    }
+
+    fun shl_u256(a: u256, n: u8): u256 {
+        a << n
+    }
+
+    #[test]
+    public fun add_u16_ok(): u16 {
+        add_u16(65534, 1)
+    }
+
+    #[test, expected_failure]
+    public fun add_u16_overflow(): u16 {
+        add_u16(65535, 1)
+    }
+
+    #[test]
+    public fun mul_u32_ok(): u32 {
+        mul_u32(65536, 65535)
+    }
+
+    #[test, expected_failure]
+    public fun mul_u32_overflow(): u32 {
+        mul_u32(65536, 65536)
+    }
+
+    #[test]
+    public fun sub_u256_ok(): u256 {
+        sub_u256(115792089237316195423570985008687907853269984665640564039457584007913129639935, 1)
+    }
+
+    #[test, expected_failure]
+    public fun sub_u256_underflow(): u256 {
+        sub_u256(0, 1)
+    }
+
+    #[test, expected_failure]
+    public fun add_u256_overflow(): u256 {
+        add_u256(115792089237316195423570985008687907853269984665640564039457584007913129639935, 1)
+    }
+
+    #[test]
+    public fun xor_u32_ok(): u32 {
+        xor_u32(4294967295, 65535)
+    }
+
+    #[test]
+    public fun cast_u256(): u256 {
+        let a = 340282366920938463463374607431768211455u128;
+        (a as u256) + 1
+    }
+
+    #[test]
+    public fun shl_u8_drops_bits(): u8 {
+        shl_u8(255, 4)
+    }
+
+    #[test, expected_failure]
+    public fun shl_u8_overflow(): u8 {
+        shl_u8(1, 8)
+    }
+
+    #[test, expected_failure]
+    public fun shr_u64_overflow(): u64 {
+        shr_u64(1, 64)
+    }
+
+    #[test]
+    public fun shl_u256_ok(): u256 {
+        shl_u256(3, 255)
+    }
+}
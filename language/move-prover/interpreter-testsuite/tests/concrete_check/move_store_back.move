@@ -0,0 +1,18 @@
+module 0x2::A {
+    #[test]
+    public fun move_and_store_back(): u64 {
+        let x = 42;
+        x = move x;
+        x
+    }
+
+    #[test]
+    public fun destroy_copies(): bool {
+        let x = true;
+        let a = copy x;
+        let b = copy x;
+        let _ = a;
+        let _ = b;
+        x
+    }
+}
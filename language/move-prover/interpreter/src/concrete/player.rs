@@ -6,7 +6,7 @@
 
 use std::{collections::BTreeMap, rc::Rc};
 
-use num::{BigInt, ToPrimitive, Zero};
+use num::{BigInt, Signed, ToPrimitive, Zero};
 
 use bytecode_interpreter_crypto::{
     ed25519_deserialize_public_key, ed25519_deserialize_signature, ed25519_verify_signature,
@@ -16,6 +16,7 @@ use move_binary_format::errors::Location;
 use move_core_types::{
     account_address::AccountAddress,
     language_storage::CORE_CODE_ADDRESS,
+    u256,
     vm_status::{sub_status, StatusCode},
 };
 use move_model::{
@@ -441,7 +442,7 @@ impl<'env> FunctionContext<'env> {
             Constant::U32(v) => TypedValue::mk_u32(*v),
             Constant::U64(v) => TypedValue::mk_u64(*v),
             Constant::U128(v) => TypedValue::mk_u128(*v),
-            Constant::U256(_) => unimplemented!(),
+            Constant::U256(v) => TypedValue::mk_u256(u256::U256::from_le_bytes(&v.to_le_bytes())),
             Constant::Address(v) => TypedValue::mk_address(
                 AccountAddress::from_hex_literal(&format!("{:#x}", v)).unwrap(),
             ),
@@ -867,9 +868,8 @@ impl<'env> FunctionContext<'env> {
                 }
                 let rhs = typed_args.remove(1);
                 let lhs = typed_args.remove(0);
-                let calculated =
-                    self.handle_binary_bitshift(op, lhs, rhs, local_state.get_type(dsts[0]));
-                Ok(vec![calculated])
+                self.handle_binary_bitshift(op, lhs, rhs, local_state.get_type(dsts[0]))
+                    .map(|calculated| vec![calculated])
             }
             // binary comparison
             Operation::Lt | Operation::Le | Operation::Ge | Operation::Gt => {
@@ -1732,25 +1732,25 @@ impl<'env> FunctionContext<'env> {
     fn handle_cast_u256(&self, val: TypedValue) -> Result<TypedValue, AbortInfo> {
         let (ty, val, _) = val.decompose();
         let v = if ty.is_u8() {
-            val.into_u8() as u128
+            u256::U256::from(val.into_u8())
         } else if ty.is_u16() {
-            val.into_u16() as u128
+            u256::U256::from(val.into_u16())
         } else if ty.is_u32() {
-            val.into_u32() as u128
+            u256::U256::from(val.into_u32())
         } else if ty.is_u64() {
-            val.into_u64() as u128
+            u256::U256::from(val.into_u64())
         } else if ty.is_u128() {
-            val.into_u128()
+            u256::U256::from(val.into_u128())
+        } else if ty.is_u256() {
+            val.into_u256()
         } else {
             let n = val.into_num();
-            match n.to_u128() {
-                None => {
-                    return Err(self.sys_abort(StatusCode::ARITHMETIC_ERROR));
-                }
-                Some(v) => v,
+            if n.is_negative() || n > BigInt::from(&u256::U256::max_value()) {
+                return Err(self.sys_abort(StatusCode::ARITHMETIC_ERROR));
             }
+            BaseValue::mk_num(n).into_u256()
         };
-        Ok(TypedValue::mk_u128(v))
+        Ok(TypedValue::mk_u256(v))
     }
 
     fn handle_binary_arithmetic(
@@ -1792,6 +1792,20 @@ impl<'env> FunctionContext<'env> {
                 }
                 Some(v) => TypedValue::mk_u8(v),
             }
+        } else if res.is_u16() {
+            match result.to_u16() {
+                None => {
+                    return Err(self.sys_abort(StatusCode::ARITHMETIC_ERROR));
+                }
+                Some(v) => TypedValue::mk_u16(v),
+            }
+        } else if res.is_u32() {
+            match result.to_u32() {
+                None => {
+                    return Err(self.sys_abort(StatusCode::ARITHMETIC_ERROR));
+                }
+                Some(v) => TypedValue::mk_u32(v),
+            }
         } else if res.is_u64() {
             match result.to_u64() {
                 None => {
@@ -1806,6 +1820,11 @@ impl<'env> FunctionContext<'env> {
                 }
                 Some(v) => TypedValue::mk_u128(v),
             }
+        } else if res.is_u256() {
+            if result.is_negative() || result > BigInt::from(&u256::U256::max_value()) {
+                return Err(self.sys_abort(StatusCode::ARITHMETIC_ERROR));
+            }
+            TypedValue::mk_u256(BaseValue::mk_num(result).into_u256())
         } else {
             if cfg!(debug_assertions) {
                 assert!(res.is_num());
@@ -1837,13 +1856,19 @@ impl<'env> FunctionContext<'env> {
 
         if res.is_u8() {
             TypedValue::mk_u8(result.to_u8().unwrap())
+        } else if res.is_u16() {
+            TypedValue::mk_u16(result.to_u16().unwrap())
+        } else if res.is_u32() {
+            TypedValue::mk_u32(result.to_u32().unwrap())
         } else if res.is_u64() {
             TypedValue::mk_u64(result.to_u64().unwrap())
+        } else if res.is_u128() {
+            TypedValue::mk_u128(result.to_u128().unwrap())
         } else {
             if cfg!(debug_assertions) {
-                assert!(res.is_u128());
+                assert!(res.is_u256());
             }
-            TypedValue::mk_u128(result.to_u128().unwrap())
+            TypedValue::mk_u256(BaseValue::mk_num(result).into_u256())
         }
     }
 
@@ -1853,13 +1878,32 @@ impl<'env> FunctionContext<'env> {
         lhs: TypedValue,
         rhs: TypedValue,
         res: &Type,
-    ) -> TypedValue {
+    ) -> Result<TypedValue, AbortInfo> {
         if cfg!(debug_assertions) {
             assert!(res.is_compatible_for_bitshift(lhs.get_ty()));
             assert!(rhs.get_ty().is_u8());
         }
         let rval = rhs.into_u8();
-        if lhs.get_ty().is_u8() {
+        let lty = lhs.get_ty();
+        let bits = if lty.is_u8() {
+            u8::BITS
+        } else if lty.is_u16() {
+            u16::BITS
+        } else if lty.is_u32() {
+            u32::BITS
+        } else if lty.is_u64() {
+            u64::BITS
+        } else if lty.is_u128() {
+            u128::BITS
+        } else {
+            assert!(lty.is_u256());
+            256
+        };
+        if u32::from(rval) >= bits {
+            return Err(self.sys_abort(StatusCode::ARITHMETIC_ERROR));
+        }
+
+        let result = if lty.is_u8() {
             let lval = lhs.into_u8();
             let result = match op {
                 Operation::Shl => lval << rval,
@@ -1867,7 +1911,23 @@ impl<'env> FunctionContext<'env> {
                 _ => unreachable!(),
             };
             TypedValue::mk_u8(result)
-        } else if lhs.get_ty().is_u64() {
+        } else if lty.is_u16() {
+            let lval = lhs.into_u16();
+            let result = match op {
+                Operation::Shl => lval << rval,
+                Operation::Shr => lval >> rval,
+                _ => unreachable!(),
+            };
+            TypedValue::mk_u16(result)
+        } else if lty.is_u32() {
+            let lval = lhs.into_u32();
+            let result = match op {
+                Operation::Shl => lval << rval,
+                Operation::Shr => lval >> rval,
+                _ => unreachable!(),
+            };
+            TypedValue::mk_u32(result)
+        } else if lty.is_u64() {
             let lval = lhs.into_u64();
             let result = match op {
                 Operation::Shl => lval << rval,
@@ -1875,8 +1935,7 @@ impl<'env> FunctionContext<'env> {
                 _ => unreachable!(),
             };
             TypedValue::mk_u64(result)
-        } else {
-            assert!(lhs.get_ty().is_u128());
+        } else if lty.is_u128() {
             let lval = lhs.into_u128();
             let result = match op {
                 Operation::Shl => lval << rval,
@@ -1884,7 +1943,16 @@ impl<'env> FunctionContext<'env> {
                 _ => unreachable!(),
             };
             TypedValue::mk_u128(result)
-        }
+        } else {
+            let lval = lhs.into_u256();
+            let result = match op {
+                Operation::Shl => lval << rval,
+                Operation::Shr => lval >> rval,
+                _ => unreachable!(),
+            };
+            TypedValue::mk_u256(result)
+        };
+        Ok(result)
     }
 
     fn handle_binary_comparison(
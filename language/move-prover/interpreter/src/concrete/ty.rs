@@ -806,8 +806,11 @@ pub fn convert_model_base_type(env: &GlobalEnv, ty: &MT::Type, subst: &[BaseType
     match ty {
         MT::Type::Primitive(MT::PrimitiveType::Bool) => BaseType::mk_bool(),
         MT::Type::Primitive(MT::PrimitiveType::U8) => BaseType::mk_u8(),
+        MT::Type::Primitive(MT::PrimitiveType::U16) => BaseType::mk_u16(),
+        MT::Type::Primitive(MT::PrimitiveType::U32) => BaseType::mk_u32(),
         MT::Type::Primitive(MT::PrimitiveType::U64) => BaseType::mk_u64(),
         MT::Type::Primitive(MT::PrimitiveType::U128) => BaseType::mk_u128(),
+        MT::Type::Primitive(MT::PrimitiveType::U256) => BaseType::mk_u256(),
         MT::Type::Primitive(MT::PrimitiveType::Num) => BaseType::mk_num(),
         MT::Type::Primitive(MT::PrimitiveType::Address) => BaseType::mk_address(),
         MT::Type::Primitive(MT::PrimitiveType::Signer) => BaseType::mk_signer(),
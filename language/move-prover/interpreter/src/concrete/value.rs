@@ -116,12 +116,14 @@ impl BaseValue {
     }
     pub fn into_u256(self) -> u256::U256 {
         match self {
-            Self::Int(v) => u256::U256::from_le_bytes(
-                &v.to_bytes_le()
-                    .1
-                    .try_into()
-                    .unwrap_or_else(|_| panic!("Cannot convert {} to U256", v)),
-            ),
+            Self::Int(v) => {
+                let mut bytes = v.to_bytes_le().1;
+                if bytes.len() > 32 {
+                    panic!("Cannot convert {} to U256", v);
+                }
+                bytes.resize(32, 0);
+                u256::U256::from_le_bytes(&bytes.try_into().unwrap())
+            }
             _ => unreachable!(),
         }
     }
@@ -264,6 +266,13 @@ impl TypedValue {
             ptr: Pointer::None,
         }
     }
+    pub fn mk_u256(v: u256::U256) -> Self {
+        Self {
+            ty: Type::mk_u256(),
+            val: BaseValue::mk_u256(v),
+            ptr: Pointer::None,
+        }
+    }
     pub fn mk_num(v: BigInt) -> Self {
         Self {
             ty: Type::mk_num(),
@@ -414,6 +423,18 @@ impl TypedValue {
         }
         self.val.into_u8()
     }
+    pub fn into_u16(self) -> u16 {
+        if cfg!(debug_assertions) {
+            assert!(self.ty.is_u16());
+        }
+        self.val.into_u16()
+    }
+    pub fn into_u32(self) -> u32 {
+        if cfg!(debug_assertions) {
+            assert!(self.ty.is_u32());
+        }
+        self.val.into_u32()
+    }
     pub fn into_u64(self) -> u64 {
         if cfg!(debug_assertions) {
             assert!(self.ty.is_u64());
@@ -426,6 +447,12 @@ impl TypedValue {
         }
         self.val.into_u128()
     }
+    pub fn into_u256(self) -> u256::U256 {
+        if cfg!(debug_assertions) {
+            assert!(self.ty.is_u256());
+        }
+        self.val.into_u256()
+    }
     pub fn into_num(self) -> BigInt {
         if cfg!(debug_assertions) {
             assert!(self.ty.is_num());
@@ -0,0 +1,11 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Err(divergence) = move_vm_libfuzzer::run(data) {
+        panic!("{}", divergence)
+    }
+});
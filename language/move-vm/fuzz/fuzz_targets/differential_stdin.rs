@@ -0,0 +1,17 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Runs the differential check on a single input read from stdin, for AFL (built with
+//! `cargo afl build`) and for replaying inputs found by other fuzzers.
+
+use std::io::Read;
+
+fn main() {
+    let mut data = vec![];
+    std::io::stdin()
+        .read_to_end(&mut data)
+        .expect("Unable to read input from stdin");
+    if let Err(divergence) = move_vm_libfuzzer::run(&data) {
+        panic!("{}", divergence)
+    }
+}
@@ -0,0 +1,251 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Differential fuzzing of the Move VM against the stackless bytecode interpreter.
+//!
+//! A fuzz input is a seed followed by argument bytes. The seed deterministically generates a
+//! module with `module-generation` and `test-generation`; if the module passes the bytecode
+//! verifier, every function whose parameters can be supplied by a caller is executed on both the
+//! Move VM and the stackless interpreter, with arguments taken from the remaining bytes of the
+//! input. Any difference in return values, aborts or change sets is reported as a
+//! `Divergence`.
+
+#![forbid(unsafe_code)]
+
+use arbitrary::Unstructured;
+use module_generation::{generate_module, ModuleGeneratorOptions};
+use move_binary_format::{
+    access::ModuleAccess,
+    errors::VMResult,
+    file_format::{FunctionDefinitionIndex, Signature, SignatureToken},
+    CompiledModule,
+};
+use move_bytecode_verifier::verify_module;
+use move_core_types::{
+    account_address::AccountAddress,
+    effects::ChangeSet,
+    identifier::Identifier,
+    u256,
+    value::{serialize_values, MoveValue},
+};
+use move_model::run_bytecode_model_builder;
+use move_stackless_bytecode_interpreter::{
+    concrete::{settings::InterpreterSettings, value::GlobalState},
+    shared::bridge::{adapt_move_vm_change_set, adapt_move_vm_result},
+    StacklessBytecodeInterpreter,
+};
+use move_vm_runtime::move_vm::MoveVM;
+use move_vm_test_utils::InMemoryStorage;
+use move_vm_types::gas::UnmeteredGasMeter;
+use rand::{rngs::StdRng, SeedableRng};
+use std::fmt;
+use test_generation::{bytecode_module, config::module_generation_settings};
+
+/// The number of leading input bytes used as the seed for module generation. Shorter inputs are
+/// padded with zeros.
+pub const SEED_LENGTH: usize = 32;
+
+/// The maximum length of a vector argument.
+const MAX_VECTOR_LENGTH: usize = 8;
+
+/// The return values, or the error, of an execution and its change set.
+pub type ExecutionResult = (Result<Vec<Vec<u8>>, String>, Result<ChangeSet, String>);
+
+/// A function call on which the Move VM and the stackless interpreter disagree.
+#[derive(Debug)]
+pub struct Divergence {
+    pub function: Identifier,
+    pub args: Vec<MoveValue>,
+    /// The Move VM's result, adapted to drop what the stackless interpreter does not report.
+    pub move_vm: ExecutionResult,
+    pub stackless_vm: ExecutionResult,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Move VM and stackless VM diverge on {}({:?})",
+            self.function, self.args
+        )?;
+        writeln!(f, "Move VM result: {:?}", self.move_vm.0)?;
+        writeln!(f, "Move VM change set: {:?}", self.move_vm.1)?;
+        writeln!(f, "stackless VM result: {:?}", self.stackless_vm.0)?;
+        write!(f, "stackless VM change set: {:?}", self.stackless_vm.1)
+    }
+}
+
+/// Split a fuzz input into the module generation seed and the argument bytes.
+pub fn split_input(data: &[u8]) -> ([u8; SEED_LENGTH], &[u8]) {
+    let (seed_bytes, rest) = data.split_at(data.len().min(SEED_LENGTH));
+    let mut seed = [0u8; SEED_LENGTH];
+    seed[..seed_bytes.len()].copy_from_slice(seed_bytes);
+    (seed, rest)
+}
+
+/// Generate a module from `seed`, returning it only if it passes the bytecode verifier. The same
+/// seed always yields the same module.
+pub fn generate_verified_module(seed: [u8; SEED_LENGTH]) -> Option<CompiledModule> {
+    let mut rng = StdRng::from_seed(seed);
+    // the bytecode generator does not handle generics reliably, so no type parameters are
+    // generated: `max_ty_params` is an exclusive bound
+    let options = ModuleGeneratorOptions {
+        max_ty_params: 1,
+        ..module_generation_settings()
+    };
+    let module = generate_module(&mut rng, options);
+    let module = bytecode_module(&mut rng, module);
+    verify_module(&module).ok().map(|_| module)
+}
+
+/// Run the differential check on a fuzz input, returning the number of functions executed, or
+/// `None` if the module does not verify. Such inputs are ignored.
+pub fn run(data: &[u8]) -> Result<Option<usize>, Box<Divergence>> {
+    let (seed, arg_bytes) = split_input(data);
+    match generate_verified_module(seed) {
+        Some(module) => run_module(&module, &mut Unstructured::new(arg_bytes)).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Execute every function of `module` which can be called with generated arguments on both VMs,
+/// returning the number of functions executed.
+pub fn run_module(module: &CompiledModule, u: &mut Unstructured) -> Result<usize, Box<Divergence>> {
+    let module_id = module.self_id();
+    let mut storage = InMemoryStorage::new();
+    let mut blob = vec![];
+    module.serialize(&mut blob).unwrap();
+    storage.publish_or_overwrite_module(module_id.clone(), blob);
+    let vm = MoveVM::new(vec![]).unwrap();
+
+    let env = run_bytecode_model_builder(vec![module]).unwrap();
+    let interpreter = StacklessBytecodeInterpreter::new(&env, None, InterpreterSettings::default());
+
+    let mut executed = 0;
+    for idx in 0..module.function_defs().len() {
+        let handle = module.function_handle_at(
+            module
+                .function_def_at(FunctionDefinitionIndex(idx as u16))
+                .function,
+        );
+        if !handle.type_parameters.is_empty() {
+            continue;
+        }
+        let args = match arguments(module.signature_at(handle.parameters), u) {
+            Ok(Some(args)) => args,
+            Ok(None) => continue,
+            // the input is exhausted
+            Err(_) => return Ok(executed),
+        };
+        let function = module.identifier_at(handle.name).to_owned();
+
+        executed += 1;
+        let mut session = vm.new_session(&storage);
+        let return_result = session
+            .execute_function_bypass_visibility(
+                &module_id,
+                &function,
+                vec![],
+                serialize_values(&args),
+                &mut UnmeteredGasMeter,
+            )
+            .map(|values| {
+                values
+                    .return_values
+                    .into_iter()
+                    .map(|(bytes, _layout)| bytes)
+                    .collect::<Vec<_>>()
+            });
+        // the effects of a failed execution are discarded
+        let move_vm_change_set = if return_result.is_ok() {
+            let change_set = session.finish().map(|(change_set, _events)| change_set);
+            adapt_move_vm_change_set(adapt_move_vm_result(change_set), &storage)
+        } else {
+            Ok(ChangeSet::new())
+        };
+        let move_vm_result = adapt_move_vm_result(return_result);
+
+        let (stackless_vm_result, stackless_vm_change_set, _) =
+            interpreter.interpret(&module_id, &function, &[], &args, &GlobalState::default());
+        let stackless_vm_change_set = if stackless_vm_result.is_ok() {
+            Ok(stackless_vm_change_set)
+        } else {
+            Ok(ChangeSet::new())
+        };
+
+        if move_vm_result != stackless_vm_result || move_vm_change_set != stackless_vm_change_set {
+            return Err(Box::new(Divergence {
+                function,
+                args,
+                move_vm: (describe(move_vm_result), describe(move_vm_change_set)),
+                stackless_vm: (
+                    describe(stackless_vm_result),
+                    describe(stackless_vm_change_set),
+                ),
+            }));
+        }
+    }
+    Ok(executed)
+}
+
+/// Generate arguments for a function with `parameters`, or `None` if it has a parameter no caller
+/// can supply, such as a struct or a reference.
+fn arguments(
+    parameters: &Signature,
+    u: &mut Unstructured,
+) -> arbitrary::Result<Option<Vec<MoveValue>>> {
+    if !parameters.0.iter().all(is_argument_type) {
+        return Ok(None);
+    }
+    parameters
+        .0
+        .iter()
+        .map(|token| argument_value(token, u))
+        .collect::<arbitrary::Result<_>>()
+        .map(Some)
+}
+
+fn describe<T>(result: VMResult<T>) -> Result<T, String> {
+    result.map_err(|err| format!("{:?}", err))
+}
+
+fn is_argument_type(token: &SignatureToken) -> bool {
+    use SignatureToken::*;
+    match token {
+        Bool | U8 | U16 | U32 | U64 | U128 | U256 | Address | Signer => true,
+        Vector(elem) => is_argument_type(elem) && **elem != Signer,
+        Struct(_)
+        | StructInstantiation(_, _)
+        | Reference(_)
+        | MutableReference(_)
+        | TypeParameter(_) => false,
+    }
+}
+
+fn argument_value(token: &SignatureToken, u: &mut Unstructured) -> arbitrary::Result<MoveValue> {
+    use SignatureToken::*;
+    Ok(match token {
+        Bool => MoveValue::Bool(u.arbitrary()?),
+        U8 => MoveValue::U8(u.arbitrary()?),
+        U16 => MoveValue::U16(u.arbitrary()?),
+        U32 => MoveValue::U32(u.arbitrary()?),
+        U64 => MoveValue::U64(u.arbitrary()?),
+        U128 => MoveValue::U128(u.arbitrary()?),
+        U256 => MoveValue::U256(u256::U256::from_le_bytes(&u.arbitrary()?)),
+        Address => MoveValue::Address(AccountAddress::new(u.arbitrary()?)),
+        Signer => MoveValue::Signer(AccountAddress::new(u.arbitrary()?)),
+        Vector(elem) => {
+            let len = u.int_in_range(0..=MAX_VECTOR_LENGTH)?;
+            MoveValue::Vector(
+                (0..len)
+                    .map(|_| argument_value(elem, u))
+                    .collect::<arbitrary::Result<_>>()?,
+            )
+        }
+        Struct(_)
+        | StructInstantiation(_, _)
+        | Reference(_)
+        | MutableReference(_)
+        | TypeParameter(_) => unreachable!("not an argument type: {:?}", token),
+    })
+}
@@ -0,0 +1,42 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_vm_libfuzzer::{generate_verified_module, run, split_input, SEED_LENGTH};
+
+#[test]
+fn generation_is_deterministic() {
+    for i in 0..4u8 {
+        let seed = [i; SEED_LENGTH];
+        assert_eq!(
+            generate_verified_module(seed),
+            generate_verified_module(seed)
+        );
+    }
+}
+
+#[test]
+fn split_short_input() {
+    let (seed, rest) = split_input(&[7, 7]);
+    assert_eq!(seed[..3], [7, 7, 0]);
+    assert!(rest.is_empty());
+}
+
+#[test]
+fn no_divergence() {
+    let mut verified_modules = 0;
+    let mut executed_functions = 0;
+    for i in 0..16u8 {
+        let mut data = vec![i; SEED_LENGTH];
+        data.extend((0..=255u8).map(|b| b.wrapping_mul(i)));
+        match run(&data) {
+            Ok(Some(executed)) => {
+                verified_modules += 1;
+                executed_functions += executed;
+            }
+            Ok(None) => (),
+            Err(divergence) => panic!("{}", divergence),
+        }
+    }
+    assert!(verified_modules > 0);
+    assert!(executed_functions > 0);
+}
@@ -0,0 +1,154 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::compiler::{as_module, compile_units};
+use move_core_types::{
+    account_address::AccountAddress,
+    effects::{AccessSet, ResourceAccess},
+    identifier::Identifier,
+    language_storage::{ModuleId, ResourceKey, StructTag},
+    value::{serialize_values, MoveValue},
+};
+use move_vm_runtime::move_vm::MoveVM;
+use move_vm_test_utils::InMemoryStorage;
+use move_vm_types::gas::UnmeteredGasMeter;
+use std::collections::BTreeSet;
+
+const TEST_ADDR: AccountAddress = AccountAddress::new([42; AccountAddress::LENGTH]);
+
+// Run `function` and apply its changes to `storage`.
+fn run(
+    vm: &MoveVM,
+    storage: &mut InMemoryStorage,
+    function: &str,
+    args: Vec<MoveValue>,
+) -> AccessSet {
+    let mut sess = vm.new_session(storage);
+    sess.execute_function_bypass_visibility(
+        &ModuleId::new(TEST_ADDR, Identifier::new("B").unwrap()),
+        &Identifier::new(function).unwrap(),
+        vec![],
+        serialize_values(&args),
+        &mut UnmeteredGasMeter,
+    )
+    .unwrap();
+    let (change_set, _, access_set) = sess.finish_with_access_set().unwrap();
+    storage.apply(change_set).unwrap();
+    access_set
+}
+
+fn key(addr: AccountAddress, name: &str) -> ResourceKey {
+    ResourceKey::new(
+        addr,
+        StructTag {
+            address: TEST_ADDR,
+            module: Identifier::new("B").unwrap(),
+            name: Identifier::new(name).unwrap(),
+            type_params: vec![],
+        },
+    )
+}
+
+#[test]
+fn access_set() {
+    let code = r#"
+        module {{ADDR}}::A {
+            public fun id(x: u64): u64 { x }
+        }
+
+        module {{ADDR}}::B {
+            use {{ADDR}}::A;
+
+            struct R has key { v: u64 }
+
+            struct S has key { v: u64 }
+
+            public fun publish(s: &signer) {
+                move_to(s, R { v: A::id(1) });
+            }
+
+            public fun touch(s: &signer, other: address) acquires R, S {
+                let addr = @{{ADDR}};
+                if (exists<S>(other)) {
+                    let S { v: _ } = move_from<S>(other);
+                };
+                let v = borrow_global<R>(other).v;
+                let r = borrow_global_mut<R>(addr);
+                r.v = r.v + v;
+                move_to(s, S { v: 0 });
+            }
+        }
+    "#;
+    let code = code.replace("{{ADDR}}", &format!("0x{}", TEST_ADDR));
+    let mut units = compile_units(&code).unwrap();
+    let mut storage = InMemoryStorage::new();
+    while let Some(unit) = units.pop() {
+        let m = as_module(unit);
+        let mut blob = vec![];
+        m.serialize(&mut blob).unwrap();
+        storage.publish_or_overwrite_module(m.self_id(), blob);
+    }
+    let vm = MoveVM::new(vec![]).unwrap();
+    let other = AccountAddress::ONE;
+    let module_a = ModuleId::new(TEST_ADDR, Identifier::new("A").unwrap());
+    let module_b = ModuleId::new(TEST_ADDR, Identifier::new("B").unwrap());
+
+    let access_set = run(
+        &vm,
+        &mut storage,
+        "publish",
+        vec![MoveValue::Signer(TEST_ADDR)],
+    );
+    assert_eq!(
+        access_set.resources().iter().collect::<Vec<_>>(),
+        vec![(
+            &key(TEST_ADDR, "R"),
+            &BTreeSet::from([ResourceAccess::MoveTo])
+        )]
+    );
+    assert!(access_set.keys_read().is_empty());
+    assert_eq!(
+        access_set.keys_written(),
+        BTreeSet::from([key(TEST_ADDR, "R")])
+    );
+    assert_eq!(
+        access_set.modules(),
+        &BTreeSet::from([module_a.clone(), module_b.clone()])
+    );
+
+    run(&vm, &mut storage, "publish", vec![MoveValue::Signer(other)]);
+    // the modules are now cached, but their accesses are still recorded
+    let access_set = run(
+        &vm,
+        &mut storage,
+        "touch",
+        vec![MoveValue::Signer(TEST_ADDR), MoveValue::Address(other)],
+    );
+    assert_eq!(
+        access_set.resources().iter().collect::<Vec<_>>(),
+        vec![
+            (
+                &key(other, "R"),
+                &BTreeSet::from([ResourceAccess::BorrowGlobal])
+            ),
+            (&key(other, "S"), &BTreeSet::from([ResourceAccess::Exists])),
+            (
+                &key(TEST_ADDR, "R"),
+                &BTreeSet::from([ResourceAccess::BorrowGlobalMut])
+            ),
+            (
+                &key(TEST_ADDR, "S"),
+                &BTreeSet::from([ResourceAccess::MoveTo])
+            ),
+        ]
+    );
+    assert_eq!(
+        access_set.keys_read(),
+        BTreeSet::from([key(other, "R"), key(other, "S"), key(TEST_ADDR, "R")])
+    );
+    assert_eq!(
+        access_set.keys_written(),
+        BTreeSet::from([key(TEST_ADDR, "R"), key(TEST_ADDR, "S")])
+    );
+    assert_eq!(access_set.modules(), &BTreeSet::from([module_a, module_b]));
+}
@@ -0,0 +1,94 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::compiler::{as_module, compile_units};
+use move_core_types::{
+    account_address::AccountAddress,
+    gas_algebra::NumBytes,
+    identifier::Identifier,
+    language_storage::ModuleId,
+    value::{serialize_values, MoveValue},
+    vm_status::StatusCode,
+};
+use move_vm_runtime::move_vm::MoveVM;
+use move_vm_test_utils::{gas_schedule::GasStatus, InMemoryStorage};
+
+const TEST_ADDR: AccountAddress = AccountAddress::new([42; AccountAddress::LENGTH]);
+
+fn grow(n: u64, gas_status: &mut GasStatus) -> Result<(), StatusCode> {
+    let code = r#"
+        address std {
+            module vector {
+                #[bytecode_instruction]
+                native public fun empty<Element>(): vector<Element>;
+
+                #[bytecode_instruction]
+                native public fun push_back<Element>(v: &mut vector<Element>, e: Element);
+            }
+        }
+
+        module {{ADDR}}::M {
+            use std::vector;
+
+            public fun grow(n: u64) {
+                let v = vector::empty<vector<u64>>();
+                let i = 0;
+                while (i < n) {
+                    vector::push_back(&mut v, vector[i, i, i, i, i, i, i, i]);
+                    i = i + 1;
+                }
+            }
+        }
+    "#;
+    let code = code.replace("{{ADDR}}", &format!("0x{}", TEST_ADDR));
+    let mut units = compile_units(&code).unwrap();
+    let m = as_module(units.pop().unwrap());
+    let mut blob = vec![];
+    m.serialize(&mut blob).unwrap();
+
+    let mut storage = InMemoryStorage::new();
+    let module_id = ModuleId::new(TEST_ADDR, Identifier::new("M").unwrap());
+    storage.publish_or_overwrite_module(module_id.clone(), blob);
+
+    let vm = MoveVM::new(vec![]).unwrap();
+    let mut sess = vm.new_session(&storage);
+    sess.execute_function_bypass_visibility(
+        &module_id,
+        &Identifier::new("grow").unwrap(),
+        vec![],
+        serialize_values(&vec![MoveValue::U64(n)]),
+        gas_status,
+    )
+    .map(|_| ())
+    .map_err(|e| e.major_status())
+}
+
+#[test]
+fn heap_memory_is_released() {
+    let mut gas_status = GasStatus::new_unmetered();
+    grow(10, &mut gas_status).unwrap();
+    assert!(gas_status.peak_heap_memory_used() > NumBytes::zero());
+    // all the vectors are dropped when returning
+    assert_eq!(gas_status.heap_memory_used(), NumBytes::zero());
+}
+
+#[test]
+fn heap_memory_quota() {
+    let quota = NumBytes::new(1000);
+
+    let mut gas_status = GasStatus::new_unmetered();
+    gas_status.set_heap_memory_quota(Some(quota));
+    grow(1, &mut gas_status).unwrap();
+    assert!(gas_status.peak_heap_memory_used() <= quota);
+
+    let mut gas_status = GasStatus::new_unmetered();
+    gas_status.set_heap_memory_quota(Some(quota));
+    assert_eq!(
+        grow(100, &mut gas_status),
+        Err(StatusCode::MEMORY_LIMIT_EXCEEDED)
+    );
+
+    let mut gas_status = GasStatus::new_unmetered();
+    grow(100, &mut gas_status).unwrap();
+    assert!(gas_status.peak_heap_memory_used() > quota);
+}
@@ -2,12 +2,20 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+mod access_set_tests;
 mod bad_entry_point_tests;
 mod bad_storage_tests;
 mod binary_format_version;
 mod exec_func_effects_tests;
 mod function_arg_tests;
+mod heap_memory_tests;
 mod loader_tests;
 mod mutated_accounts_tests;
+mod native_function_tests;
 mod nested_loop_tests;
+mod nested_session_tests;
+mod republish_tests;
 mod return_value_tests;
+mod runtime_limits_tests;
+mod savepoint_tests;
+mod verified_module_cache_tests;
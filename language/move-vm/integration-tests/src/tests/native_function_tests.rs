@@ -0,0 +1,150 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::compiler::{as_module, compile_units};
+use move_binary_format::errors::VMResult;
+use move_core_types::{
+    account_address::AccountAddress,
+    gas_algebra::InternalGas,
+    identifier::Identifier,
+    language_storage::ModuleId,
+    value::{serialize_values, MoveValue},
+    vm_status::StatusCode,
+};
+use move_vm_runtime::{
+    move_vm::MoveVM,
+    native_functions::{NativeFunction, NativeSignature, NativeType},
+};
+use move_vm_test_utils::InMemoryStorage;
+use move_vm_types::{
+    gas::UnmeteredGasMeter, natives::function::NativeResult, pop_arg, values::Value,
+};
+use std::sync::Arc;
+
+const TEST_ADDR: AccountAddress = AccountAddress::new([42; AccountAddress::LENGTH]);
+
+fn module_blob() -> Vec<u8> {
+    let code = r#"
+        module {{ADDR}}::M {
+            native fun combine(a: u64, b: u64): u64;
+
+            public fun call(a: u64, b: u64): u64 {
+                combine(a, b)
+            }
+        }
+    "#;
+    let code = code.replace("{{ADDR}}", &format!("0x{}", TEST_ADDR));
+    let mut units = compile_units(&code).unwrap();
+    let m = as_module(units.pop().unwrap());
+    let mut blob = vec![];
+    m.serialize(&mut blob).unwrap();
+    blob
+}
+
+fn setup() -> InMemoryStorage {
+    let mut storage = InMemoryStorage::new();
+    storage.publish_or_overwrite_module(
+        ModuleId::new(TEST_ADDR, Identifier::new("M").unwrap()),
+        module_blob(),
+    );
+    storage
+}
+
+fn native(f: fn(u64, u64) -> u64) -> NativeFunction {
+    Arc::new(move |_context, _ty_args, mut args| {
+        let b = pop_arg!(args, u64);
+        let a = pop_arg!(args, u64);
+        Ok(NativeResult::ok(
+            InternalGas::zero(),
+            vec![Value::u64(f(a, b))].into(),
+        ))
+    })
+}
+
+fn native_entry(
+    f: fn(u64, u64) -> u64,
+) -> (AccountAddress, Identifier, Identifier, NativeFunction) {
+    (
+        TEST_ADDR,
+        Identifier::new("M").unwrap(),
+        Identifier::new("combine").unwrap(),
+        native(f),
+    )
+}
+
+fn call(vm: &MoveVM, storage: &InMemoryStorage) -> VMResult<u64> {
+    let mut sess = vm.new_session(storage);
+    let values = sess.execute_function_bypass_visibility(
+        &ModuleId::new(TEST_ADDR, Identifier::new("M").unwrap()),
+        &Identifier::new("call").unwrap(),
+        vec![],
+        serialize_values(&vec![MoveValue::U64(2), MoveValue::U64(3)]),
+        &mut UnmeteredGasMeter,
+    )?;
+    let (bytes, _) = &values.return_values[0];
+    Ok(bcs_u64(bytes))
+}
+
+fn bcs_u64(bytes: &[u8]) -> u64 {
+    u64::from_le_bytes(bytes.try_into().unwrap())
+}
+
+#[test]
+fn register_natives_on_live_vm() {
+    let storage = setup();
+    let vm = MoveVM::new(vec![]).unwrap();
+    // modules in storage are expected to load, so a missing native is an unexpected error
+    assert_eq!(
+        call(&vm, &storage).unwrap_err().major_status(),
+        StatusCode::UNEXPECTED_VERIFIER_ERROR
+    );
+
+    vm.register_natives(vec![native_entry(|a, b| a + b)]);
+    assert_eq!(call(&vm, &storage).unwrap(), 5);
+
+    // replacing the native evicts the loaded module, so the new implementation is used
+    vm.register_natives(vec![native_entry(|a, b| a * b)]);
+    assert_eq!(call(&vm, &storage).unwrap(), 6);
+}
+
+#[test]
+fn typed_native_signature_is_checked() {
+    let storage = setup();
+    let typed_entry = |parameters| {
+        let (addr, module_name, func_name, func) = native_entry(|a, b| a + b);
+        let signature = NativeSignature {
+            type_parameters: 0,
+            parameters,
+            return_: vec![NativeType::U64],
+        };
+        (addr, module_name, func_name, signature, func)
+    };
+
+    let vm = MoveVM::new(vec![]).unwrap();
+    vm.register_typed_natives(vec![typed_entry(vec![NativeType::U64, NativeType::U64])]);
+    assert_eq!(call(&vm, &storage).unwrap(), 5);
+
+    let vm = MoveVM::new(vec![]).unwrap();
+    vm.register_typed_natives(vec![typed_entry(vec![NativeType::U64])]);
+    let err = call(&vm, &storage).unwrap_err();
+    assert_eq!(err.major_status(), StatusCode::UNEXPECTED_VERIFIER_ERROR);
+    assert!(err
+        .message()
+        .unwrap()
+        .contains("NATIVE_FUNCTION_SIGNATURE_MISMATCH"));
+    // publishing reports the mismatch directly
+    let empty_storage = InMemoryStorage::new();
+    let mut sess = vm.new_session(&empty_storage);
+    let err = sess
+        .publish_module(module_blob(), TEST_ADDR, &mut UnmeteredGasMeter)
+        .unwrap_err();
+    assert_eq!(
+        err.major_status(),
+        StatusCode::NATIVE_FUNCTION_SIGNATURE_MISMATCH
+    );
+    drop(sess);
+
+    // registering a native without a signature drops the previous one
+    vm.register_natives(vec![native_entry(|a, b| a + b)]);
+    assert_eq!(call(&vm, &storage).unwrap(), 5);
+}
@@ -0,0 +1,252 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::compiler::{as_module, compile_units};
+use move_core_types::{
+    account_address::AccountAddress,
+    effects::{AccessSet, ChangeSet, Op, ResourceAccess},
+    identifier::Identifier,
+    language_storage::{ModuleId, ResourceKey, StructTag, TypeTag},
+    resolver::MoveResolver,
+    value::{serialize_values, MoveValue},
+    vm_status::StatusCode,
+};
+use move_vm_runtime::{layered_resolver::LayeredResolver, move_vm::MoveVM, session::Session};
+use move_vm_test_utils::InMemoryStorage;
+use move_vm_types::gas::UnmeteredGasMeter;
+use std::collections::BTreeSet;
+
+const TEST_ADDR: AccountAddress = AccountAddress::new([42; AccountAddress::LENGTH]);
+
+fn counter_module() -> Vec<u8> {
+    let code = r#"
+        module {{ADDR}}::Counter {
+            struct Counter has key { value: u64 }
+            public fun init(account: &signer) {
+                move_to(account, Counter { value: 0 })
+            }
+            public fun increment(addr: address) acquires Counter {
+                let counter = borrow_global_mut<Counter>(addr);
+                counter.value = counter.value + 1;
+            }
+            public fun destroy(addr: address) acquires Counter {
+                let Counter { value: _ } = move_from<Counter>(addr);
+            }
+        }
+    "#;
+    let code = code.replace("{{ADDR}}", &format!("0x{}", TEST_ADDR));
+    let mut units = compile_units(&code).unwrap();
+    let m = as_module(units.pop().unwrap());
+    let mut blob = vec![];
+    m.serialize(&mut blob).unwrap();
+    blob
+}
+
+fn counter_module_id() -> ModuleId {
+    ModuleId::new(TEST_ADDR, Identifier::new("Counter").unwrap())
+}
+
+fn counter_tag() -> StructTag {
+    StructTag {
+        address: TEST_ADDR,
+        module: Identifier::new("Counter").unwrap(),
+        name: Identifier::new("Counter").unwrap(),
+        type_params: vec![],
+    }
+}
+
+fn call<S: MoveResolver>(sess: &mut Session<S>, function: &str, arg: MoveValue) {
+    sess.execute_function_bypass_visibility(
+        &counter_module_id(),
+        &Identifier::new(function).unwrap(),
+        vec![],
+        serialize_values(&vec![arg]),
+        &mut UnmeteredGasMeter,
+    )
+    .unwrap();
+}
+
+fn resource_op(change_set: &ChangeSet) -> Option<Op<Vec<u8>>> {
+    change_set
+        .resources()
+        .find(|(addr, tag, _)| *addr == TEST_ADDR && **tag == counter_tag())
+        .map(|(_, _, op)| op.map(|blob| blob.to_vec()))
+}
+
+fn counter_value(value: u64) -> Vec<u8> {
+    MoveValue::U64(value).simple_serialize().unwrap()
+}
+
+#[test]
+fn publish_and_init_in_child_session() {
+    let storage = InMemoryStorage::new();
+    let vm = MoveVM::new(vec![]).unwrap();
+
+    let mut sess = vm.new_session(&storage);
+    sess.publish_module(counter_module(), TEST_ADDR, &mut UnmeteredGasMeter)
+        .unwrap();
+    let resolver = sess.layered_resolver().unwrap();
+    let mut child = sess.new_child_session(&resolver).unwrap();
+    call(&mut child, "init", MoveValue::Signer(TEST_ADDR));
+
+    // a grandchild sees the effects of both its ancestors
+    let grandchild_resolver = child.layered_resolver().unwrap();
+    let mut grandchild = child.new_child_session(&grandchild_resolver).unwrap();
+    call(&mut grandchild, "increment", MoveValue::Address(TEST_ADDR));
+    let (change_set, events, access_set) = grandchild.finish_with_access_set().unwrap();
+    child
+        .merge_child_effects(change_set, events, access_set)
+        .unwrap();
+
+    let (child_change_set, events, access_set) = child.finish_with_access_set().unwrap();
+    assert!(child_change_set.modules().next().is_none());
+    sess.merge_child_effects(child_change_set, events, access_set)
+        .unwrap();
+    let (change_set, _) = sess.finish().unwrap();
+
+    assert_eq!(
+        change_set
+            .modules()
+            .map(|(_, _, op)| op)
+            .collect::<Vec<_>>(),
+        vec![Op::New(counter_module().as_slice())]
+    );
+    assert_eq!(resource_op(&change_set), Some(Op::New(counter_value(1))));
+
+    // the same as finishing the parent and squashing the child's changes by hand
+    let mut sess = vm.new_session(&storage);
+    sess.publish_module(counter_module(), TEST_ADDR, &mut UnmeteredGasMeter)
+        .unwrap();
+    let (mut expected, _) = sess.finish().unwrap();
+    let resolver = LayeredResolver::new(&storage, expected.clone());
+    let mut sess = vm.new_session(&resolver);
+    call(&mut sess, "init", MoveValue::Signer(TEST_ADDR));
+    call(&mut sess, "increment", MoveValue::Address(TEST_ADDR));
+    expected.squash(sess.finish().unwrap().0).unwrap();
+    assert_eq!(change_set, expected);
+}
+
+#[test]
+fn merge_follows_squash() {
+    let mut storage = InMemoryStorage::new();
+    storage.publish_or_overwrite_module(counter_module_id(), counter_module());
+    let vm = MoveVM::new(vec![]).unwrap();
+    let mut sess = vm.new_session(&storage);
+    call(&mut sess, "init", MoveValue::Signer(TEST_ADDR));
+    let (change_set, _) = sess.finish().unwrap();
+    storage.apply(change_set).unwrap();
+
+    // parent modifies, child deletes
+    let mut sess = vm.new_session(&storage);
+    call(&mut sess, "increment", MoveValue::Address(TEST_ADDR));
+    let resolver = sess.layered_resolver().unwrap();
+    let mut child = sess.new_child_session(&resolver).unwrap();
+    call(&mut child, "destroy", MoveValue::Address(TEST_ADDR));
+    let (child_change_set, events, access_set) = child.finish_with_access_set().unwrap();
+    sess.merge_child_effects(child_change_set, events, access_set)
+        .unwrap();
+    let (change_set, _) = sess.finish().unwrap();
+    assert_eq!(resource_op(&change_set), Some(Op::Delete));
+
+    // parent deletes, child creates
+    let mut sess = vm.new_session(&storage);
+    call(&mut sess, "destroy", MoveValue::Address(TEST_ADDR));
+    let resolver = sess.layered_resolver().unwrap();
+    let mut child = sess.new_child_session(&resolver).unwrap();
+    call(&mut child, "init", MoveValue::Signer(TEST_ADDR));
+    call(&mut child, "increment", MoveValue::Address(TEST_ADDR));
+    let (child_change_set, events, access_set) = child.finish_with_access_set().unwrap();
+    sess.merge_child_effects(child_change_set, events, access_set)
+        .unwrap();
+    let (change_set, _) = sess.finish().unwrap();
+    assert_eq!(resource_op(&change_set), Some(Op::Modify(counter_value(1))));
+
+    // a child which did nothing leaves the parent untouched
+    let mut sess = vm.new_session(&storage);
+    call(&mut sess, "increment", MoveValue::Address(TEST_ADDR));
+    let resolver = sess.layered_resolver().unwrap();
+    let child = sess.new_child_session(&resolver).unwrap();
+    let (child_change_set, events, access_set) = child.finish_with_access_set().unwrap();
+    sess.merge_child_effects(child_change_set, events, access_set)
+        .unwrap();
+    let (change_set, _) = sess.finish().unwrap();
+    assert_eq!(resource_op(&change_set), Some(Op::Modify(counter_value(1))));
+}
+
+#[test]
+fn inconsistent_merge_fails() {
+    let mut storage = InMemoryStorage::new();
+    storage.publish_or_overwrite_module(counter_module_id(), counter_module());
+    let vm = MoveVM::new(vec![]).unwrap();
+
+    let mut sess = vm.new_session(&storage);
+    let resolver = sess.layered_resolver().unwrap();
+    let mut child = sess.new_child_session(&resolver).unwrap();
+    call(&mut child, "init", MoveValue::Signer(TEST_ADDR));
+    let (child_change_set, events, access_set) = child.finish_with_access_set().unwrap();
+    sess.merge_child_effects(child_change_set.clone(), events.clone(), access_set)
+        .unwrap();
+
+    // the resource created by the child already exists in the parent
+    let err = sess
+        .merge_child_effects(child_change_set, events, AccessSet::new())
+        .unwrap_err();
+    assert_eq!(
+        err.major_status(),
+        StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR
+    );
+}
+
+#[test]
+fn inconsistent_merge_leaves_parent_untouched() {
+    let mut storage = InMemoryStorage::new();
+    storage.publish_or_overwrite_module(counter_module_id(), counter_module());
+    let vm = MoveVM::new(vec![]).unwrap();
+    let mut sess = vm.new_session(&storage);
+
+    // the creation at the first address can be merged, not the deletion at the second one
+    let other_addr = AccountAddress::ZERO;
+    let mut change_set = ChangeSet::new();
+    change_set
+        .add_resource_op(other_addr, counter_tag(), Op::New(counter_value(0)))
+        .unwrap();
+    change_set
+        .add_resource_op(TEST_ADDR, counter_tag(), Op::Delete)
+        .unwrap();
+    let events = vec![(vec![0], 0, TypeTag::U64, counter_value(0))];
+    let err = sess
+        .merge_child_effects(change_set, events, AccessSet::new())
+        .unwrap_err();
+    assert_eq!(
+        err.major_status(),
+        StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR
+    );
+
+    let (change_set, events) = sess.finish().unwrap();
+    assert_eq!(change_set, ChangeSet::new());
+    assert!(events.is_empty());
+}
+
+#[test]
+fn merge_child_accesses() {
+    let mut storage = InMemoryStorage::new();
+    storage.publish_or_overwrite_module(counter_module_id(), counter_module());
+    let vm = MoveVM::new(vec![]).unwrap();
+    let mut sess = vm.new_session(&storage);
+    call(&mut sess, "init", MoveValue::Signer(TEST_ADDR));
+
+    let resolver = sess.layered_resolver().unwrap();
+    let mut child = sess.new_child_session(&resolver).unwrap();
+    call(&mut child, "increment", MoveValue::Address(TEST_ADDR));
+    let (child_change_set, events, access_set) = child.finish_with_access_set().unwrap();
+    sess.merge_child_effects(child_change_set, events, access_set)
+        .unwrap();
+
+    let (_, _, access_set) = sess.finish_with_access_set().unwrap();
+    let key = ResourceKey::new(TEST_ADDR, counter_tag());
+    assert_eq!(
+        access_set.resources()[&key],
+        BTreeSet::from([ResourceAccess::MoveTo, ResourceAccess::BorrowGlobalMut])
+    );
+    assert!(access_set.modules().contains(&counter_module_id()));
+}
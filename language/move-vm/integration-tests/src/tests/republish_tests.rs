@@ -0,0 +1,78 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::compiler::{as_module, compile_units};
+use move_binary_format::CompiledModule;
+use move_core_types::{
+    account_address::AccountAddress, metadata::UpgradePolicy, vm_status::StatusCode,
+};
+use move_vm_runtime::move_vm::MoveVM;
+use move_vm_test_utils::InMemoryStorage;
+use move_vm_types::gas::UnmeteredGasMeter;
+
+const TEST_ADDR: AccountAddress = AccountAddress::new([42; AccountAddress::LENGTH]);
+
+fn compile_module(code: &str) -> CompiledModule {
+    let code = code.replace("{{ADDR}}", &format!("0x{}", TEST_ADDR));
+    as_module(compile_units(&code).unwrap().pop().unwrap())
+}
+
+fn serialize(module: &CompiledModule) -> Vec<u8> {
+    let mut blob = vec![];
+    module.serialize(&mut blob).unwrap();
+    blob
+}
+
+/// Storage holding a module N which no longer loads, as the module M it depends on is missing
+fn storage_with_unloadable_module(policy: UpgradePolicy) -> InMemoryStorage {
+    let mut n = compile_module(
+        r#"
+        module {{ADDR}}::M {
+            public fun foo() {}
+        }
+
+        module {{ADDR}}::N {
+            use {{ADDR}}::M;
+
+            public fun bar() { M::foo(); }
+        }
+    "#,
+    );
+    n.metadata.push(policy.to_metadata());
+    let mut storage = InMemoryStorage::new();
+    storage.publish_or_overwrite_module(n.self_id(), serialize(&n));
+    storage
+}
+
+fn republish_relax_compatibility(storage: &InMemoryStorage) -> Result<(), StatusCode> {
+    let new_n = compile_module(
+        r#"
+        module {{ADDR}}::N {
+            public fun bar() {}
+        }
+    "#,
+    );
+    let vm = MoveVM::new(vec![]).unwrap();
+    let mut sess = vm.new_session(storage);
+    sess.publish_module_bundle_relax_compatibility(
+        vec![serialize(&new_n)],
+        TEST_ADDR,
+        &mut UnmeteredGasMeter,
+    )
+    .map_err(|err| err.major_status())
+}
+
+#[test]
+fn republish_unloadable_module_relax_compatibility() {
+    let storage = storage_with_unloadable_module(UpgradePolicy::Compatible);
+    assert_eq!(republish_relax_compatibility(&storage), Ok(()));
+}
+
+#[test]
+fn republish_unloadable_immutable_module_relax_compatibility() {
+    let storage = storage_with_unloadable_module(UpgradePolicy::Immutable);
+    assert_eq!(
+        republish_relax_compatibility(&storage),
+        Err(StatusCode::UPGRADE_POLICY_VIOLATION)
+    );
+}
@@ -0,0 +1,253 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::compiler::{as_module, compile_units};
+use move_core_types::{
+    account_address::AccountAddress,
+    identifier::Identifier,
+    language_storage::ModuleId,
+    value::{serialize_values, MoveValue},
+    vm_status::StatusCode,
+};
+use move_vm_runtime::{config::VMConfig, move_vm::MoveVM};
+use move_vm_test_utils::InMemoryStorage;
+use move_vm_types::gas::UnmeteredGasMeter;
+
+const TEST_ADDR: AccountAddress = AccountAddress::new([42; AccountAddress::LENGTH]);
+
+fn run(config: VMConfig, function: &str, args: Vec<MoveValue>) -> Result<(), StatusCode> {
+    let code = r#"
+        module 0x1::vector {
+            #[bytecode_instruction]
+            native public fun push_back<Element>(v: &mut vector<Element>, e: Element);
+        }
+
+        module {{ADDR}}::M {
+            use 0x1::vector;
+
+            struct S has drop { v: vector<vector<u64>> }
+
+            struct Bag has key { v: vector<u64> }
+
+            struct R<T> has key { x: T }
+
+            public fun add3(a: u64, b: u64, c: u64): u64 {
+                a + (b + c)
+            }
+
+            public fun recurse(n: u64) {
+                if (n > 0) recurse(n - 1)
+            }
+
+            public fun pack_nested(x: u64) {
+                S { v: vector[vector[x]] };
+            }
+
+            public fun take(_v: vector<u64>) {}
+
+            public fun shuffle(v: vector<u64>) {
+                let i = 0;
+                while (i < 10) {
+                    let w = v;
+                    v = w;
+                    i = i + 1;
+                };
+            }
+
+            public fun duplicate(v: vector<u64>) {
+                let w = copy v;
+                take(copy w);
+                take(w);
+            }
+
+            public fun push(v: &mut vector<u64>, n: u64) {
+                let i = 0;
+                while (i < n) {
+                    vector::push_back(v, i);
+                    i = i + 1;
+                };
+            }
+
+            public fun push_in_callees(n: u64) {
+                let v = vector[];
+                let i = 0;
+                while (i < 4) {
+                    push(&mut v, n);
+                    i = i + 1;
+                };
+            }
+
+            public fun push_to_global(account: signer, n: u64) acquires Bag {
+                move_to(&account, Bag { v: vector[] });
+                let i = 0;
+                while (i < 4) {
+                    push(&mut borrow_global_mut<Bag>(@{{ADDR}}).v, n);
+                    i = i + 1;
+                };
+            }
+
+            public fun exists_r() {
+                exists<R<vector<vector<vector<u64>>>>>(@{{ADDR}});
+            }
+        }
+    "#;
+    let code = code.replace("{{ADDR}}", &format!("0x{}", TEST_ADDR));
+    let mut units = compile_units(&code).unwrap();
+    let m = as_module(units.pop().unwrap());
+    let mut blob = vec![];
+    m.serialize(&mut blob).unwrap();
+
+    let mut storage = InMemoryStorage::new();
+    let module_id = ModuleId::new(TEST_ADDR, Identifier::new("M").unwrap());
+    storage.publish_or_overwrite_module(module_id.clone(), blob);
+
+    let vm = MoveVM::new_with_config(vec![], config).unwrap();
+    let mut sess = vm.new_session(&storage);
+    sess.execute_function_bypass_visibility(
+        &module_id,
+        &Identifier::new(function).unwrap(),
+        vec![],
+        serialize_values(&args),
+        &mut UnmeteredGasMeter,
+    )
+    .map(|_| ())
+    .map_err(|e| e.major_status())
+}
+
+#[test]
+fn operand_stack_size() {
+    let args = || vec![MoveValue::U64(1), MoveValue::U64(2), MoveValue::U64(3)];
+    assert_eq!(run(VMConfig::default(), "add3", args()), Ok(()));
+    let config = VMConfig {
+        max_operand_stack_size: 2,
+        ..Default::default()
+    };
+    assert_eq!(
+        run(config, "add3", args()),
+        Err(StatusCode::EXECUTION_STACK_OVERFLOW)
+    );
+}
+
+#[test]
+fn call_stack_size() {
+    let args = || vec![MoveValue::U64(10)];
+    assert_eq!(run(VMConfig::default(), "recurse", args()), Ok(()));
+    let config = VMConfig {
+        max_call_stack_size: 5,
+        ..Default::default()
+    };
+    assert_eq!(
+        run(config, "recurse", args()),
+        Err(StatusCode::CALL_STACK_OVERFLOW)
+    );
+}
+
+#[test]
+fn value_depth() {
+    let args = || vec![MoveValue::U64(1)];
+    // `S` has depth 4: the struct, two vectors and an integer
+    let config = VMConfig {
+        max_value_depth: Some(4),
+        ..Default::default()
+    };
+    assert_eq!(run(config, "pack_nested", args()), Ok(()));
+    let config = VMConfig {
+        max_value_depth: Some(3),
+        ..Default::default()
+    };
+    assert_eq!(
+        run(config, "pack_nested", args()),
+        Err(StatusCode::VM_MAX_VALUE_DEPTH_REACHED)
+    );
+}
+
+#[test]
+fn locals_memory() {
+    let args = || vec![MoveValue::Vector(vec![MoveValue::U64(0); 16])];
+    assert_eq!(run(VMConfig::default(), "take", args()), Ok(()));
+    // a vector of 16 `u64`s has an abstract memory size of 128
+    let config = VMConfig {
+        max_locals_memory: Some(128),
+        ..Default::default()
+    };
+    assert_eq!(run(config, "take", args()), Ok(()));
+    let config = VMConfig {
+        max_locals_memory: Some(127),
+        ..Default::default()
+    };
+    assert_eq!(
+        run(config, "take", args()),
+        Err(StatusCode::VM_MAX_LOCALS_MEMORY_REACHED)
+    );
+}
+
+#[test]
+fn locals_memory_moves() {
+    let args = || vec![MoveValue::Vector(vec![MoveValue::U64(0); 16])];
+    // the vector and the loop counter: moving the vector out of and back into the locals does
+    // not account for it twice
+    let config = VMConfig {
+        max_locals_memory: Some(144),
+        ..Default::default()
+    };
+    assert_eq!(run(config, "shuffle", args()), Ok(()));
+}
+
+#[test]
+fn locals_memory_copies() {
+    let args = || vec![MoveValue::Vector(vec![MoveValue::U64(0); 16])];
+    // the vector and its copy, and the copy passed to `take`
+    let config = VMConfig {
+        max_locals_memory: Some(384),
+        ..Default::default()
+    };
+    assert_eq!(run(config, "duplicate", args()), Ok(()));
+    let config = VMConfig {
+        max_locals_memory: Some(383),
+        ..Default::default()
+    };
+    assert_eq!(
+        run(config, "duplicate", args()),
+        Err(StatusCode::VM_MAX_LOCALS_MEMORY_REACHED)
+    );
+}
+
+#[test]
+fn locals_memory_through_references() {
+    // each call to `push` stays within the limit, but the elements pushed stay accounted after
+    // the call returns, whether the vector is owned by the caller or by global storage
+    let config = || VMConfig {
+        max_locals_memory: Some(256),
+        ..Default::default()
+    };
+    let args = || vec![MoveValue::U64(4)];
+    assert_eq!(run(VMConfig::default(), "push_in_callees", args()), Ok(()));
+    assert_eq!(
+        run(config(), "push_in_callees", args()),
+        Err(StatusCode::VM_MAX_LOCALS_MEMORY_REACHED)
+    );
+    let args = || vec![MoveValue::Signer(TEST_ADDR), MoveValue::U64(4)];
+    assert_eq!(run(VMConfig::default(), "push_to_global", args()), Ok(()));
+    assert_eq!(
+        run(config(), "push_to_global", args()),
+        Err(StatusCode::VM_MAX_LOCALS_MEMORY_REACHED)
+    );
+}
+
+#[test]
+fn type_instantiation_size() {
+    // `R<vector<vector<vector<u64>>>>` has 5 type nodes
+    let config = VMConfig {
+        max_type_instantiation_size: Some(5),
+        ..Default::default()
+    };
+    assert_eq!(run(config, "exists_r", vec![]), Ok(()));
+    let config = VMConfig {
+        max_type_instantiation_size: Some(4),
+        ..Default::default()
+    };
+    assert_eq!(
+        run(config, "exists_r", vec![]),
+        Err(StatusCode::VM_MAX_TYPE_NODES_REACHED)
+    );
+}
@@ -0,0 +1,278 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::compiler::{as_module, compile_units};
+use move_core_types::{
+    account_address::AccountAddress,
+    effects::Op,
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag, TypeTag},
+    value::{serialize_values, MoveValue},
+    vm_status::StatusCode,
+};
+use move_vm_runtime::{move_vm::MoveVM, session::Session};
+use move_vm_test_utils::InMemoryStorage;
+use move_vm_types::{gas::UnmeteredGasMeter, loaded_data::runtime_types::Type, values::Value};
+
+const TEST_ADDR: AccountAddress = AccountAddress::new([42; AccountAddress::LENGTH]);
+
+fn compile_module(code: &str) -> Vec<u8> {
+    let code = code.replace("{{ADDR}}", &format!("0x{}", TEST_ADDR));
+    let mut units = compile_units(&code).unwrap();
+    let m = as_module(units.pop().unwrap());
+    let mut blob = vec![];
+    m.serialize(&mut blob).unwrap();
+    blob
+}
+
+fn counter_module() -> Vec<u8> {
+    compile_module(
+        r#"
+        module {{ADDR}}::Counter {
+            struct Counter has key { value: u64 }
+            public fun publish(account: &signer) {
+                move_to(account, Counter { value: 0 })
+            }
+            public fun increment(addr: address) acquires Counter {
+                let counter = borrow_global_mut<Counter>(addr);
+                counter.value = counter.value + 1;
+            }
+            public fun value(addr: address): u64 acquires Counter {
+                borrow_global<Counter>(addr).value
+            }
+        }
+    "#,
+    )
+}
+
+fn version_module(version: u64) -> Vec<u8> {
+    compile_module(&format!(
+        r#"
+        module {{{{ADDR}}}}::Version {{
+            public fun version(): u64 {{ {} }}
+        }}
+    "#,
+        version
+    ))
+}
+
+fn call(
+    sess: &mut Session<InMemoryStorage>,
+    module: &str,
+    function: &str,
+    args: Vec<MoveValue>,
+) -> Result<Vec<MoveValue>, StatusCode> {
+    let module_id = ModuleId::new(TEST_ADDR, Identifier::new(module).unwrap());
+    let results = sess
+        .execute_function_bypass_visibility(
+            &module_id,
+            &Identifier::new(function).unwrap(),
+            vec![],
+            serialize_values(&args),
+            &mut UnmeteredGasMeter,
+        )
+        .map_err(|e| e.major_status())?;
+    Ok(results
+        .return_values
+        .into_iter()
+        .map(|(bytes, layout)| MoveValue::simple_deserialize(&bytes, &layout).unwrap())
+        .collect())
+}
+
+#[test]
+fn rollback_resources_and_events() {
+    let mut storage = InMemoryStorage::new();
+    let module_id = ModuleId::new(TEST_ADDR, Identifier::new("Counter").unwrap());
+    storage.publish_or_overwrite_module(module_id, counter_module());
+
+    let vm = MoveVM::new(vec![]).unwrap();
+    let mut sess = vm.new_session(&storage);
+    let account1 = AccountAddress::random();
+    let account2 = AccountAddress::random();
+
+    // prologue
+    call(
+        &mut sess,
+        "Counter",
+        "publish",
+        vec![MoveValue::Signer(account1)],
+    )
+    .unwrap();
+    call(
+        &mut sess,
+        "Counter",
+        "increment",
+        vec![MoveValue::Address(account1)],
+    )
+    .unwrap();
+    sess.get_data_store()
+        .emit_event(vec![0], 0, Type::U64, Value::u64(1))
+        .unwrap();
+    let savepoint = sess.savepoint().unwrap();
+
+    // payload
+    call(
+        &mut sess,
+        "Counter",
+        "increment",
+        vec![MoveValue::Address(account1)],
+    )
+    .unwrap();
+    call(
+        &mut sess,
+        "Counter",
+        "publish",
+        vec![MoveValue::Signer(account2)],
+    )
+    .unwrap();
+    sess.get_data_store()
+        .emit_event(vec![0], 1, Type::U64, Value::u64(2))
+        .unwrap();
+    assert_eq!(
+        call(
+            &mut sess,
+            "Counter",
+            "value",
+            vec![MoveValue::Address(account1)]
+        ),
+        Ok(vec![MoveValue::U64(2)])
+    );
+    sess.rollback_to(&savepoint).unwrap();
+
+    assert_eq!(
+        call(
+            &mut sess,
+            "Counter",
+            "value",
+            vec![MoveValue::Address(account1)]
+        ),
+        Ok(vec![MoveValue::U64(1)])
+    );
+    assert_eq!(
+        call(
+            &mut sess,
+            "Counter",
+            "value",
+            vec![MoveValue::Address(account2)]
+        ),
+        Err(StatusCode::MISSING_DATA)
+    );
+
+    // a savepoint can be rolled back to again
+    call(
+        &mut sess,
+        "Counter",
+        "increment",
+        vec![MoveValue::Address(account1)],
+    )
+    .unwrap();
+    sess.rollback_to(&savepoint).unwrap();
+
+    // epilogue
+    sess.get_data_store()
+        .emit_event(vec![0], 1, Type::U64, Value::u64(3))
+        .unwrap();
+    let (changes, events) = sess.finish().unwrap();
+
+    let tag = StructTag {
+        address: TEST_ADDR,
+        module: Identifier::new("Counter").unwrap(),
+        name: Identifier::new("Counter").unwrap(),
+        type_params: vec![],
+    };
+    let value = 1u64.to_le_bytes();
+    assert_eq!(
+        changes.resources().collect::<Vec<_>>(),
+        vec![(account1, &tag, Op::New(&value[..]))]
+    );
+    assert_eq!(
+        events,
+        vec![
+            (vec![0], 0, TypeTag::U64, 1u64.to_le_bytes().to_vec()),
+            (vec![0], 1, TypeTag::U64, 3u64.to_le_bytes().to_vec()),
+        ]
+    );
+}
+
+#[test]
+fn rollback_module_publishing() {
+    let storage = InMemoryStorage::new();
+    let vm = MoveVM::new(vec![]).unwrap();
+    let mut sess = vm.new_session(&storage);
+
+    let savepoint = sess.savepoint().unwrap();
+    sess.publish_module(version_module(1), TEST_ADDR, &mut UnmeteredGasMeter)
+        .unwrap();
+    // loads the module into the loader cache
+    assert_eq!(
+        call(&mut sess, "Version", "version", vec![]),
+        Ok(vec![MoveValue::U64(1)])
+    );
+    sess.rollback_to(&savepoint).unwrap();
+
+    assert_eq!(
+        call(&mut sess, "Version", "version", vec![]),
+        Err(StatusCode::LINKER_ERROR)
+    );
+
+    // publishing another version of the module is not a republishing, and the loader does not
+    // use the cached version of the rolled back module
+    sess.publish_module(version_module(2), TEST_ADDR, &mut UnmeteredGasMeter)
+        .unwrap();
+    assert_eq!(
+        call(&mut sess, "Version", "version", vec![]),
+        Ok(vec![MoveValue::U64(2)])
+    );
+
+    let (changes, _) = sess.finish().unwrap();
+    let module = version_module(2);
+    assert_eq!(
+        changes.modules().collect::<Vec<_>>(),
+        vec![(
+            TEST_ADDR,
+            &Identifier::new("Version").unwrap(),
+            Op::New(&module[..])
+        )]
+    );
+}
+
+#[test]
+fn rollback_invalidates_later_savepoints() {
+    let storage = InMemoryStorage::new();
+    let vm = MoveVM::new(vec![]).unwrap();
+    let mut sess = vm.new_session(&storage);
+
+    let savepoint1 = sess.savepoint().unwrap();
+    let savepoint2 = sess.savepoint().unwrap();
+    sess.rollback_to(&savepoint2).unwrap();
+    sess.rollback_to(&savepoint1).unwrap();
+    assert_eq!(
+        sess.rollback_to(&savepoint2).unwrap_err().major_status(),
+        StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR
+    );
+    sess.rollback_to(&savepoint1).unwrap();
+
+    // a savepoint created after the rollback does not revive the invalidated one
+    let savepoint3 = sess.savepoint().unwrap();
+    assert_eq!(
+        sess.rollback_to(&savepoint2).unwrap_err().major_status(),
+        StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR
+    );
+    sess.rollback_to(&savepoint3).unwrap();
+}
+
+#[test]
+fn rollback_to_savepoint_of_another_session() {
+    let storage = InMemoryStorage::new();
+    let vm = MoveVM::new(vec![]).unwrap();
+    let mut sess1 = vm.new_session(&storage);
+    let mut sess2 = vm.new_session(&storage);
+
+    let savepoint = sess1.savepoint().unwrap();
+    sess2.savepoint().unwrap();
+    assert_eq!(
+        sess2.rollback_to(&savepoint).unwrap_err().major_status(),
+        StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR
+    );
+    sess1.rollback_to(&savepoint).unwrap();
+}
@@ -0,0 +1,113 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::compiler::{as_module, compile_units};
+use move_bytecode_verifier::VerifierConfig;
+use move_core_types::{
+    account_address::AccountAddress, identifier::Identifier, language_storage::ModuleId,
+    vm_status::StatusCode,
+};
+use move_vm_runtime::{
+    config::VMConfig, move_vm::MoveVM, verified_module_cache::VerifiedModuleCache,
+};
+use move_vm_test_utils::InMemoryStorage;
+use move_vm_types::gas::UnmeteredGasMeter;
+use std::{fs, sync::Arc};
+
+const TEST_ADDR: AccountAddress = AccountAddress::new([42; AccountAddress::LENGTH]);
+
+fn module_blob() -> Vec<u8> {
+    let code = r#"
+        module {{ADDR}}::M {
+            public fun add(a: u64, b: u64): u64 { a + b }
+        }
+    "#;
+    let code = code.replace("{{ADDR}}", &format!("0x{}", TEST_ADDR));
+    let mut units = compile_units(&code).unwrap();
+    let m = as_module(units.pop().unwrap());
+    let mut blob = vec![];
+    m.serialize(&mut blob).unwrap();
+    blob
+}
+
+fn load_module(cache: &Arc<VerifiedModuleCache>) {
+    let mut storage = InMemoryStorage::new();
+    let module_id = ModuleId::new(TEST_ADDR, Identifier::new("M").unwrap());
+    storage.publish_or_overwrite_module(module_id.clone(), module_blob());
+
+    let vm = MoveVM::new_with_config(
+        vec![],
+        VMConfig {
+            verified_module_cache: Some(cache.clone()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    vm.load_module(&module_id, &storage).unwrap();
+}
+
+#[test]
+fn cache_is_shared_and_persisted() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cache").join("verified_modules");
+
+    let cache = Arc::new(VerifiedModuleCache::load(&path).unwrap());
+    assert!(cache.is_empty());
+    load_module(&cache);
+    assert_eq!(cache.len(), 1);
+    // a second VM sharing the cache finds the module already verified
+    load_module(&cache);
+    assert_eq!(cache.len(), 1);
+    cache.save().unwrap();
+
+    let cache = Arc::new(VerifiedModuleCache::load(&path).unwrap());
+    assert_eq!(cache.len(), 1);
+    load_module(&cache);
+    assert_eq!(cache.len(), 1);
+}
+
+#[test]
+fn cache_is_keyed_by_verifier_config() {
+    let cache = Arc::new(VerifiedModuleCache::new());
+    load_module(&cache);
+    assert_eq!(cache.len(), 1);
+
+    // a VM with a stricter config must verify the module again, and reject it
+    let storage = InMemoryStorage::new();
+    let vm = MoveVM::new_with_config(
+        vec![],
+        VMConfig {
+            verifier: VerifierConfig {
+                max_function_parameters: Some(1),
+                ..Default::default()
+            },
+            verified_module_cache: Some(cache.clone()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let mut sess = vm.new_session(&storage);
+    let err = sess
+        .publish_module(module_blob(), TEST_ADDR, &mut UnmeteredGasMeter)
+        .unwrap_err();
+    assert_eq!(err.major_status(), StatusCode::TOO_MANY_PARAMETERS);
+    assert_eq!(cache.len(), 1);
+}
+
+#[test]
+fn cache_from_other_verifier_version_is_discarded() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("verified_modules");
+
+    let cache = Arc::new(VerifiedModuleCache::load(&path).unwrap());
+    load_module(&cache);
+    cache.save().unwrap();
+
+    // bump the verifier version recorded in the header
+    let mut bytes = fs::read(&path).unwrap();
+    bytes[4] = bytes[4].wrapping_add(1);
+    fs::write(&path, bytes).unwrap();
+
+    let cache = VerifiedModuleCache::load(&path).unwrap();
+    assert!(cache.is_empty());
+}
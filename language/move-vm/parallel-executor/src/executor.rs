@@ -0,0 +1,231 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    mvhashmap::{Incarnation, MVHashMap, ResourceKey, TxnIndex, Version},
+    view::MVView,
+};
+use move_binary_format::errors::VMResult;
+use move_core_types::{
+    effects::{ChangeSet, Event, Op},
+    resolver::MoveResolver,
+};
+use move_vm_runtime::{move_vm::MoveVM, session::Session};
+use parking_lot::Mutex;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+/// A unit of work executed in its own session.
+///
+/// A transaction must be deterministic: given the same state it must perform the same calls on
+/// the session, as it may be executed several times before its result is committed.
+pub trait Transaction: Sync {
+    fn execute<S: MoveResolver>(&self, session: &mut Session<S>) -> VMResult<()>;
+}
+
+/// The effects of a successfully executed transaction, or the error it failed with.
+pub type TransactionOutput = VMResult<(ChangeSet, Vec<Event>)>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockExecutionError {
+    /// The transaction at the given index published modules. Sessions executed in parallel share
+    /// the loader's code cache, so module publishing is not supported and the `MoveVM` used for
+    /// the block should be discarded.
+    ModulePublishing(TxnIndex),
+}
+
+impl fmt::Display for BlockExecutionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::ModulePublishing(idx) => write!(
+                f,
+                "transaction {} published modules, which is not supported in parallel execution",
+                idx
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BlockExecutionError {}
+
+/// The latest incarnation of a transaction.
+#[derive(Default)]
+struct ExecutionState {
+    incarnation: Option<Incarnation>,
+    reads: Vec<(ResourceKey, Option<Version>)>,
+    write_keys: BTreeSet<ResourceKey>,
+    output: Option<TransactionOutput>,
+    publishes_modules: bool,
+}
+
+/// Executes a block of transactions in parallel, producing the same outputs as executing them
+/// one after another, each on top of the changes of the successful transactions before it.
+///
+/// Execution proceeds in rounds. In each round, the transactions still to be executed run
+/// concurrently against a multi-version map holding the latest writes of every transaction. The
+/// read set of each transaction is then validated in block order: a transaction is committed if
+/// every resource it read still resolves to the same version, and all preceding transactions
+/// are committed. Transactions that fail validation are executed again in the next round. The
+/// first uncommitted transaction only depends on committed ones, so each round commits at least
+/// one transaction.
+///
+/// Only the resources read through the session's resolver are validated. Sessions are created
+/// without native extensions, and transactions must not call natives depending on one, e.g. the
+/// table natives, which panic on the missing extension: the state they access would be neither
+/// versioned nor validated.
+pub struct ParallelExecutor<'a> {
+    vm: &'a MoveVM,
+    concurrency_level: usize,
+}
+
+impl<'a> ParallelExecutor<'a> {
+    /// Create an executor using one thread per CPU.
+    pub fn new(vm: &'a MoveVM) -> Self {
+        Self {
+            vm,
+            concurrency_level: num_cpus::get(),
+        }
+    }
+
+    /// Set the maximum number of transactions executed at the same time.
+    pub fn with_concurrency_level(mut self, concurrency_level: usize) -> Self {
+        self.concurrency_level = concurrency_level.max(1);
+        self
+    }
+
+    /// Execute `txns` on top of `storage`, returning the output of each transaction in block
+    /// order. Changes are not applied to `storage`.
+    pub fn execute_block<S, T>(
+        &self,
+        storage: &S,
+        txns: &[T],
+    ) -> Result<Vec<TransactionOutput>, BlockExecutionError>
+    where
+        S: MoveResolver + Sync,
+        T: Transaction,
+    {
+        let versioned = MVHashMap::new();
+        let states: Vec<_> = txns
+            .iter()
+            .map(|_| Mutex::new(ExecutionState::default()))
+            .collect();
+
+        let mut committed = 0;
+        let mut to_execute: Vec<TxnIndex> = (0..txns.len()).collect();
+        while !to_execute.is_empty() {
+            self.execute_round(storage, txns, &versioned, &states, &to_execute);
+
+            if let Some(idx) = to_execute
+                .iter()
+                .find(|idx| states[**idx].lock().publishes_modules)
+            {
+                return Err(BlockExecutionError::ModulePublishing(*idx));
+            }
+
+            while committed < txns.len() && Self::validate(&versioned, &states, committed) {
+                committed += 1;
+            }
+            to_execute = (committed..txns.len())
+                .filter(|idx| !Self::validate(&versioned, &states, *idx))
+                .collect();
+        }
+
+        Ok(states
+            .into_iter()
+            .map(|state| {
+                state
+                    .into_inner()
+                    .output
+                    .expect("all transactions must be executed")
+            })
+            .collect())
+    }
+
+    fn execute_round<S, T>(
+        &self,
+        storage: &S,
+        txns: &[T],
+        versioned: &MVHashMap,
+        states: &[Mutex<ExecutionState>],
+        to_execute: &[TxnIndex],
+    ) where
+        S: MoveResolver + Sync,
+        T: Transaction,
+    {
+        let next = AtomicUsize::new(0);
+        let num_workers = self.concurrency_level.min(to_execute.len());
+        thread::scope(|scope| {
+            for _ in 0..num_workers {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::SeqCst);
+                    match to_execute.get(i) {
+                        Some(idx) => {
+                            self.execute_transaction(storage, &txns[*idx], versioned, states, *idx)
+                        }
+                        None => break,
+                    }
+                });
+            }
+        });
+    }
+
+    fn execute_transaction<S, T>(
+        &self,
+        storage: &S,
+        txn: &T,
+        versioned: &MVHashMap,
+        states: &[Mutex<ExecutionState>],
+        txn_idx: TxnIndex,
+    ) where
+        S: MoveResolver,
+        T: Transaction,
+    {
+        let view = MVView::new(storage, versioned, txn_idx);
+        let mut session = self.vm.new_session(&view);
+        let output = txn.execute(&mut session).and_then(|()| session.finish());
+        let reads = view.into_reads();
+
+        let mut writes = BTreeMap::new();
+        let mut publishes_modules = false;
+        if let Ok((change_set, _)) = &output {
+            publishes_modules = change_set.modules().next().is_some();
+            for (addr, tag, op) in change_set.resources() {
+                let value = match op {
+                    Op::New(blob) | Op::Modify(blob) => Some(blob.to_vec()),
+                    Op::Delete => None,
+                };
+                writes.insert((addr, tag.clone()), value);
+            }
+        }
+
+        let mut state = states[txn_idx].lock();
+        let incarnation = state.incarnation.map_or(0, |incarnation| incarnation + 1);
+        versioned.write(txn_idx, incarnation, &state.write_keys, &writes);
+        *state = ExecutionState {
+            incarnation: Some(incarnation),
+            reads,
+            write_keys: writes.into_keys().collect(),
+            output: Some(output),
+            publishes_modules,
+        };
+    }
+
+    /// Check that every resource read by the latest incarnation of `txn_idx` still resolves to
+    /// the version it observed. State accessed through native extensions is not covered, see
+    /// `ParallelExecutor`.
+    fn validate(
+        versioned: &MVHashMap,
+        states: &[Mutex<ExecutionState>],
+        txn_idx: TxnIndex,
+    ) -> bool {
+        let state = states[txn_idx].lock();
+        state
+            .reads
+            .iter()
+            .all(|(key, version)| versioned.read(key, txn_idx).version() == *version)
+    }
+}
@@ -0,0 +1,19 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! Parallel execution of a block of Move VM sessions.
+//!
+//! Transactions are executed optimistically over a shared `MoveResolver`, with the writes of
+//! each transaction kept in a multi-version map so that later transactions can read them before
+//! they are committed. The read set of every transaction is validated after execution and
+//! transactions that observed a stale value are executed again, so the outputs are the same as
+//! those of a sequential execution of the block.
+
+mod executor;
+mod mvhashmap;
+mod view;
+
+pub use executor::{BlockExecutionError, ParallelExecutor, Transaction, TransactionOutput};
+pub use mvhashmap::{Incarnation, TxnIndex};
@@ -0,0 +1,96 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_core_types::{account_address::AccountAddress, language_storage::StructTag};
+use parking_lot::RwLock;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Bound,
+};
+
+/// Position of a transaction in the block.
+pub type TxnIndex = usize;
+
+/// Number of times a transaction has been (re-)executed, starting at 0.
+pub type Incarnation = usize;
+
+/// The version of a value written by a transaction.
+pub type Version = (TxnIndex, Incarnation);
+
+/// Resources are the only state that can be written by a transaction executed in parallel.
+pub type ResourceKey = (AccountAddress, StructTag);
+
+/// The outcome of reading a key on behalf of a transaction: either the value written by the
+/// closest preceding transaction, or nothing, meaning the value comes from the base storage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReadResult {
+    /// The value at `Version`, where `None` means the resource was deleted.
+    Versioned(Version, Option<Vec<u8>>),
+    /// No preceding transaction wrote the key.
+    Storage,
+}
+
+impl ReadResult {
+    pub fn version(&self) -> Option<Version> {
+        match self {
+            Self::Versioned(version, _) => Some(*version),
+            Self::Storage => None,
+        }
+    }
+}
+
+/// A multi-version map from resource keys to the values written by each transaction of the
+/// block. A transaction reading a key sees the value written by the transaction with the
+/// highest index below its own, which is what it would see in a sequential execution once all
+/// preceding transactions are final.
+#[derive(Default)]
+pub(crate) struct MVHashMap {
+    data: RwLock<BTreeMap<ResourceKey, BTreeMap<TxnIndex, (Incarnation, Option<Vec<u8>>)>>>,
+}
+
+impl MVHashMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read `key` as seen by transaction `txn_idx`.
+    pub fn read(&self, key: &ResourceKey, txn_idx: TxnIndex) -> ReadResult {
+        let data = self.data.read();
+        match data.get(key).and_then(|versions| {
+            versions
+                .range((Bound::Unbounded, Bound::Excluded(txn_idx)))
+                .next_back()
+        }) {
+            Some((idx, (incarnation, value))) => {
+                ReadResult::Versioned((*idx, *incarnation), value.clone())
+            }
+            None => ReadResult::Storage,
+        }
+    }
+
+    /// Replace the writes of `txn_idx` by `writes`, produced by the given incarnation. Keys in
+    /// `prev_keys` that are no longer written are removed, so later transactions do not observe
+    /// stale values from a previous incarnation.
+    pub fn write(
+        &self,
+        txn_idx: TxnIndex,
+        incarnation: Incarnation,
+        prev_keys: &BTreeSet<ResourceKey>,
+        writes: &BTreeMap<ResourceKey, Option<Vec<u8>>>,
+    ) {
+        let mut data = self.data.write();
+        for key in prev_keys {
+            if writes.contains_key(key) {
+                continue;
+            }
+            if let Some(versions) = data.get_mut(key) {
+                versions.remove(&txn_idx);
+            }
+        }
+        for (key, value) in writes {
+            data.entry(key.clone())
+                .or_default()
+                .insert(txn_idx, (incarnation, value.clone()));
+        }
+    }
+}
@@ -0,0 +1,63 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::mvhashmap::{MVHashMap, ReadResult, ResourceKey, TxnIndex, Version};
+use move_core_types::{
+    account_address::AccountAddress,
+    language_storage::{ModuleId, StructTag},
+    resolver::{ModuleResolver, MoveResolver, ResourceResolver},
+};
+use std::cell::RefCell;
+
+/// The state seen by one incarnation of a transaction: the writes of preceding transactions in
+/// the multi-version map on top of the base storage. Every resource read is recorded together
+/// with the version it observed, so the read set can be validated after execution.
+pub(crate) struct MVView<'a, S> {
+    base: &'a S,
+    versioned: &'a MVHashMap,
+    txn_idx: TxnIndex,
+    reads: RefCell<Vec<(ResourceKey, Option<Version>)>>,
+}
+
+impl<'a, S: MoveResolver> MVView<'a, S> {
+    pub fn new(base: &'a S, versioned: &'a MVHashMap, txn_idx: TxnIndex) -> Self {
+        Self {
+            base,
+            versioned,
+            txn_idx,
+            reads: RefCell::new(vec![]),
+        }
+    }
+
+    pub fn into_reads(self) -> Vec<(ResourceKey, Option<Version>)> {
+        self.reads.into_inner()
+    }
+}
+
+impl<'a, S: MoveResolver> ModuleResolver for MVView<'a, S> {
+    type Error = S::Err;
+
+    // Modules cannot change within a block, so they are read from the base storage directly.
+    fn get_module(&self, module_id: &ModuleId) -> Result<Option<Vec<u8>>, Self::Error> {
+        self.base.get_module(module_id)
+    }
+}
+
+impl<'a, S: MoveResolver> ResourceResolver for MVView<'a, S> {
+    type Error = S::Err;
+
+    fn get_resource(
+        &self,
+        address: &AccountAddress,
+        tag: &StructTag,
+    ) -> Result<Option<Vec<u8>>, Self::Error> {
+        let key = (*address, tag.clone());
+        let result = self.versioned.read(&key, self.txn_idx);
+        let version = result.version();
+        self.reads.borrow_mut().push((key, version));
+        match result {
+            ReadResult::Versioned(_, value) => Ok(value),
+            ReadResult::Storage => self.base.get_resource(address, tag),
+        }
+    }
+}
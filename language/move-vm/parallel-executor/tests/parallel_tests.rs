@@ -0,0 +1,275 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_binary_format::{errors::VMResult, CompiledModule};
+use move_compiler::{compiled_unit::AnnotatedCompiledUnit, Compiler};
+use move_core_types::{
+    account_address::AccountAddress,
+    identifier::Identifier,
+    language_storage::ModuleId,
+    resolver::MoveResolver,
+    value::{serialize_values, MoveValue},
+};
+use move_vm_parallel_executor::{
+    BlockExecutionError, ParallelExecutor, Transaction, TransactionOutput,
+};
+use move_vm_runtime::{move_vm::MoveVM, session::Session};
+use move_vm_test_utils::InMemoryStorage;
+use move_vm_types::gas::UnmeteredGasMeter;
+use std::fs;
+
+const TEST_ADDR: AccountAddress = AccountAddress::new([42; AccountAddress::LENGTH]);
+
+const CODE: &str = r#"
+    module {{ADDR}}::M {
+        use std::signer;
+
+        struct Counter has key { value: u64 }
+
+        struct Balance has key { value: u64 }
+
+        public fun increment(account: &signer) acquires Counter {
+            let addr = signer::address_of(account);
+            if (exists<Counter>(addr)) {
+                let counter = borrow_global_mut<Counter>(addr);
+                counter.value = counter.value + 1;
+            } else {
+                move_to(account, Counter { value: 1 });
+            }
+        }
+
+        public fun mint(account: &signer, amount: u64) acquires Balance {
+            let addr = signer::address_of(account);
+            if (exists<Balance>(addr)) {
+                let balance = borrow_global_mut<Balance>(addr);
+                balance.value = balance.value + amount;
+            } else {
+                move_to(account, Balance { value: amount });
+            }
+        }
+
+        public fun transfer(from: address, to: address, amount: u64) acquires Balance {
+            let from_balance = borrow_global_mut<Balance>(from);
+            assert!(from_balance.value >= amount, 1);
+            from_balance.value = from_balance.value - amount;
+            let to_balance = borrow_global_mut<Balance>(to);
+            to_balance.value = to_balance.value + amount;
+        }
+
+        public fun burn(account: &signer) acquires Balance {
+            let Balance { value: _ } = move_from<Balance>(signer::address_of(account));
+        }
+    }
+
+    module std::signer {
+        native public fun borrow_address(s: &signer): &address;
+
+        public fun address_of(s: &signer): address {
+            *borrow_address(s)
+        }
+    }
+"#;
+
+fn compile_modules() -> Vec<CompiledModule> {
+    let dir = tempfile::tempdir().unwrap();
+    let file_path = dir.path().join("modules.move");
+    fs::write(
+        &file_path,
+        CODE.replace("{{ADDR}}", &format!("0x{}", TEST_ADDR)),
+    )
+    .unwrap();
+    let (_, units) = Compiler::from_files(
+        vec![file_path.to_str().unwrap().to_string()],
+        vec![],
+        move_stdlib::move_stdlib_named_addresses(),
+    )
+    .build_and_report()
+    .unwrap();
+    units
+        .into_iter()
+        .map(|unit| match unit {
+            AnnotatedCompiledUnit::Module(annot_module) => annot_module.named_module.module,
+            AnnotatedCompiledUnit::Script(_) => panic!("expected module got script"),
+        })
+        .collect()
+}
+
+fn setup() -> (MoveVM, InMemoryStorage) {
+    let mut storage = InMemoryStorage::new();
+    for module in compile_modules() {
+        let mut blob = vec![];
+        module.serialize(&mut blob).unwrap();
+        storage.publish_or_overwrite_module(module.self_id(), blob);
+    }
+    let natives = move_stdlib::natives::all_natives(
+        AccountAddress::ONE,
+        move_stdlib::natives::GasParameters::zeros(),
+    );
+    (MoveVM::new(natives).unwrap(), storage)
+}
+
+/// A call to a function of `M`.
+#[derive(Debug, Clone)]
+struct Call {
+    function: &'static str,
+    args: Vec<MoveValue>,
+}
+
+impl Transaction for Call {
+    fn execute<S: MoveResolver>(&self, session: &mut Session<S>) -> VMResult<()> {
+        session
+            .execute_function_bypass_visibility(
+                &ModuleId::new(TEST_ADDR, Identifier::new("M").unwrap()),
+                &Identifier::new(self.function).unwrap(),
+                vec![],
+                serialize_values(&self.args),
+                &mut UnmeteredGasMeter,
+            )
+            .map(|_| ())
+    }
+}
+
+fn account(i: u64) -> AccountAddress {
+    AccountAddress::from_hex_literal(&format!("0x{:x}", 0x100 + i)).unwrap()
+}
+
+fn increment(i: u64) -> Call {
+    Call {
+        function: "increment",
+        args: vec![MoveValue::Signer(account(i))],
+    }
+}
+
+fn mint(i: u64, amount: u64) -> Call {
+    Call {
+        function: "mint",
+        args: vec![MoveValue::Signer(account(i)), MoveValue::U64(amount)],
+    }
+}
+
+fn transfer(from: u64, to: u64, amount: u64) -> Call {
+    Call {
+        function: "transfer",
+        args: vec![
+            MoveValue::Address(account(from)),
+            MoveValue::Address(account(to)),
+            MoveValue::U64(amount),
+        ],
+    }
+}
+
+fn burn(i: u64) -> Call {
+    Call {
+        function: "burn",
+        args: vec![MoveValue::Signer(account(i))],
+    }
+}
+
+/// Execute `txns` one after another, applying the changes of each successful transaction
+/// before executing the next one.
+fn execute_serially(storage: &InMemoryStorage, txns: &[Call]) -> Vec<TransactionOutput> {
+    let (vm, _) = setup();
+    let mut storage = storage.clone();
+    let mut outputs = vec![];
+    for txn in txns {
+        let mut session = vm.new_session(&storage);
+        let output = txn.execute(&mut session).and_then(|()| session.finish());
+        if let Ok((change_set, _)) = &output {
+            storage.apply(change_set.clone()).unwrap();
+        }
+        outputs.push(output);
+    }
+    outputs
+}
+
+/// Check that executing `txns` in parallel produces the same outputs as a serial execution, for
+/// a range of concurrency levels.
+fn assert_parallel_matches_serial(txns: &[Call]) {
+    let (vm, storage) = setup();
+    let expected = execute_serially(&storage, txns);
+    assert!(expected.iter().any(|output| output.is_ok()));
+    for concurrency_level in [1, 2, 4, 8] {
+        let outputs = ParallelExecutor::new(&vm)
+            .with_concurrency_level(concurrency_level)
+            .execute_block(&storage, txns)
+            .unwrap();
+        assert_eq!(outputs, expected);
+    }
+}
+
+/// A linear congruential generator, so that workloads are the same on every run.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self, bound: u64) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 33) % bound
+    }
+}
+
+fn random_block(seed: u64, num_accounts: u64, num_txns: usize) -> Vec<Call> {
+    let mut rng = Rng(seed);
+    (0..num_txns)
+        .map(|_| match rng.next(4) {
+            0 => increment(rng.next(num_accounts)),
+            1 => mint(rng.next(num_accounts), rng.next(100)),
+            2 => transfer(rng.next(num_accounts), rng.next(num_accounts), rng.next(50)),
+            _ => burn(rng.next(num_accounts)),
+        })
+        .collect()
+}
+
+#[test]
+fn independent_transactions() {
+    let txns: Vec<_> = (0..32).map(increment).collect();
+    assert_parallel_matches_serial(&txns);
+}
+
+#[test]
+fn conflicting_transactions() {
+    let txns: Vec<_> = (0..32).map(|_| increment(0)).collect();
+    assert_parallel_matches_serial(&txns);
+}
+
+#[test]
+fn dependent_transfers() {
+    let mut txns = vec![mint(0, 100), mint(1, 0)];
+    txns.extend((0..30).map(|i| transfer(i % 2, (i + 1) % 2, 10 + i)));
+    txns.push(burn(0));
+    txns.push(transfer(0, 1, 1));
+    assert_parallel_matches_serial(&txns);
+}
+
+#[test]
+fn random_workloads() {
+    for seed in 0..16 {
+        assert_parallel_matches_serial(&random_block(seed, 4, 64));
+        assert_parallel_matches_serial(&random_block(seed, 32, 64));
+    }
+}
+
+struct Publish(Vec<u8>);
+
+impl Transaction for Publish {
+    fn execute<S: MoveResolver>(&self, session: &mut Session<S>) -> VMResult<()> {
+        session.publish_module(self.0.clone(), AccountAddress::ONE, &mut UnmeteredGasMeter)
+    }
+}
+
+#[test]
+fn module_publishing_is_rejected() {
+    let (vm, _) = setup();
+    let module = compile_modules()
+        .into_iter()
+        .find(|module| module.self_id().address() == &AccountAddress::ONE)
+        .unwrap();
+    let mut blob = vec![];
+    module.serialize(&mut blob).unwrap();
+
+    let storage = InMemoryStorage::new();
+    let result = ParallelExecutor::new(&vm).execute_block(&storage, &[Publish(blob)]);
+    assert_eq!(result, Err(BlockExecutionError::ModulePublishing(0)));
+}
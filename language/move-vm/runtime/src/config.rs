@@ -1,8 +1,10 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::verified_module_cache::VerifiedModuleCache;
 use move_binary_format::file_format_common::VERSION_MAX;
 use move_bytecode_verifier::VerifierConfig;
+use std::sync::Arc;
 
 /// Dynamic config options for the Move VM.
 pub struct VMConfig {
@@ -11,6 +13,29 @@ pub struct VMConfig {
     // When this flag is set to true, MoveVM will perform type check at every instruction
     // execution to ensure that type safety cannot be violated at runtime.
     pub paranoid_type_checks: bool,
+    /// Maximal number of values on the operand stack. Exceeding it fails with
+    /// `EXECUTION_STACK_OVERFLOW`.
+    pub max_operand_stack_size: usize,
+    /// Maximal number of frames on the call stack. Exceeding it fails with `CALL_STACK_OVERFLOW`.
+    pub max_call_stack_size: usize,
+    /// Maximal depth of a value, in terms of the nesting of its type, if any. It is checked when
+    /// packing structs and vectors, and replaces the default limit when converting types to
+    /// layouts. Exceeding it fails with `VM_MAX_VALUE_DEPTH_REACHED`.
+    pub max_value_depth: Option<usize>,
+    /// Maximal abstract memory size of the values held by the locals of all the functions on the
+    /// call stack, if any. It is checked when entering a function and after instructions which
+    /// can grow a value, i.e., `StLoc`, `WriteRef` and `VecPushBack`. Values written through
+    /// references stay accounted until removed through a reference, including those owned by a
+    /// caller or by global storage. Exceeding it fails with `VM_MAX_LOCALS_MEMORY_REACHED`.
+    pub max_locals_memory: Option<u64>,
+    /// Maximal number of type nodes when instantiating a generic struct during execution, not
+    /// counting the field types, if any. It replaces the default limit. Exceeding it fails with
+    /// `VM_MAX_TYPE_NODES_REACHED`.
+    pub max_type_instantiation_size: Option<usize>,
+    /// Cache of modules which passed the bytecode verifier, if any. It can be shared between
+    /// `MoveVM` instances and persisted to disk to skip re-verifying modules across processes.
+    /// A persisted cache is trusted: modules recorded in it are not verified.
+    pub verified_module_cache: Option<Arc<VerifiedModuleCache>>,
 }
 
 impl Default for VMConfig {
@@ -19,6 +44,12 @@ impl Default for VMConfig {
             verifier: VerifierConfig::default(),
             max_binary_format_version: VERSION_MAX,
             paranoid_type_checks: false,
+            max_operand_stack_size: 1024,
+            max_call_stack_size: 1024,
+            max_value_depth: None,
+            max_locals_memory: None,
+            max_type_instantiation_size: None,
+            verified_module_cache: None,
         }
     }
 }
@@ -7,10 +7,10 @@ use crate::loader::Loader;
 use move_binary_format::errors::*;
 use move_core_types::{
     account_address::AccountAddress,
-    effects::{AccountChangeSet, ChangeSet, Event, Op},
+    effects::{AccessSet, AccountChangeSet, ChangeSet, Event, Op, ResourceAccess},
     gas_algebra::NumBytes,
     identifier::Identifier,
-    language_storage::{ModuleId, TypeTag},
+    language_storage::{ModuleId, ResourceKey, TypeTag},
     resolver::MoveResolver,
     value::MoveTypeLayout,
     vm_status::StatusCode,
@@ -20,7 +20,10 @@ use move_vm_types::{
     loaded_data::runtime_types::Type,
     values::{GlobalValue, Value},
 };
-use std::collections::btree_map::BTreeMap;
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet},
+};
 
 pub struct AccountDataCache {
     data_map: BTreeMap<Type, (MoveTypeLayout, GlobalValue)>,
@@ -34,6 +37,23 @@ impl AccountDataCache {
             module_map: BTreeMap::new(),
         }
     }
+
+    fn copy(&self) -> PartialVMResult<Self> {
+        let mut data_map = BTreeMap::new();
+        for (ty, (layout, gv)) in &self.data_map {
+            data_map.insert(ty.clone(), (layout.clone(), gv.copy_value()?));
+        }
+        Ok(Self {
+            data_map,
+            module_map: self.module_map.clone(),
+        })
+    }
+}
+
+/// The state of a `TransactionDataCache` when a savepoint was created.
+pub(crate) struct DataCacheSavepoint {
+    account_map: BTreeMap<AccountAddress, AccountDataCache>,
+    num_events: usize,
 }
 
 /// Transaction data cache. Keep updates within a transaction so they can all be published at
@@ -54,6 +74,11 @@ pub(crate) struct TransactionDataCache<'r, 'l, S> {
     loader: &'l Loader,
     account_map: BTreeMap<AccountAddress, AccountDataCache>,
     event_data: Vec<(Vec<u8>, u64, Type, MoveTypeLayout, Value)>,
+    // Accesses are not rolled back with the data, as they may have influenced the execution.
+    resource_accesses: BTreeMap<(AccountAddress, Type), BTreeSet<ResourceAccess>>,
+    module_accesses: RefCell<BTreeSet<ModuleId>>,
+    // Accesses of the child sessions whose effects were merged into the cache.
+    child_accesses: AccessSet,
 }
 
 impl<'r, 'l, S: MoveResolver> TransactionDataCache<'r, 'l, S> {
@@ -65,7 +90,34 @@ impl<'r, 'l, S: MoveResolver> TransactionDataCache<'r, 'l, S> {
             loader,
             account_map: BTreeMap::new(),
             event_data: vec![],
+            resource_accesses: BTreeMap::new(),
+            module_accesses: RefCell::new(BTreeSet::new()),
+            child_accesses: AccessSet::new(),
+        }
+    }
+
+    /// The resources and modules accessed so far, including by merged child sessions. Modules are
+    /// completed with the dependencies of the modules loaded, as those found in the loader's
+    /// cache do not load their dependencies.
+    pub(crate) fn access_set(&self) -> PartialVMResult<AccessSet> {
+        let mut access_set = self.child_accesses.clone();
+        for ((addr, ty), accesses) in &self.resource_accesses {
+            let struct_tag = match self.loader.type_to_type_tag(ty)? {
+                TypeTag::Struct(struct_tag) => *struct_tag,
+                _ => return Err(PartialVMError::new(StatusCode::INTERNAL_TYPE_ERROR)),
+            };
+            let key = ResourceKey::new(*addr, struct_tag);
+            for access in accesses {
+                access_set.add_resource_access(key.clone(), *access);
+            }
         }
+        for module_id in self
+            .loader
+            .dependency_closure(self.module_accesses.borrow().iter())
+        {
+            access_set.add_module_access(module_id);
+        }
+        Ok(access_set)
     }
 
     /// Make a write set from the updated (dirty, deleted) global resources along with
@@ -137,6 +189,185 @@ impl<'r, 'l, S: MoveResolver> TransactionDataCache<'r, 'l, S> {
         Ok((change_set, events))
     }
 
+    pub(crate) fn remote(&self) -> &'r S {
+        self.remote
+    }
+
+    /// The changes the cache would produce if it was turned into effects now, leaving the cache
+    /// untouched.
+    pub(crate) fn pending_changes(&self) -> PartialVMResult<ChangeSet> {
+        let copy = TransactionDataCache {
+            remote: self.remote,
+            loader: self.loader,
+            account_map: Self::copy_account_map(&self.account_map)?,
+            event_data: vec![],
+            resource_accesses: BTreeMap::new(),
+            module_accesses: RefCell::new(BTreeSet::new()),
+            child_accesses: AccessSet::new(),
+        };
+        let (change_set, _) = copy.into_effects()?;
+        Ok(change_set)
+    }
+
+    /// Apply the effects of a session run on top of the pending changes of this cache, so that
+    /// `into_effects` returns the changes of this cache squashed with `change_set`. As with
+    /// `ChangeSet::squash`, an operation inconsistent with the state of the cache is an error,
+    /// in which case the cache is left as it was.
+    pub(crate) fn merge_effects(
+        &mut self,
+        change_set: ChangeSet,
+        events: Vec<Event>,
+    ) -> PartialVMResult<()> {
+        let savepoint = self.savepoint()?;
+        let result = self.merge_effects_impl(change_set, events);
+        if result.is_err() {
+            self.rollback_to(&savepoint)?;
+        }
+        result
+    }
+
+    /// Record the accesses of a child session, whether or not its effects could be merged, as
+    /// they may have influenced its execution.
+    pub(crate) fn merge_accesses(&mut self, access_set: AccessSet) {
+        self.child_accesses.extend(access_set);
+    }
+
+    fn merge_effects_impl(
+        &mut self,
+        change_set: ChangeSet,
+        events: Vec<Event>,
+    ) -> PartialVMResult<()> {
+        let loader = self.loader;
+        let inconsistent = |msg: String| {
+            PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR).with_message(msg)
+        };
+
+        // Modules are merged first, as the types of the resources may be defined by them.
+        let mut all_resources = vec![];
+        for (addr, account_change_set) in change_set.into_inner() {
+            let (modules, resources) = account_change_set.into_inner();
+            all_resources.push((addr, resources));
+            for (name, op) in modules {
+                let account_cache =
+                    Self::get_mut_or_insert_with(&mut self.account_map, &addr, || {
+                        (addr, AccountDataCache::new())
+                    });
+                let pending = account_cache.module_map.get(&name).map(|(_, flag)| *flag);
+                let (blob, is_republishing) = match (op, pending) {
+                    (Op::New(_), Some(_)) | (Op::Delete, _) => {
+                        return Err(inconsistent(format!(
+                            "cannot merge change to module {}::{}",
+                            addr, name
+                        )))
+                    }
+                    (Op::New(blob), None) => (blob, false),
+                    (Op::Modify(blob), None) => (blob, true),
+                    (Op::Modify(blob), Some(is_republishing)) => (blob, is_republishing),
+                };
+                account_cache
+                    .module_map
+                    .insert(name, (blob, is_republishing));
+            }
+        }
+
+        for (addr, resources) in all_resources {
+            for (struct_tag, op) in resources {
+                let ty = loader
+                    .load_type(&TypeTag::Struct(Box::new(struct_tag.clone())), &*self)
+                    .map_err(|e| e.to_partial())?;
+                let layout = loader.type_to_type_layout(&ty)?;
+                let (gv, _) = self.load_resource(addr, &ty)?;
+                let blob = match (op, gv.exists()?) {
+                    (Op::New(blob), false) => Some(blob),
+                    (Op::Modify(blob), true) => {
+                        gv.move_from()?;
+                        Some(blob)
+                    }
+                    (Op::Delete, true) => {
+                        gv.move_from()?;
+                        None
+                    }
+                    _ => {
+                        return Err(inconsistent(format!(
+                            "cannot merge change to resource {} at {}",
+                            struct_tag, addr
+                        )))
+                    }
+                };
+                if let Some(blob) = blob {
+                    let val = Value::simple_deserialize(&blob, &layout).ok_or_else(|| {
+                        PartialVMError::new(StatusCode::FAILED_TO_DESERIALIZE_RESOURCE)
+                    })?;
+                    gv.move_to(val).map_err(|(err, _)| err)?;
+                }
+            }
+        }
+
+        for (guid, seq_num, ty_tag, blob) in events {
+            let ty = loader
+                .load_type(&ty_tag, &*self)
+                .map_err(|e| e.to_partial())?;
+            let layout = loader.type_to_type_layout(&ty)?;
+            let val = Value::simple_deserialize(&blob, &layout)
+                .ok_or_else(|| PartialVMError::new(StatusCode::INTERNAL_TYPE_ERROR))?;
+            self.event_data.push((guid, seq_num, ty, layout, val));
+        }
+        Ok(())
+    }
+
+    /// Save a copy of the resources and modules in the cache, and the number of events emitted.
+    pub(crate) fn savepoint(&self) -> PartialVMResult<DataCacheSavepoint> {
+        Ok(DataCacheSavepoint {
+            account_map: Self::copy_account_map(&self.account_map)?,
+            num_events: self.event_data.len(),
+        })
+    }
+
+    /// Restore the cache to its state in `savepoint`, dropping the resources, modules and events
+    /// written since. The savepoint is copied again, so that it can be rolled back to multiple
+    /// times.
+    pub(crate) fn rollback_to(&mut self, savepoint: &DataCacheSavepoint) -> PartialVMResult<()> {
+        // Modules published since the savepoint that do not exist in storage may have been
+        // loaded into the loader cache, which must forget them. Modules that exist in storage can
+        // stay: republishing does not replace the cached version of a module.
+        let mut unpublished = BTreeSet::new();
+        for (addr, account_cache) in &self.account_map {
+            for name in account_cache.module_map.keys() {
+                let published_before = matches!(
+                    savepoint.account_map.get(addr),
+                    Some(cache) if cache.module_map.contains_key(name)
+                );
+                if published_before {
+                    continue;
+                }
+                let module_id = ModuleId::new(*addr, name.clone());
+                let in_storage = self
+                    .remote
+                    .get_module(&module_id)
+                    .map_err(|_| PartialVMError::new(StatusCode::STORAGE_ERROR))?
+                    .is_some();
+                if !in_storage {
+                    unpublished.insert(module_id);
+                }
+            }
+        }
+        self.loader.evict_modules(&unpublished);
+
+        self.account_map = Self::copy_account_map(&savepoint.account_map)?;
+        self.event_data.truncate(savepoint.num_events);
+        Ok(())
+    }
+
+    fn copy_account_map(
+        account_map: &BTreeMap<AccountAddress, AccountDataCache>,
+    ) -> PartialVMResult<BTreeMap<AccountAddress, AccountDataCache>> {
+        let mut copy = BTreeMap::new();
+        for (addr, account_cache) in account_map {
+            copy.insert(*addr, account_cache.copy()?);
+        }
+        Ok(copy)
+    }
+
     pub(crate) fn num_mutated_accounts(&self, sender: &AccountAddress) -> u64 {
         // The sender's account will always be mutated.
         let mut total_mutated_accounts: u64 = 1;
@@ -231,7 +462,15 @@ impl<'r, 'l, S: MoveResolver> DataStore for TransactionDataCache<'r, 'l, S> {
         ))
     }
 
+    fn record_resource_access(&mut self, addr: AccountAddress, ty: &Type, access: ResourceAccess) {
+        self.resource_accesses
+            .entry((addr, ty.clone()))
+            .or_default()
+            .insert(access);
+    }
+
     fn load_module(&self, module_id: &ModuleId) -> VMResult<Vec<u8>> {
+        self.record_module_access(module_id);
         if let Some(account_cache) = self.account_map.get(module_id.address()) {
             if let Some((blob, _is_republishing)) = account_cache.module_map.get(module_id.name()) {
                 return Ok(blob.clone());
@@ -286,6 +525,10 @@ impl<'r, 'l, S: MoveResolver> DataStore for TransactionDataCache<'r, 'l, S> {
             .is_some())
     }
 
+    fn record_module_access(&self, module_id: &ModuleId) {
+        self.module_accesses.borrow_mut().insert(module_id.clone());
+    }
+
     fn emit_event(
         &mut self,
         guid: Vec<u8>,
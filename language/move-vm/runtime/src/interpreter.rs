@@ -14,7 +14,8 @@ use move_binary_format::{
 };
 use move_core_types::{
     account_address::AccountAddress,
-    gas_algebra::{NumArgs, NumBytes},
+    effects::ResourceAccess,
+    gas_algebra::{AbstractMemorySize, NumArgs, NumBytes},
     language_storage::TypeTag,
     vm_status::{StatusCode, StatusType},
 };
@@ -26,7 +27,7 @@ use move_vm_types::{
         self, GlobalValue, IntegerValue, Locals, Reference, Struct, StructRef, VMValueCast, Value,
         Vector, VectorRef,
     },
-    views::TypeView,
+    views::{TypeView, ValueView},
 };
 
 use crate::native_extensions::NativeContextExtensions;
@@ -69,6 +70,8 @@ pub(crate) struct Interpreter {
     call_stack: CallStack,
     /// Whether to perform a paranoid type safety checks at runtime.
     paranoid_type_checks: bool,
+    /// Abstract memory size of the values held by the locals of the call stack.
+    locals_memory: LocalsMemory,
 }
 
 struct TypeWithLoader<'a, 'b> {
@@ -94,10 +97,12 @@ impl Interpreter {
         extensions: &mut NativeContextExtensions,
         loader: &Loader,
     ) -> VMResult<Vec<Value>> {
+        let vm_config = loader.vm_config();
         Interpreter {
-            operand_stack: Stack::new(),
-            call_stack: CallStack::new(),
-            paranoid_type_checks: loader.vm_config().paranoid_type_checks,
+            operand_stack: Stack::new(vm_config.max_operand_stack_size),
+            call_stack: CallStack::new(vm_config.max_call_stack_size),
+            paranoid_type_checks: vm_config.paranoid_type_checks,
+            locals_memory: LocalsMemory::new(vm_config.max_locals_memory),
         }
         .execute_main(
             loader, data_store, gas_meter, extensions, function, ty_args, args,
@@ -122,6 +127,9 @@ impl Interpreter {
     ) -> VMResult<Vec<Value>> {
         let mut locals = Locals::new(function.local_count());
         for (i, value) in args.into_iter().enumerate() {
+            gas_meter
+                .use_heap_memory_for_values([&value])
+                .map_err(|e| self.set_location(e))?;
             locals
                 .store_loc(i, value)
                 .map_err(|e| self.set_location(e))?;
@@ -138,15 +146,18 @@ impl Interpreter {
                     .map_err(|err| self.maybe_core_dump(err, &current_frame))?;
             match exit_code {
                 ExitCode::Return => {
-                    let non_ref_vals = current_frame
+                    self.locals_memory.release_locals(&current_frame.locals);
+                    let non_ref_vals: Vec<_> = current_frame
                         .locals
                         .drop_all_values()
-                        .map(|(_idx, val)| val);
+                        .map(|(_idx, val)| val)
+                        .collect();
 
                     // TODO: Check if the error location is set correctly.
                     gas_meter
-                        .charge_drop_frame(non_ref_vals.into_iter())
+                        .charge_drop_frame(non_ref_vals.iter())
                         .map_err(|e| self.set_location(e))?;
+                    gas_meter.release_heap_memory_for_values(non_ref_vals.iter());
 
                     if let Some(frame) = self.call_stack.pop() {
                         // Note: the caller will find the callee's return values at the top of the shared operand stack
@@ -293,7 +304,7 @@ impl Interpreter {
     ///
     /// The locals must be loaded before calling this.
     fn make_new_frame(
-        &self,
+        &mut self,
         function: Arc<Function>,
         ty_args: Vec<Type>,
         locals: Locals,
@@ -311,6 +322,7 @@ impl Interpreter {
         } else {
             vec![]
         };
+        self.locals_memory.store_locals(&locals)?;
         Ok(Frame {
             pc: 0,
             locals,
@@ -386,6 +398,7 @@ impl Interpreter {
             }),
             args.iter(),
         )?;
+        gas_meter.release_heap_memory_for_values(args.iter());
 
         let result = native_function(&mut native_context, ty_args.clone(), args)?;
 
@@ -394,6 +407,7 @@ impl Interpreter {
         let return_values = match result.result {
             Ok(vals) => {
                 gas_meter.charge_native_function(result.cost, Some(vals.iter()))?;
+                gas_meter.use_heap_memory_for_values(vals.iter())?;
                 vals
             }
             Err(code) => {
@@ -506,7 +520,9 @@ impl Interpreter {
         data_store: &'b mut impl DataStore,
         addr: AccountAddress,
         ty: &Type,
+        access: ResourceAccess,
     ) -> PartialVMResult<&'b mut GlobalValue> {
+        data_store.record_resource_access(addr, ty, access);
         match data_store.load_resource(addr, ty) {
             Ok((gv, load_res)) => {
                 if let Some(loaded) = load_res {
@@ -548,7 +564,12 @@ impl Interpreter {
         addr: AccountAddress,
         ty: &Type,
     ) -> PartialVMResult<()> {
-        let res = Self::load_resource(gas_meter, data_store, addr, ty)?.borrow_global();
+        let access = if is_mut {
+            ResourceAccess::BorrowGlobalMut
+        } else {
+            ResourceAccess::BorrowGlobal
+        };
+        let res = Self::load_resource(gas_meter, data_store, addr, ty, access)?.borrow_global();
         gas_meter.charge_borrow_global(
             is_mut,
             is_generic,
@@ -569,7 +590,7 @@ impl Interpreter {
         addr: AccountAddress,
         ty: &Type,
     ) -> PartialVMResult<()> {
-        let gv = Self::load_resource(gas_meter, data_store, addr, ty)?;
+        let gv = Self::load_resource(gas_meter, data_store, addr, ty, ResourceAccess::Exists)?;
         let exists = gv.exists()?;
         gas_meter.charge_exists(is_generic, TypeWithLoader { ty, loader }, exists)?;
         self.operand_stack.push(Value::bool(exists))?;
@@ -586,13 +607,15 @@ impl Interpreter {
         addr: AccountAddress,
         ty: &Type,
     ) -> PartialVMResult<()> {
-        let resource = match Self::load_resource(gas_meter, data_store, addr, ty)?.move_from() {
+        let gv = Self::load_resource(gas_meter, data_store, addr, ty, ResourceAccess::MoveFrom)?;
+        let resource = match gv.move_from() {
             Ok(resource) => {
                 gas_meter.charge_move_from(
                     is_generic,
                     TypeWithLoader { ty, loader },
                     Some(&resource),
                 )?;
+                gas_meter.use_heap_memory_for_values([&resource])?;
                 resource
             }
             Err(err) => {
@@ -616,7 +639,7 @@ impl Interpreter {
         ty: &Type,
         resource: Value,
     ) -> PartialVMResult<()> {
-        let gv = Self::load_resource(gas_meter, data_store, addr, ty)?;
+        let gv = Self::load_resource(gas_meter, data_store, addr, ty, ResourceAccess::MoveTo)?;
         // NOTE(Gas): To maintain backward compatibility, we need to charge gas after attempting
         //            the move_to operation.
         match gv.move_to(resource) {
@@ -627,6 +650,7 @@ impl Interpreter {
                     gv.view().unwrap(),
                     true,
                 )?;
+                gas_meter.release_heap_memory_for_values([gv.view().unwrap()]);
                 Ok(())
             }
             Err((err, resource)) => {
@@ -739,7 +763,7 @@ impl Interpreter {
         loader: &Loader,
     ) -> PartialVMResult<()> {
         debug_writeln!(buf, "Call Stack:")?;
-        for (i, frame) in self.call_stack.0.iter().enumerate() {
+        for (i, frame) in self.call_stack.frames.iter().enumerate() {
             self.debug_print_frame(buf, loader, i, frame)?;
         }
         debug_writeln!(buf, "Operand Stack:")?;
@@ -761,7 +785,7 @@ impl Interpreter {
     /// of an execution.
     fn internal_state_str(&self, current_frame: &Frame) -> String {
         let mut internal_state = "Call stack:\n".to_string();
-        for (i, frame) in self.call_stack.0.iter().enumerate() {
+        for (i, frame) in self.call_stack.frames.iter().enumerate() {
             internal_state.push_str(
                 format!(
                     " frame #{}: {} [pc = {}]\n",
@@ -775,7 +799,7 @@ impl Interpreter {
         internal_state.push_str(
             format!(
                 "*frame #{}: {} [pc = {}]:\n",
-                self.call_stack.0.len(),
+                self.call_stack.frames.len(),
                 current_frame.function.pretty_string(),
                 current_frame.pc,
             )
@@ -814,7 +838,7 @@ impl Interpreter {
         // is the last one)
         let stack_trace = self
             .call_stack
-            .0
+            .frames
             .iter()
             .rev()
             .take(count)
@@ -830,29 +854,27 @@ impl Interpreter {
     }
 }
 
-// TODO Determine stack size limits based on gas limit
-const OPERAND_STACK_SIZE_LIMIT: usize = 1024;
-const CALL_STACK_SIZE_LIMIT: usize = 1024;
-
 /// The operand stack.
 struct Stack {
     value: Vec<Value>,
     types: Vec<Type>,
+    max_size: usize,
 }
 
 impl Stack {
-    /// Create a new empty operand stack.
-    fn new() -> Self {
+    /// Create a new empty operand stack holding at most `max_size` values.
+    fn new(max_size: usize) -> Self {
         Stack {
             value: vec![],
             types: vec![],
+            max_size,
         }
     }
 
     /// Push a `Value` on the stack if the max stack size has not been reached. Abort execution
     /// otherwise.
     fn push(&mut self, value: Value) -> PartialVMResult<()> {
-        if self.value.len() < OPERAND_STACK_SIZE_LIMIT {
+        if self.value.len() < self.max_size {
             self.value.push(value);
             Ok(())
         } else {
@@ -898,7 +920,7 @@ impl Stack {
     /// Push a `Value` on the stack if the max stack size has not been reached. Abort execution
     /// otherwise.
     fn push_ty(&mut self, ty: Type) -> PartialVMResult<()> {
-        if self.types.len() < OPERAND_STACK_SIZE_LIMIT {
+        if self.types.len() < self.max_size {
             self.types.push(ty);
             Ok(())
         } else {
@@ -938,18 +960,24 @@ impl Stack {
 
 /// A call stack.
 // #[derive(Debug)]
-struct CallStack(Vec<Frame>);
+struct CallStack {
+    frames: Vec<Frame>,
+    max_size: usize,
+}
 
 impl CallStack {
-    /// Create a new empty call stack.
-    fn new() -> Self {
-        CallStack(vec![])
+    /// Create a new empty call stack holding at most `max_size` frames.
+    fn new(max_size: usize) -> Self {
+        CallStack {
+            frames: vec![],
+            max_size,
+        }
     }
 
     /// Push a `Frame` on the call stack.
     fn push(&mut self, frame: Frame) -> ::std::result::Result<(), Frame> {
-        if self.0.len() < CALL_STACK_SIZE_LIMIT {
-            self.0.push(frame);
+        if self.frames.len() < self.max_size {
+            self.frames.push(frame);
             Ok(())
         } else {
             Err(frame)
@@ -958,11 +986,11 @@ impl CallStack {
 
     /// Pop a `Frame` off the call stack.
     fn pop(&mut self) -> Option<Frame> {
-        self.0.pop()
+        self.frames.pop()
     }
 
     fn current_location(&self) -> Location {
-        let location_opt = self.0.last().map(|frame| frame.location());
+        let location_opt = self.frames.last().map(|frame| frame.location());
         location_opt.unwrap_or(Location::Undefined)
     }
 }
@@ -978,6 +1006,76 @@ struct Frame {
     local_tys: Vec<Type>,
 }
 
+/// Tracks the abstract memory size of the values held by the locals of the frames on the call
+/// stack against the configured limit, if any. The size is updated by the values stored and
+/// removed, without visiting all the locals.
+///
+/// Values written through references are accounted when written and released when removed
+/// through a reference, whichever frame or global resource owns them, so that a function cannot
+/// exceed the limit by growing a value borrowed from its caller or from global storage. The
+/// locals of a frame are released when it returns.
+struct LocalsMemory {
+    size: AbstractMemorySize,
+    max_locals_memory: Option<u64>,
+}
+
+impl LocalsMemory {
+    fn new(max_locals_memory: Option<u64>) -> Self {
+        Self {
+            size: AbstractMemorySize::zero(),
+            max_locals_memory,
+        }
+    }
+
+    fn check(&self) -> PartialVMResult<()> {
+        match self.max_locals_memory {
+            Some(max_locals_memory) if u64::from(self.size) > max_locals_memory => Err(
+                PartialVMError::new(StatusCode::VM_MAX_LOCALS_MEMORY_REACHED),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Account for the locals of a new frame.
+    fn store_locals(&mut self, locals: &Locals) -> PartialVMResult<()> {
+        if self.max_locals_memory.is_some() {
+            self.size += locals.legacy_abstract_memory_size();
+            self.check()?;
+        }
+        Ok(())
+    }
+
+    /// Account for the locals of a returning frame being dropped.
+    fn release_locals(&mut self, locals: &Locals) {
+        if self.max_locals_memory.is_some() {
+            self.release(locals.legacy_abstract_memory_size());
+        }
+    }
+
+    /// Account for `val` being stored in the locals or written through a reference.
+    fn grow(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        if self.max_locals_memory.is_some() {
+            self.size += val.legacy_abstract_memory_size();
+            self.check()?;
+        }
+        Ok(())
+    }
+
+    /// Account for `val` being removed from the locals or through a reference.
+    fn shrink(&mut self, val: impl ValueView) {
+        if self.max_locals_memory.is_some() {
+            self.release(val.legacy_abstract_memory_size());
+        }
+    }
+
+    fn release(&mut self, size: AbstractMemorySize) {
+        self.size = self
+            .size
+            .checked_sub(size)
+            .unwrap_or_else(AbstractMemorySize::zero);
+    }
+}
+
 /// An `ExitCode` from `execute_code_unit`.
 #[derive(Debug)]
 enum ExitCode {
@@ -1679,7 +1777,8 @@ impl Frame {
                 match instruction {
                     Bytecode::Pop => {
                         let popped_val = interpreter.operand_stack.pop()?;
-                        gas_meter.charge_pop(popped_val)?;
+                        gas_meter.charge_pop(&popped_val)?;
+                        gas_meter.release_heap_memory_for_values([popped_val]);
                     }
                     Bytecode::Ret => {
                         gas_meter.charge_simple_instr(S::Ret)?;
@@ -1741,6 +1840,7 @@ impl Frame {
                         })?;
 
                         gas_meter.charge_ld_const_after_deserialization(&val)?;
+                        gas_meter.use_heap_memory_for_values([&val])?;
 
                         interpreter.operand_stack.push(val)?
                     }
@@ -1756,17 +1856,25 @@ impl Frame {
                         // TODO(Gas): We should charge gas before copying the value.
                         let local = self.locals.copy_loc(*idx as usize)?;
                         gas_meter.charge_copy_loc(&local)?;
+                        gas_meter.use_heap_memory_for_values([&local])?;
                         interpreter.operand_stack.push(local)?;
                     }
                     Bytecode::MoveLoc(idx) => {
                         let local = self.locals.move_loc(*idx as usize)?;
                         gas_meter.charge_move_loc(&local)?;
+                        interpreter.locals_memory.shrink(&local);
 
                         interpreter.operand_stack.push(local)?;
                     }
                     Bytecode::StLoc(idx) => {
                         let value_to_store = interpreter.operand_stack.pop()?;
                         gas_meter.charge_store_loc(&value_to_store)?;
+                        if !self.locals.is_invalid(*idx as usize)? {
+                            let old_val = self.locals.move_loc(*idx as usize)?;
+                            gas_meter.release_heap_memory_for_values([&old_val]);
+                            interpreter.locals_memory.shrink(&old_val);
+                        }
+                        interpreter.locals_memory.grow(&value_to_store)?;
                         self.locals.store_loc(*idx as usize, value_to_store)?;
                     }
                     Bytecode::Call(idx) => {
@@ -1814,10 +1922,15 @@ impl Frame {
                     }
                     Bytecode::Pack(sd_idx) => {
                         let field_count = resolver.field_count(*sd_idx);
+                        resolver
+                            .loader()
+                            .check_value_depth(&resolver.get_struct_type(*sd_idx))?;
                         gas_meter.charge_pack(
                             false,
                             interpreter.operand_stack.last_n(field_count as usize)?,
                         )?;
+                        gas_meter
+                            .use_heap_memory(values::struct_heap_size(field_count as usize))?;
                         let args = interpreter.operand_stack.popn(field_count)?;
                         interpreter
                             .operand_stack
@@ -1825,10 +1938,17 @@ impl Frame {
                     }
                     Bytecode::PackGeneric(si_idx) => {
                         let field_count = resolver.field_instantiation_count(*si_idx);
+                        // Only instantiate the struct type when its depth needs to be checked
+                        if resolver.loader().vm_config().max_value_depth.is_some() {
+                            let ty = resolver.instantiate_generic_type(*si_idx, self.ty_args())?;
+                            resolver.loader().check_value_depth(&ty)?;
+                        }
                         gas_meter.charge_pack(
                             true,
                             interpreter.operand_stack.last_n(field_count as usize)?,
                         )?;
+                        gas_meter
+                            .use_heap_memory(values::struct_heap_size(field_count as usize))?;
                         let args = interpreter.operand_stack.popn(field_count)?;
                         interpreter
                             .operand_stack
@@ -1838,6 +1958,9 @@ impl Frame {
                         let struct_ = interpreter.operand_stack.pop_as::<Struct>()?;
 
                         gas_meter.charge_unpack(false, struct_.field_views())?;
+                        gas_meter.release_heap_memory(values::struct_heap_size(
+                            struct_.field_views().len(),
+                        ));
 
                         for value in struct_.unpack()? {
                             interpreter.operand_stack.push(value)?;
@@ -1847,6 +1970,9 @@ impl Frame {
                         let struct_ = interpreter.operand_stack.pop_as::<Struct>()?;
 
                         gas_meter.charge_unpack(true, struct_.field_views())?;
+                        gas_meter.release_heap_memory(values::struct_heap_size(
+                            struct_.field_views().len(),
+                        ));
 
                         // TODO: Whether or not we want this gas metering in the loop is
                         // questionable.  However, if we don't have it in the loop we could wind up
@@ -1859,12 +1985,16 @@ impl Frame {
                         let reference = interpreter.operand_stack.pop_as::<Reference>()?;
                         gas_meter.charge_read_ref(reference.value_view())?;
                         let value = reference.read_ref()?;
+                        gas_meter.use_heap_memory_for_values([&value])?;
                         interpreter.operand_stack.push(value)?;
                     }
                     Bytecode::WriteRef => {
                         let reference = interpreter.operand_stack.pop_as::<Reference>()?;
                         let value = interpreter.operand_stack.pop()?;
                         gas_meter.charge_write_ref(&value, reference.value_view())?;
+                        gas_meter.release_heap_memory_for_values([reference.value_view()]);
+                        interpreter.locals_memory.shrink(reference.value_view());
+                        interpreter.locals_memory.grow(&value)?;
                         reference.write_ref(value)?;
                     }
                     Bytecode::CastU8 => {
@@ -1998,6 +2128,7 @@ impl Frame {
                         let lhs = interpreter.operand_stack.pop()?;
                         let rhs = interpreter.operand_stack.pop()?;
                         gas_meter.charge_eq(&lhs, &rhs)?;
+                        gas_meter.release_heap_memory_for_values([&lhs, &rhs]);
                         interpreter
                             .operand_stack
                             .push(Value::bool(lhs.equals(&rhs)?))?;
@@ -2006,6 +2137,7 @@ impl Frame {
                         let lhs = interpreter.operand_stack.pop()?;
                         let rhs = interpreter.operand_stack.pop()?;
                         gas_meter.charge_neq(&lhs, &rhs)?;
+                        gas_meter.release_heap_memory_for_values([&lhs, &rhs]);
                         interpreter
                             .operand_stack
                             .push(Value::bool(!lhs.equals(&rhs)?))?;
@@ -2145,8 +2277,12 @@ impl Frame {
                             make_ty!(&ty),
                             interpreter.operand_stack.last_n(*num as usize)?,
                         )?;
+                        gas_meter.use_heap_memory(values::vector_heap_size(&ty, *num as usize))?;
                         let elements = interpreter.operand_stack.popn(*num as u16)?;
                         let value = Vector::pack(&ty, elements)?;
+                        resolver
+                            .loader()
+                            .check_value_depth(&Type::Vector(Box::new(ty)))?;
                         interpreter.operand_stack.push(value)?;
                     }
                     Bytecode::VecLen(si) => {
@@ -2180,6 +2316,8 @@ impl Frame {
                         let vec_ref = interpreter.operand_stack.pop_as::<VectorRef>()?;
                         let ty = &resolver.instantiate_single_type(*si, self.ty_args())?;
                         gas_meter.charge_vec_push_back(make_ty!(ty), &elem)?;
+                        gas_meter.use_heap_memory(values::vector_elem_heap_size(ty))?;
+                        interpreter.locals_memory.grow(&elem)?;
                         vec_ref.push_back(elem, ty)?;
                     }
                     Bytecode::VecPopBack(si) => {
@@ -2187,6 +2325,10 @@ impl Frame {
                         let ty = &resolver.instantiate_single_type(*si, self.ty_args())?;
                         let res = vec_ref.pop(ty);
                         gas_meter.charge_vec_pop_back(make_ty!(ty), res.as_ref().ok())?;
+                        if let Ok(elem) = &res {
+                            gas_meter.release_heap_memory(values::vector_elem_heap_size(ty));
+                            interpreter.locals_memory.shrink(elem);
+                        }
                         interpreter.operand_stack.push(res?)?;
                     }
                     Bytecode::VecUnpack(si, num) => {
@@ -2197,6 +2339,7 @@ impl Frame {
                             NumArgs::new(*num),
                             vec_val.elem_views(),
                         )?;
+                        gas_meter.release_heap_memory(values::vector_heap_size(ty, *num as usize));
                         let elements = vec_val.unpack(ty, *num)?;
                         for value in elements {
                             interpreter.operand_stack.push(value)?;
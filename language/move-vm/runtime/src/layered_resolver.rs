@@ -0,0 +1,68 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_core_types::{
+    account_address::AccountAddress,
+    effects::ChangeSet,
+    language_storage::{ModuleId, StructTag},
+    resolver::{ModuleResolver, MoveResolver, ResourceResolver},
+};
+
+/// A resolver which sees a set of pending changes on top of another resolver, as if they had
+/// been applied to it.
+///
+/// This is what a child session runs against, see `Session::layered_resolver`. Layered
+/// resolvers can be stacked, so a child session can itself have children.
+pub struct LayeredResolver<'a, S> {
+    base: &'a S,
+    changes: ChangeSet,
+}
+
+impl<'a, S: MoveResolver> LayeredResolver<'a, S> {
+    pub fn new(base: &'a S, changes: ChangeSet) -> Self {
+        Self { base, changes }
+    }
+
+    pub fn base(&self) -> &'a S {
+        self.base
+    }
+
+    /// The changes layered on top of the base resolver.
+    pub fn changes(&self) -> &ChangeSet {
+        &self.changes
+    }
+
+    pub fn into_changes(self) -> ChangeSet {
+        self.changes
+    }
+}
+
+impl<'a, S: MoveResolver> ModuleResolver for LayeredResolver<'a, S> {
+    type Error = S::Err;
+
+    fn get_module(&self, module_id: &ModuleId) -> Result<Option<Vec<u8>>, Self::Error> {
+        if let Some(account_changes) = self.changes.accounts().get(module_id.address()) {
+            if let Some(op) = account_changes.modules().get(module_id.name()) {
+                return Ok(op.clone().ok());
+            }
+        }
+        self.base.get_module(module_id)
+    }
+}
+
+impl<'a, S: MoveResolver> ResourceResolver for LayeredResolver<'a, S> {
+    type Error = S::Err;
+
+    fn get_resource(
+        &self,
+        address: &AccountAddress,
+        tag: &StructTag,
+    ) -> Result<Option<Vec<u8>>, Self::Error> {
+        if let Some(account_changes) = self.changes.accounts().get(address) {
+            if let Some(op) = account_changes.resources().get(tag) {
+                return Ok(op.clone().ok());
+            }
+        }
+        self.base.get_resource(address, tag)
+    }
+}
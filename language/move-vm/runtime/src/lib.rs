@@ -12,6 +12,7 @@
 
 pub mod data_cache;
 mod interpreter;
+pub mod layered_resolver;
 mod loader;
 pub mod logging;
 pub mod move_vm;
@@ -22,6 +23,7 @@ pub mod session;
 #[macro_use]
 mod tracing;
 pub mod config;
+pub mod verified_module_cache;
 
 // Only include debugging functionality in debug builds
 #[cfg(any(debug_assertions, feature = "debugging"))]
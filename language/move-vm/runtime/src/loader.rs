@@ -5,8 +5,11 @@
 use crate::{
     config::VMConfig,
     logging::expect_no_verification_errors,
-    native_functions::{NativeFunction, NativeFunctions, UnboxedNativeFunction},
+    native_functions::{
+        NativeFunction, NativeFunctions, NativeSignature, NativeType, UnboxedNativeFunction,
+    },
     session::LoadedFunctionInstantiation,
+    verified_module_cache::VerifiedModuleCache,
 };
 use move_binary_format::{
     access::{ModuleAccess, ScriptAccess},
@@ -23,6 +26,7 @@ use move_binary_format::{
 };
 use move_bytecode_verifier::{self, cyclic_dependencies, dependencies};
 use move_core_types::{
+    account_address::AccountAddress,
     identifier::{IdentStr, Identifier},
     language_storage::{ModuleId, StructTag, TypeTag},
     metadata::Metadata,
@@ -76,6 +80,11 @@ where
     fn get(&self, key: &K) -> Option<&Arc<V>> {
         self.id_map.get(key).and_then(|idx| self.binaries.get(*idx))
     }
+
+    // The binary is kept, so the indices of other binaries do not change.
+    fn remove(&mut self, key: &K) {
+        self.id_map.remove(key);
+    }
 }
 
 // A script cache is a map from the hash value of a script and the `Script` itself.
@@ -473,7 +482,7 @@ pub(crate) struct Loader {
     scripts: RwLock<ScriptCache>,
     module_cache: RwLock<ModuleCache>,
     type_cache: RwLock<TypeCache>,
-    natives: NativeFunctions,
+    natives: RwLock<NativeFunctions>,
 
     // The below field supports a hack to workaround well-known issues with the
     // loader cache. This cache is not designed to support module upgrade or deletion.
@@ -516,7 +525,7 @@ impl Loader {
             scripts: RwLock::new(ScriptCache::new()),
             module_cache: RwLock::new(ModuleCache::new()),
             type_cache: RwLock::new(TypeCache::new()),
-            natives,
+            natives: RwLock::new(natives),
             invalidated: RwLock::new(false),
             module_cache_hits: RwLock::new(BTreeSet::new()),
             vm_config,
@@ -556,6 +565,25 @@ impl Loader {
         }
     }
 
+    /// The modules `ids` together with the transitive dependencies of those in the cache.
+    pub(crate) fn dependency_closure<'a>(
+        &self,
+        ids: impl IntoIterator<Item = &'a ModuleId>,
+    ) -> BTreeSet<ModuleId> {
+        let module_cache = self.module_cache.read();
+        let mut closure = BTreeSet::new();
+        let mut worklist: Vec<_> = ids.into_iter().cloned().collect();
+        while let Some(id) = worklist.pop() {
+            if !closure.insert(id.clone()) {
+                continue;
+            }
+            if let Some(module) = module_cache.module_at(&id) {
+                worklist.extend(module.module().immediate_dependencies());
+            }
+        }
+        closure
+    }
+
     /// Flush this cache if it is marked as invalidated.
     pub(crate) fn flush_if_invalidated(&self) {
         let mut invalidated = self.invalidated.write();
@@ -577,6 +605,79 @@ impl Loader {
         *self.invalidated.read()
     }
 
+    /// Remove the modules `ids`, and the modules depending on them, from the cache. This is used
+    /// when a session rolls back the publishing of modules, which may have been loaded since.
+    /// The types and functions of removed modules remain in the cache, as they are referred to by
+    /// index.
+    pub(crate) fn evict_modules(&self, ids: &BTreeSet<ModuleId>) {
+        let mut module_cache = self.module_cache.write();
+        let mut evicted: BTreeSet<_> = ids
+            .iter()
+            .filter(|id| module_cache.modules.get(id).is_some())
+            .cloned()
+            .collect();
+        if evicted.is_empty() {
+            return;
+        }
+        loop {
+            let dependents: Vec<_> = module_cache
+                .modules
+                .id_map
+                .keys()
+                .filter(|id| {
+                    !evicted.contains(*id)
+                        && module_cache
+                            .module_at(id)
+                            .expect("module must be cached")
+                            .module()
+                            .immediate_dependencies()
+                            .iter()
+                            .any(|dep| evicted.contains(dep))
+                })
+                .cloned()
+                .collect();
+            if dependents.is_empty() {
+                break;
+            }
+            evicted.extend(dependents);
+        }
+        for id in &evicted {
+            module_cache.modules.remove(id);
+        }
+        drop(module_cache);
+
+        // scripts link against the modules they use, so they may refer to evicted modules
+        *self.scripts.write() = ScriptCache::new();
+        self.module_cache_hits
+            .write()
+            .retain(|id| !evicted.contains(id));
+    }
+
+    /// Add natives, replacing any native with the same name, and evict the modules declaring
+    /// them from the cache so they are linked against the new natives when loaded again.
+    pub(crate) fn register_natives(
+        &self,
+        natives: impl IntoIterator<
+            Item = (
+                AccountAddress,
+                Identifier,
+                Identifier,
+                NativeFunction,
+                Option<NativeSignature>,
+            ),
+        >,
+    ) {
+        let mut modules = BTreeSet::new();
+        let mut table = self.natives.write();
+        for (addr, module_name, func_name, func, signature) in natives {
+            modules.insert(ModuleId::new(addr, module_name.clone()));
+            table.insert(addr, module_name, func_name, func, signature);
+        }
+        // the module cache is locked before the natives when loading, so release them first
+        drop(table);
+        self.evict_modules(&modules);
+    }
+
     /// Copies metadata out of a modules bytecode if available.
     pub(crate) fn get_metadata(&self, module: ModuleId, key: &[u8]) -> Option<Metadata> {
         let cache = self.module_cache.read();
@@ -811,7 +912,7 @@ impl Loader {
         // module will NOT show up in `module_cache`. In the module republishing case, it means
         // that the old module is still in the `module_cache`, unless a new Loader is created,
         // which means that a new MoveVM instance needs to be created.
-        move_bytecode_verifier::verify_module_with_config(&self.vm_config.verifier, module)?;
+        self.verify_module_with_cache(module, None)?;
         self.check_natives(module)?;
 
         let mut visited = BTreeSet::new();
@@ -884,25 +985,24 @@ impl Loader {
     }
 
     // All native functions must be known to the loader, unless we are compiling with feature
-    // `lazy_natives`.
+    // `lazy_natives`. Natives registered with a signature must match their Move declaration.
     fn check_natives(&self, module: &CompiledModule) -> VMResult<()> {
         fn check_natives_impl(loader: &Loader, module: &CompiledModule) -> PartialVMResult<()> {
-            if !cfg!(feature = "lazy_natives") {
-                for (idx, native_function) in module
-                    .function_defs()
-                    .iter()
-                    .filter(|fdv| fdv.is_native())
-                    .enumerate()
-                {
-                    let fh = module.function_handle_at(native_function.function);
-                    let mh = module.module_handle_at(fh.module);
-                    loader
-                        .natives
-                        .resolve(
-                            module.address_identifier_at(mh.address),
-                            module.identifier_at(mh.name).as_str(),
-                            module.identifier_at(fh.name).as_str(),
-                        )
+            let natives = loader.natives.read();
+            for (idx, native_function) in module
+                .function_defs()
+                .iter()
+                .filter(|fdv| fdv.is_native())
+                .enumerate()
+            {
+                let fh = module.function_handle_at(native_function.function);
+                let mh = module.module_handle_at(fh.module);
+                let addr = module.address_identifier_at(mh.address);
+                let module_name = module.identifier_at(mh.name).as_str();
+                let func_name = module.identifier_at(fh.name).as_str();
+                if !cfg!(feature = "lazy_natives") {
+                    natives
+                        .resolve(addr, module_name, func_name)
                         .ok_or_else(|| {
                             verification_error(
                                 StatusCode::MISSING_DEPENDENCY,
@@ -911,6 +1011,32 @@ impl Loader {
                             )
                         })?;
                 }
+                if let Some(expected) = natives.resolve_signature(addr, module_name, func_name) {
+                    let to_native_types = |sig_idx| {
+                        module
+                            .signature_at(sig_idx)
+                            .0
+                            .iter()
+                            .map(|tok| NativeType::from_signature_token(module, tok))
+                            .collect()
+                    };
+                    let declared = NativeSignature {
+                        type_parameters: fh.type_parameters.len(),
+                        parameters: to_native_types(fh.parameters),
+                        return_: to_native_types(fh.return_),
+                    };
+                    if &declared != expected {
+                        return Err(verification_error(
+                            StatusCode::NATIVE_FUNCTION_SIGNATURE_MISMATCH,
+                            IndexKind::FunctionHandle,
+                            idx as TableIndex,
+                        )
+                        .with_message(format!(
+                            "native function `{}` is declared as {:?} but registered as {:?}",
+                            func_name, declared, expected
+                        )));
+                    }
+                }
             }
             // TODO: fix check and error code if we leave something around for native structs.
             // For now this generates the only error test cases care about...
@@ -991,6 +1117,7 @@ impl Loader {
         bundle_unverified: &BTreeSet<ModuleId>,
         data_store: &impl DataStore,
     ) -> VMResult<Arc<Module>> {
+        data_store.record_module_access(id);
         // if the module is already in the code cache, load the cached version
         if let Some(cached) = self.module_cache.read().module_at(id) {
             self.module_cache_hits.write().insert(id.clone());
@@ -1017,6 +1144,39 @@ impl Loader {
         Ok(module_ref)
     }
 
+    // Run the bytecode verifier checks that can be performed with the module itself, unless the
+    // verified module cache knows the module already passed them under the same config.
+    fn verify_module_with_cache(
+        &self,
+        module: &CompiledModule,
+        bytes: Option<&[u8]>,
+    ) -> VMResult<()> {
+        let verify =
+            || move_bytecode_verifier::verify_module_with_config(&self.vm_config.verifier, module);
+        let cache = match &self.vm_config.verified_module_cache {
+            Some(cache) => cache,
+            None => return verify(),
+        };
+        let mut blob = vec![];
+        let bytes = match bytes {
+            Some(bytes) => bytes,
+            // modules being published are only available deserialized
+            None if module.serialize(&mut blob).is_ok() => &blob,
+            None => return verify(),
+        };
+        let key = VerifiedModuleCache::key(
+            &self.vm_config.verifier,
+            self.vm_config.max_binary_format_version,
+            bytes,
+        );
+        if cache.contains(&key) {
+            return Ok(());
+        }
+        verify()?;
+        cache.insert(key);
+        Ok(())
+    }
+
     // Load, deserialize, and check the module with the bytecode verifier, without linking
     fn load_and_verify_module(
         &self,
@@ -1059,7 +1219,7 @@ impl Loader {
         }
 
         // bytecode verifier checks that can be performed with the module itself
-        move_bytecode_verifier::verify_module_with_config(&self.vm_config.verifier, &module)
+        self.verify_module_with_cache(&module, Some(&bytes))
             .map_err(expect_no_verification_errors)?;
         self.check_natives(&module)
             .map_err(expect_no_verification_errors)?;
@@ -1103,7 +1263,7 @@ impl Loader {
 
         // if linking goes well, insert the module to the code cache
         let mut locked_cache = self.module_cache.write();
-        let module_ref = locked_cache.insert(&self.natives, id.clone(), module)?;
+        let module_ref = locked_cache.insert(&self.natives.read(), id.clone(), module)?;
         drop(locked_cache); // explicit unlock
 
         Ok(module_ref)
@@ -1478,13 +1638,21 @@ impl<'a> Resolver<'a> {
 
         // Before instantiating the type, count the # of nodes of all type arguments plus
         // existing type instantiation.
-        // If that number is larger than MAX_TYPE_INSTANTIATION_NODES, refuse to construct this type.
+        // If that number is larger than MAX_TYPE_INSTANTIATION_NODES, or the configured
+        // `max_type_instantiation_size`, refuse to construct this type.
         // This prevents constructing larger and lager types via struct instantiation.
+        let (max_nodes, status) = match self.loader.vm_config.max_type_instantiation_size {
+            Some(max_nodes) => (max_nodes, StatusCode::VM_MAX_TYPE_NODES_REACHED),
+            None => (
+                MAX_TYPE_INSTANTIATION_NODES,
+                StatusCode::TOO_MANY_TYPE_NODES,
+            ),
+        };
         let mut sum_nodes: usize = 1;
         for ty in ty_args.iter().chain(struct_inst.instantiation.iter()) {
             sum_nodes = sum_nodes.saturating_add(self.loader.count_type_nodes(ty));
-            if sum_nodes > MAX_TYPE_INSTANTIATION_NODES {
-                return Err(PartialVMError::new(StatusCode::TOO_MANY_TYPE_NODES));
+            if sum_nodes > max_nodes {
+                return Err(PartialVMError::new(status));
             }
         }
 
@@ -2430,6 +2598,7 @@ struct StructInfo {
     annotated_struct_layout: Option<MoveStructLayout>,
     node_count: Option<usize>,
     annotated_node_count: Option<usize>,
+    value_depth: Option<usize>,
 }
 
 impl StructInfo {
@@ -2440,6 +2609,7 @@ impl StructInfo {
             annotated_struct_layout: None,
             node_count: None,
             annotated_node_count: None,
+            value_depth: None,
         }
     }
 }
@@ -2608,7 +2778,7 @@ impl Loader {
         if *count > MAX_TYPE_TO_LAYOUT_NODES {
             return Err(PartialVMError::new(StatusCode::TOO_MANY_TYPE_NODES));
         }
-        if depth > VALUE_DEPTH_MAX {
+        if depth > self.vm_config.max_value_depth.unwrap_or(VALUE_DEPTH_MAX) {
             return Err(PartialVMError::new(StatusCode::VM_MAX_VALUE_DEPTH_REACHED));
         }
         Ok(match ty {
@@ -2739,7 +2909,7 @@ impl Loader {
         if *count > MAX_TYPE_TO_LAYOUT_NODES {
             return Err(PartialVMError::new(StatusCode::TOO_MANY_TYPE_NODES));
         }
-        if depth > VALUE_DEPTH_MAX {
+        if depth > self.vm_config.max_value_depth.unwrap_or(VALUE_DEPTH_MAX) {
             return Err(PartialVMError::new(StatusCode::VM_MAX_VALUE_DEPTH_REACHED));
         }
         Ok(match ty {
@@ -2786,6 +2956,85 @@ impl Loader {
         let mut count = 0;
         self.type_to_fully_annotated_layout_impl(ty, &mut count, 1)
     }
+
+    /// Check that values of type `ty` are not nested deeper than `max_value_depth`, if it is
+    /// configured.
+    pub(crate) fn check_value_depth(&self, ty: &Type) -> PartialVMResult<()> {
+        match self.vm_config.max_value_depth {
+            Some(max_depth) => self.calculate_value_depth(ty, 1, max_depth).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the depth of values of type `ty`, relative to `depth`, the depth at which they
+    /// are nested.
+    fn calculate_value_depth(
+        &self,
+        ty: &Type,
+        depth: usize,
+        max_depth: usize,
+    ) -> PartialVMResult<usize> {
+        if depth > max_depth {
+            return Err(PartialVMError::new(StatusCode::VM_MAX_VALUE_DEPTH_REACHED));
+        }
+        Ok(match ty {
+            Type::Bool
+            | Type::U8
+            | Type::U16
+            | Type::U32
+            | Type::U64
+            | Type::U128
+            | Type::U256
+            | Type::Address
+            | Type::Signer
+            | Type::TyParam(_) => 1,
+            Type::Vector(ty) | Type::Reference(ty) | Type::MutableReference(ty) => {
+                self.calculate_value_depth(ty, depth + 1, max_depth)? + 1
+            }
+            Type::Struct(gidx) => self.struct_gidx_value_depth(*gidx, &[], depth, max_depth)?,
+            Type::StructInstantiation(gidx, ty_args) => {
+                self.struct_gidx_value_depth(*gidx, ty_args, depth, max_depth)?
+            }
+        })
+    }
+
+    fn struct_gidx_value_depth(
+        &self,
+        gidx: CachedStructIndex,
+        ty_args: &[Type],
+        depth: usize,
+        max_depth: usize,
+    ) -> PartialVMResult<usize> {
+        if let Some(struct_map) = self.type_cache.read().structs.get(&gidx) {
+            if let Some(struct_info) = struct_map.get(ty_args) {
+                if let Some(value_depth) = struct_info.value_depth {
+                    if depth + value_depth - 1 > max_depth {
+                        return Err(PartialVMError::new(StatusCode::VM_MAX_VALUE_DEPTH_REACHED));
+                    }
+                    return Ok(value_depth);
+                }
+            }
+        }
+
+        let struct_type = self.module_cache.read().struct_at(gidx);
+        let mut value_depth = 1;
+        for ty in &struct_type.fields {
+            let field_depth =
+                self.calculate_value_depth(&ty.subst(ty_args)?, depth + 1, max_depth)?;
+            value_depth = value_depth.max(field_depth + 1);
+        }
+
+        let mut cache = self.type_cache.write();
+        let info = cache
+            .structs
+            .entry(gidx)
+            .or_insert_with(HashMap::new)
+            .entry(ty_args.to_vec())
+            .or_insert_with(StructInfo::new);
+        info.value_depth = Some(value_depth);
+
+        Ok(value_depth)
+    }
 }
 
 // Public APIs for external uses.
@@ -5,8 +5,12 @@
 use std::{collections::BTreeSet, sync::Arc};
 
 use crate::{
-    config::VMConfig, data_cache::TransactionDataCache, native_extensions::NativeContextExtensions,
-    native_functions::NativeFunction, runtime::VMRuntime, session::Session,
+    config::VMConfig,
+    data_cache::TransactionDataCache,
+    native_extensions::NativeContextExtensions,
+    native_functions::{NativeFunction, NativeSignature},
+    runtime::VMRuntime,
+    session::Session,
 };
 use move_binary_format::{
     errors::{Location, VMResult},
@@ -65,6 +69,48 @@ impl MoveVM {
         self.runtime.new_session_with_extensions(remote, extensions)
     }
 
+    /// Add natives to the VM, replacing any native with the same name.
+    ///
+    /// Modules declaring the natives are evicted from the code cache, together with the modules
+    /// depending on them, so they are linked against the new natives when loaded again. Sessions
+    /// which already loaded a native keep calling the implementation they loaded.
+    pub fn register_natives(
+        &self,
+        natives: impl IntoIterator<Item = (AccountAddress, Identifier, Identifier, NativeFunction)>,
+    ) {
+        self.runtime.loader().register_natives(
+            natives
+                .into_iter()
+                .map(|(addr, module_name, func_name, func)| {
+                    (addr, module_name, func_name, func, None)
+                }),
+        )
+    }
+
+    /// Add natives to the VM as in `register_natives`, together with their signatures. A module
+    /// declaring one of these natives fails to load with `NATIVE_FUNCTION_SIGNATURE_MISMATCH` if
+    /// the Move declaration does not match the signature.
+    pub fn register_typed_natives(
+        &self,
+        natives: impl IntoIterator<
+            Item = (
+                AccountAddress,
+                Identifier,
+                Identifier,
+                NativeSignature,
+                NativeFunction,
+            ),
+        >,
+    ) {
+        self.runtime
+            .loader()
+            .register_natives(natives.into_iter().map(
+                |(addr, module_name, func_name, signature, func)| {
+                    (addr, module_name, func_name, func, Some(signature))
+                },
+            ))
+    }
+
     /// Load a module into VM's code cache
     pub fn load_module<'r, S: MoveResolver>(
         &self,
@@ -3,8 +3,33 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use better_any::{Tid, TidAble, TidExt};
+use move_binary_format::errors::PartialVMResult;
 use std::{any::TypeId, collections::HashMap};
 
+/// An extension whose state can be saved when a savepoint is created in a session, and restored
+/// when the session rolls back to it (see `Session::savepoint`). Extensions must be added with
+/// `NativeContextExtensions::add_restorable` for their state to be restored.
+pub trait RestorableExtension<'a>: TidAble<'a> {
+    /// Return a copy of the extension. The copy must share no mutable state with the extension,
+    /// as it is kept aside and swapped in on rollback.
+    fn copy_state(&self) -> PartialVMResult<Self>
+    where
+        Self: Sized;
+}
+
+type CopyFn<'a> = fn(&dyn Tid<'a>) -> PartialVMResult<Box<dyn Tid<'a>>>;
+
+fn copy_extension<'a, T: RestorableExtension<'a>>(
+    ext: &dyn Tid<'a>,
+) -> PartialVMResult<Box<dyn Tid<'a>>> {
+    Ok(Box::new(ext.downcast_ref::<T>().unwrap().copy_state()?))
+}
+
+/// The saved state of the restorable extensions in a `NativeContextExtensions`.
+pub(crate) struct ExtensionsSavepoint<'a> {
+    map: HashMap<TypeId, Box<dyn Tid<'a>>>,
+}
+
 /// A data type to represent a heterogeneous collection of extensions which are available to
 /// native functions. A value to this is passed into the session function execution.
 ///
@@ -16,6 +41,7 @@ use std::{any::TypeId, collections::HashMap};
 #[derive(Default)]
 pub struct NativeContextExtensions<'a> {
     map: HashMap<TypeId, Box<dyn Tid<'a>>>,
+    restorable: HashMap<TypeId, CopyFn<'a>>,
 }
 
 impl<'a> NativeContextExtensions<'a> {
@@ -26,6 +52,17 @@ impl<'a> NativeContextExtensions<'a> {
         )
     }
 
+    /// Like `add`, but the state of the extension is restored when a session rolls back to a
+    /// savepoint. Extensions added with `add` are left as they are on rollback.
+    pub fn add_restorable<T: RestorableExtension<'a>>(&mut self, ext: T) {
+        self.add(ext);
+        self.restorable.insert(T::id(), copy_extension::<T>);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
     pub fn get<T: TidAble<'a>>(&self) -> &T {
         self.map
             .get(&T::id())
@@ -45,6 +82,7 @@ impl<'a> NativeContextExtensions<'a> {
     }
 
     pub fn remove<T: TidAble<'a>>(&mut self) -> T {
+        self.restorable.remove(&T::id());
         // can't use expect below because it requires `T: Debug`.
         match self
             .map
@@ -56,12 +94,44 @@ impl<'a> NativeContextExtensions<'a> {
             Err(_) => panic!("downcast error"),
         }
     }
+
+    /// Save a copy of the restorable extensions.
+    pub(crate) fn savepoint(&self) -> PartialVMResult<ExtensionsSavepoint<'a>> {
+        Ok(ExtensionsSavepoint {
+            map: self.copy_restorable(&self.map)?,
+        })
+    }
+
+    /// Restore the restorable extensions to their state in `savepoint`. The savepoint is copied
+    /// again, so that it can be rolled back to multiple times.
+    pub(crate) fn rollback_to(
+        &mut self,
+        savepoint: &ExtensionsSavepoint<'a>,
+    ) -> PartialVMResult<()> {
+        let restored = self.copy_restorable(&savepoint.map)?;
+        self.map.extend(restored);
+        Ok(())
+    }
+
+    fn copy_restorable(
+        &self,
+        map: &HashMap<TypeId, Box<dyn Tid<'a>>>,
+    ) -> PartialVMResult<HashMap<TypeId, Box<dyn Tid<'a>>>> {
+        let mut copy = HashMap::new();
+        for (id, copy_fn) in &self.restorable {
+            if let Some(ext) = map.get(id) {
+                copy.insert(*id, copy_fn(ext.as_ref())?);
+            }
+        }
+        Ok(copy)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::native_extensions::NativeContextExtensions;
+    use crate::native_extensions::{NativeContextExtensions, RestorableExtension};
     use better_any::{Tid, TidAble};
+    use move_binary_format::errors::PartialVMResult;
 
     #[derive(Tid)]
     struct Ext<'a> {
@@ -73,11 +143,42 @@ mod tests {
         let mut v: u64 = 23;
         let e = Ext { a: &mut v };
         let mut exts = NativeContextExtensions::default();
+        assert!(exts.is_empty());
         exts.add(e);
+        assert!(!exts.is_empty());
         *exts.get_mut::<Ext>().a += 1;
         assert_eq!(*exts.get_mut::<Ext>().a, 24);
         *exts.get_mut::<Ext>().a += 1;
         let e1 = exts.remove::<Ext>();
         assert_eq!(*e1.a, 25)
     }
+
+    #[derive(Tid)]
+    struct Counter {
+        count: u64,
+    }
+
+    impl<'a> RestorableExtension<'a> for Counter {
+        fn copy_state(&self) -> PartialVMResult<Self> {
+            Ok(Counter { count: self.count })
+        }
+    }
+
+    #[test]
+    fn restorable_ext() {
+        let mut v: u64 = 23;
+        let mut exts = NativeContextExtensions::default();
+        exts.add(Ext { a: &mut v });
+        exts.add_restorable(Counter { count: 1 });
+        let savepoint = exts.savepoint().unwrap();
+        *exts.get_mut::<Ext>().a += 1;
+        exts.get_mut::<Counter>().count += 1;
+        exts.rollback_to(&savepoint).unwrap();
+        // only restorable extensions are rolled back
+        assert_eq!(*exts.get_mut::<Ext>().a, 24);
+        assert_eq!(exts.get::<Counter>().count, 1);
+        exts.get_mut::<Counter>().count += 2;
+        exts.rollback_to(&savepoint).unwrap();
+        assert_eq!(exts.get::<Counter>().count, 1);
+    }
 }
@@ -5,7 +5,11 @@
 use crate::{
     interpreter::Interpreter, loader::Resolver, native_extensions::NativeContextExtensions,
 };
-use move_binary_format::errors::{ExecutionState, PartialVMError, PartialVMResult};
+use move_binary_format::{
+    access::ModuleAccess,
+    errors::{ExecutionState, PartialVMError, PartialVMResult},
+    file_format::{CompiledModule, SignatureToken, TypeParameterIndex},
+};
 use move_core_types::{
     account_address::AccountAddress,
     identifier::Identifier,
@@ -56,8 +60,87 @@ pub fn make_table_from_iter<S: Into<Box<str>>>(
         .collect()
 }
 
+/// The type of a parameter or return value of a native function, as declared when registering
+/// the native with a signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NativeType {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    Address,
+    Signer,
+    Vector(Box<NativeType>),
+    Struct {
+        address: AccountAddress,
+        module: Identifier,
+        name: Identifier,
+        type_arguments: Vec<NativeType>,
+    },
+    Reference(Box<NativeType>),
+    MutableReference(Box<NativeType>),
+    TypeParameter(TypeParameterIndex),
+}
+
+impl NativeType {
+    /// Convert a type in the signature of a function declared by `module`.
+    pub fn from_signature_token(module: &CompiledModule, token: &SignatureToken) -> Self {
+        use SignatureToken as S;
+
+        let convert = |token| Box::new(Self::from_signature_token(module, token));
+        match token {
+            S::Bool => Self::Bool,
+            S::U8 => Self::U8,
+            S::U16 => Self::U16,
+            S::U32 => Self::U32,
+            S::U64 => Self::U64,
+            S::U128 => Self::U128,
+            S::U256 => Self::U256,
+            S::Address => Self::Address,
+            S::Signer => Self::Signer,
+            S::Vector(ty) => Self::Vector(convert(ty)),
+            S::Reference(ty) => Self::Reference(convert(ty)),
+            S::MutableReference(ty) => Self::MutableReference(convert(ty)),
+            S::TypeParameter(idx) => Self::TypeParameter(*idx),
+            S::Struct(idx) | S::StructInstantiation(idx, _) => {
+                let type_arguments = match token {
+                    S::StructInstantiation(_, tys) => tys
+                        .iter()
+                        .map(|ty| Self::from_signature_token(module, ty))
+                        .collect(),
+                    _ => vec![],
+                };
+                let struct_handle = module.struct_handle_at(*idx);
+                let module_handle = module.module_handle_at(struct_handle.module);
+                Self::Struct {
+                    address: *module.address_identifier_at(module_handle.address),
+                    module: module.identifier_at(module_handle.name).to_owned(),
+                    name: module.identifier_at(struct_handle.name).to_owned(),
+                    type_arguments,
+                }
+            }
+        }
+    }
+}
+
+/// The declared signature of a native function. It is checked against the Move declaration of
+/// the native when the declaring module is loaded, so a Rust implementation expecting other
+/// arguments or results than Move passes is rejected before it runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NativeSignature {
+    pub type_parameters: usize,
+    pub parameters: Vec<NativeType>,
+    pub return_: Vec<NativeType>,
+}
+
 pub(crate) struct NativeFunctions(
-    HashMap<AccountAddress, HashMap<String, HashMap<String, NativeFunction>>>,
+    HashMap<
+        AccountAddress,
+        HashMap<String, HashMap<String, (NativeFunction, Option<NativeSignature>)>>,
+    >,
 );
 
 impl NativeFunctions {
@@ -67,25 +150,57 @@ impl NativeFunctions {
         module_name: &str,
         func_name: &str,
     ) -> Option<NativeFunction> {
-        self.0.get(addr)?.get(module_name)?.get(func_name).cloned()
+        self.resolve_entry(addr, module_name, func_name)
+            .map(|(func, _)| func.clone())
+    }
+
+    pub fn resolve_signature(
+        &self,
+        addr: &AccountAddress,
+        module_name: &str,
+        func_name: &str,
+    ) -> Option<&NativeSignature> {
+        self.resolve_entry(addr, module_name, func_name)?.1.as_ref()
+    }
+
+    fn resolve_entry(
+        &self,
+        addr: &AccountAddress,
+        module_name: &str,
+        func_name: &str,
+    ) -> Option<&(NativeFunction, Option<NativeSignature>)> {
+        self.0.get(addr)?.get(module_name)?.get(func_name)
     }
 
     pub fn new<I>(natives: I) -> PartialVMResult<Self>
     where
         I: IntoIterator<Item = (AccountAddress, Identifier, Identifier, NativeFunction)>,
     {
-        let mut map = HashMap::new();
+        let mut natives_map = Self(HashMap::new());
         for (addr, module_name, func_name, func) in natives.into_iter() {
-            let modules = map.entry(addr).or_insert_with(HashMap::new);
-            let funcs = modules
-                .entry(module_name.into_string())
-                .or_insert_with(HashMap::new);
-
-            if funcs.insert(func_name.into_string(), func).is_some() {
+            if natives_map.insert(addr, module_name, func_name, func, None) {
                 return Err(PartialVMError::new(StatusCode::DUPLICATE_NATIVE_FUNCTION));
             }
         }
-        Ok(Self(map))
+        Ok(natives_map)
+    }
+
+    /// Add a native, replacing any native with the same name. Returns whether one was replaced.
+    pub fn insert(
+        &mut self,
+        addr: AccountAddress,
+        module_name: Identifier,
+        func_name: Identifier,
+        func: NativeFunction,
+        signature: Option<NativeSignature>,
+    ) -> bool {
+        let modules = self.0.entry(addr).or_insert_with(HashMap::new);
+        let funcs = modules
+            .entry(module_name.into_string())
+            .or_insert_with(HashMap::new);
+        funcs
+            .insert(func_name.into_string(), (func, signature))
+            .is_some()
     }
 }
 
@@ -9,20 +9,21 @@ use crate::{
     loader::{Function, Loader},
     native_extensions::NativeContextExtensions,
     native_functions::{NativeFunction, NativeFunctions},
-    session::{LoadedFunctionInstantiation, SerializedReturnValues, Session},
+    session::{next_session_id, LoadedFunctionInstantiation, SerializedReturnValues, Session},
 };
 use move_binary_format::{
     access::ModuleAccess,
     compatibility::Compatibility,
     errors::{verification_error, Location, PartialVMError, PartialVMResult, VMResult},
     file_format::LocalIndex,
-    normalized, CompiledModule, IndexKind,
+    upgrade_policy, CompiledModule, IndexKind,
 };
 use move_bytecode_verifier::script_signature;
 use move_core_types::{
     account_address::AccountAddress,
     identifier::{IdentStr, Identifier},
     language_storage::{ModuleId, TypeTag},
+    metadata::UpgradePolicy,
     resolver::MoveResolver,
     value::MoveTypeLayout,
     vm_status::StatusCode,
@@ -64,6 +65,9 @@ impl VMRuntime {
             runtime: self,
             data_cache: TransactionDataCache::new(remote, &self.loader),
             native_extensions,
+            id: next_session_id(),
+            savepoints: vec![],
+            next_savepoint_id: 0,
         }
     }
 
@@ -111,22 +115,35 @@ impl VMRuntime {
         // Collect ids for modules that are published together
         let mut bundle_unverified = BTreeSet::new();
 
-        // For now, we assume that all modules can be republished, as long as the new module is
-        // backward compatible with the old module.
-        //
-        // TODO: in the future, we may want to add restrictions on module republishing, possibly by
-        // changing the bytecode format to include an `is_upgradable` flag in the CompiledModule.
+        // A module can be republished as long as the new module respects the upgrade policy of
+        // the old module: by default, the new module must be backward compatible with the old
+        // module.
         for module in &compiled_modules {
             let module_id = module.self_id();
 
-            if data_store.exists_module(&module_id)? && compat.need_check_compat() {
-                let old_module_ref = self.loader.load_module(&module_id, data_store)?;
-                let old_module = old_module_ref.module();
-                let old_m = normalized::Module::new(old_module);
-                let new_m = normalized::Module::new(module);
-                compat
-                    .check(&old_m, &new_m)
-                    .map_err(|e| e.finish(Location::Undefined))?;
+            if data_store.exists_module(&module_id)? {
+                if compat.need_check_compat() {
+                    let old_module_ref = self.loader.load_module(&module_id, data_store)?;
+                    let old_module = old_module_ref.module();
+                    upgrade_policy::check_upgrade_policy(compat, old_module, module)
+                        .map_err(|e| e.finish(Location::Undefined))?;
+                } else {
+                    // Without compatibility check, the old module may no longer load, so its
+                    // policy is read from its bytes. A `compatible` module has nothing to check
+                    // then, but the stricter policies are always enforced.
+                    let old_blob = data_store.load_module(&module_id)?;
+                    let old_module = CompiledModule::deserialize_with_max_version(
+                        &old_blob,
+                        self.loader.vm_config().max_binary_format_version,
+                    )
+                    .map_err(|e| e.finish(Location::Module(module_id.clone())))?;
+                    let old_policy = upgrade_policy::upgrade_policy(&old_module)
+                        .map_err(|e| e.finish(Location::Module(module_id.clone())))?;
+                    if old_policy != UpgradePolicy::Compatible {
+                        upgrade_policy::check_upgrade_policy(compat, &old_module, module)
+                            .map_err(|e| e.finish(Location::Undefined))?;
+                    }
+                }
             }
             if !bundle_unverified.insert(module_id) {
                 return Err(PartialVMError::new(StatusCode::DUPLICATE_MODULE_NAME)
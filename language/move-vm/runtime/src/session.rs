@@ -3,7 +3,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    data_cache::TransactionDataCache, native_extensions::NativeContextExtensions,
+    data_cache::{DataCacheSavepoint, TransactionDataCache},
+    layered_resolver::LayeredResolver,
+    native_extensions::{ExtensionsSavepoint, NativeContextExtensions},
     runtime::VMRuntime,
 };
 use move_binary_format::{
@@ -13,23 +15,52 @@ use move_binary_format::{
 };
 use move_core_types::{
     account_address::AccountAddress,
-    effects::{ChangeSet, Event},
+    effects::{AccessSet, ChangeSet, Event},
     identifier::IdentStr,
     language_storage::{ModuleId, TypeTag},
     resolver::MoveResolver,
     value::MoveTypeLayout,
+    vm_status::StatusCode,
 };
 use move_vm_types::{
     data_store::DataStore,
     gas::GasMeter,
     loaded_data::runtime_types::{CachedStructIndex, StructType, Type},
 };
-use std::{borrow::Borrow, sync::Arc};
+use std::{
+    borrow::Borrow,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
 
 pub struct Session<'r, 'l, S> {
     pub(crate) runtime: &'l VMRuntime,
     pub(crate) data_cache: TransactionDataCache<'r, 'l, S>,
     pub(crate) native_extensions: NativeContextExtensions<'r>,
+    // Identifies the session in its savepoints, so that a savepoint of another session is rejected.
+    pub(crate) id: u64,
+    // Identifiers of the savepoints which can be rolled back to, in the order they were created.
+    // Rolling back invalidates the savepoints created after the target.
+    pub(crate) savepoints: Vec<u64>,
+    pub(crate) next_savepoint_id: u64,
+}
+
+/// Source of the session identifiers, unique in the process.
+static NEXT_SESSION_ID: AtomicU64 = AtomicU64::new(0);
+
+pub(crate) fn next_session_id() -> u64 {
+    NEXT_SESSION_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// The state of a session at some point of its execution, which the session can be rolled back
+/// to. See `Session::savepoint`.
+pub struct Savepoint<'r> {
+    session_id: u64,
+    id: u64,
+    data_cache: DataCacheSavepoint,
+    native_extensions: ExtensionsSavepoint<'r>,
 }
 
 /// Serialized return values from function/script execution
@@ -152,6 +183,8 @@ impl<'r, 'l, S: MoveResolver> Session<'r, 'l, S> {
     ///   - The sender address does not match that of the module.
     ///   - (Republishing-only) the module to be updated is not backward compatible with the old module.
     ///   - (Republishing-only) the module to be updated introduces cyclic dependencies.
+    ///   - (Republishing-only) the module to be updated violates the upgrade policy of the old
+    ///     module.
     ///
     /// The Move VM should not be able to produce other user errors.
     /// Besides, no user input should cause the Move VM to return an invariant violation.
@@ -180,8 +213,8 @@ impl<'r, 'l, S: MoveResolver> Session<'r, 'l, S> {
     /// In case an invariant violation occurs, the whole Session should be considered corrupted and
     /// one shall not proceed with effect generation.
     ///
-    /// This operation performs compatibility checks if a module is replaced. See also
-    /// `move_binary_format::compatibility`.
+    /// This operation performs compatibility and upgrade policy checks if a module is replaced. See
+    /// also `move_binary_format::compatibility` and `move_binary_format::upgrade_policy`.
     pub fn publish_module_bundle(
         &mut self,
         modules: Vec<Vec<u8>>,
@@ -197,7 +230,9 @@ impl<'r, 'l, S: MoveResolver> Session<'r, 'l, S> {
         )
     }
 
-    /// Same like `publish_module_bundle` but with a custom compatibility check.
+    /// Same like `publish_module_bundle` but with a custom compatibility check. The custom check
+    /// applies to modules with the `compatible` upgrade policy; stricter policies are always
+    /// enforced.
     pub fn publish_module_bundle_with_compat_config(
         &mut self,
         modules: Vec<Vec<u8>>,
@@ -229,6 +264,137 @@ impl<'r, 'l, S: MoveResolver> Session<'r, 'l, S> {
         )
     }
 
+    /// Create a savepoint, which the session can later be rolled back to with `rollback_to`, e.g.
+    /// to discard the effects of a transaction payload which aborted while keeping those of the
+    /// prologue.
+    ///
+    /// The savepoint holds a copy of the resources read or written by the session so far, so
+    /// creating it is linear in their size.
+    pub fn savepoint(&mut self) -> VMResult<Savepoint<'r>> {
+        let data_cache = self
+            .data_cache
+            .savepoint()
+            .map_err(|e| e.finish(Location::Undefined))?;
+        let native_extensions = self
+            .native_extensions
+            .savepoint()
+            .map_err(|e| e.finish(Location::Undefined))?;
+        let id = self.next_savepoint_id;
+        self.next_savepoint_id += 1;
+        self.savepoints.push(id);
+        Ok(Savepoint {
+            session_id: self.id,
+            id,
+            data_cache,
+            native_extensions,
+        })
+    }
+
+    /// Roll the session back to `savepoint`, which must have been created by this session. This
+    /// discards the resources written, the modules published and the events emitted since the
+    /// savepoint was created, and restores the native extensions added with
+    /// `NativeContextExtensions::add_restorable`. Modules whose publishing is discarded are
+    /// removed from the loader cache. Gas charged since the savepoint is not refunded.
+    ///
+    /// A savepoint can be rolled back to several times. Rolling back invalidates the savepoints
+    /// created after `savepoint`, and rolling back to them, or to a savepoint created by another
+    /// session, returns an invariant violation.
+    pub fn rollback_to(&mut self, savepoint: &Savepoint<'r>) -> VMResult<()> {
+        if savepoint.session_id != self.id {
+            return Err(
+                PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+                    .with_message("savepoint was created by another session".to_string())
+                    .finish(Location::Undefined),
+            );
+        }
+        let position = match self.savepoints.iter().position(|id| *id == savepoint.id) {
+            Some(position) => position,
+            None => {
+                return Err(
+                    PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+                        .with_message("savepoint was invalidated by a rollback".to_string())
+                        .finish(Location::Undefined),
+                )
+            }
+        };
+        self.data_cache
+            .rollback_to(&savepoint.data_cache)
+            .map_err(|e| e.finish(Location::Undefined))?;
+        self.native_extensions
+            .rollback_to(&savepoint.native_extensions)
+            .map_err(|e| e.finish(Location::Undefined))?;
+        self.savepoints.truncate(position + 1);
+        Ok(())
+    }
+
+    /// Create a resolver which sees the storage of this session with its pending changes applied,
+    /// for a child session to run against, see `new_child_session`.
+    ///
+    /// The resolver holds a snapshot of the changes: writes performed by this session after the
+    /// resolver was created are not visible to it, and the child's effects should only be merged
+    /// back if this session was not modified in the meantime.
+    pub fn layered_resolver(&self) -> VMResult<LayeredResolver<'r, S>> {
+        let changes = self
+            .data_cache
+            .pending_changes()
+            .map_err(|e| e.finish(Location::Undefined))?;
+        Ok(LayeredResolver::new(self.data_cache.remote(), changes))
+    }
+
+    /// Create a child session over `resolver`, which shares the loader and its code cache with
+    /// this session. Modules published by this session are loaded from the resolver the first
+    /// time they are used, as in any other session.
+    ///
+    /// The child session has no native extensions, and would not see the state of the extensions
+    /// of this session: an invariant violation is returned if this session has any.
+    ///
+    /// A typical use is running the initialization of a module published in the same
+    /// transaction:
+    ///
+    /// ```ignore
+    /// session.publish_module(module, sender, gas_meter)?;
+    /// let resolver = session.layered_resolver()?;
+    /// let mut child = session.new_child_session(&resolver)?;
+    /// child.execute_function_bypass_visibility(&module_id, init, vec![], args, gas_meter)?;
+    /// let (change_set, events, access_set) = child.finish_with_access_set()?;
+    /// session.merge_child_effects(change_set, events, access_set)?;
+    /// ```
+    pub fn new_child_session<'c>(
+        &self,
+        resolver: &'c LayeredResolver<'r, S>,
+    ) -> VMResult<Session<'c, 'l, LayeredResolver<'r, S>>> {
+        if !self.native_extensions.is_empty() {
+            return Err(
+                PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+                    .with_message(
+                        "child sessions are not supported with native extensions".to_string(),
+                    )
+                    .finish(Location::Undefined),
+            );
+        }
+        Ok(self.runtime.new_session(resolver))
+    }
+
+    /// Merge the effects of a child session, as returned by `finish_with_access_set`, into this
+    /// session. The effects produced by `finish` are then those of this session squashed with
+    /// the child's, as with `ChangeSet::squash`, and the child's events follow the events of
+    /// this session. The child's accesses are added to those of this session.
+    ///
+    /// An invariant violation is returned if the effects are inconsistent with the state of the
+    /// session, e.g. a resource created by the child already exists. None of the effects are
+    /// merged then, but the accesses still are.
+    pub fn merge_child_effects(
+        &mut self,
+        change_set: ChangeSet,
+        events: Vec<Event>,
+        access_set: AccessSet,
+    ) -> VMResult<()> {
+        self.data_cache.merge_accesses(access_set);
+        self.data_cache
+            .merge_effects(change_set, events)
+            .map_err(|e| e.finish(Location::Undefined))
+    }
+
     pub fn num_mutated_accounts(&self, sender: &AccountAddress) -> u64 {
         self.data_cache.num_mutated_accounts(sender)
     }
@@ -259,6 +425,20 @@ impl<'r, 'l, S: MoveResolver> Session<'r, 'l, S> {
         Ok((change_set, events, native_extensions))
     }
 
+    /// Same like `finish`, but also returns the resources and modules accessed by the session.
+    ///
+    /// Accesses performed by natives through the native extensions, such as reads and writes of
+    /// table items, are not recorded: callers relying on the access set for conflict detection
+    /// must track them separately, or not provide such extensions.
+    pub fn finish_with_access_set(self) -> VMResult<(ChangeSet, Vec<Event>, AccessSet)> {
+        let access_set = self
+            .data_cache
+            .access_set()
+            .map_err(|e| e.finish(Location::Undefined))?;
+        let (change_set, events) = self.finish()?;
+        Ok((change_set, events, access_set))
+    }
+
     /// Load a script and all of its types into cache
     pub fn load_script(
         &self,
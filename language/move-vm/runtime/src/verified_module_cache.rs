@@ -0,0 +1,188 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A cache of modules which passed the bytecode verifier, shared between `MoveVM` instances and
+//! optionally persisted to disk.
+//!
+//! Entries are keyed by a hash of the module bytes together with everything verification
+//! depends on: the `VerifierConfig`, the maximal binary format version and the verifier version.
+//! The file also records the verifier version, so a cache written by a different verifier is
+//! discarded when loaded. Only the checks performed by `verify_module_with_config` are skipped
+//! on a hit; linking and native checks still run, as they depend on the loader's state.
+//!
+//! A persisted cache is trusted input: the file is not authenticated, so anyone who can write
+//! it can make modules of their choosing skip bytecode verification. Only load files written by
+//! a trusted process, and never ones received from untrusted parties.
+
+use move_bytecode_verifier::{VerifierConfig, VERIFIER_VERSION};
+use parking_lot::RwLock;
+use sha3::{Digest, Sha3_256};
+use std::{
+    collections::BTreeSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+const MAGIC: &[u8; 4] = b"MVMC";
+const HEADER_SIZE: usize = MAGIC.len() + 4;
+const KEY_SIZE: usize = 32;
+
+/// Version of the encoding of the `VerifierConfig` in cache keys. Bump it when the encoding
+/// changes, e.g., when a field is added to the config.
+const CONFIG_ENCODING_VERSION: u8 = 1;
+
+type CacheKey = [u8; KEY_SIZE];
+
+pub struct VerifiedModuleCache {
+    path: Option<PathBuf>,
+    verified: RwLock<BTreeSet<CacheKey>>,
+}
+
+impl VerifiedModuleCache {
+    /// Create an empty cache which is not persisted.
+    pub fn new() -> Self {
+        Self {
+            path: None,
+            verified: RwLock::new(BTreeSet::new()),
+        }
+    }
+
+    /// Create a cache persisted at `path`, loading its entries if the file exists. A file which
+    /// is malformed or written by a different verifier version is ignored, and overwritten by
+    /// the next call to `save`. The entries of the file are trusted, see the module
+    /// documentation.
+    pub fn load(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let verified = match fs::read(&path) {
+            Ok(bytes) => Self::decode(&bytes).unwrap_or_default(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => BTreeSet::new(),
+            Err(err) => return Err(err),
+        };
+        Ok(Self {
+            path: Some(path),
+            verified: RwLock::new(verified),
+        })
+    }
+
+    /// The file the cache is persisted to, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Write the cache to its file. This is a no-op for a cache which is not persisted.
+    pub fn save(&self) -> io::Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // write to a temporary file first, so a concurrent reader never sees a partial cache
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, self.encode())?;
+        fs::rename(&tmp_path, path)
+    }
+
+    /// Number of verified modules in the cache.
+    pub fn len(&self) -> usize {
+        self.verified.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.verified.read().is_empty()
+    }
+
+    pub fn clear(&self) {
+        self.verified.write().clear()
+    }
+
+    pub(crate) fn key(
+        config: &VerifierConfig,
+        max_binary_format_version: u32,
+        module_bytes: &[u8],
+    ) -> CacheKey {
+        let mut sha3_256 = Sha3_256::new();
+        sha3_256.update(VERIFIER_VERSION.to_le_bytes());
+        sha3_256.update(max_binary_format_version.to_le_bytes());
+        sha3_256.update(encode_config(config));
+        sha3_256.update(module_bytes);
+        sha3_256.finalize().into()
+    }
+
+    pub(crate) fn contains(&self, key: &CacheKey) -> bool {
+        self.verified.read().contains(key)
+    }
+
+    pub(crate) fn insert(&self, key: CacheKey) {
+        self.verified.write().insert(key);
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let verified = self.verified.read();
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + verified.len() * KEY_SIZE);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERIFIER_VERSION.to_le_bytes());
+        for key in verified.iter() {
+            bytes.extend_from_slice(key);
+        }
+        bytes
+    }
+
+    fn decode(bytes: &[u8]) -> Option<BTreeSet<CacheKey>> {
+        if bytes.len() < HEADER_SIZE || &bytes[..MAGIC.len()] != MAGIC {
+            return None;
+        }
+        let version = u32::from_le_bytes(bytes[MAGIC.len()..HEADER_SIZE].try_into().ok()?);
+        let keys = &bytes[HEADER_SIZE..];
+        if version != VERIFIER_VERSION || keys.len() % KEY_SIZE != 0 {
+            return None;
+        }
+        keys.chunks(KEY_SIZE)
+            .map(|key| key.try_into().ok())
+            .collect()
+    }
+}
+
+/// Encode every field of `config` which verification depends on, prefixed by the version of the
+/// encoding.
+fn encode_config(config: &VerifierConfig) -> Vec<u8> {
+    // destructure the config, so adding a field fails to compile until it is encoded here
+    let VerifierConfig {
+        max_loop_depth,
+        max_function_parameters,
+        max_generic_instantiation_length,
+        max_basic_blocks,
+        max_value_stack_size,
+        max_type_nodes,
+        max_push_size,
+        max_dependency_depth,
+    } = config;
+
+    fn encode_limit(bytes: &mut Vec<u8>, limit: Option<usize>) {
+        match limit {
+            None => bytes.push(0),
+            Some(limit) => {
+                bytes.push(1);
+                bytes.extend_from_slice(&(limit as u64).to_le_bytes());
+            }
+        }
+    }
+
+    let mut bytes = vec![CONFIG_ENCODING_VERSION];
+    encode_limit(&mut bytes, *max_loop_depth);
+    encode_limit(&mut bytes, *max_function_parameters);
+    encode_limit(&mut bytes, *max_generic_instantiation_length);
+    encode_limit(&mut bytes, *max_basic_blocks);
+    bytes.extend_from_slice(&(*max_value_stack_size as u64).to_le_bytes());
+    encode_limit(&mut bytes, *max_type_nodes);
+    encode_limit(&mut bytes, *max_push_size);
+    bytes.extend_from_slice(&max_dependency_depth.to_le_bytes());
+    bytes
+}
+
+impl Default for VerifiedModuleCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
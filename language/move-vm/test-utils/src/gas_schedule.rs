@@ -113,6 +113,9 @@ pub struct GasStatus<'a> {
     cost_table: &'a CostTable,
     gas_left: InternalGas,
     charge: bool,
+    heap_memory_quota: Option<NumBytes>,
+    heap_memory_used: NumBytes,
+    peak_heap_memory_used: NumBytes,
 }
 
 impl<'a> GasStatus<'a> {
@@ -125,6 +128,9 @@ impl<'a> GasStatus<'a> {
             gas_left: gas_left.to_unit(),
             cost_table,
             charge: true,
+            heap_memory_quota: None,
+            heap_memory_used: NumBytes::zero(),
+            peak_heap_memory_used: NumBytes::zero(),
         }
     }
 
@@ -137,6 +143,9 @@ impl<'a> GasStatus<'a> {
             gas_left: InternalGas::new(0),
             cost_table: &ZERO_COST_SCHEDULE,
             charge: false,
+            heap_memory_quota: None,
+            heap_memory_used: NumBytes::zero(),
+            peak_heap_memory_used: NumBytes::zero(),
         }
     }
 
@@ -197,6 +206,23 @@ impl<'a> GasStatus<'a> {
     pub fn set_metering(&mut self, enabled: bool) {
         self.charge = enabled
     }
+
+    /// Limit the heap memory used at any point of the execution to `quota` bytes, or remove the
+    /// limit if `quota` is `None`. Exceeding the quota fails with `MEMORY_LIMIT_EXCEEDED`, even
+    /// when metering is disabled.
+    pub fn set_heap_memory_quota(&mut self, quota: Option<NumBytes>) {
+        self.heap_memory_quota = quota
+    }
+
+    /// Return the heap memory currently in use.
+    pub fn heap_memory_used(&self) -> NumBytes {
+        self.heap_memory_used
+    }
+
+    /// Return the largest amount of heap memory in use at any point so far.
+    pub fn peak_heap_memory_used(&self) -> NumBytes {
+        self.peak_heap_memory_used
+    }
 }
 
 fn get_simple_instruction_opcode(instr: SimpleInstruction) -> Opcodes {
@@ -549,6 +575,42 @@ impl<'b> GasMeter for GasStatus<'b> {
     ) -> PartialVMResult<()> {
         Ok(())
     }
+
+    fn use_heap_memory(&mut self, amount: NumBytes) -> PartialVMResult<()> {
+        self.heap_memory_used += amount;
+        self.peak_heap_memory_used =
+            std::cmp::max(self.peak_heap_memory_used, self.heap_memory_used);
+        match self.heap_memory_quota {
+            Some(quota) if self.heap_memory_used > quota => {
+                Err(PartialVMError::new(StatusCode::MEMORY_LIMIT_EXCEEDED))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn release_heap_memory(&mut self, amount: NumBytes) {
+        self.heap_memory_used = self
+            .heap_memory_used
+            .checked_sub(amount)
+            .unwrap_or_else(NumBytes::zero);
+    }
+
+    fn use_heap_memory_for_values(
+        &mut self,
+        vals: impl IntoIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.use_heap_memory(heap_memory_size(vals))
+    }
+
+    fn release_heap_memory_for_values(&mut self, vals: impl IntoIterator<Item = impl ValueView>) {
+        self.release_heap_memory(heap_memory_size(vals))
+    }
+}
+
+/// Returns the total heap memory owned by `vals`.
+fn heap_memory_size(vals: impl IntoIterator<Item = impl ValueView>) -> NumBytes {
+    vals.into_iter()
+        .fold(NumBytes::zero(), |acc, val| acc + val.heap_memory_size())
 }
 
 pub fn new_from_instructions(mut instrs: Vec<(Bytecode, GasCost)>) -> CostTable {
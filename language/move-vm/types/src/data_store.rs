@@ -8,8 +8,8 @@ use crate::{
 };
 use move_binary_format::errors::{PartialVMResult, VMResult};
 use move_core_types::{
-    account_address::AccountAddress, gas_algebra::NumBytes, language_storage::ModuleId,
-    value::MoveTypeLayout,
+    account_address::AccountAddress, effects::ResourceAccess, gas_algebra::NumBytes,
+    language_storage::ModuleId, value::MoveTypeLayout,
 };
 
 /// Provide an implementation for bytecodes related to data with a given data store.
@@ -31,6 +31,9 @@ pub trait DataStore {
         ty: &Type,
     ) -> PartialVMResult<(&mut GlobalValue, Option<Option<NumBytes>>)>;
 
+    /// Record an access to a resource by a global storage instruction.
+    fn record_resource_access(&mut self, addr: AccountAddress, ty: &Type, access: ResourceAccess);
+
     /// Get the serialized format of a `CompiledModule` given a `ModuleId`.
     fn load_module(&self, module_id: &ModuleId) -> VMResult<Vec<u8>>;
 
@@ -45,6 +48,10 @@ pub trait DataStore {
     /// Check if this module exists.
     fn exists_module(&self, module_id: &ModuleId) -> VMResult<bool>;
 
+    /// Record that a module was requested, whether it is loaded from storage or found in the
+    /// loader's cache.
+    fn record_module_access(&self, module_id: &ModuleId);
+
     // ---
     // EventStore operations
     // ---
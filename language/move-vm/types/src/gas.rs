@@ -230,6 +230,36 @@ pub trait GasMeter {
         &mut self,
         locals: impl Iterator<Item = impl ValueView>,
     ) -> PartialVMResult<()>;
+
+    /// Records that `amount` bytes of heap memory have been allocated for a container, i.e., when
+    /// packing a struct or a vector, or pushing to a vector. Should fail if the memory used by
+    /// the execution exceeds the quota of the meter.
+    ///
+    /// The amounts are estimates of `ValueView::heap_memory_size`.
+    fn use_heap_memory(&mut self, _amount: NumBytes) -> PartialVMResult<()> {
+        Ok(())
+    }
+
+    /// Records that `amount` bytes of heap memory have been released by a container, i.e., when
+    /// unpacking a struct or a vector, or popping from a vector.
+    fn release_heap_memory(&mut self, _amount: NumBytes) {}
+
+    /// Records that the heap memory owned by `vals` has been allocated, e.g., when copying a
+    /// value or moving a resource out of storage. Should fail if the memory used by the
+    /// execution exceeds the quota of the meter.
+    ///
+    /// Meters tracking memory usage are expected to use `ValueView::heap_memory_size`, which
+    /// traverses the values.
+    fn use_heap_memory_for_values(
+        &mut self,
+        _vals: impl IntoIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        Ok(())
+    }
+
+    /// Records that the heap memory owned by `vals` has been released, i.e., when the values are
+    /// dropped or moved to storage.
+    fn release_heap_memory_for_values(&mut self, _vals: impl IntoIterator<Item = impl ValueView>) {}
 }
 
 /// A dummy gas meter that does not meter anything.
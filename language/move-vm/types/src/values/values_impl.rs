@@ -13,7 +13,7 @@ use move_binary_format::{
 use move_core_types::{
     account_address::AccountAddress,
     effects::Op,
-    gas_algebra::AbstractMemorySize,
+    gas_algebra::{AbstractMemorySize, NumBytes},
     u256,
     value::{MoveStructLayout, MoveTypeLayout},
     vm_status::{sub_status::NFE_VECTOR_ERROR_BASE, StatusCode},
@@ -22,6 +22,7 @@ use std::{
     cell::RefCell,
     fmt::{self, Debug, Display},
     iter,
+    mem::size_of,
     rc::Rc,
 };
 
@@ -439,6 +440,40 @@ impl Value {
     }
 }
 
+impl GlobalValueImpl {
+    fn copy_value(&self) -> PartialVMResult<Self> {
+        let copy_fields = |fields: &Rc<RefCell<Vec<ValueImpl>>>| -> PartialVMResult<_> {
+            Ok(Rc::new(RefCell::new(
+                fields
+                    .borrow()
+                    .iter()
+                    .map(|v| v.copy_value())
+                    .collect::<PartialVMResult<_>>()?,
+            )))
+        };
+
+        Ok(match self {
+            Self::None => Self::None,
+            Self::Deleted => Self::Deleted,
+            Self::Fresh { fields } => Self::Fresh {
+                fields: copy_fields(fields)?,
+            },
+            Self::Cached { fields, status } => Self::Cached {
+                fields: copy_fields(fields)?,
+                status: Rc::new(RefCell::new(*status.borrow())),
+            },
+        })
+    }
+}
+
+impl GlobalValue {
+    /// Make a deep copy of the global value, including whether it has been modified. The copy
+    /// shares no state with the original, so it can be used to restore the original later on.
+    pub fn copy_value(&self) -> PartialVMResult<Self> {
+        Ok(Self(self.0.copy_value()?))
+    }
+}
+
 /***************************************************************************************
  *
  * Equality
@@ -1054,6 +1089,18 @@ impl Locals {
         res.into_iter()
     }
 
+    /// Returns the sum of the abstract memory sizes of the values stored in the locals. Invalid
+    /// locals, i.e., locals which are not initialized or have been moved from, are not counted.
+    pub fn legacy_abstract_memory_size(&self) -> AbstractMemorySize {
+        self.0
+            .borrow()
+            .iter()
+            .filter(|v| !matches!(v, ValueImpl::Invalid))
+            .fold(AbstractMemorySize::zero(), |acc, v| {
+                acc + v.legacy_abstract_memory_size()
+            })
+    }
+
     pub fn is_invalid(&self, idx: usize) -> PartialVMResult<bool> {
         let v = self.0.borrow();
         match v.get(idx) {
@@ -2387,6 +2434,56 @@ impl Reference {
     }
 }
 
+/***************************************************************************************
+ *
+ * Heap Memory Size
+ *
+ *   Estimates of the heap memory used by values, based on their representation. These are
+ *   reported to the gas meter, which can use them to bound the memory used by an execution.
+ *
+ **************************************************************************************/
+
+/// The estimated size in bytes of the heap allocation of a struct or vector, excluding its
+/// elements: the reference counts, the borrow flag and the vector header.
+pub(crate) const CONTAINER_HEAP_SIZE: u64 =
+    (2 * size_of::<usize>() + size_of::<RefCell<Vec<ValueImpl>>>()) as u64;
+
+/// The size in bytes of a field of a struct, or of an element of a vector of non-primitive
+/// values.
+pub(crate) const VALUE_SIZE: u64 = size_of::<ValueImpl>() as u64;
+
+fn vector_elem_size(elem_ty: &Type) -> u64 {
+    (match elem_ty {
+        Type::U8 => size_of::<u8>(),
+        Type::U16 => size_of::<u16>(),
+        Type::U32 => size_of::<u32>(),
+        Type::U64 => size_of::<u64>(),
+        Type::U128 => size_of::<u128>(),
+        Type::U256 => size_of::<u256::U256>(),
+        Type::Bool => size_of::<bool>(),
+        Type::Address => size_of::<AccountAddress>(),
+        _ => return VALUE_SIZE,
+    }) as u64
+}
+
+/// Returns the number of bytes of heap memory allocated for a struct with `num_fields` fields,
+/// not including the memory owned by the fields.
+pub fn struct_heap_size(num_fields: usize) -> NumBytes {
+    NumBytes::new(CONTAINER_HEAP_SIZE + num_fields as u64 * VALUE_SIZE)
+}
+
+/// Returns the number of bytes of heap memory allocated for a vector of `len` elements of type
+/// `elem_ty`, not including the memory owned by the elements.
+pub fn vector_heap_size(elem_ty: &Type, len: usize) -> NumBytes {
+    NumBytes::new(CONTAINER_HEAP_SIZE + len as u64 * vector_elem_size(elem_ty))
+}
+
+/// Returns the number of bytes of heap memory used by an element of type `elem_ty` in a vector,
+/// not including the memory owned by the element.
+pub fn vector_elem_heap_size(elem_ty: &Type) -> NumBytes {
+    NumBytes::new(vector_elem_size(elem_ty))
+}
+
 /***************************************************************************************
  *
  * Struct Operations
@@ -2,7 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use move_core_types::{
-    account_address::AccountAddress, gas_algebra::AbstractMemorySize, language_storage::TypeTag,
+    account_address::AccountAddress,
+    gas_algebra::{AbstractMemorySize, NumBytes},
+    language_storage::TypeTag,
 };
 use std::mem::size_of;
 
@@ -120,6 +122,90 @@ pub trait ValueView {
 
         acc.0
     }
+
+    /// Returns the estimated number of bytes of heap memory owned by the value, i.e., the memory
+    /// of the structs and vectors it contains. Primitive values live on the stack or inside their
+    /// container, and references do not own the memory they point to.
+    fn heap_memory_size(&self) -> NumBytes {
+        use crate::values::{CONTAINER_HEAP_SIZE, VALUE_SIZE};
+
+        struct Acc(u64);
+
+        impl Acc {
+            fn add_container(&mut self, len: usize, elem_size: usize) {
+                self.0 += CONTAINER_HEAP_SIZE + (len * elem_size) as u64;
+            }
+        }
+
+        impl ValueVisitor for Acc {
+            fn visit_u8(&mut self, _depth: usize, _val: u8) {}
+
+            fn visit_u16(&mut self, _depth: usize, _val: u16) {}
+
+            fn visit_u32(&mut self, _depth: usize, _val: u32) {}
+
+            fn visit_u64(&mut self, _depth: usize, _val: u64) {}
+
+            fn visit_u128(&mut self, _depth: usize, _val: u128) {}
+
+            fn visit_u256(&mut self, _depth: usize, _val: move_core_types::u256::U256) {}
+
+            fn visit_bool(&mut self, _depth: usize, _val: bool) {}
+
+            fn visit_address(&mut self, _depth: usize, _val: AccountAddress) {}
+
+            fn visit_struct(&mut self, _depth: usize, len: usize) -> bool {
+                self.add_container(len, VALUE_SIZE as usize);
+                true
+            }
+
+            fn visit_vec(&mut self, _depth: usize, len: usize) -> bool {
+                self.add_container(len, VALUE_SIZE as usize);
+                true
+            }
+
+            fn visit_vec_u8(&mut self, _depth: usize, vals: &[u8]) {
+                self.add_container(vals.len(), size_of::<u8>());
+            }
+
+            fn visit_vec_u16(&mut self, _depth: usize, vals: &[u16]) {
+                self.add_container(vals.len(), size_of::<u16>());
+            }
+
+            fn visit_vec_u32(&mut self, _depth: usize, vals: &[u32]) {
+                self.add_container(vals.len(), size_of::<u32>());
+            }
+
+            fn visit_vec_u64(&mut self, _depth: usize, vals: &[u64]) {
+                self.add_container(vals.len(), size_of::<u64>());
+            }
+
+            fn visit_vec_u128(&mut self, _depth: usize, vals: &[u128]) {
+                self.add_container(vals.len(), size_of::<u128>());
+            }
+
+            fn visit_vec_u256(&mut self, _depth: usize, vals: &[move_core_types::u256::U256]) {
+                self.add_container(vals.len(), size_of::<move_core_types::u256::U256>());
+            }
+
+            fn visit_vec_bool(&mut self, _depth: usize, vals: &[bool]) {
+                self.add_container(vals.len(), size_of::<bool>());
+            }
+
+            fn visit_vec_address(&mut self, _depth: usize, vals: &[AccountAddress]) {
+                self.add_container(vals.len(), size_of::<AccountAddress>());
+            }
+
+            fn visit_ref(&mut self, _depth: usize, _is_global: bool) -> bool {
+                false
+            }
+        }
+
+        let mut acc = Acc(0);
+        self.visit(&mut acc);
+
+        NumBytes::new(acc.0)
+    }
 }
 
 /// Trait that defines a visitor that could be used to traverse a value recursively.
@@ -203,6 +289,10 @@ where
         <T as ValueView>::legacy_abstract_memory_size(*self)
     }
 
+    fn heap_memory_size(&self) -> NumBytes {
+        <T as ValueView>::heap_memory_size(*self)
+    }
+
     fn visit(&self, visitor: &mut impl ValueVisitor) {
         <T as ValueView>::visit(*self, visitor)
     }
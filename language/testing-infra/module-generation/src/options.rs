@@ -15,7 +15,8 @@ pub struct ModuleGeneratorOptions {
     pub max_structs: usize,
     /// The maximum number of functions that can be generated for a module.
     pub max_functions: usize,
-    /// The maximum number of type parameters functions and structs.
+    /// The exclusive upper bound on the number of type parameters of functions and structs, at
+    /// least 1. A bound of 1 generates no type parameters.
     pub max_ty_params: usize,
     /// The maximum size that generated byte arrays can be.
     pub byte_array_max_size: usize,
@@ -48,7 +48,7 @@ impl Pad {
 
     fn pad_address_identifier_table(&mut self, module: &mut CompiledModule) {
         module.address_identifiers = (0..(self.table_size + module.address_identifiers.len()))
-            .map(|_| AccountAddress::random())
+            .map(|_| AccountAddress::new(self.gen.gen()))
             .collect()
     }
 
@@ -13,7 +13,7 @@ use move_binary_format::{
     },
 };
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     fmt,
 };
 
@@ -425,7 +425,7 @@ pub struct AbstractState {
     pub instantiation: Vec<AbilitySet>,
 
     /// A HashMap mapping local indicies to `AbstractValue`s and `BorrowState`s
-    locals: HashMap<usize, (AbstractValue, BorrowState)>,
+    locals: BTreeMap<usize, (AbstractValue, BorrowState)>,
 
     /// Temporary location for storing the results of instruction effects for
     /// access by subsequent instructions' effects
@@ -460,7 +460,7 @@ impl AbstractState {
         AbstractState {
             stack: Vec::new(),
             instantiation: Vec::new(),
-            locals: HashMap::new(),
+            locals: BTreeMap::new(),
             register: None,
             module: InstantiableModule::new(compiled_module),
             acquires_global_resources: Vec::new(),
@@ -475,7 +475,7 @@ impl AbstractState {
     /// the (available) locals that the state will have, as well as the module state
     pub fn from_locals(
         module: CompiledModule,
-        locals: HashMap<usize, (AbstractValue, BorrowState)>,
+        locals: BTreeMap<usize, (AbstractValue, BorrowState)>,
         instantiation: Vec<AbilitySet>,
         acquires_global_resources: Vec<StructDefinitionIndex>,
         call_graph: CallGraph,
@@ -668,7 +668,7 @@ impl AbstractState {
     }
 
     /// Get all of the locals
-    pub fn get_locals(&self) -> &HashMap<usize, (AbstractValue, BorrowState)> {
+    pub fn get_locals(&self) -> &BTreeMap<usize, (AbstractValue, BorrowState)> {
         &self.locals
     }
 
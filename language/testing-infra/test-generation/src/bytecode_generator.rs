@@ -14,13 +14,13 @@ use crate::{
 use move_binary_format::{
     access::ModuleAccess,
     file_format::{
-        Bytecode, CodeOffset, CompiledModule, ConstantPoolIndex, FieldHandleIndex,
+        Bytecode, CodeOffset, CompiledModule, Constant, ConstantPoolIndex, FieldHandleIndex,
         FieldInstantiationIndex, FunctionHandle, FunctionHandleIndex, FunctionInstantiation,
         FunctionInstantiationIndex, LocalIndex, SignatureToken, StructDefInstantiation,
         StructDefInstantiationIndex, StructDefinitionIndex, StructFieldInformation, TableIndex,
     },
 };
-use move_core_types::u256::U256;
+use move_core_types::{account_address::AccountAddress, u256::U256};
 use rand::{rngs::StdRng, Rng};
 use tracing::{debug, error, warn};
 
@@ -872,12 +872,25 @@ impl<'a> BytecodeGenerator<'a> {
     }
 
     pub fn generate_module(&mut self, mut module: CompiledModule) -> Option<CompiledModule> {
+        // Addresses are inhabited by loading the constant at index 0
+        if module.constant_pool.is_empty() {
+            module.constant_pool.push(Constant {
+                type_: SignatureToken::Address,
+                data: AccountAddress::ZERO.to_vec(),
+            });
+        }
         let mut fdefs = module.function_defs.clone();
         let mut call_graph = CallGraph::new(module.function_handles.len());
         for fdef in fdefs.iter_mut() {
             if let Some(code) = &mut fdef.code {
                 let f_handle = &module.function_handles[fdef.function.0 as usize].clone();
-                let locals_sigs = module.signatures[code.locals.0 as usize].0.clone();
+                // Local indices start with the parameters, which are not part of `code.locals`
+                let locals_sigs = module.signatures[f_handle.parameters.0 as usize]
+                    .0
+                    .iter()
+                    .chain(module.signatures[code.locals.0 as usize].0.iter())
+                    .cloned()
+                    .collect::<Vec<_>>();
                 let mut fn_context = FunctionGenerationContext::new(
                     fdef.function,
                     call_graph.max_calling_depth(fdef.function),
@@ -209,7 +209,9 @@ pub enum Status {
     Valid,
 }
 
-fn bytecode_module(rng: &mut StdRng, module: CompiledModule) -> CompiledModule {
+/// Fill in the function bodies of a module generated by `module_generation`, retrying until the
+/// bytecode generator succeeds.
+pub fn bytecode_module(rng: &mut StdRng, module: CompiledModule) -> CompiledModule {
     let mut generated_module = BytecodeGenerator::new(rng).generate_module(module.clone());
     // Module generation can retry under certain circumstances
     while generated_module.is_none() {
@@ -8,7 +8,7 @@ use move_binary_format::file_format::{
     ModuleHandleIndex, Signature, SignatureIndex, SignatureToken,
 };
 use move_core_types::identifier::Identifier;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use test_generation::abstract_state::{AbstractState, AbstractValue, CallGraph};
 
 mod common;
@@ -39,7 +39,7 @@ fn generate_module_with_function() -> CompiledModule {
 fn bytecode_call() {
     let module = generate_module_with_function();
     let mut state1 =
-        AbstractState::from_locals(module, HashMap::new(), vec![], vec![], CallGraph::new(0));
+        AbstractState::from_locals(module, BTreeMap::new(), vec![], vec![], CallGraph::new(0));
     state1.stack_push(AbstractValue::new_primitive(SignatureToken::U64));
     state1.stack_push(AbstractValue::new_primitive(SignatureToken::Bool));
     let (state2, _) = common::run_instruction(Bytecode::Call(FunctionHandleIndex::new(0)), state1);
@@ -55,7 +55,7 @@ fn bytecode_call() {
 fn bytecode_call_function_signature_not_satisfied() {
     let module = generate_module_with_function();
     let state1 =
-        AbstractState::from_locals(module, HashMap::new(), vec![], vec![], CallGraph::new(0));
+        AbstractState::from_locals(module, BTreeMap::new(), vec![], vec![], CallGraph::new(0));
     common::run_instruction(Bytecode::Call(FunctionHandleIndex::new(0)), state1);
 }
 
@@ -64,7 +64,7 @@ fn bytecode_call_function_signature_not_satisfied() {
 fn bytecode_call_return_not_pushed() {
     let module = generate_module_with_function();
     let mut state1 =
-        AbstractState::from_locals(module, HashMap::new(), vec![], vec![], CallGraph::new(0));
+        AbstractState::from_locals(module, BTreeMap::new(), vec![], vec![], CallGraph::new(0));
     state1.stack_push(AbstractValue::new_primitive(SignatureToken::U64));
     state1.stack_push(AbstractValue::new_primitive(SignatureToken::Bool));
     let (state2, _) = common::run_instruction(Bytecode::Call(FunctionHandleIndex::new(0)), state1);
@@ -0,0 +1,37 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+extern crate test_generation;
+use module_generation::{generate_module, ModuleGeneratorOptions};
+use move_binary_format::file_format::SignatureToken;
+use move_bytecode_verifier::verify_module;
+use rand::{rngs::StdRng, SeedableRng};
+use test_generation::{bytecode_module, config::module_generation_settings};
+
+//---------------------------------------------------------------------------
+// Generated module tests
+//---------------------------------------------------------------------------
+
+#[test]
+fn generated_modules_verify() {
+    // generics are not handled reliably by the bytecode generator, so no type parameters are
+    // generated: `max_ty_params` is an exclusive bound
+    let options = ModuleGeneratorOptions {
+        max_ty_params: 1,
+        ..module_generation_settings()
+    };
+    for seed in 0..8u8 {
+        let mut rng = StdRng::from_seed([seed; 32]);
+        let module = generate_module(&mut rng, options.clone());
+        let module = bytecode_module(&mut rng, module);
+        assert_eq!(
+            module.constant_pool[0].type_,
+            SignatureToken::Address,
+            "seed {}",
+            seed
+        );
+        if let Err(err) = verify_module(&module) {
+            panic!("seed {}: {:?}", seed, err)
+        }
+    }
+}
@@ -14,7 +14,7 @@ use move_binary_format::{
     views::{StructDefinitionView, ViewInternals},
 };
 use move_core_types::identifier::Identifier;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use test_generation::{
     abilities,
     abstract_state::{AbstractState, AbstractValue, CallGraph},
@@ -97,7 +97,7 @@ fn get_field_signature<'a>(module: &'a CompiledModule, handle: &FieldHandle) ->
 fn bytecode_pack_signature_not_satisfied() {
     let module = generate_module_with_struct(false);
     let state1 =
-        AbstractState::from_locals(module, HashMap::new(), vec![], vec![], CallGraph::new(0));
+        AbstractState::from_locals(module, BTreeMap::new(), vec![], vec![], CallGraph::new(0));
     common::run_instruction(Bytecode::Pack(StructDefinitionIndex::new(0)), state1);
 }
 
@@ -105,7 +105,7 @@ fn bytecode_pack_signature_not_satisfied() {
 fn bytecode_pack() {
     let module = generate_module_with_struct(false);
     let mut state1 =
-        AbstractState::from_locals(module, HashMap::new(), vec![], vec![], CallGraph::new(0));
+        AbstractState::from_locals(module, BTreeMap::new(), vec![], vec![], CallGraph::new(0));
     let (struct_value1, tokens) = create_struct_value(&state1.module.module);
     for token in tokens {
         let abstract_value = AbstractValue {
@@ -128,7 +128,7 @@ fn bytecode_pack() {
 fn bytecode_unpack_signature_not_satisfied() {
     let module = generate_module_with_struct(false);
     let state1 =
-        AbstractState::from_locals(module, HashMap::new(), vec![], vec![], CallGraph::new(0));
+        AbstractState::from_locals(module, BTreeMap::new(), vec![], vec![], CallGraph::new(0));
     common::run_instruction(Bytecode::Unpack(StructDefinitionIndex::new(0)), state1);
 }
 
@@ -136,7 +136,7 @@ fn bytecode_unpack_signature_not_satisfied() {
 fn bytecode_unpack() {
     let module = generate_module_with_struct(false);
     let mut state1 =
-        AbstractState::from_locals(module, HashMap::new(), vec![], vec![], CallGraph::new(0));
+        AbstractState::from_locals(module, BTreeMap::new(), vec![], vec![], CallGraph::new(0));
     let (struct_value, tokens) = create_struct_value(&state1.module.module);
     state1.stack_push(struct_value);
     let (state2, _) =
@@ -152,7 +152,7 @@ fn bytecode_unpack() {
 fn bytecode_exists() {
     let module = generate_module_with_struct(true);
     let mut state1 =
-        AbstractState::from_locals(module, HashMap::new(), vec![], vec![], CallGraph::new(0));
+        AbstractState::from_locals(module, BTreeMap::new(), vec![], vec![], CallGraph::new(0));
     state1.stack_push(AbstractValue::new_primitive(SignatureToken::Address));
     let (state2, _) =
         common::run_instruction(Bytecode::Exists(StructDefinitionIndex::new(0)), state1);
@@ -168,7 +168,7 @@ fn bytecode_exists() {
 fn bytecode_exists_struct_is_not_resource() {
     let module = generate_module_with_struct(false);
     let mut state1 =
-        AbstractState::from_locals(module, HashMap::new(), vec![], vec![], CallGraph::new(0));
+        AbstractState::from_locals(module, BTreeMap::new(), vec![], vec![], CallGraph::new(0));
     state1.stack_push(AbstractValue::new_primitive(SignatureToken::Address));
     common::run_instruction(Bytecode::Exists(StructDefinitionIndex::new(0)), state1);
 }
@@ -178,7 +178,7 @@ fn bytecode_exists_struct_is_not_resource() {
 fn bytecode_exists_no_address_on_stack() {
     let module = generate_module_with_struct(true);
     let state1 =
-        AbstractState::from_locals(module, HashMap::new(), vec![], vec![], CallGraph::new(0));
+        AbstractState::from_locals(module, BTreeMap::new(), vec![], vec![], CallGraph::new(0));
     common::run_instruction(Bytecode::Exists(StructDefinitionIndex::new(0)), state1);
 }
 
@@ -187,7 +187,7 @@ fn bytecode_movefrom() {
     let module = generate_module_with_struct(true);
     let mut state1 = AbstractState::from_locals(
         module,
-        HashMap::new(),
+        BTreeMap::new(),
         vec![],
         vec![StructDefinitionIndex::new(0)],
         CallGraph::new(0),
@@ -212,7 +212,7 @@ fn bytecode_movefrom() {
 fn bytecode_movefrom_struct_is_not_resource() {
     let module = generate_module_with_struct(false);
     let mut state1 =
-        AbstractState::from_locals(module, HashMap::new(), vec![], vec![], CallGraph::new(0));
+        AbstractState::from_locals(module, BTreeMap::new(), vec![], vec![], CallGraph::new(0));
     state1.stack_push(AbstractValue::new_primitive(SignatureToken::Address));
     common::run_instruction(Bytecode::MoveFrom(StructDefinitionIndex::new(0)), state1);
 }
@@ -222,7 +222,7 @@ fn bytecode_movefrom_struct_is_not_resource() {
 fn bytecode_movefrom_no_address_on_stack() {
     let module = generate_module_with_struct(true);
     let state1 =
-        AbstractState::from_locals(module, HashMap::new(), vec![], vec![], CallGraph::new(0));
+        AbstractState::from_locals(module, BTreeMap::new(), vec![], vec![], CallGraph::new(0));
     common::run_instruction(Bytecode::MoveFrom(StructDefinitionIndex::new(0)), state1);
 }
 
@@ -230,7 +230,7 @@ fn bytecode_movefrom_no_address_on_stack() {
 fn bytecode_moveto() {
     let module = generate_module_with_struct(true);
     let mut state1 =
-        AbstractState::from_locals(module, HashMap::new(), vec![], vec![], CallGraph::new(0));
+        AbstractState::from_locals(module, BTreeMap::new(), vec![], vec![], CallGraph::new(0));
     state1.stack_push(AbstractValue::new_reference(
         SignatureToken::Reference(Box::new(SignatureToken::Signer)),
         AbilitySet::EMPTY | Ability::Drop,
@@ -246,7 +246,7 @@ fn bytecode_moveto() {
 fn bytecode_moveto_struct_is_not_resource() {
     let module = generate_module_with_struct(false);
     let mut state1 =
-        AbstractState::from_locals(module, HashMap::new(), vec![], vec![], CallGraph::new(0));
+        AbstractState::from_locals(module, BTreeMap::new(), vec![], vec![], CallGraph::new(0));
     state1.stack_push(AbstractValue::new_reference(
         SignatureToken::Reference(Box::new(SignatureToken::Signer)),
         AbilitySet::EMPTY | Ability::Drop,
@@ -260,7 +260,7 @@ fn bytecode_moveto_struct_is_not_resource() {
 fn bytecode_moveto_no_struct_on_stack() {
     let module = generate_module_with_struct(true);
     let mut state1 =
-        AbstractState::from_locals(module, HashMap::new(), vec![], vec![], CallGraph::new(0));
+        AbstractState::from_locals(module, BTreeMap::new(), vec![], vec![], CallGraph::new(0));
     state1.stack_push(AbstractValue::new_reference(
         SignatureToken::Reference(Box::new(SignatureToken::Signer)),
         AbilitySet::EMPTY | Ability::Drop,
@@ -281,7 +281,7 @@ fn bytecode_mutborrowfield() {
         get_field_signature(&module, &module.field_handles[field_handle_idx.0 as usize]).clone();
 
     let mut state1 =
-        AbstractState::from_locals(module, HashMap::new(), vec![], vec![], CallGraph::new(0));
+        AbstractState::from_locals(module, BTreeMap::new(), vec![], vec![], CallGraph::new(0));
     let struct_value = create_struct_value(&state1.module.module).0;
     state1.stack_push(AbstractValue {
         token: SignatureToken::MutableReference(Box::new(struct_value.token)),
@@ -311,7 +311,7 @@ fn bytecode_mutborrowfield_stack_has_no_reference() {
     let field_handle_idx = FieldHandleIndex((module.field_handles.len() - 1) as u16);
 
     let state1 =
-        AbstractState::from_locals(module, HashMap::new(), vec![], vec![], CallGraph::new(0));
+        AbstractState::from_locals(module, BTreeMap::new(), vec![], vec![], CallGraph::new(0));
     common::run_instruction(Bytecode::MutBorrowField(field_handle_idx), state1);
 }
 
@@ -327,7 +327,7 @@ fn bytecode_mutborrowfield_ref_is_immutable() {
     let field_handle_idx = FieldHandleIndex((module.field_handles.len() - 1) as u16);
 
     let mut state1 =
-        AbstractState::from_locals(module, HashMap::new(), vec![], vec![], CallGraph::new(0));
+        AbstractState::from_locals(module, BTreeMap::new(), vec![], vec![], CallGraph::new(0));
     let struct_value = create_struct_value(&state1.module.module).0;
     state1.stack_push(AbstractValue {
         token: SignatureToken::Reference(Box::new(struct_value.token)),
@@ -349,7 +349,7 @@ fn bytecode_immborrowfield() {
         get_field_signature(&module, &module.field_handles[field_handle_idx.0 as usize]).clone();
 
     let mut state1 =
-        AbstractState::from_locals(module, HashMap::new(), vec![], vec![], CallGraph::new(0));
+        AbstractState::from_locals(module, BTreeMap::new(), vec![], vec![], CallGraph::new(0));
     let struct_value = create_struct_value(&state1.module.module).0;
     state1.stack_push(AbstractValue {
         token: SignatureToken::Reference(Box::new(struct_value.token)),
@@ -379,7 +379,7 @@ fn bytecode_immborrowfield_stack_has_no_reference() {
     let field_handle_idx = FieldHandleIndex((module.field_handles.len() - 1) as u16);
 
     let state1 =
-        AbstractState::from_locals(module, HashMap::new(), vec![], vec![], CallGraph::new(0));
+        AbstractState::from_locals(module, BTreeMap::new(), vec![], vec![], CallGraph::new(0));
     common::run_instruction(Bytecode::ImmBorrowField(field_handle_idx), state1);
 }
 
@@ -395,7 +395,7 @@ fn bytecode_immborrowfield_ref_is_mutable() {
     let field_handle_idx = FieldHandleIndex((module.field_handles.len() - 1) as u16);
 
     let mut state1 =
-        AbstractState::from_locals(module, HashMap::new(), vec![], vec![], CallGraph::new(0));
+        AbstractState::from_locals(module, BTreeMap::new(), vec![], vec![], CallGraph::new(0));
     let struct_value = create_struct_value(&state1.module.module).0;
     state1.stack_push(AbstractValue {
         token: SignatureToken::MutableReference(Box::new(struct_value.token)),
@@ -408,7 +408,7 @@ fn bytecode_immborrowfield_ref_is_mutable() {
 fn bytecode_borrowglobal() {
     let module = generate_module_with_struct(true);
     let mut state1 =
-        AbstractState::from_locals(module, HashMap::new(), vec![], vec![], CallGraph::new(0));
+        AbstractState::from_locals(module, BTreeMap::new(), vec![], vec![], CallGraph::new(0));
     let struct_value = create_struct_value(&state1.module.module).0;
     state1.stack_push(AbstractValue::new_primitive(SignatureToken::Address));
     let (state2, _) = common::run_instruction(
@@ -430,7 +430,7 @@ fn bytecode_borrowglobal() {
 fn bytecode_borrowglobal_struct_is_not_resource() {
     let module = generate_module_with_struct(false);
     let mut state1 =
-        AbstractState::from_locals(module, HashMap::new(), vec![], vec![], CallGraph::new(0));
+        AbstractState::from_locals(module, BTreeMap::new(), vec![], vec![], CallGraph::new(0));
     state1.stack_push(AbstractValue::new_primitive(SignatureToken::Address));
     common::run_instruction(
         Bytecode::MutBorrowGlobal(StructDefinitionIndex::new(0)),
@@ -443,7 +443,7 @@ fn bytecode_borrowglobal_struct_is_not_resource() {
 fn bytecode_borrowglobal_no_address_on_stack() {
     let module = generate_module_with_struct(true);
     let state1 =
-        AbstractState::from_locals(module, HashMap::new(), vec![], vec![], CallGraph::new(0));
+        AbstractState::from_locals(module, BTreeMap::new(), vec![], vec![], CallGraph::new(0));
     common::run_instruction(
         Bytecode::MutBorrowGlobal(StructDefinitionIndex::new(0)),
         state1,
@@ -6,7 +6,7 @@ pub mod dependency_graph;
 pub mod layout;
 pub mod module_cache;
 
-use crate::dependency_graph::DependencyGraph;
+use crate::{dependency_graph::DependencyGraph, module_cache::GetModule};
 use move_binary_format::{access::ModuleAccess, file_format::CompiledModule};
 use move_core_types::language_storage::ModuleId;
 
@@ -94,3 +94,12 @@ impl<'a> Modules<'a> {
         Ok(all_deps)
     }
 }
+
+impl<'a> GetModule for Modules<'a> {
+    type Error = anyhow::Error;
+    type Item = &'a CompiledModule;
+
+    fn get_module_by_id(&self, id: &ModuleId) -> Result<Option<&'a CompiledModule>> {
+        Ok(self.0.get(id).copied())
+    }
+}
@@ -3,13 +3,19 @@
 
 use super::reroot_path;
 use clap::*;
+use colored::Colorize;
 use move_package::{Architecture, BuildConfig};
 use std::path::PathBuf;
 
 /// Build the package at `path`. If no path is provided defaults to current directory.
 #[derive(Parser)]
 #[clap(name = "build")]
-pub struct Build;
+pub struct Build {
+    /// Report the dependencies and named addresses declared in the package manifest that the
+    /// package does not use. Dev dependencies are only reported when building in test mode.
+    #[clap(long = "warn-unused-deps")]
+    pub warn_unused_deps: bool,
+}
 
 impl Build {
     pub fn execute(self, path: Option<PathBuf>, config: BuildConfig) -> anyhow::Result<()> {
@@ -25,6 +31,13 @@ impl Build {
         let architecture = config.architecture.unwrap_or(Architecture::Move);
 
         match architecture {
+            Architecture::Move | Architecture::AsyncMove if self.warn_unused_deps => {
+                let (_, unused) = config
+                    .compile_package_and_find_unused(&rerooted_path, &mut std::io::stdout())?;
+                for declaration in unused {
+                    println!("{} {}", "WARNING".bold().yellow(), declaration);
+                }
+            }
             Architecture::Move | Architecture::AsyncMove => {
                 config.compile_package(&rerooted_path, &mut std::io::stdout())?;
             }
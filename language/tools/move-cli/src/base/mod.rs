@@ -10,6 +10,7 @@ pub mod info;
 pub mod movey_login;
 pub mod movey_upload;
 pub mod new;
+pub mod package;
 pub mod prove;
 pub mod test;
 
@@ -0,0 +1,39 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::base::reroot_path;
+use clap::*;
+use move_package::BuildConfig;
+use std::path::PathBuf;
+
+/// Print, for each package in the package graph, the named addresses in scope for it: whether
+/// they are declared by the package, renamed from or inherited from a dependency, and where in the
+/// graph their value is assigned.
+#[derive(Parser)]
+#[clap(name = "addresses")]
+pub struct Addresses {
+    /// Print the report as JSON.
+    #[clap(long = "json")]
+    pub json: bool,
+}
+
+impl Addresses {
+    pub fn execute(self, path: Option<PathBuf>, config: BuildConfig) -> anyhow::Result<()> {
+        let rerooted_path = reroot_path(path)?;
+        // Keep stdout clean for the JSON output
+        let resolved_graph = if self.json {
+            config.resolution_graph_for_package(&rerooted_path, &mut std::io::stderr())?
+        } else {
+            config.resolution_graph_for_package(&rerooted_path, &mut std::io::stdout())?
+        };
+        let report = resolved_graph.named_address_report();
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            for package in report {
+                package.print(&mut std::io::stdout())?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::read_modules;
+use crate::base::reroot_path;
+use anyhow::bail;
+use clap::*;
+use colored::Colorize;
+use move_binary_format::{
+    access::ModuleAccess,
+    compatibility::{Compatibility, Incompatibility, UpgradeItem},
+    file_format::{FunctionDefinitionIndex, StructDefinitionIndex},
+    normalized, CompiledModule,
+};
+use move_command_line_common::files::FileHash;
+use move_compiler::compiled_unit::{CompiledUnitEnum, NamedCompiledModule};
+use move_core_types::language_storage::ModuleId;
+use move_ir_types::location::Loc;
+use move_package::{compilation::compiled_package::CompiledUnitWithSource, BuildConfig};
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+/// Check whether the modules of the package can be published as an upgrade of the given modules,
+/// e.g., the modules currently published on chain. Every compatibility check (linking, struct
+/// layout, friend linking, abilities and type parameter constraints) is performed on its own, and
+/// each incompatibility is listed with the declarations before and after the upgrade.
+#[derive(Parser)]
+#[clap(name = "check-upgrade")]
+pub struct CheckUpgrade {
+    /// Directory containing the `.mv` files of the modules to upgrade.
+    #[clap(long = "against", parse(from_os_str))]
+    pub against: PathBuf,
+}
+
+impl CheckUpgrade {
+    pub fn execute(self, path: Option<PathBuf>, config: BuildConfig) -> anyhow::Result<()> {
+        // Read the modules before rerooting, as the directory may be relative to the current one
+        let old_modules = read_modules(&self.against)?;
+        let rerooted_path = reroot_path(path)?;
+        let package = config.compile_package(&rerooted_path, &mut std::io::stdout())?;
+        let new_modules: BTreeMap<ModuleId, (&NamedCompiledModule, &CompiledUnitWithSource)> =
+            package
+                .root_modules()
+                .filter_map(|unit| match &unit.unit {
+                    CompiledUnitEnum::Module(m) => Some((m.module.self_id(), (m, unit))),
+                    CompiledUnitEnum::Script(_) => None,
+                })
+                .collect();
+
+        let mut failed = false;
+        for (id, (_, old_module)) in &old_modules {
+            let id_str = format!("0x{}::{}", id.address().short_str_lossless(), id.name());
+            let (new_module, unit) = match new_modules.get(id) {
+                Some(new_module) => new_module,
+                None => {
+                    failed = true;
+                    println!(
+                        "{} {}: module removed from package '{}'",
+                        "INCOMPATIBLE".bold().red(),
+                        id_str,
+                        package.compiled_package_info.package_name
+                    );
+                    continue;
+                }
+            };
+            let incompatibilities = Compatibility::incompatibilities(
+                &normalized::Module::new(old_module),
+                &normalized::Module::new(&new_module.module),
+            );
+            if incompatibilities.is_empty() {
+                println!("{} {}", "COMPATIBLE".bold().green(), id_str);
+                continue;
+            }
+            failed = true;
+            println!("{} {}", "INCOMPATIBLE".bold().red(), id_str);
+            let source = fs::read_to_string(&unit.source_path).unwrap_or_default();
+            for incompatibility in incompatibilities {
+                let loc = item_location(new_module, &incompatibility.item);
+                print_incompatibility(
+                    &incompatibility,
+                    &format!(
+                        "{}:{}",
+                        unit.source_path.display(),
+                        line_and_column(&source, loc)
+                    ),
+                );
+            }
+        }
+        if failed {
+            bail!("Upgrade compatibility check failed")
+        }
+        Ok(())
+    }
+}
+
+fn print_incompatibility(incompatibility: &Incompatibility, location: &str) {
+    println!(
+        "  [{}] {}: {}",
+        incompatibility.check, incompatibility.item, incompatibility.reason
+    );
+    println!("    at {}", location);
+    if let Some(old) = &incompatibility.old {
+        println!("    old: {}", old);
+    }
+    match &incompatibility.new {
+        Some(new) => println!("    new: {}", new),
+        None => println!("    new: <none>"),
+    }
+}
+
+/// The location of the declaration of `item` in `module`, or of the module itself if the item
+/// does not exist anymore
+fn item_location(module: &NamedCompiledModule, item: &UpgradeItem) -> Loc {
+    let compiled: &CompiledModule = &module.module;
+    let source_map = &module.source_map;
+    let loc = match item {
+        UpgradeItem::Struct(name) => compiled
+            .struct_defs()
+            .iter()
+            .position(|def| {
+                compiled.identifier_at(compiled.struct_handle_at(def.struct_handle).name)
+                    == name.as_ident_str()
+            })
+            .and_then(|idx| {
+                source_map
+                    .get_struct_source_map(StructDefinitionIndex(idx as u16))
+                    .ok()
+            })
+            .map(|s| s.definition_location),
+        UpgradeItem::Function(name) => compiled
+            .function_defs()
+            .iter()
+            .position(|def| {
+                compiled.identifier_at(compiled.function_handle_at(def.function).name)
+                    == name.as_ident_str()
+            })
+            .and_then(|idx| {
+                source_map
+                    .get_function_source_map(FunctionDefinitionIndex(idx as u16))
+                    .ok()
+            })
+            .map(|f| f.definition_location),
+        UpgradeItem::Module | UpgradeItem::Friend(_) => None,
+    };
+    loc.unwrap_or(source_map.definition_location)
+}
+
+/// The (1-based) `line:column` of the start of `loc` in `source`
+fn line_and_column(source: &str, loc: Loc) -> String {
+    if FileHash::new(source) != loc.file_hash() {
+        // The source changed since the compilation
+        return "?".to_string();
+    }
+    let prefix = &source[..(loc.start() as usize).min(source.len())];
+    let line = prefix.matches('\n').count() + 1;
+    let column = prefix.len() - prefix.rfind('\n').map_or(0, |idx| idx + 1) + 1;
+    format!("{}:{}", line, column)
+}
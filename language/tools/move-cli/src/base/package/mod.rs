@@ -0,0 +1,67 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+pub mod addresses;
+pub mod check_upgrade;
+pub mod tree;
+pub mod verify_bytecode;
+
+use addresses::Addresses;
+use anyhow::{bail, Context};
+use check_upgrade::CheckUpgrade;
+use clap::*;
+use move_binary_format::CompiledModule;
+use move_command_line_common::files::{extension_equals, find_filenames, MOVE_COMPILED_EXTENSION};
+use move_core_types::language_storage::ModuleId;
+use move_package::BuildConfig;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use tree::Tree;
+use verify_bytecode::VerifyBytecode;
+
+/// Inspect the package at `path` and its package graph.
+#[derive(Parser)]
+#[clap(name = "package")]
+pub struct Package {
+    #[clap(subcommand)]
+    pub cmd: PackageCommand,
+}
+
+#[derive(Parser)]
+pub enum PackageCommand {
+    Addresses(Addresses),
+    CheckUpgrade(CheckUpgrade),
+    Tree(Tree),
+    VerifyBytecode(VerifyBytecode),
+}
+
+impl Package {
+    pub fn execute(self, path: Option<PathBuf>, config: BuildConfig) -> anyhow::Result<()> {
+        match self.cmd {
+            PackageCommand::Addresses(c) => c.execute(path, config),
+            PackageCommand::CheckUpgrade(c) => c.execute(path, config),
+            PackageCommand::Tree(c) => c.execute(path, config),
+            PackageCommand::VerifyBytecode(c) => c.execute(path, config),
+        }
+    }
+}
+
+/// Reads the modules in `dir`, along with their serialized form
+fn read_modules(dir: &Path) -> anyhow::Result<BTreeMap<ModuleId, (Vec<u8>, CompiledModule)>> {
+    if !dir.is_dir() {
+        bail!("'{}' is not a directory", dir.display())
+    }
+    let mut modules = BTreeMap::new();
+    for file in find_filenames(&[dir], |path| {
+        extension_equals(path, MOVE_COMPILED_EXTENSION)
+    })? {
+        let bytes = fs::read(&file)?;
+        let module = CompiledModule::deserialize(&bytes)
+            .with_context(|| format!("Unable to deserialize module at '{}'", file))?;
+        modules.insert(module.self_id(), (bytes, module));
+    }
+    Ok(modules)
+}
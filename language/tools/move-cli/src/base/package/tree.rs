@@ -0,0 +1,49 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::base::reroot_path;
+use clap::*;
+use move_package::BuildConfig;
+use move_symbol_pool::Symbol;
+use std::path::PathBuf;
+
+/// Print the dependency tree of the package. Packages whose dependencies are already listed
+/// earlier in the tree are marked with `(*)`, and the address renamings and assignments declared
+/// for each dependency are shown in braces.
+#[derive(Parser)]
+#[clap(name = "tree")]
+pub struct Tree {
+    /// Print the tree of the packages depending on this package instead.
+    #[clap(long = "invert", short = 'i')]
+    pub invert: Option<String>,
+    /// Maximum depth of the tree to print.
+    #[clap(long = "depth")]
+    pub depth: Option<usize>,
+    /// Print the tree as JSON.
+    #[clap(long = "json")]
+    pub json: bool,
+}
+
+impl Tree {
+    pub fn execute(self, path: Option<PathBuf>, config: BuildConfig) -> anyhow::Result<()> {
+        let rerooted_path = reroot_path(path)?;
+        // Keep stdout clean for the JSON output
+        let resolved_graph = if self.json {
+            config.resolution_graph_for_package(&rerooted_path, &mut std::io::stderr())?
+        } else {
+            config.resolution_graph_for_package(&rerooted_path, &mut std::io::stdout())?
+        };
+        let tree = match self.invert {
+            Some(package) => {
+                resolved_graph.reverse_dependency_tree(Symbol::from(package), self.depth)?
+            }
+            None => resolved_graph.dependency_tree(self.depth),
+        };
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&tree)?);
+        } else {
+            tree.print(&mut std::io::stdout())?;
+        }
+        Ok(())
+    }
+}
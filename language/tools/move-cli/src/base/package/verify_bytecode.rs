@@ -0,0 +1,196 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use super::read_modules;
+use crate::base::reroot_path;
+use anyhow::bail;
+use clap::*;
+use colored::Colorize;
+use move_binary_format::{binary_views::BinaryIndexedView, CompiledModule};
+use move_command_line_common::testing::format_diff;
+use move_compiler::compiled_unit::CompiledUnitEnum;
+use move_core_types::language_storage::ModuleId;
+use move_disassembler::disassembler::Disassembler;
+use move_ir_types::location::Spanned;
+use move_package::BuildConfig;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+/// Rebuild the package and check that its modules match the given bytecode, e.g., the bytecode
+/// deployed on chain. Dependencies are resolved as usual, so dependencies pinned with a `digest`
+/// in the manifest must match it. Each rebuilt module is serialized with the bytecode version of
+/// the module it is compared against.
+#[derive(Parser)]
+#[clap(name = "verify-bytecode")]
+pub struct VerifyBytecode {
+    /// Directory containing the `.mv` files of the modules to verify against.
+    #[clap(parse(from_os_str))]
+    pub bytecode_dir: PathBuf,
+    /// Consider modules which only differ in their metadata as matching.
+    #[clap(long = "ignore-metadata")]
+    pub ignore_metadata: bool,
+}
+
+/// The result of comparing a rebuilt module against the module it is verified against
+enum Verification {
+    Identical,
+    IdenticalModuloMetadata,
+    /// The rebuilt and supplied modules differ. Holds the diff of the disassembly of each function
+    /// that differs, or else of the metadata or of the whole module.
+    Different(Vec<(String, String)>),
+    /// The module was not found among the supplied modules
+    Missing,
+    /// The supplied module is not part of the package
+    Unexpected,
+}
+
+impl VerifyBytecode {
+    pub fn execute(self, path: Option<PathBuf>, config: BuildConfig) -> anyhow::Result<()> {
+        // Read the modules before rerooting, as the directory may be relative to the current one
+        let supplied = read_modules(&self.bytecode_dir)?;
+        let rerooted_path = reroot_path(path)?;
+        let package = config.compile_package(&rerooted_path, &mut std::io::stdout())?;
+        let rebuilt: BTreeMap<ModuleId, CompiledModule> = package
+            .root_modules()
+            .filter_map(|unit| match &unit.unit {
+                CompiledUnitEnum::Module(m) => Some((m.module.self_id(), m.module.clone())),
+                CompiledUnitEnum::Script(_) => None,
+            })
+            .collect();
+
+        let mut results = BTreeMap::new();
+        for (id, module) in &rebuilt {
+            let verification = match supplied.get(id) {
+                None => Verification::Missing,
+                Some((bytes, supplied_module)) => {
+                    self.verify_module(module, bytes, supplied_module)?
+                }
+            };
+            results.insert(id.clone(), verification);
+        }
+        for id in supplied.keys() {
+            if !rebuilt.contains_key(id) {
+                results.insert(id.clone(), Verification::Unexpected);
+            }
+        }
+
+        let mut failed = false;
+        for (id, verification) in results {
+            let id = format!("0x{}::{}", id.address().short_str_lossless(), id.name());
+            match verification {
+                Verification::Identical => println!("{} {}", "MATCH".bold().green(), id),
+                Verification::IdenticalModuloMetadata => {
+                    println!("{} {} (modulo metadata)", "MATCH".bold().green(), id)
+                }
+                Verification::Different(diffs) => {
+                    failed = true;
+                    println!("{} {}", "MISMATCH".bold().red(), id);
+                    for (what, diff) in diffs {
+                        println!("{} differs:\n{}", what, diff);
+                    }
+                }
+                Verification::Missing => {
+                    failed = true;
+                    println!(
+                        "{} {}: not found in {}",
+                        "MISSING".bold().red(),
+                        id,
+                        self.bytecode_dir.display()
+                    );
+                }
+                Verification::Unexpected => {
+                    failed = true;
+                    println!(
+                        "{} {}: not part of package '{}'",
+                        "UNEXPECTED".bold().red(),
+                        id,
+                        package.compiled_package_info.package_name
+                    );
+                }
+            }
+        }
+        if failed {
+            bail!("Bytecode verification failed")
+        }
+        Ok(())
+    }
+
+    fn verify_module(
+        &self,
+        rebuilt: &CompiledModule,
+        supplied_bytes: &[u8],
+        supplied: &CompiledModule,
+    ) -> anyhow::Result<Verification> {
+        let serialize = |module: &CompiledModule| -> anyhow::Result<Vec<u8>> {
+            let mut bytes = vec![];
+            module.serialize_for_version(Some(supplied.version), &mut bytes)?;
+            Ok(bytes)
+        };
+        if serialize(rebuilt)? == supplied_bytes {
+            return Ok(Verification::Identical);
+        }
+        if self.ignore_metadata {
+            let without_metadata = |module: &CompiledModule| {
+                let mut module = module.clone();
+                module.metadata.clear();
+                serialize(&module)
+            };
+            if without_metadata(rebuilt)? == without_metadata(supplied)? {
+                return Ok(Verification::IdenticalModuloMetadata);
+            }
+        }
+
+        let disassembler = |module| {
+            Disassembler::from_view(
+                BinaryIndexedView::Module(module),
+                Spanned::unsafe_no_loc(()).loc,
+            )
+        };
+        let rebuilt_disassembler = disassembler(rebuilt)?;
+        let supplied_disassembler = disassembler(supplied)?;
+        let rebuilt_functions: BTreeMap<_, _> = rebuilt_disassembler
+            .disassemble_function_defs()?
+            .into_iter()
+            .collect();
+        let supplied_functions: BTreeMap<_, _> = supplied_disassembler
+            .disassemble_function_defs()?
+            .into_iter()
+            .collect();
+        let names: BTreeSet<_> = rebuilt_functions
+            .keys()
+            .chain(supplied_functions.keys())
+            .collect();
+        let mut diffs = vec![];
+        for name in names {
+            let expected = supplied_functions.get(name).map_or("", String::as_str);
+            let actual = rebuilt_functions.get(name).map_or("", String::as_str);
+            if expected != actual {
+                diffs.push((
+                    format!("function `{}`", name),
+                    format_diff(expected, actual),
+                ));
+            }
+        }
+        if diffs.is_empty() && rebuilt.metadata != supplied.metadata {
+            diffs.push((
+                "metadata".to_string(),
+                format_diff(
+                    format!("{:#?}", supplied.metadata),
+                    format!("{:#?}", rebuilt.metadata),
+                ),
+            ));
+        }
+        if diffs.is_empty() {
+            diffs.push((
+                "module".to_string(),
+                format_diff(
+                    supplied_disassembler.disassemble()?,
+                    rebuilt_disassembler.disassemble()?,
+                ),
+            ));
+        }
+        Ok(Verification::Different(diffs))
+    }
+}
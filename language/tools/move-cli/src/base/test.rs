@@ -7,7 +7,8 @@ use anyhow::Result;
 use clap::*;
 use move_command_line_common::files::{FileHash, MOVE_COVERAGE_MAP_EXTENSION};
 use move_compiler::{
-    diagnostics::{self, codes::Severity},
+    compiled_unit::CompiledUnit,
+    diagnostics::{self, codes::Severity, FilesSourceText},
     shared::{NumberFormat, NumericalAddress},
     unit_test::{plan_builder::construct_test_plan, TestPlan},
     PASS_CFGIR,
@@ -76,6 +77,11 @@ pub struct Test {
     /// Collect coverage information for later use with the various `move coverage` subcommands
     #[clap(long = "coverage")]
     pub compute_coverage: bool,
+    /// Persist the modules which passed bytecode verification to this file, and skip verifying
+    /// them again in later runs. The file is trusted: modules recorded in a tampered file are
+    /// not verified.
+    #[clap(name = "verified_module_cache", long = "verified_module_cache")]
+    pub verified_module_cache: Option<PathBuf>,
 
     /// Use the EVM-based execution backend.
     /// Does not work with --stackless.
@@ -104,6 +110,7 @@ impl Test {
             check_stackless_vm,
             verbose_mode,
             compute_coverage,
+            verified_module_cache,
             #[cfg(feature = "evm-backend")]
             evm,
         } = self;
@@ -117,6 +124,7 @@ impl Test {
             check_stackless_vm,
             verbose: verbose_mode,
             ignore_compile_warnings,
+            verified_module_cache,
             #[cfg(feature = "evm-backend")]
             evm,
 
@@ -156,7 +164,8 @@ pub fn run_move_unit_tests<W: Write + Send>(
     compute_coverage: bool,
     writer: &mut W,
 ) -> Result<UnitTestResult> {
-    let mut test_plan = None;
+    let mut test_plan = vec![];
+    let mut test_files = FilesSourceText::new();
     build_config.test_mode = true;
     build_config.dev_mode = true;
 
@@ -198,8 +207,9 @@ pub fn run_move_unit_tests<W: Write + Send>(
     // Compile the package. We need to intercede in the compilation, process being performed by the
     // Move package system, to first grab the compilation env, construct the test plan from it, and
     // then save it, before resuming the rest of the compilation and returning the results and
-    // control back to the Move package system.
-    build_plan.compile_with_driver(writer, |compiler| {
+    // control back to the Move package system. Compilation is incremental, so the driver may be
+    // invoked several times, but the root package is always recompiled in test mode.
+    let compiled_package = build_plan.compile_incrementally_with_driver(writer, |compiler| {
         let (files, comments_and_compiler_res) = compiler.run::<PASS_CFGIR>().unwrap();
         let (_, compiler) =
             diagnostics::unwrap_or_report_diagnostics(&files, comments_and_compiler_res);
@@ -219,15 +229,22 @@ pub fn run_move_unit_tests<W: Write + Send>(
         let compilation_result = compiler.at_cfgir(cfgir).build();
 
         let (units, _) = diagnostics::unwrap_or_report_diagnostics(&files, compilation_result);
-        test_plan = Some((built_test_plan, files.clone(), units.clone()));
+        test_plan.extend(built_test_plan.unwrap_or_default());
+        test_files.extend(files.clone());
         Ok((files, units))
     })?;
 
-    let (test_plan, mut files, units) = test_plan.unwrap();
-    files.extend(dep_file_map);
-    let test_plan = test_plan.unwrap();
+    test_files.extend(dep_file_map);
     let no_tests = test_plan.is_empty();
-    let test_plan = TestPlan::new(test_plan, files, units);
+    let mut test_plan = TestPlan::new(test_plan, test_files, vec![]);
+    // Modules which were not recompiled are only available from the compiled package
+    test_plan.module_info = compiled_package
+        .all_modules()
+        .filter_map(|unit| match &unit.unit {
+            CompiledUnit::Module(module) => Some((module.module.self_id(), module.clone())),
+            CompiledUnit::Script(_) => None,
+        })
+        .collect();
 
     let trace_path = pkg_path.join(".trace");
     let coverage_map_path = pkg_path
@@ -4,8 +4,8 @@
 
 use base::{
     build::Build, coverage::Coverage, disassemble::Disassemble, docgen::Docgen, errmap::Errmap,
-    info::Info, movey_login::MoveyLogin, movey_upload::MoveyUpload, new::New, prove::Prove,
-    test::Test,
+    info::Info, movey_login::MoveyLogin, movey_upload::MoveyUpload, new::New, package::Package,
+    prove::Prove, test::Test,
 };
 use move_package::BuildConfig;
 
@@ -72,6 +72,7 @@ pub enum Command {
     Info(Info),
     MoveyUpload(MoveyUpload),
     New(New),
+    Package(Package),
     Prove(Prove),
     Test(Test),
     /// Execute a sandbox command.
@@ -96,6 +97,15 @@ pub enum Command {
     },
     #[clap(name = "movey-login")]
     MoveyLogin(MoveyLogin),
+    /// Evaluate Move expressions and statements interactively against the modules and resources
+    /// stored on disk in `storage-dir`.
+    #[clap(name = "repl")]
+    Repl {
+        /// Directory storing Move resources, events, and module bytecodes produced by module publishing
+        /// and script execution.
+        #[clap(long, default_value = DEFAULT_STORAGE_DIR, parse(from_os_str))]
+        storage_dir: PathBuf,
+    },
 }
 
 pub fn run_cli(
@@ -117,6 +127,7 @@ pub fn run_cli(
         Command::Info(c) => c.execute(move_args.package_path, move_args.build_config),
         Command::MoveyUpload(c) => c.execute(move_args.package_path),
         Command::New(c) => c.execute_with_defaults(move_args.package_path),
+        Command::Package(c) => c.execute(move_args.package_path, move_args.build_config),
         Command::Prove(c) => c.execute(move_args.package_path, move_args.build_config),
         Command::Test(c) => c.execute(
             move_args.package_path,
@@ -133,6 +144,13 @@ pub fn run_cli(
         ),
         Command::Experimental { storage_dir, cmd } => cmd.handle_command(&move_args, &storage_dir),
         Command::MoveyLogin(c) => c.execute(),
+        Command::Repl { storage_dir } => sandbox::commands::repl(
+            natives,
+            cost_table,
+            error_descriptions,
+            &move_args,
+            &storage_dir,
+        ),
     }
 }
 
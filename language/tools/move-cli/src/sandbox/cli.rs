@@ -12,8 +12,9 @@ use crate::{
 use anyhow::Result;
 use clap::Parser;
 use move_core_types::{
-    errmap::ErrorMapping, language_storage::TypeTag, parser,
-    transaction_argument::TransactionArgument,
+    errmap::ErrorMapping,
+    language_storage::{ModuleId, TypeTag},
+    parser,
 };
 use move_package::compilation::package_layout::CompiledPackageLayout;
 use move_vm_test_utils::gas_schedule::CostTable;
@@ -75,21 +76,23 @@ pub enum SandboxCommand {
         )]
         signers: Vec<String>,
         /// Possibly-empty list of arguments passed to the transaction (e.g., `i` in
-        /// `main(i: u64)`). Must match the arguments types expected by `script_file`.
-        /// Supported argument types are
+        /// `main(i: u64)`). Must match the arguments types expected by `script_file`, which
+        /// determine how each argument is parsed:
         /// bool literals (true, false),
-        /// u64 literals (e.g., 10, 58),
+        /// integer literals (e.g., 10, 58, 255u8),
         /// address literals (e.g., 0x12, 0x0000000000000000000000000000000f),
-        /// hexadecimal strings (e.g., x"0012" will parse as the vector<u8> value [00, 12]), and
-        /// ASCII strings (e.g., 'b"hi" will parse as the vector<u8> value [68, 69]).
+        /// vectors (e.g., [1, 2, 3]; a vector<u8> can also be written as a hexadecimal string
+        /// like x"0012" or an ASCII string like b"hi"),
+        /// strings for std::string::String and std::ascii::String (e.g., "hello"),
+        /// options for std::option::Option (e.g., none, some(1)), and
+        /// structs as a list of fields (e.g., {x: 1, y: [true]}).
         #[clap(
             long = "args",
-            parse(try_from_str = parser::parse_transaction_argument),
             takes_value(true),
             multiple_values(true),
             multiple_occurrences(true)
         )]
-        args: Vec<TransactionArgument>,
+        args: Vec<String>,
         /// Possibly-empty list of type arguments passed to the transaction (e.g., `T` in
         /// `main<T>()`). Must match the type arguments kinds expected by `script_file`.
         #[clap(
@@ -109,6 +112,11 @@ pub enum SandboxCommand {
         /// deleted resources) will NOT be committed to disk.
         #[clap(long = "dry-run", short = 'n')]
         dry_run: bool,
+        /// If set, the transaction and a hash of its effects are appended to the transaction log
+        /// `transactions.log` in `storage-dir`, from which it can be replayed with `replay`.
+        /// Transactions that fail or are run with `--dry-run` are not recorded.
+        #[clap(long = "record")]
+        record: bool,
     },
     /// Run expected value tests using the given batch file.
     #[clap(name = "exp-test")]
@@ -128,13 +136,53 @@ pub enum SandboxCommand {
         /// Path to a resource, events file, or module stored on disk.
         #[clap(name = "file", parse(from_os_str))]
         file: PathBuf,
+        /// Print resources and events as JSON.
+        #[clap(long = "json")]
+        json: bool,
+    },
+    /// Print as JSON every resource stored on disk whose type matches a struct tag pattern.
+    #[clap(name = "query")]
+    Query {
+        /// The struct tag to match (e.g., `0x1::M::S`, or `0x1::M::S<u64>` to match a single
+        /// instantiation).
+        #[clap(name = "pattern")]
+        pattern: String,
+    },
+    /// Print the events stored on disk, decoded using the layouts of their types, optionally
+    /// filtered by key, type, emitting module, and sequence number.
+    #[clap(name = "events")]
+    Events {
+        /// Only print events with this key, as hex (e.g., as printed by this command).
+        #[clap(long = "key")]
+        key: Option<String>,
+        /// Only print events with a payload of this type (e.g., `0x1::M::E`, or `0x1::M::E<u64>`
+        /// to match a single instantiation).
+        #[clap(long = "type", parse(try_from_str = parser::parse_type_tag))]
+        type_: Option<TypeTag>,
+        /// Only print events emitted by this module (e.g., `0x1::M`), i.e., whose payload is a
+        /// struct declared in this module.
+        #[clap(long = "module", parse(try_from_str = sandbox::commands::parse_module_id))]
+        module: Option<ModuleId>,
+        /// Only print events with a sequence number greater than or equal to this one.
+        #[clap(long = "from")]
+        from: Option<u64>,
+        /// Only print events with a sequence number less than or equal to this one.
+        #[clap(long = "to")]
+        to: Option<u64>,
+        /// Print the events as JSON.
+        #[clap(long = "json")]
+        json: bool,
     },
     /// Delete all resources, events, and modules stored on disk under `storage-dir`.
     /// Does *not* delete anything in `src`.
     Clean {},
     /// Run well-formedness checks on the `storage-dir` and `install-dir` directories.
     #[clap(name = "doctor")]
-    Doctor {},
+    Doctor {
+        /// Repair the problems found where possible, and print a report of the changes.
+        #[clap(long = "fix")]
+        fix: bool,
+    },
     /// Generate struct layout bindings for the modules stored on disk under `storage-dir`
     // TODO: expand this to generate script bindings, etc.?.
     #[clap(name = "generate")]
@@ -142,6 +190,60 @@ pub enum SandboxCommand {
         #[clap(subcommand)]
         cmd: GenerateCommand,
     },
+    /// Save, restore and compare snapshots of the resources, events, and modules stored on disk
+    /// under `storage-dir`.
+    #[clap(name = "snapshot")]
+    Snapshot {
+        #[clap(subcommand)]
+        cmd: SnapshotCommand,
+    },
+    /// Re-execute the transactions of a transaction log recorded with `run --record`, starting
+    /// from a snapshot, and check that they produce the recorded effects. Neither the snapshot nor
+    /// `storage-dir` are modified.
+    #[clap(name = "replay")]
+    Replay {
+        /// Path to the transaction log.
+        #[clap(name = "log", parse(from_os_str))]
+        log: PathBuf,
+        /// Name of the snapshot to replay the transactions from.
+        #[clap(name = "snapshot")]
+        snapshot: String,
+    },
+}
+
+#[derive(Parser)]
+pub enum SnapshotCommand {
+    /// Save the current contents of `storage-dir` as a named snapshot.
+    #[clap(name = "save")]
+    Save {
+        /// Name of the snapshot.
+        #[clap(name = "name")]
+        name: String,
+        /// Replace the snapshot if it already exists.
+        #[clap(long = "overwrite")]
+        overwrite: bool,
+    },
+    /// Replace the contents of `storage-dir` with a snapshot.
+    #[clap(name = "restore")]
+    Restore {
+        /// Name of the snapshot.
+        #[clap(name = "name")]
+        name: String,
+    },
+    /// List the saved snapshots.
+    #[clap(name = "list")]
+    List {},
+    /// Show the modules, resources, and events added, changed, or deleted since a snapshot.
+    #[clap(name = "diff")]
+    Diff {
+        /// Name of the snapshot to compare from.
+        #[clap(name = "name")]
+        name: String,
+        /// Name of the snapshot to compare to. By default, compare to the current contents of
+        /// `storage-dir`.
+        #[clap(name = "other")]
+        other: Option<String>,
+    },
 }
 
 #[derive(Parser)]
@@ -235,6 +337,7 @@ impl SandboxCommand {
                 type_args,
                 gas_budget,
                 dry_run,
+                record,
             } => {
                 let context =
                     PackageContext::new(&move_args.package_path, &move_args.build_config)?;
@@ -252,6 +355,7 @@ impl SandboxCommand {
                     type_args.to_vec(),
                     *gas_budget,
                     *dry_run,
+                    *record,
                     move_args.verbose,
                 )
             }
@@ -267,10 +371,37 @@ impl SandboxCommand {
                 *use_temp_dir,
                 *track_cov,
             ),
-            SandboxCommand::View { file } => {
+            SandboxCommand::View { file, json } => {
                 let state = PackageContext::new(&move_args.package_path, &move_args.build_config)?
                     .prepare_state(storage_dir)?;
-                sandbox::commands::view(&state, file)
+                sandbox::commands::view(&state, file, *json)
+            }
+            SandboxCommand::Query { pattern } => {
+                let state = PackageContext::new(&move_args.package_path, &move_args.build_config)?
+                    .prepare_state(storage_dir)?;
+                sandbox::commands::query(&state, pattern)
+            }
+            SandboxCommand::Events {
+                key,
+                type_,
+                module,
+                from,
+                to,
+                json,
+            } => {
+                let state = PackageContext::new(&move_args.package_path, &move_args.build_config)?
+                    .prepare_state(storage_dir)?;
+                let filter = sandbox::commands::EventFilter {
+                    key: key
+                        .as_deref()
+                        .map(sandbox::commands::parse_event_key)
+                        .transpose()?,
+                    type_: type_.clone(),
+                    module: module.clone(),
+                    from: *from,
+                    to: *to,
+                };
+                sandbox::commands::events(&state, &filter, *json)
             }
             SandboxCommand::Clean {} => {
                 // delete storage
@@ -293,16 +424,39 @@ impl SandboxCommand {
                 }
                 Ok(())
             }
-            SandboxCommand::Doctor {} => {
+            SandboxCommand::Doctor { fix } => {
                 let state = PackageContext::new(&move_args.package_path, &move_args.build_config)?
                     .prepare_state(storage_dir)?;
-                sandbox::commands::doctor(&state)
+                sandbox::commands::doctor(&state, *fix)
             }
             SandboxCommand::Generate { cmd } => {
                 let state = PackageContext::new(&move_args.package_path, &move_args.build_config)?
                     .prepare_state(storage_dir)?;
                 handle_generate_commands(cmd, &state)
             }
+            SandboxCommand::Snapshot { cmd } => {
+                // Snapshots only involve the contents of `storage-dir`, so the package is neither
+                // built nor loaded into storage
+                let state = OnDiskStateView::create(Path::new(DEFAULT_BUILD_DIR), storage_dir)?;
+                handle_snapshot_commands(cmd, &state)
+            }
+            SandboxCommand::Replay { log, snapshot } => {
+                let state = OnDiskStateView::create(Path::new(DEFAULT_BUILD_DIR), storage_dir)?;
+                sandbox::commands::replay(natives, cost_table, &state, log, snapshot)
+            }
+        }
+    }
+}
+
+fn handle_snapshot_commands(cmd: &SnapshotCommand, state: &OnDiskStateView) -> Result<()> {
+    match cmd {
+        SnapshotCommand::Save { name, overwrite } => {
+            sandbox::commands::snapshot::save(state, name, *overwrite)
+        }
+        SnapshotCommand::Restore { name } => sandbox::commands::snapshot::restore(state, name),
+        SnapshotCommand::List {} => sandbox::commands::snapshot::list(state),
+        SnapshotCommand::Diff { name, other } => {
+            sandbox::commands::snapshot::diff(state, name, other.as_deref())
         }
     }
 }
@@ -3,12 +3,18 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::sandbox::utils::on_disk_state_view::OnDiskStateView;
-use move_binary_format::{access::ModuleAccess, errors::PartialVMError};
-use move_bytecode_utils::Modules;
-use move_core_types::vm_status::StatusCode;
+use move_binary_format::{access::ModuleAccess, errors::PartialVMError, CompiledModule};
+use move_bytecode_utils::{layout::StructLayoutBuilder, Modules};
+use move_core_types::{
+    account_address::AccountAddress,
+    parser,
+    value::{MoveStructLayout, MoveTypeLayout},
+    vm_status::StatusCode,
+};
+use move_resource_viewer::MoveValueAnnotator;
 
 use anyhow::{bail, Result};
-use std::{ffi::OsStr, path::Path};
+use std::{ffi::OsStr, fs, path::Path};
 
 /// Run sanity checks on storage and build dirs. This is primarily intended for testing the CLI;
 /// doctor should never fail unless `publish --ignore-breaking changes` is used or files under
@@ -18,7 +24,12 @@ use std::{ffi::OsStr, path::Path};
 /// (3) all resources can be deserialized
 /// (4) all events can be deserialized
 /// (5) build/mv_interfaces is consistent with the global storage (TODO?)
-pub fn doctor(state: &OnDiskStateView) -> Result<()> {
+/// If `fix` is set, problems are repaired where possible instead, see `fix_storage`.
+pub fn doctor(state: &OnDiskStateView, fix: bool) -> Result<()> {
+    if fix {
+        return fix_storage(state);
+    }
+
     fn parent_addr(p: &Path) -> &OsStr {
         p.parent().unwrap().parent().unwrap().file_name().unwrap()
     }
@@ -91,3 +102,242 @@ pub fn doctor(state: &OnDiskStateView) -> Result<()> {
 
     Ok(())
 }
+
+/// Repair storage, printing a report of every change:
+/// - modules are verified, and linked against their dependencies, with the bytecode verifier.
+///   Modules failing these checks cannot be repaired, so they are only reported.
+/// - resources whose layout changed compatibly, by appending fields whose values can be defaulted
+///   (e.g., to `0`, `false` or an empty vector), are rewritten with the default values.
+/// - other resources that cannot be deserialized, e.g. because their type no longer exists, are
+///   moved to the quarantine directory next to the storage directory.
+/// - orphaned events, whose payload can no longer be deserialized, are removed.
+///
+/// Fails if problems remain after the repair.
+fn fix_storage(state: &OnDiskStateView) -> Result<()> {
+    // paths are reported relative to the directory holding the storage and quarantine directories
+    let parent_dir = state.storage_dir().parent().unwrap();
+    let mut fixed = 0;
+    let mut unfixed = 0;
+
+    let all_modules = state.get_all_modules()?;
+    let code_cache = Modules::new(&all_modules);
+    for module in &all_modules {
+        if let Err(e) = verify_module_with_dependencies(module, &code_cache) {
+            println!("Module {} is invalid: {:#}", module.self_id(), e);
+            unfixed += 1;
+        }
+    }
+
+    for resource_path in state.resource_paths().collect::<Vec<_>>() {
+        let error = match state.view_resource(&resource_path) {
+            Ok(_) => continue,
+            Err(error) => error,
+        };
+        let name = relative_path(state.storage_dir(), &resource_path);
+        match extend_resource(state, &resource_path) {
+            Ok(Some(added_fields)) => println!(
+                "Rewrote resource {} with default values for its new fields: {}",
+                name,
+                added_fields.join(", ")
+            ),
+            Ok(None) | Err(_) => {
+                let quarantine_path = state.quarantine(&resource_path)?;
+                println!(
+                    "Quarantined resource {}, which cannot be deserialized ({:#}), to {}",
+                    name,
+                    error,
+                    relative_path(parent_dir, &quarantine_path)
+                );
+            }
+        }
+        fixed += 1;
+    }
+
+    let annotator = MoveValueAnnotator::new(state);
+    for event_path in state.event_paths().collect::<Vec<_>>() {
+        let name = relative_path(state.storage_dir(), &event_path);
+        let events = match state.get_events(&event_path) {
+            Ok(events) => events,
+            Err(error) => {
+                let quarantine_path = state.quarantine(&event_path)?;
+                println!(
+                    "Quarantined events {}, which cannot be deserialized ({:#}), to {}",
+                    name,
+                    error,
+                    relative_path(parent_dir, &quarantine_path)
+                );
+                fixed += 1;
+                continue;
+            }
+        };
+        let num_events = events.len();
+        let events: Vec<_> = events
+            .into_iter()
+            .filter(|(_, _, type_, data)| annotator.view_value(type_, data).is_ok())
+            .collect();
+        if events.len() < num_events {
+            state.save_events(&event_path, &events)?;
+            println!(
+                "Removed {} orphaned event(s) from {}",
+                num_events - events.len(),
+                name
+            );
+            fixed += 1;
+        }
+    }
+
+    if unfixed > 0 {
+        bail!(
+            "Fixed {} problem(s), {} problem(s) could not be fixed",
+            fixed,
+            unfixed
+        )
+    }
+    if fixed == 0 {
+        println!("No problems found.")
+    } else {
+        println!("Fixed {} problem(s).", fixed)
+    }
+    Ok(())
+}
+
+/// Run the checks of the bytecode verifier on `module`, including the checks against its
+/// dependencies in `code_cache`.
+fn verify_module_with_dependencies(module: &CompiledModule, code_cache: &Modules) -> Result<()> {
+    move_bytecode_verifier::verify_module(module)?;
+    let imm_deps = code_cache.get_immediate_dependencies(&module.self_id())?;
+    move_bytecode_verifier::dependencies::verify_module(module, imm_deps)?;
+    move_bytecode_verifier::cyclic_dependencies::verify_module(
+        module,
+        |module_id| {
+            code_cache
+                .get_module(module_id)
+                .map_err(|_| PartialVMError::new(StatusCode::MISSING_DEPENDENCY))
+                .map(|m| m.immediate_dependencies())
+        },
+        |module_id| {
+            code_cache
+                .get_module(module_id)
+                .map_err(|_| PartialVMError::new(StatusCode::MISSING_DEPENDENCY))
+                .map(|m| m.immediate_friends())
+        },
+    )?;
+    Ok(())
+}
+
+/// `path` relative to `dir`, to keep reports short
+fn relative_path(dir: &Path, path: &Path) -> String {
+    path.strip_prefix(dir).unwrap_or(path).display().to_string()
+}
+
+/// If the resource stored at `resource_path` is a prefix of its current layout, i.e., fields were
+/// appended to its type since it was stored, and all the missing fields can be defaulted, rewrite
+/// it with the default values and return the names of the missing fields.
+fn extend_resource(state: &OnDiskStateView, resource_path: &Path) -> Result<Option<Vec<String>>> {
+    let tag = parser::parse_struct_tag(&resource_path.file_stem().unwrap().to_string_lossy())?;
+    let fields = match StructLayoutBuilder::build_with_fields(&tag, &state)? {
+        MoveStructLayout::WithFields(fields) => fields,
+        _ => unreachable!("layouts built with fields have fields"),
+    };
+    let mut bytes = fs::read(resource_path)?;
+    let mut offset = 0;
+    let mut num_stored_fields = 0;
+    while offset < bytes.len() && num_stored_fields < fields.len() {
+        match value_len(&fields[num_stored_fields].layout, &bytes[offset..]) {
+            Some(len) => offset += len,
+            None => return Ok(None),
+        }
+        num_stored_fields += 1;
+    }
+    if offset != bytes.len() || num_stored_fields == fields.len() {
+        return Ok(None);
+    }
+    let missing_fields = &fields[num_stored_fields..];
+    for field in missing_fields {
+        match default_value(&field.layout) {
+            Some(value) => bytes.extend(value),
+            None => return Ok(None),
+        }
+    }
+    // check that the value is now valid before overwriting the stored one
+    MoveValueAnnotator::new(state).view_resource(&tag, &bytes)?;
+    fs::write(resource_path, &bytes)?;
+    Ok(Some(
+        missing_fields
+            .iter()
+            .map(|field| field.name.to_string())
+            .collect(),
+    ))
+}
+
+/// The length of the BCS-serialized value of type `layout` at the start of `bytes`, if `bytes`
+/// start with a valid value
+fn value_len(layout: &MoveTypeLayout, bytes: &[u8]) -> Option<usize> {
+    let len = match layout {
+        MoveTypeLayout::Bool => match bytes.first() {
+            Some(0 | 1) => 1,
+            _ => return None,
+        },
+        MoveTypeLayout::U8 => 1,
+        MoveTypeLayout::U16 => 2,
+        MoveTypeLayout::U32 => 4,
+        MoveTypeLayout::U64 => 8,
+        MoveTypeLayout::U128 => 16,
+        MoveTypeLayout::U256 => 32,
+        MoveTypeLayout::Address | MoveTypeLayout::Signer => AccountAddress::LENGTH,
+        MoveTypeLayout::Vector(elem) => {
+            let (num_elems, mut len) = uleb128_len(bytes)?;
+            for _ in 0..num_elems {
+                len += value_len(elem, bytes.get(len..)?)?;
+            }
+            len
+        }
+        MoveTypeLayout::Struct(s) => {
+            let mut len = 0;
+            for field in s.clone().into_fields() {
+                len += value_len(&field, bytes.get(len..)?)?;
+            }
+            len
+        }
+    };
+    if len > bytes.len() {
+        return None;
+    }
+    Some(len)
+}
+
+/// The ULEB128-encoded length at the start of `bytes`, and the number of bytes encoding it
+fn uleb128_len(bytes: &[u8]) -> Option<(u64, usize)> {
+    let mut value: u64 = 0;
+    for (i, byte) in bytes.iter().enumerate().take(10) {
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+/// The BCS-serialized default value of type `layout`, if it has one: zero for integers and
+/// addresses, `false`, empty vectors, and structs whose fields have default values.
+fn default_value(layout: &MoveTypeLayout) -> Option<Vec<u8>> {
+    Some(match layout {
+        MoveTypeLayout::Bool | MoveTypeLayout::U8 => vec![0],
+        MoveTypeLayout::U16 => vec![0; 2],
+        MoveTypeLayout::U32 => vec![0; 4],
+        MoveTypeLayout::U64 => vec![0; 8],
+        MoveTypeLayout::U128 => vec![0; 16],
+        MoveTypeLayout::U256 => vec![0; 32],
+        MoveTypeLayout::Address => vec![0; AccountAddress::LENGTH],
+        MoveTypeLayout::Signer => return None,
+        // the ULEB128 encoding of a zero length
+        MoveTypeLayout::Vector(_) => vec![0],
+        MoveTypeLayout::Struct(s) => {
+            let mut bytes = vec![];
+            for field in s.clone().into_fields() {
+                bytes.extend(default_value(&field)?);
+            }
+            bytes
+        }
+    })
+}
@@ -0,0 +1,128 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::sandbox::{
+    commands::view::matches_pattern,
+    utils::on_disk_state_view::{Event, OnDiskStateView},
+};
+use anyhow::{anyhow, bail, Result};
+use move_core_types::{
+    account_address::AccountAddress,
+    identifier::Identifier,
+    language_storage::{ModuleId, TypeTag},
+};
+use move_resource_viewer::{json::JsonValue, MoveValueAnnotator};
+
+/// Selects the events printed by `move sandbox events`. Unset filters match every event.
+#[derive(Debug, Default)]
+pub struct EventFilter {
+    /// The event key, i.e., the BCS-serialized GUID of the event handle
+    pub key: Option<Vec<u8>>,
+    /// The payload type. A struct type without type arguments matches every instantiation.
+    pub type_: Option<TypeTag>,
+    /// The module emitting the event. Since only the module declaring a struct can create values
+    /// of it, this is the module declaring the struct type of the payload.
+    pub module: Option<ModuleId>,
+    /// The smallest sequence number
+    pub from: Option<u64>,
+    /// The largest sequence number
+    pub to: Option<u64>,
+}
+
+impl EventFilter {
+    fn matches(&self, (key, sequence_number, type_, _): &Event) -> bool {
+        if matches!(&self.key, Some(k) if k != key)
+            || matches!(self.from, Some(from) if *sequence_number < from)
+            || matches!(self.to, Some(to) if *sequence_number > to)
+        {
+            return false;
+        }
+        if let Some(pattern) = &self.type_ {
+            let matches_type = match (pattern, type_) {
+                (TypeTag::Struct(pattern), TypeTag::Struct(tag)) => matches_pattern(pattern, tag),
+                _ => pattern == type_,
+            };
+            if !matches_type {
+                return false;
+            }
+        }
+        match (&self.module, type_) {
+            (None, _) => true,
+            (Some(module), TypeTag::Struct(tag)) => &tag.module_id() == module,
+            (Some(_), _) => false,
+        }
+    }
+}
+
+/// Print the events stored on disk that match `filter`, ordered by key and sequence number, with
+/// their payloads decoded using the layouts of their types. The events are printed as JSON if
+/// `json` is set.
+pub fn events(state: &OnDiskStateView, filter: &EventFilter, json: bool) -> Result<()> {
+    let mut events: Vec<_> = state
+        .get_all_events()?
+        .into_iter()
+        .filter(|event| filter.matches(event))
+        .collect();
+    events.sort_by(|(k1, s1, _, _), (k2, s2, _, _)| (k1, s1).cmp(&(k2, s2)));
+
+    let annotator = MoveValueAnnotator::new(state);
+    let values = events
+        .iter()
+        .map(|(_, _, type_, data)| annotator.view_value(type_, data))
+        .collect::<Result<Vec<_>>>()?;
+
+    if json {
+        let results: Vec<_> = events
+            .iter()
+            .zip(&values)
+            .map(|((key, sequence_number, type_, _), value)| EventResult {
+                key: format!("0x{}", hex::encode(key)),
+                sequence_number: sequence_number.to_string(),
+                type_: type_.to_string(),
+                data: JsonValue(value),
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&results)?);
+    } else if events.is_empty() {
+        println!("Events not found.")
+    } else {
+        for ((key, sequence_number, type_, _), value) in events.iter().zip(&values) {
+            println!(
+                "key: 0x{}, sequence number: {}, type: {}",
+                hex::encode(key),
+                sequence_number,
+                type_
+            );
+            println!("{}", value);
+        }
+    }
+    Ok(())
+}
+
+#[derive(serde::Serialize)]
+struct EventResult<'a> {
+    key: String,
+    sequence_number: String,
+    #[serde(rename = "type")]
+    type_: String,
+    data: JsonValue<'a>,
+}
+
+/// Parse an event key given as a hex string, with or without a `0x` prefix.
+pub fn parse_event_key(s: &str) -> Result<Vec<u8>> {
+    Ok(hex::decode(s.strip_prefix("0x").unwrap_or(s))?)
+}
+
+/// Parse a module ID of the form `<address>::<name>`, e.g. `0x1::M`.
+pub fn parse_module_id(s: &str) -> Result<ModuleId> {
+    let (address, name) = s
+        .split_once("::")
+        .ok_or_else(|| anyhow!("Invalid module `{}`, expected `<address>::<name>`", s))?;
+    if name.contains("::") {
+        bail!("Invalid module `{}`, expected `<address>::<name>`", s)
+    }
+    Ok(ModuleId::new(
+        AccountAddress::from_hex_literal(address)?,
+        Identifier::new(name)?,
+    ))
+}
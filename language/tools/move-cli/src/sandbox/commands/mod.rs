@@ -3,14 +3,21 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod doctor;
+pub mod events;
 pub mod generate;
 pub mod publish;
+pub mod repl;
+pub mod replay;
 pub mod run;
+pub mod snapshot;
 pub mod test;
 pub mod view;
 
 pub use doctor::*;
+pub use events::*;
 pub use publish::*;
+pub use repl::*;
+pub use replay::*;
 pub use run::*;
 pub use test::*;
 pub use view::*;
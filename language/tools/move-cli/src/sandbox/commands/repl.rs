@@ -0,0 +1,654 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    sandbox::{
+        self,
+        utils::{
+            explain_execution_effects, explain_execution_error, get_gas_status,
+            maybe_commit_effects, on_disk_state_view::OnDiskStateView, PackageContext,
+        },
+    },
+    Move, NativeFunctionRecord,
+};
+use anyhow::{anyhow, bail, Result};
+use move_binary_format::errors::VMError;
+use move_command_line_common::{
+    address::NumericalAddress, env::get_bytecode_version_from_env, parser::NumberFormat,
+};
+use move_compiler::{
+    diagnostics::{codes::Severity, report_diagnostics_to_buffer, Diagnostics, FilesSourceText},
+    expansion::ast::Address,
+    naming::ast::{Type, TypeName_, Type_},
+    typing::ast::{FunctionBody_, Program, SequenceItem_},
+    Compiler, PASS_TYPING,
+};
+use move_core_types::{
+    account_address::AccountAddress,
+    effects::ChangeSet,
+    errmap::ErrorMapping,
+    identifier::{IdentStr, Identifier},
+    language_storage::ModuleId,
+    parser,
+    value::MoveValue,
+    vm_status::{AbortLocation, VMStatus},
+};
+use move_resource_viewer::MoveValueAnnotator;
+use move_symbol_pool::Symbol;
+use move_vm_runtime::move_vm::MoveVM;
+use move_vm_test_utils::gas_schedule::{CostTable, GasStatus};
+use move_vm_types::loaded_data::runtime_types::Type as RuntimeType;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::{self, BufRead, Write},
+    path::Path,
+};
+use tempfile::TempDir;
+
+/// Address of the module generated for each entry. The module only exists in the session that
+/// evaluates the entry and is never written to storage.
+const REPL_ADDRESS: AccountAddress = AccountAddress::ZERO;
+const REPL_MODULE_NAME: &str = "repl";
+const REPL_FUNCTION_NAME: &str = "run";
+
+const HELP: &str = "\
+Enter a Move expression or statement to evaluate it, `let <name> = <expression>` to keep its value
+for later entries, or `use <module>` to import a module in later entries. Values whose type does
+not have `copy` are moved into the entry that uses them.
+
+Commands:
+  :publish                        Build the package and publish its modules
+  :view <address> <struct tag>    Show the resource of type <struct tag> stored under <address>
+  :gas [<budget> | off]           Show or set the gas budget of each entry
+  :signer <name> <address>        Bind <name> to a signer for <address>
+  :rollback                       Undo the last entry that changed storage or bindings
+  :help                           Show this message
+  :quit                           Exit";
+
+/// A value kept between entries by `let`
+#[derive(Clone)]
+struct Binding {
+    /// The type of the value, as Move source
+    ty: String,
+    value: Vec<u8>,
+}
+
+/// What to restore to undo an entry
+struct Checkpoint {
+    /// A copy of storage before the entry, if the entry changed storage
+    storage: Option<TempDir>,
+    bindings: BTreeMap<String, Binding>,
+}
+
+struct Repl<'a> {
+    natives: Vec<NativeFunctionRecord>,
+    cost_table: &'a CostTable,
+    error_descriptions: &'a ErrorMapping,
+    move_args: &'a Move,
+    storage_dir: &'a Path,
+    state: OnDiskStateView,
+    named_addresses: BTreeMap<String, NumericalAddress>,
+    uses: Vec<String>,
+    bindings: BTreeMap<String, Binding>,
+    checkpoints: Vec<Checkpoint>,
+    gas_budget: Option<u64>,
+    /// Gas used by the last entry, if gas metering is enabled
+    gas_used: Option<u64>,
+    /// Holds the source of the generated modules and the interface files of the modules in storage
+    temp_dir: TempDir,
+}
+
+/// Read Move expressions and statements from stdin and evaluate them one by one against the
+/// modules and resources in `storage_dir`, until stdin is closed or `:quit` is entered.
+///
+/// Each entry is compiled as the body of a function in a module generated for it, which is
+/// published in the session evaluating the entry only. The effects of the entry are committed to
+/// `storage_dir` and its value is printed along with its type.
+pub fn repl(
+    natives: Vec<NativeFunctionRecord>,
+    cost_table: &CostTable,
+    error_descriptions: &ErrorMapping,
+    move_args: &Move,
+    storage_dir: &Path,
+) -> Result<()> {
+    let context = PackageContext::new(&move_args.package_path, &move_args.build_config)?;
+    let mut repl = Repl {
+        natives,
+        cost_table,
+        error_descriptions,
+        move_args,
+        storage_dir,
+        state: context.prepare_state(storage_dir)?,
+        named_addresses: named_addresses(&context),
+        uses: vec![],
+        bindings: BTreeMap::new(),
+        checkpoints: vec![],
+        gas_budget: None,
+        gas_used: None,
+        temp_dir: tempfile::tempdir()?,
+    };
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("> ");
+        io::stdout().flush()?;
+        let mut entry = match lines.next() {
+            Some(line) => line?,
+            None => break,
+        };
+        // keep reading lines until all brackets are closed
+        while !is_complete(&entry) {
+            print!(". ");
+            io::stdout().flush()?;
+            match lines.next() {
+                Some(line) => {
+                    entry.push('\n');
+                    entry.push_str(&line?)
+                }
+                None => break,
+            }
+        }
+        let entry = entry.trim();
+        if entry == ":quit" {
+            break;
+        }
+        if let Err(e) = repl.handle(entry) {
+            println!("Error: {:#}", e)
+        }
+    }
+    Ok(())
+}
+
+impl<'a> Repl<'a> {
+    fn handle(&mut self, entry: &str) -> Result<()> {
+        if entry.is_empty() {
+            return Ok(());
+        }
+        if let Some(command) = entry.strip_prefix(':') {
+            return self.command(&command.split_whitespace().collect::<Vec<_>>());
+        }
+        if entry.starts_with("use ") {
+            return self.add_use(entry);
+        }
+        match entry.strip_prefix("let ") {
+            Some(binding) => {
+                let (lhs, rhs) = binding
+                    .split_once('=')
+                    .filter(|(_, rhs)| !rhs.starts_with('='))
+                    .ok_or_else(|| anyhow!("Expected `let <name> = <expression>`"))?;
+                let (name, ty) = match lhs.split_once(':') {
+                    Some((name, ty)) => (name.trim(), Some(ty.trim())),
+                    None => (lhs.trim(), None),
+                };
+                if !Identifier::is_valid(name) {
+                    bail!("Only `let <name> = <expression>` bindings are supported")
+                }
+                self.evaluate(rhs.trim().trim_end_matches(';'), Some((name, ty)))
+            }
+            None => self.evaluate(entry, None),
+        }
+    }
+
+    fn command(&mut self, args: &[&str]) -> Result<()> {
+        match args {
+            ["help"] => println!("{}", HELP),
+            ["publish"] => self.publish()?,
+            ["view", address, tag] => self.view(address, tag)?,
+            ["gas"] => match (self.gas_budget, self.gas_used) {
+                (None, _) => {
+                    println!("Gas metering is disabled. Set a budget with `:gas <budget>`.")
+                }
+                (Some(budget), used) => {
+                    println!("Gas budget: {}", budget);
+                    if let Some(used) = used {
+                        println!("Gas used by the last entry: {}", used)
+                    }
+                }
+            },
+            ["gas", "off"] => {
+                self.gas_budget = None;
+                self.gas_used = None;
+            }
+            ["gas", budget] => {
+                let budget = budget
+                    .parse::<u64>()
+                    .map_err(|_| anyhow!("Invalid gas budget `{}`", budget))?;
+                // fail now rather than on the next entry if the budget is too high
+                get_gas_status(self.cost_table, Some(budget))?;
+                self.gas_budget = Some(budget);
+                self.gas_used = None;
+            }
+            ["signer", name, address] => {
+                if !Identifier::is_valid(name) {
+                    bail!("Invalid name `{}`", name)
+                }
+                let address = parse_address(address)?;
+                self.checkpoint(false)?;
+                self.bindings.insert(
+                    name.to_string(),
+                    Binding {
+                        ty: "signer".to_string(),
+                        value: MoveValue::Signer(address)
+                            .simple_serialize()
+                            .expect("signers must serialize"),
+                    },
+                );
+                println!("{}: signer = {}", name, address.to_hex_literal())
+            }
+            ["rollback"] => match self.checkpoints.pop() {
+                Some(Checkpoint { storage, bindings }) => {
+                    if let Some(storage) = storage {
+                        self.state.restore_copy(storage.path())?
+                    }
+                    self.bindings = bindings;
+                    println!("Rolled back the last change")
+                }
+                None => println!("Nothing to roll back"),
+            },
+            _ => bail!(
+                "Unknown command `:{}`. Enter `:help` for the list of commands",
+                args.join(" ")
+            ),
+        }
+        Ok(())
+    }
+
+    /// Build the package and publish its modules
+    fn publish(&mut self) -> Result<()> {
+        let context =
+            PackageContext::new(&self.move_args.package_path, &self.move_args.build_config)?;
+        self.state = context.prepare_state(self.storage_dir)?;
+        self.named_addresses = named_addresses(&context);
+        self.checkpoint(true)?;
+        sandbox::commands::publish(
+            self.natives.clone(),
+            self.cost_table,
+            &self.state,
+            context.package(),
+            false,
+            false,
+            false,
+            false,
+            None,
+            self.move_args.verbose,
+        )
+    }
+
+    fn view(&self, address: &str, tag: &str) -> Result<()> {
+        let address = parse_address(address)?;
+        let tag = parser::parse_struct_tag(tag)?;
+        match self.state.get_resource_bytes(address, tag.clone())? {
+            Some(bytes) => println!(
+                "{}",
+                MoveValueAnnotator::new(&self.state).view_resource(&tag, &bytes)?
+            ),
+            None => println!(
+                "No resource of type {} is stored under {}",
+                tag,
+                address.to_hex_literal()
+            ),
+        }
+        Ok(())
+    }
+
+    /// Import a module in all later entries
+    fn add_use(&mut self, entry: &str) -> Result<()> {
+        self.uses
+            .push(format!("{};", entry.trim_end_matches(';').trim_end()));
+        if let Err(e) = self.typecheck(&self.module_source("", "", "")) {
+            self.uses.pop();
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Evaluate `code` and print its value, or bind the value to `binding` (a name and an optional
+    /// type annotation).
+    fn evaluate(&mut self, code: &str, binding: Option<(&str, Option<&str>)>) -> Result<()> {
+        let params = self.referenced_bindings(code);
+        let param_list = params
+            .iter()
+            .map(|name| format!("{}: {}", name, self.bindings[name].ty))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        // find out the type of the entry, which is the return type of the generated function
+        let probe = match binding.and_then(|(_, ty)| ty) {
+            Some(ty) => format!("({{\n{}\n}}: {});", code, ty),
+            None => format!("{{\n{}\n}};", code),
+        };
+        let result_ty = self.result_type(&self.module_source(&param_list, "", &probe))?;
+        let return_ty = if result_ty == "()" {
+            String::new()
+        } else {
+            format!(": {}", result_ty)
+        };
+        let module = self.build(&self.module_source(&param_list, &return_ty, code))?;
+
+        let module_id = ModuleId::new(REPL_ADDRESS, Identifier::new(REPL_MODULE_NAME)?);
+        let function = IdentStr::new(REPL_FUNCTION_NAME)?;
+        let vm = MoveVM::new(self.natives.clone()).unwrap();
+        let mut session = vm.new_session(&self.state);
+        session
+            .publish_module(module, REPL_ADDRESS, &mut GasStatus::new_unmetered())
+            .map_err(|e| anyhow!("Unable to load the entry: {}", e.into_vm_status()))?;
+        let instantiation = session
+            .load_function(&module_id, function, &[])
+            .map_err(|e| anyhow!("Unable to load the entry: {}", e.into_vm_status()))?;
+        let return_tags = instantiation
+            .return_
+            .iter()
+            .map(|ty| session.get_type_tag(ty))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| anyhow!("Unable to load the entry: {}", e.into_vm_status()))?;
+        if binding.is_some() && return_tags.len() != 1 {
+            bail!("Cannot bind a value of type {}", result_ty)
+        }
+        // bindings passed by value are moved into the entry, unless they can be copied. Signers
+        // are kept so that they can be used by any number of entries.
+        let mut moved = vec![];
+        for (name, ty) in params.iter().zip(&instantiation.parameters) {
+            let abilities = session
+                .get_type_abilities(ty)
+                .map_err(|e| anyhow!("Unable to load the entry: {}", e.into_vm_status()))?;
+            if !abilities.has_copy() && !matches!(ty, RuntimeType::Signer) {
+                moved.push(name.clone())
+            }
+        }
+
+        let args = params
+            .iter()
+            .map(|name| self.bindings[name].value.clone())
+            .collect::<Vec<_>>();
+        let mut gas_status = get_gas_status(self.cost_table, self.gas_budget)?;
+        let res = session.execute_function_bypass_visibility(
+            &module_id,
+            function,
+            vec![],
+            args,
+            &mut gas_status,
+        );
+        self.gas_used = self
+            .gas_budget
+            .map(|budget| budget - u64::from(gas_status.remaining_gas()));
+        let return_values = match res {
+            Ok(values) => values.return_values,
+            Err(err) => return self.explain_error(err, &module_id),
+        };
+        let (changeset, events) = session.finish().map_err(|e| e.into_vm_status())?;
+        let changeset = without_modules(changeset)?;
+
+        if self.move_args.verbose {
+            explain_execution_effects(&changeset, &events, &self.state)?
+        }
+        let changes_storage = changeset.resources().next().is_some() || !events.is_empty();
+        if changes_storage || binding.is_some() || !moved.is_empty() {
+            self.checkpoint(changes_storage)?
+        }
+        maybe_commit_effects(true, changeset, events, &self.state)?;
+        for name in moved {
+            self.bindings.remove(&name);
+        }
+
+        let annotator = MoveValueAnnotator::new(&self.state);
+        let values = return_tags
+            .iter()
+            .zip(&return_values)
+            .map(|(tag, (value, _))| Ok(annotator.view_value(tag, value)?.to_string()))
+            .collect::<Result<Vec<_>>>()?;
+        match binding {
+            Some((name, _)) => {
+                println!("{}: {} = {}", name, return_tags[0], values[0]);
+                self.bindings.insert(
+                    name.to_string(),
+                    Binding {
+                        ty: result_ty,
+                        value: return_values[0].0.clone(),
+                    },
+                );
+            }
+            None => match values.as_slice() {
+                [] => (),
+                [value] => println!("_: {} = {}", return_tags[0], value),
+                _ => println!(
+                    "_: ({}) = ({})",
+                    return_tags
+                        .iter()
+                        .map(|tag| tag.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    values.join(", ")
+                ),
+            },
+        }
+        Ok(())
+    }
+
+    /// Print why an entry failed. Failures in the generated module are reported without a location
+    /// since the module is not in storage.
+    fn explain_error(&self, error: VMError, module_id: &ModuleId) -> Result<()> {
+        match error.clone().into_vm_status() {
+            VMStatus::MoveAbort(AbortLocation::Module(id), abort_code) if &id == module_id => {
+                println!("Execution aborted with code {}", abort_code)
+            }
+            VMStatus::ExecutionFailure {
+                status_code,
+                location: AbortLocation::Module(id),
+                ..
+            } if &id == module_id => println!("Execution failed with {:?}", status_code),
+            _ => explain_execution_error(
+                self.error_descriptions,
+                error,
+                &self.state,
+                &[],
+                &[],
+                &[],
+                &[],
+                &[],
+            )?,
+        }
+        Ok(())
+    }
+
+    /// Save what is needed to undo the next change
+    fn checkpoint(&mut self, save_storage: bool) -> Result<()> {
+        let storage = if save_storage {
+            let dir = tempfile::tempdir()?;
+            self.state.save_copy(dir.path())?;
+            Some(dir)
+        } else {
+            None
+        };
+        self.checkpoints.push(Checkpoint {
+            storage,
+            bindings: self.bindings.clone(),
+        });
+        Ok(())
+    }
+
+    /// The names of the bindings that `code` refers to
+    fn referenced_bindings(&self, code: &str) -> Vec<String> {
+        let idents = code
+            .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .collect::<BTreeSet<_>>();
+        self.bindings
+            .keys()
+            .filter(|name| idents.contains(name.as_str()))
+            .cloned()
+            .collect()
+    }
+
+    fn module_source(&self, params: &str, return_ty: &str, body: &str) -> String {
+        format!(
+            "module {}::{} {{\n{}\npublic fun {}({}){} {{\n{}\n}}\n}}\n",
+            REPL_ADDRESS.to_hex_literal(),
+            REPL_MODULE_NAME,
+            self.uses.join("\n"),
+            REPL_FUNCTION_NAME,
+            params,
+            return_ty,
+            body
+        )
+    }
+
+    /// The type of the first statement of the function of module `source`, as Move source
+    fn result_type(&self, source: &str) -> Result<String> {
+        let program = self.typecheck(source)?;
+        let function = program
+            .modules
+            .key_cloned_iter()
+            .find(|(_, module)| module.is_source_module)
+            .and_then(|(_, module)| {
+                module
+                    .functions
+                    .get_(&Symbol::from(REPL_FUNCTION_NAME))
+                    .cloned()
+            })
+            .ok_or_else(|| anyhow!("Unable to find the function generated for the entry"))?;
+        let ty = match &function.body.value {
+            FunctionBody_::Defined(seq) => seq.iter().find_map(|item| match &item.value {
+                SequenceItem_::Seq(exp) => Some(exp.ty.clone()),
+                _ => None,
+            }),
+            FunctionBody_::Native => None,
+        };
+        type_source(&ty.ok_or_else(|| anyhow!("Unable to infer the type of the entry"))?)
+    }
+
+    fn typecheck(&self, source: &str) -> Result<Program> {
+        let (files, res) = self.compiler(source)?.run::<PASS_TYPING>()?;
+        match res {
+            Ok((_, compiler)) => Ok(compiler.into_ast().1),
+            Err(diags) => Err(diagnostics_error(&files, diags)),
+        }
+    }
+
+    /// Compile module `source` and return its bytecode
+    fn build(&self, source: &str) -> Result<Vec<u8>> {
+        let (files, res) = self.compiler(source)?.build()?;
+        let (mut units, _warnings) = res.map_err(|diags| diagnostics_error(&files, diags))?;
+        match units.pop() {
+            Some(unit) => Ok(unit
+                .into_compiled_unit()
+                .serialize(get_bytecode_version_from_env())),
+            None => bail!("Unable to compile the entry"),
+        }
+    }
+
+    /// A compiler for module `source` that resolves dependencies against the modules in storage
+    fn compiler(&self, source: &str) -> Result<Compiler<'static>> {
+        let path = self.temp_dir.path().join("repl.move");
+        fs::write(&path, source)?;
+        Ok(Compiler::from_files(
+            vec![path.to_string_lossy().to_string()],
+            vec![self.state.storage_dir().to_string_lossy().to_string()],
+            self.named_addresses.clone(),
+        )
+        .set_interface_files_dir(self.temp_dir.path().to_string_lossy().to_string()))
+    }
+}
+
+fn named_addresses(context: &PackageContext) -> BTreeMap<String, NumericalAddress> {
+    context
+        .package()
+        .compiled_package_info
+        .address_alias_instantiation
+        .iter()
+        .map(|(name, addr)| {
+            (
+                name.to_string(),
+                NumericalAddress::new(addr.into_bytes(), NumberFormat::Hex),
+            )
+        })
+        .collect()
+}
+
+fn parse_address(address: &str) -> Result<AccountAddress> {
+    AccountAddress::from_hex_literal(address).map_err(|_| anyhow!("Invalid address `{}`", address))
+}
+
+/// An error reporting the errors in `diags`. Warnings are left out, as most of them are about
+/// unused `use` declarations, which are added to every entry.
+fn diagnostics_error(files: &FilesSourceText, diags: Diagnostics) -> anyhow::Error {
+    let errors = diags
+        .into_vec()
+        .into_iter()
+        .filter(|diag| diag.info().severity() > Severity::Warning)
+        .collect();
+    anyhow!(
+        String::from_utf8_lossy(&report_diagnostics_to_buffer(files, errors))
+            .trim_end()
+            .to_string()
+    )
+}
+
+/// `changeset` without the publishing of the generated module
+fn without_modules(changeset: ChangeSet) -> Result<ChangeSet> {
+    let mut resources = ChangeSet::new();
+    for (addr, account) in changeset.into_inner() {
+        for (struct_tag, op) in account.into_resources() {
+            resources.add_resource_op(addr, struct_tag, op)?;
+        }
+    }
+    Ok(resources)
+}
+
+/// `ty` as Move source
+fn type_source(ty: &Type) -> Result<String> {
+    let type_list = |tys: &[Type]| -> Result<String> {
+        Ok(tys
+            .iter()
+            .map(type_source)
+            .collect::<Result<Vec<_>>>()?
+            .join(", "))
+    };
+    Ok(match &ty.value {
+        // `Anything` is the type of entries that always abort
+        Type_::Unit | Type_::Anything => "()".to_string(),
+        Type_::Apply(_, name, tys) => match &name.value {
+            TypeName_::Multiple(_) => format!("({})", type_list(tys)?),
+            TypeName_::Builtin(builtin) if tys.is_empty() => builtin.value.to_string(),
+            TypeName_::Builtin(builtin) => format!("{}<{}>", builtin.value, type_list(tys)?),
+            TypeName_::ModuleType(module, struct_name) => {
+                let address = match &module.value.address {
+                    Address::Numerical(_, address) => address.value.into_inner().to_hex_literal(),
+                    Address::NamedUnassigned(name) => name.to_string(),
+                };
+                let name = format!("{}::{}::{}", address, module.value.module, struct_name);
+                if tys.is_empty() {
+                    name
+                } else {
+                    format!("{}<{}>", name, type_list(tys)?)
+                }
+            }
+        },
+        Type_::Ref(_, _) => bail!(
+            "The value of an entry cannot be a reference. Dereference it with `*` or copy it \
+             into a local"
+        ),
+        Type_::Param(_) | Type_::Var(_) | Type_::UnresolvedError => {
+            bail!("Unable to infer the type of the entry")
+        }
+    })
+}
+
+/// Whether every bracket opened in `entry` is closed. Brackets in string literals are ignored.
+fn is_complete(entry: &str) -> bool {
+    let mut depth = 0i64;
+    let mut in_string = false;
+    let mut chars = entry.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if in_string => {
+                chars.next();
+            }
+            '"' => in_string = !in_string,
+            '(' | '{' | '[' if !in_string => depth += 1,
+            ')' | '}' | ']' if !in_string => depth -= 1,
+            _ => (),
+        }
+    }
+    depth <= 0 && !in_string
+}
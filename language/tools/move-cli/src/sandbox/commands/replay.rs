@@ -0,0 +1,95 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    sandbox::{
+        commands::run::execute,
+        utils::{
+            maybe_commit_effects,
+            on_disk_state_view::OnDiskStateView,
+            transaction_log::{effects_hash, TransactionRecord, TransactionTarget},
+        },
+    },
+    NativeFunctionRecord, DEFAULT_STORAGE_DIR,
+};
+use anyhow::{anyhow, bail, Result};
+use move_core_types::{parser, resolver::ModuleResolver};
+use move_vm_runtime::move_vm::MoveVM;
+use move_vm_test_utils::gas_schedule::CostTable;
+use std::path::Path;
+
+/// Re-execute the transactions recorded in the log at `log_path`, starting from snapshot
+/// `snapshot`, and check that each of them produces the recorded effects. The transactions are
+/// executed against a temporary copy of the snapshot, so neither the snapshot nor the current
+/// state are modified.
+pub fn replay(
+    natives: impl IntoIterator<Item = NativeFunctionRecord>,
+    cost_table: &CostTable,
+    state: &OnDiskStateView,
+    log_path: &Path,
+    snapshot: &str,
+) -> Result<()> {
+    let records = TransactionRecord::read_log(log_path)?;
+    let temp_dir = tempfile::tempdir()?;
+    let replay_state = state.copy_snapshot(snapshot, &temp_dir.path().join(DEFAULT_STORAGE_DIR))?;
+    let vm = MoveVM::new(natives).unwrap();
+
+    let mut mismatches = 0;
+    for (i, record) in records.iter().enumerate() {
+        let (bytecode, script_name_opt) = match &record.target {
+            TransactionTarget::EntryFunction { module, function } => (
+                replay_state
+                    .get_module(module)?
+                    .ok_or_else(|| anyhow!("Module {} does not exist", record.target))?,
+                Some(function.to_string()),
+            ),
+            TransactionTarget::Script { bytecode } => (hex::decode(bytecode)?, None),
+        };
+        let type_args = record
+            .type_args
+            .iter()
+            .map(|ty| parser::parse_type_tag(ty))
+            .collect::<Result<Vec<_>>>()?;
+        let res = execute(
+            &vm,
+            cost_table,
+            &replay_state,
+            &bytecode,
+            &script_name_opt,
+            &record.signers,
+            &record.args,
+            type_args,
+            record.gas_budget,
+        )?;
+
+        print!("Transaction {} ({}): ", i + 1, record.target);
+        match res {
+            Err(err) => {
+                mismatches += 1;
+                println!("failed with {}", err.into_vm_status())
+            }
+            Ok((changeset, events)) => {
+                let hash = effects_hash(&changeset, &events)?;
+                if hash == record.change_set_hash {
+                    println!("ok")
+                } else {
+                    mismatches += 1;
+                    println!(
+                        "produced different effects (expected change set hash {}, found {})",
+                        record.change_set_hash, hash
+                    )
+                }
+                maybe_commit_effects(true, changeset, events, &replay_state)?
+            }
+        }
+    }
+
+    if mismatches > 0 {
+        bail!(
+            "{} of {} transaction(s) could not be reproduced",
+            mismatches,
+            records.len()
+        )
+    }
+    Ok(())
+}
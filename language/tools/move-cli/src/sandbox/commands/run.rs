@@ -5,26 +5,35 @@
 use crate::{
     sandbox::utils::{
         contains_module, explain_execution_effects, explain_execution_error, get_gas_status,
-        is_bytecode_file, maybe_commit_effects, on_disk_state_view::OnDiskStateView,
+        is_bytecode_file, maybe_commit_effects,
+        on_disk_state_view::OnDiskStateView,
+        transaction_log::{effects_hash, TransactionRecord, TransactionTarget},
     },
     NativeFunctionRecord,
 };
 use anyhow::{anyhow, bail, Result};
-use move_binary_format::file_format::CompiledModule;
+use move_binary_format::{errors::VMResult, file_format::CompiledModule};
 use move_command_line_common::env::get_bytecode_version_from_env;
 use move_core_types::{
     account_address::AccountAddress,
+    effects::{ChangeSet, Event},
     errmap::ErrorMapping,
-    identifier::IdentStr,
+    identifier::{IdentStr, Identifier},
     language_storage::TypeTag,
+    parser,
     transaction_argument::{convert_txn_args, TransactionArgument},
     value::MoveValue,
 };
 use move_package::compilation::compiled_package::CompiledPackage;
-use move_vm_runtime::move_vm::MoveVM;
+use move_vm_runtime::{
+    move_vm::MoveVM,
+    session::{LoadedFunctionInstantiation, Session},
+};
 use move_vm_test_utils::gas_schedule::CostTable;
+use move_vm_types::loaded_data::runtime_types::Type;
 use std::{fs, path::Path};
 
+#[allow(clippy::too_many_arguments)]
 pub fn run(
     natives: impl IntoIterator<Item = NativeFunctionRecord>,
     cost_table: &CostTable,
@@ -34,10 +43,11 @@ pub fn run(
     script_path: &Path,
     script_name_opt: &Option<String>,
     signers: &[String],
-    txn_args: &[TransactionArgument],
+    txn_args: &[String],
     vm_type_args: Vec<TypeTag>,
     gas_budget: Option<u64>,
     dry_run: bool,
+    record: bool,
     verbose: bool,
 ) -> Result<()> {
     if !script_path.exists() {
@@ -45,6 +55,7 @@ pub fn run(
     };
     let bytecode_version = get_bytecode_version_from_env();
 
+    let mut symbolizer = package.stack_trace_symbolizer();
     let bytecode = if is_bytecode_file(script_path) {
         assert!(
             state.is_module_path(script_path) || !contains_module(script_path),
@@ -62,7 +73,10 @@ move run` must be applied to a module inside `storage/`",
             .find(|unit| unit.unit.source_map().check(&file_contents));
         // script source file; package is already compiled so load it up
         match script_opt {
-            Some(unit) => unit.unit.serialize(bytecode_version),
+            Some(unit) => {
+                symbolizer.set_script(unit.unit.name().as_str(), unit.unit.source_map());
+                unit.unit.serialize(bytecode_version)
+            }
             None => bail!("Unable to find script in file {:?}", script_path),
         }
     };
@@ -71,17 +85,90 @@ move run` must be applied to a module inside `storage/`",
         .iter()
         .map(|s| AccountAddress::from_hex_literal(s))
         .collect::<Result<Vec<AccountAddress>, _>>()?;
-    // TODO: parse Value's directly instead of going through the indirection of TransactionArgument?
-    let vm_args: Vec<Vec<u8>> = convert_txn_args(txn_args);
 
     let vm = MoveVM::new(natives).unwrap();
-    let mut gas_status = get_gas_status(cost_table, gas_budget)?;
-    let mut session = vm.new_session(state);
+    let res = execute(
+        &vm,
+        cost_table,
+        state,
+        &bytecode,
+        script_name_opt,
+        &signer_addresses,
+        txn_args,
+        vm_type_args.clone(),
+        gas_budget,
+    )?;
 
     let script_type_parameters = vec![];
     let script_parameters = vec![];
+    match res {
+        Err(err) => {
+            let stack_trace = symbolizer.symbolize_error(&err);
+            explain_execution_error(
+                error_descriptions,
+                err,
+                state,
+                &script_type_parameters,
+                &script_parameters,
+                &vm_type_args,
+                &signer_addresses,
+                txn_args,
+            )?;
+            if !stack_trace.is_empty() {
+                print!("Stack trace:\n{}", stack_trace)
+            }
+            Ok(())
+        }
+        Ok((changeset, events)) => {
+            if verbose {
+                explain_execution_effects(&changeset, &events, state)?
+            }
+            if record && !dry_run {
+                let target = match script_name_opt {
+                    Some(script_name) => TransactionTarget::EntryFunction {
+                        module: CompiledModule::deserialize(&bytecode)
+                            .map_err(|e| anyhow!("Error deserializing module: {:?}", e))?
+                            .self_id(),
+                        function: Identifier::new(script_name.as_str())?,
+                    },
+                    None => TransactionTarget::Script {
+                        bytecode: hex::encode(&bytecode),
+                    },
+                };
+                TransactionRecord {
+                    target,
+                    signers: signer_addresses,
+                    args: txn_args.to_vec(),
+                    type_args: vm_type_args.iter().map(|ty| ty.to_string()).collect(),
+                    gas_budget,
+                    change_set_hash: effects_hash(&changeset, &events)?,
+                }
+                .append_to(&state.transaction_log_path())?
+            }
+            maybe_commit_effects(!dry_run, changeset, events, state)
+        }
+    }
+}
+
+/// Execute the script `bytecode`, or the entry function `script_name_opt` of the module
+/// `bytecode`, returning its effects or the error raised by the VM.
+pub(crate) fn execute(
+    vm: &MoveVM,
+    cost_table: &CostTable,
+    state: &OnDiskStateView,
+    bytecode: &[u8],
+    script_name_opt: &Option<String>,
+    signers: &[AccountAddress],
+    txn_args: &[String],
+    vm_type_args: Vec<TypeTag>,
+    gas_budget: Option<u64>,
+) -> Result<VMResult<(ChangeSet, Vec<Event>)>> {
+    let mut gas_status = get_gas_status(cost_table, gas_budget)?;
+    let mut session = vm.new_session(state);
+    let vm_args = serialize_args(&session, bytecode, script_name_opt, &vm_type_args, txn_args)?;
+
     // TODO rethink move-cli arguments for executing functions
-    let vm_args = signer_addresses
+    let vm_args = signers
         .iter()
         .map(|a| {
             MoveValue::Signer(*a)
@@ -93,40 +180,99 @@ move run` must be applied to a module inside `storage/`",
     let res = match script_name_opt {
         Some(script_name) => {
             // script fun. parse module, extract script ID to pass to VM
-            let module = CompiledModule::deserialize(&bytecode)
+            let module = CompiledModule::deserialize(bytecode)
                 .map_err(|e| anyhow!("Error deserializing module: {:?}", e))?;
             session.execute_entry_function(
                 &module.self_id(),
                 IdentStr::new(script_name)?,
-                vm_type_args.clone(),
+                vm_type_args,
                 vm_args,
                 &mut gas_status,
             )
         }
-        None => session.execute_script(
-            bytecode.to_vec(),
-            vm_type_args.clone(),
-            vm_args,
-            &mut gas_status,
-        ),
+        None => session.execute_script(bytecode.to_vec(), vm_type_args, vm_args, &mut gas_status),
     };
 
-    if let Err(err) = res {
-        explain_execution_error(
-            error_descriptions,
-            err,
-            state,
-            &script_type_parameters,
-            &script_parameters,
-            &vm_type_args,
-            &signer_addresses,
-            txn_args,
-        )
-    } else {
-        let (changeset, events) = session.finish().map_err(|e| e.into_vm_status())?;
-        if verbose {
-            explain_execution_effects(&changeset, &events, state)?
+    Ok(match res {
+        Err(err) => Err(err),
+        Ok(_) => Ok(session.finish().map_err(|e| e.into_vm_status())?),
+    })
+}
+
+/// Parse and serialize `txn_args` according to the types of the non-signer parameters of the
+/// function being run. If the function cannot be loaded, the arguments are parsed as plain
+/// transaction arguments instead and the error is left to the VM to report.
+fn serialize_args(
+    session: &Session<OnDiskStateView>,
+    bytecode: &[u8],
+    script_name_opt: &Option<String>,
+    vm_type_args: &[TypeTag],
+    txn_args: &[String],
+) -> Result<Vec<Vec<u8>>> {
+    let instantiation = match script_name_opt {
+        Some(script_name) => CompiledModule::deserialize(bytecode)
+            .ok()
+            .and_then(|module| {
+                session
+                    .load_function(
+                        &module.self_id(),
+                        IdentStr::new(script_name).ok()?,
+                        vm_type_args,
+                    )
+                    .ok()
+            }),
+        None => session.load_script(bytecode, vm_type_args.to_vec()).ok(),
+    };
+    let (type_arguments, params) = match instantiation {
+        Some(LoadedFunctionInstantiation {
+            type_arguments,
+            parameters,
+            ..
+        }) => (
+            type_arguments,
+            parameters
+                .into_iter()
+                .filter(|ty| !is_signer(ty))
+                .collect::<Vec<_>>(),
+        ),
+        None => {
+            let txn_args = txn_args
+                .iter()
+                .map(|arg| parser::parse_transaction_argument(arg))
+                .collect::<Result<Vec<TransactionArgument>>>()?;
+            return Ok(convert_txn_args(&txn_args));
         }
-        maybe_commit_effects(!dry_run, changeset, events, state)
+    };
+    if params.len() != txn_args.len() {
+        bail!(
+            "Incorrect number of arguments: function expected {}, but found {}",
+            params.len(),
+            txn_args.len()
+        )
+    }
+
+    params
+        .iter()
+        .zip(txn_args)
+        .map(|(ty, arg)| {
+            let ty = ty
+                .subst(&type_arguments)
+                .map_err(|e| anyhow!("Unable to instantiate argument type: {}", e))?;
+            let tag = session.get_type_tag(&ty)?;
+            let layout = session.get_fully_annotated_type_layout(&tag)?;
+            let value = parser::parse_value(arg, &layout)
+                .map_err(|e| anyhow!("Invalid argument {} of type {}: {}", arg, tag, e))?;
+            value
+                .simple_serialize()
+                .ok_or_else(|| anyhow!("Unable to serialize argument {}", arg))
+        })
+        .collect()
+}
+
+fn is_signer(ty: &Type) -> bool {
+    match ty {
+        Type::Signer => true,
+        Type::Reference(ty) => matches!(ty.as_ref(), Type::Signer),
+        _ => false,
     }
 }
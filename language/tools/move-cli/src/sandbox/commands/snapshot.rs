@@ -0,0 +1,234 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::sandbox::utils::{
+    on_disk_state_view::OnDiskStateView, print_struct_diff_with_indent, print_struct_with_indent,
+};
+use anyhow::Result;
+use move_core_types::{account_address::AccountAddress, parser};
+use move_resource_viewer::AnnotatedMoveValue;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Save the current state of `storage-dir` as snapshot `name`
+pub fn save(state: &OnDiskStateView, name: &str, overwrite: bool) -> Result<()> {
+    state.save_snapshot(name, overwrite)?;
+    println!("Saved snapshot `{}`", name);
+    Ok(())
+}
+
+/// Replace the contents of `storage-dir` with snapshot `name`
+pub fn restore(state: &OnDiskStateView, name: &str) -> Result<()> {
+    state.restore_snapshot(name)?;
+    println!("Restored snapshot `{}`", name);
+    Ok(())
+}
+
+/// List the saved snapshots with a summary of their contents
+pub fn list(state: &OnDiskStateView) -> Result<()> {
+    let names = state.snapshot_names()?;
+    if names.is_empty() {
+        println!("No snapshots.");
+    }
+    for name in names {
+        let snapshot = state.snapshot(&name)?;
+        println!(
+            "{}: {} module(s), {} resource(s), {} event stream(s)",
+            name,
+            snapshot.module_paths().count(),
+            snapshot.resource_paths().count(),
+            snapshot.event_paths().count()
+        );
+    }
+    Ok(())
+}
+
+/// Print the modules, resources and events added, changed or deleted between snapshot `name` and
+/// snapshot `other`, or the current state if `other` is not set
+pub fn diff(state: &OnDiskStateView, name: &str, other: Option<&str>) -> Result<()> {
+    let before = state.snapshot(name)?;
+    let other_snapshot;
+    let after = match other {
+        Some(other) => {
+            other_snapshot = state.snapshot(other)?;
+            println!("Changes from snapshot `{}` to snapshot `{}`:", name, other);
+            &other_snapshot
+        }
+        None => {
+            println!("Changes from snapshot `{}` to the current state:", name);
+            state
+        }
+    };
+
+    let mut unchanged = true;
+    let module_changes = changes(
+        data_files(&before, before.module_paths())?,
+        data_files(after, after.module_paths())?,
+    );
+    if !module_changes.is_empty() {
+        unchanged = false;
+        println!("Modules:");
+        for (path, change) in module_changes {
+            println!("  {} {}", change.symbol(), module_name(&path));
+        }
+    }
+
+    let resource_changes = changes(
+        data_files(&before, before.resource_paths())?,
+        data_files(after, after.resource_paths())?,
+    );
+    if !resource_changes.is_empty() {
+        unchanged = false;
+        println!("Resources:");
+        for (path, change) in resource_changes {
+            println!("  {} {}", change.symbol(), resource_name(&path));
+            let old = before.storage_dir().join(&path);
+            let new = after.storage_dir().join(&path);
+            match change {
+                Change::Added => print_struct_with_indent(&after.view_resource(&new)?.unwrap(), 6),
+                Change::Deleted => {
+                    print_struct_with_indent(&before.view_resource(&old)?.unwrap(), 6)
+                }
+                Change::Changed => print_struct_diff_with_indent(
+                    &before.view_resource(&old)?.unwrap(),
+                    &after.view_resource(&new)?.unwrap(),
+                    6,
+                ),
+            }
+        }
+    }
+
+    let event_changes = changes(
+        data_files(&before, before.event_paths())?,
+        data_files(after, after.event_paths())?,
+    );
+    if !event_changes.is_empty() {
+        unchanged = false;
+        println!("Events:");
+        for (path, change) in event_changes {
+            println!("  {} {}", change.symbol(), event_stream_name(&path));
+            let old_events = match change {
+                Change::Added => vec![],
+                _ => before.view_events(&before.storage_dir().join(&path))?,
+            };
+            let new_events = match change {
+                Change::Deleted => vec![],
+                _ => after.view_events(&after.storage_dir().join(&path))?,
+            };
+            // Event streams are append-only, so only print the events that differ
+            let common = old_events
+                .iter()
+                .zip(&new_events)
+                .take_while(|(old, new)| old.to_string() == new.to_string())
+                .count();
+            for event in &old_events[common..] {
+                print_event("-", event);
+            }
+            for event in &new_events[common..] {
+                print_event("+", event);
+            }
+        }
+    }
+
+    if unchanged {
+        println!("No changes.");
+    }
+    Ok(())
+}
+
+enum Change {
+    Added,
+    Changed,
+    Deleted,
+}
+
+impl Change {
+    fn symbol(&self) -> &'static str {
+        match self {
+            Change::Added => "+",
+            Change::Changed => "~",
+            Change::Deleted => "-",
+        }
+    }
+}
+
+/// The contents of the files at `paths`, indexed by their path relative to the storage
+/// directory of `state`
+fn data_files(
+    state: &OnDiskStateView,
+    paths: impl Iterator<Item = PathBuf>,
+) -> Result<BTreeMap<PathBuf, Vec<u8>>> {
+    let mut files = BTreeMap::new();
+    for path in paths {
+        let contents = fs::read(&path)?;
+        files.insert(
+            path.strip_prefix(state.storage_dir())?.to_path_buf(),
+            contents,
+        );
+    }
+    Ok(files)
+}
+
+fn changes(
+    before: BTreeMap<PathBuf, Vec<u8>>,
+    mut after: BTreeMap<PathBuf, Vec<u8>>,
+) -> BTreeMap<PathBuf, Change> {
+    let mut changes = BTreeMap::new();
+    for (path, old) in before {
+        match after.remove(&path) {
+            None => {
+                changes.insert(path, Change::Deleted);
+            }
+            Some(new) if new != old => {
+                changes.insert(path, Change::Changed);
+            }
+            Some(_) => (),
+        }
+    }
+    for path in after.into_keys() {
+        changes.insert(path, Change::Added);
+    }
+    changes
+}
+
+/// The address of the data stored at `path`, in the `<address>/<kind>/<file>` layout of the
+/// storage directory
+fn address(path: &Path) -> String {
+    let dir = path
+        .components()
+        .next()
+        .unwrap()
+        .as_os_str()
+        .to_string_lossy();
+    match AccountAddress::from_hex_literal(&dir) {
+        Ok(address) => format!("0x{}", address.short_str_lossless()),
+        Err(_) => dir.to_string(),
+    }
+}
+
+fn print_event(symbol: &str, event: &AnnotatedMoveValue) {
+    for line in event.to_string().lines() {
+        println!("{}      {}", symbol, line);
+    }
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem().unwrap().to_string_lossy().to_string()
+}
+
+fn module_name(path: &Path) -> String {
+    format!("{}::{}", address(path), file_stem(path))
+}
+
+fn resource_name(path: &Path) -> String {
+    let stem = file_stem(path);
+    let type_ = parser::parse_type_tag(&stem).map_or(stem, |tag| tag.to_string());
+    format!("{} at {}", type_, address(path))
+}
+
+fn event_stream_name(path: &Path) -> String {
+    format!("event stream {} at {}", file_stem(path), address(path))
+}
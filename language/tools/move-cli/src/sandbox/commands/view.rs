@@ -7,23 +7,35 @@ use crate::sandbox::utils::{
 };
 
 use anyhow::{bail, Result};
+use move_core_types::{account_address::AccountAddress, language_storage::StructTag, parser};
+use move_resource_viewer::json::JsonStruct;
 use std::path::Path;
-/// Print a module or resource stored in `file`
-pub fn view(state: &OnDiskStateView, path: &Path) -> Result<()> {
+/// Print a module or resource stored in `file`. Resources and events are printed as JSON if
+/// `json` is set.
+pub fn view(state: &OnDiskStateView, path: &Path, json: bool) -> Result<()> {
     if state.is_resource_path(path) {
         match state.view_resource(path)? {
+            Some(resource) if json => {
+                println!("{}", serde_json::to_string_pretty(&resource.json())?)
+            }
             Some(resource) => println!("{}", resource),
+            None if json => println!("null"),
             None => println!("Resource not found."),
         }
     } else if state.is_event_path(path) {
         let events = state.view_events(path)?;
-        if events.is_empty() {
+        if json {
+            let events: Vec<_> = events.iter().map(|event| event.json()).collect();
+            println!("{}", serde_json::to_string_pretty(&events)?)
+        } else if events.is_empty() {
             println!("Events not found.")
         } else {
             for event in events {
                 println!("{}", event)
             }
         }
+    } else if json {
+        bail!("`--json` is only supported for resources and events")
     } else if is_bytecode_file(path) {
         let bytecode_opt = if contains_module(path) {
             OnDiskStateView::view_module(path)?
@@ -40,3 +52,50 @@ pub fn view(state: &OnDiskStateView, path: &Path) -> Result<()> {
     }
     Ok(())
 }
+
+/// Print as JSON every resource whose type matches `pattern`, across all addresses. A pattern
+/// without type arguments matches every instantiation of the struct.
+pub fn query(state: &OnDiskStateView, pattern: &str) -> Result<()> {
+    let pattern = parser::parse_struct_tag(pattern)?;
+    let mut resources = vec![];
+    for path in state.resource_paths() {
+        let tag = match parser::parse_struct_tag(&path.file_stem().unwrap().to_string_lossy()) {
+            Ok(tag) => tag,
+            Err(_) => continue,
+        };
+        if !matches_pattern(&pattern, &tag) {
+            continue;
+        }
+        // Resources are stored under `<address>/resources/`
+        let addr_dir = path.parent().and_then(Path::parent).unwrap();
+        let address =
+            AccountAddress::from_hex_literal(&addr_dir.file_name().unwrap().to_string_lossy())?;
+        if let Some(resource) = state.view_resource(&path)? {
+            resources.push((address, tag, resource));
+        }
+    }
+    resources.sort_by(|(a1, t1, _), (a2, t2, _)| (a1, t1).cmp(&(a2, t2)));
+
+    let resources: Vec<_> = resources
+        .iter()
+        .map(|(address, _, resource)| QueryResult {
+            address: format!("0x{}", address.to_canonical_string()),
+            resource: resource.json(),
+        })
+        .collect();
+    println!("{}", serde_json::to_string_pretty(&resources)?);
+    Ok(())
+}
+
+#[derive(serde::Serialize)]
+struct QueryResult<'a> {
+    address: String,
+    resource: JsonStruct<'a>,
+}
+
+pub(crate) fn matches_pattern(pattern: &StructTag, tag: &StructTag) -> bool {
+    pattern.address == tag.address
+        && pattern.module == tag.module
+        && pattern.name == tag.name
+        && (pattern.type_params.is_empty() || pattern.type_params == tag.type_params)
+}
@@ -24,7 +24,6 @@ use move_core_types::{
     effects::{ChangeSet, Event, Op},
     errmap::ErrorMapping,
     language_storage::{ModuleId, TypeTag},
-    transaction_argument::TransactionArgument,
     vm_status::{AbortLocation, StatusCode, VMStatus},
 };
 use move_ir_types::location::Loc;
@@ -39,6 +38,7 @@ use std::{
 
 pub mod on_disk_state_view;
 pub mod package_context;
+pub mod transaction_log;
 
 use move_bytecode_utils::module_cache::GetModule;
 use move_vm_test_utils::gas_schedule::{CostTable, GasStatus};
@@ -104,7 +104,7 @@ pub(crate) fn explain_publish_changeset(changeset: &ChangeSet) {
 }
 
 // Print a struct with a specified outer indent
-fn print_struct_with_indent(value: &AnnotatedMoveStruct, indent: u64) {
+pub(crate) fn print_struct_with_indent(value: &AnnotatedMoveStruct, indent: u64) {
     let indent_str: String = (0..indent).map(|_| " ").collect::<String>();
     let value_str = format!("{}", value);
     let lines = value_str.split('\n');
@@ -114,7 +114,7 @@ fn print_struct_with_indent(value: &AnnotatedMoveStruct, indent: u64) {
 }
 
 // Print struct diff with a specified outer indent
-fn print_struct_diff_with_indent(
+pub(crate) fn print_struct_diff_with_indent(
     value1: &AnnotatedMoveStruct,
     value2: &AnnotatedMoveStruct,
     indent: u64,
@@ -277,7 +277,7 @@ pub(crate) fn maybe_commit_effects(
 pub(crate) fn explain_type_error(
     script_params: &[SignatureToken],
     signers: &[AccountAddress],
-    txn_args: &[TransactionArgument],
+    txn_args: &[String],
 ) {
     use SignatureToken::*;
     let expected_num_signers = script_params
@@ -364,6 +364,13 @@ pub(crate) fn explain_publish_error(
                 println!("Linking API for structs/functions of module {} has changed. Need to redeploy all dependent modules.", module_id)
             }
         }
+        VMStatus::Error(UPGRADE_POLICY_VIOLATION) => {
+            println!(
+                "Upgrade policy of module {} violated--publishing aborted: {}",
+                module_id,
+                error_clone.message().map_or("", |msg| msg.as_str())
+            );
+        }
         VMStatus::Error(CYCLIC_MODULE_DEPENDENCY) => {
             println!(
                 "Publishing module {} introduces cyclic dependencies.",
@@ -461,7 +468,7 @@ pub(crate) fn explain_execution_error(
     script_parameters: &[SignatureToken],
     vm_type_args: &[TypeTag],
     signers: &[AccountAddress],
-    txn_args: &[TransactionArgument],
+    txn_args: &[String],
 ) -> Result<()> {
     use StatusCode::*;
     match error.into_vm_status() {
@@ -513,6 +520,11 @@ pub(crate) fn explain_execution_error(
                     .to_string(),
                 EXECUTION_STACK_OVERFLOW => "an execution stack overflow".to_string(),
                 CALL_STACK_OVERFLOW => "a call stack overflow".to_string(),
+                VM_MAX_VALUE_DEPTH_REACHED => "a value nested too deeply".to_string(),
+                VM_MAX_TYPE_NODES_REACHED => "a type instantiation too large".to_string(),
+                VM_MAX_LOCALS_MEMORY_REACHED => {
+                    "the locals on the call stack using too much memory".to_string()
+                }
                 OUT_OF_GAS => "an out of gas error".to_string(),
                 _ => format!("a {} error", status_code.status_type()),
             };
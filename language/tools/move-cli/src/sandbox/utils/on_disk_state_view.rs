@@ -27,7 +27,8 @@ use std::{
     path::{Path, PathBuf},
 };
 
-type Event = (Vec<u8>, u64, TypeTag, Vec<u8>);
+/// An event as stored on disk: its key, sequence number, type, and BCS-serialized payload
+pub type Event = (Vec<u8>, u64, TypeTag, Vec<u8>);
 
 /// subdirectory of `DEFAULT_STORAGE_DIR`/<addr> where resources are stored
 pub const RESOURCES_DIR: &str = "resources";
@@ -39,6 +40,17 @@ pub const EVENTS_DIR: &str = "events";
 /// file under `DEFAULT_BUILD_DIR` where a registry of generated struct layouts are stored
 pub const STRUCT_LAYOUTS_FILE: &str = "struct_layouts.yaml";
 
+/// extension of the directory next to the storage directory where snapshots of the storage are
+/// saved, e.g. `storage.snapshots` for `storage`
+pub const SNAPSHOTS_EXTENSION: &str = "snapshots";
+
+/// extension of the directory next to the storage directory where `doctor --fix` moves the files
+/// it cannot repair, e.g. `storage.quarantine` for `storage`
+pub const QUARANTINE_EXTENSION: &str = "quarantine";
+
+/// file under `DEFAULT_STORAGE_DIR` where `run --record` logs the executed transactions
+pub const TRANSACTION_LOG_FILE: &str = "transactions.log";
+
 #[derive(Debug)]
 pub struct OnDiskStateView {
     build_dir: PathBuf,
@@ -74,6 +86,103 @@ impl OnDiskStateView {
         self.build_dir.join(STRUCT_LAYOUTS_FILE)
     }
 
+    pub fn storage_dir(&self) -> &PathBuf {
+        &self.storage_dir
+    }
+
+    pub fn transaction_log_path(&self) -> PathBuf {
+        self.storage_dir.join(TRANSACTION_LOG_FILE)
+    }
+
+    /// Directory holding the snapshots of `storage_dir`. It is kept next to (rather than inside)
+    /// `storage_dir` so that snapshots survive `clean` and are never mistaken for stored data.
+    pub fn snapshots_dir(&self) -> PathBuf {
+        let mut name = self.storage_dir.file_name().unwrap().to_os_string();
+        name.push(".");
+        name.push(SNAPSHOTS_EXTENSION);
+        self.storage_dir.with_file_name(name)
+    }
+
+    fn get_snapshot_path(&self, name: &str) -> Result<PathBuf> {
+        if name.is_empty()
+            || name.starts_with('.')
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+        {
+            bail!(
+                "Invalid snapshot name `{}`. Snapshot names may only contain ASCII letters, \
+                 digits, '_', '-' and '.', and cannot start with '.'",
+                name
+            )
+        }
+        Ok(self.snapshots_dir().join(name))
+    }
+
+    /// Save a copy of the current contents of `storage_dir` as snapshot `name`, replacing the
+    /// existing snapshot of that name if `overwrite` is set
+    pub fn save_snapshot(&self, name: &str, overwrite: bool) -> Result<()> {
+        let path = self.get_snapshot_path(name)?;
+        if path.exists() {
+            if !overwrite {
+                bail!("Snapshot `{}` already exists", name)
+            }
+            fs::remove_dir_all(&path)?;
+        }
+        self.save_copy(&path)
+    }
+
+    /// Replace the contents of `storage_dir` with those of snapshot `name`
+    pub fn restore_snapshot(&self, name: &str) -> Result<()> {
+        let snapshot = self.snapshot(name)?;
+        self.restore_copy(&snapshot.storage_dir)
+    }
+
+    /// Save a copy of the current contents of `storage_dir` in directory `dir`
+    pub fn save_copy(&self, dir: &Path) -> Result<()> {
+        copy_dir(&self.storage_dir, dir)
+    }
+
+    /// Replace the contents of `storage_dir` with the copy saved in directory `dir`
+    pub fn restore_copy(&self, dir: &Path) -> Result<()> {
+        fs::remove_dir_all(&self.storage_dir)?;
+        copy_dir(dir, &self.storage_dir)
+    }
+
+    /// A view of the state saved in snapshot `name`
+    pub fn snapshot(&self, name: &str) -> Result<Self> {
+        let path = self.get_snapshot_path(name)?;
+        if !path.is_dir() {
+            bail!("Snapshot `{}` does not exist", name)
+        }
+        Self::create(self.build_dir.clone(), path)
+    }
+
+    /// A copy of snapshot `name` in `storage_dir`, which can be modified without affecting the
+    /// snapshot
+    pub fn copy_snapshot(&self, name: &str, storage_dir: &Path) -> Result<Self> {
+        let snapshot = self.snapshot(name)?;
+        copy_dir(&snapshot.storage_dir, storage_dir)?;
+        Self::create(self.build_dir.as_path(), storage_dir)
+    }
+
+    /// The names of all the saved snapshots, in alphabetical order
+    pub fn snapshot_names(&self) -> Result<Vec<String>> {
+        let snapshots_dir = self.snapshots_dir();
+        if !snapshots_dir.exists() {
+            return Ok(vec![]);
+        }
+        let mut names = vec![];
+        for entry in fs::read_dir(snapshots_dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                names.push(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
     fn is_data_path(&self, p: &Path, parent_dir: &str) -> bool {
         if !p.exists() {
             return false;
@@ -163,6 +272,26 @@ impl OnDiskStateView {
         Self::get_bytes(&self.get_module_path(module_id))
     }
 
+    /// Directory holding the files that `doctor --fix` removed from `storage_dir`, at the same
+    /// paths relative to it.
+    pub fn quarantine_dir(&self) -> PathBuf {
+        let mut name = self.storage_dir.file_name().unwrap().to_os_string();
+        name.push(".");
+        name.push(QUARANTINE_EXTENSION);
+        self.storage_dir.with_file_name(name)
+    }
+
+    /// Move the file at `path` in `storage_dir` to the quarantine directory, and return its new
+    /// path.
+    pub fn quarantine(&self, path: &Path) -> Result<PathBuf> {
+        let relative_path = path.canonicalize()?;
+        let relative_path = relative_path.strip_prefix(&self.storage_dir)?;
+        let quarantine_path = self.quarantine_dir().join(relative_path);
+        fs::create_dir_all(quarantine_path.parent().unwrap())?;
+        fs::rename(path, &quarantine_path)?;
+        Ok(quarantine_path)
+    }
+
     /// Check if a module at `addr`/`module_id` exists
     pub fn has_module(&self, module_id: &ModuleId) -> bool {
         self.get_module_path(module_id).exists()
@@ -220,7 +349,7 @@ impl OnDiskStateView {
         }
     }
 
-    fn get_events(&self, events_path: &Path) -> Result<Vec<Event>> {
+    pub fn get_events(&self, events_path: &Path) -> Result<Vec<Event>> {
         Ok(if events_path.exists() {
             match Self::get_bytes(events_path)? {
                 Some(events_data) => bcs::from_bytes::<Vec<Event>>(&events_data)?,
@@ -231,6 +360,15 @@ impl OnDiskStateView {
         })
     }
 
+    /// Return the events stored on disk for all event keys, in no particular order.
+    pub fn get_all_events(&self) -> Result<Vec<Event>> {
+        let mut events = vec![];
+        for path in self.event_paths() {
+            events.extend(self.get_events(&path)?);
+        }
+        Ok(events)
+    }
+
     pub fn view_events(&self, events_path: &Path) -> Result<Vec<AnnotatedMoveValue>> {
         let annotator = MoveValueAnnotator::new(self);
         self.get_events(events_path)?
@@ -323,6 +461,16 @@ impl OnDiskStateView {
         Ok(fs::write(path, &bcs::to_bytes(&event_log)?)?)
     }
 
+    /// Replace the events stored at `events_path` with `events`, deleting the file if there are
+    /// none left
+    pub fn save_events(&self, events_path: &Path, events: &[Event]) -> Result<()> {
+        if events.is_empty() {
+            Ok(fs::remove_file(events_path)?)
+        } else {
+            Ok(fs::write(events_path, &bcs::to_bytes(events)?)?)
+        }
+    }
+
     /// Save `module` on disk under the path `module.address()`/`module.name()`
     pub fn save_module(&self, module_id: &ModuleId, module_bytes: &[u8]) -> Result<()> {
         let path = self.get_module_path(module_id);
@@ -400,6 +548,21 @@ impl OnDiskStateView {
     }
 }
 
+/// Recursively copy the contents of directory `src` into directory `dst`
+fn copy_dir(src: &Path, dst: &Path) -> Result<()> {
+    fs::create_dir_all(dst)?;
+    for entry in walkdir::WalkDir::new(src).min_depth(1) {
+        let entry = entry?;
+        let target = dst.join(entry.path().strip_prefix(src)?);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
 impl ModuleResolver for OnDiskStateView {
     type Error = anyhow::Error;
     fn get_module(&self, module_id: &ModuleId) -> Result<Option<Vec<u8>>, Self::Error> {
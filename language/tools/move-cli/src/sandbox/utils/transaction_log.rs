@@ -0,0 +1,117 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The transaction log recorded by `sandbox run --record`: one JSON object per line, describing
+//! a transaction and a hash of its effects, so that `sandbox replay` can re-execute the
+//! transactions and check that they produce the same effects.
+
+use anyhow::{anyhow, Result};
+use move_core_types::{
+    account_address::AccountAddress,
+    effects::{ChangeSet, Event, Op},
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag},
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fmt,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+};
+
+/// The code executed by a transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionTarget {
+    EntryFunction {
+        module: ModuleId,
+        function: Identifier,
+    },
+    /// A script, as hex-encoded bytecode
+    Script { bytecode: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionRecord {
+    pub target: TransactionTarget,
+    pub signers: Vec<AccountAddress>,
+    /// The arguments, as passed to `sandbox run`
+    pub args: Vec<String>,
+    pub type_args: Vec<String>,
+    pub gas_budget: Option<u64>,
+    /// The hash of the change set and events produced by the transaction, see `effects_hash`
+    pub change_set_hash: String,
+}
+
+impl TransactionRecord {
+    /// Append the record to the log at `path`, creating the log if needed
+    pub fn append_to(&self, path: &Path) -> Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Read all the records of the log at `path`
+    pub fn read_log(path: &Path) -> Result<Vec<Self>> {
+        fs::read_to_string(path)?
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str(line).map_err(|e| {
+                    anyhow!(
+                        "Invalid transaction at line {} of {}: {}",
+                        i + 1,
+                        path.display(),
+                        e
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for TransactionTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TransactionTarget::EntryFunction { module, function } => write!(
+                f,
+                "0x{}::{}::{}",
+                module.address().short_str_lossless(),
+                module.name(),
+                function
+            ),
+            TransactionTarget::Script { .. } => write!(f, "script"),
+        }
+    }
+}
+
+/// A hex-encoded SHA-256 hash of the BCS encoding of the change set and events of a
+/// transaction. Accounts, modules and resources are encoded in order, so that the hash does not
+/// depend on how the change set was built.
+pub fn effects_hash(changeset: &ChangeSet, events: &[Event]) -> Result<String> {
+    type Ops<K> = Vec<(K, u8, Vec<u8>)>;
+    fn ops<'a, K: Clone + 'a>(ops: impl Iterator<Item = (&'a K, &'a Op<Vec<u8>>)>) -> Ops<K> {
+        ops.map(|(key, op)| match op {
+            Op::New(blob) => (key.clone(), 0, blob.clone()),
+            Op::Modify(blob) => (key.clone(), 1, blob.clone()),
+            Op::Delete => (key.clone(), 2, vec![]),
+        })
+        .collect()
+    }
+    let accounts: Vec<(AccountAddress, Ops<Identifier>, Ops<StructTag>)> = changeset
+        .accounts()
+        .iter()
+        .map(|(addr, account)| {
+            (
+                *addr,
+                ops(account.modules().iter()),
+                ops(account.resources().iter()),
+            )
+        })
+        .collect();
+    let bytes = bcs::to_bytes(&(accounts, events))?;
+    Ok(hex::encode(Sha256::digest(&bytes)))
+}
@@ -0,0 +1,5 @@
+module upgrade::Bank {
+    public fun deposit(): u64 {
+        0
+    }
+}
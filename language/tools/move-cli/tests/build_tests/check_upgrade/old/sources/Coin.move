@@ -0,0 +1,26 @@
+module upgrade::Coin {
+    friend upgrade::Bank;
+
+    struct Coin has key, store {
+        value: u64,
+    }
+
+    struct Marker<phantom T> has drop {}
+
+    struct Gone {}
+
+    public fun value(coin: &Coin): u64 {
+        coin.value
+    }
+
+    public fun split(coin: &mut Coin, amount: u64): Coin {
+        coin.value = coin.value - amount;
+        Coin { value: amount }
+    }
+
+    public(friend) fun mint(value: u64): Coin {
+        Coin { value }
+    }
+
+    public entry fun noop() {}
+}
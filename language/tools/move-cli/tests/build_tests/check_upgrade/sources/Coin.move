@@ -0,0 +1,24 @@
+module upgrade::Coin {
+    struct Coin has store {
+        value: u128,
+    }
+
+    struct Marker<phantom T: copy> has drop {}
+
+    public fun value(coin: &Coin): u128 {
+        coin.value
+    }
+
+    public fun split(coin: &mut Coin, amount: u128): Coin {
+        coin.value = coin.value - amount;
+        Coin { value: amount }
+    }
+
+    fun mint(value: u128): Coin {
+        Coin { value }
+    }
+
+    public entry fun noop() {}
+
+    public fun added() {}
+}
@@ -0,0 +1,5 @@
+module 0x42::B {
+    use 0x42::A;
+
+    public fun double(): u64 { A::value() * 2 }
+}
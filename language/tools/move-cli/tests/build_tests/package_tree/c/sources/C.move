@@ -0,0 +1 @@
+module C::C {}
@@ -0,0 +1 @@
+module D::D {}
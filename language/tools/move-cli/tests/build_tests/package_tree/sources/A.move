@@ -0,0 +1 @@
+module A::A {}
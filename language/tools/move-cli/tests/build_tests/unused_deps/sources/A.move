@@ -0,0 +1,6 @@
+module A::A {
+    fun f() {
+        U::U::f();
+        T::T::g();
+    }
+}
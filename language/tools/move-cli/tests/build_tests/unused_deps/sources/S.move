@@ -0,0 +1,6 @@
+// The address `Unused` only appears in comments and identifiers: Unused::X
+address Script {
+module M {
+    fun f(): address { @Script }
+}
+}
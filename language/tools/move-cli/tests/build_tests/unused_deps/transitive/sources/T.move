@@ -0,0 +1,3 @@
+module T::T {
+    public fun g() {}
+}
@@ -0,0 +1 @@
+module N::N {}
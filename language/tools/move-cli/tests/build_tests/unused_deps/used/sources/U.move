@@ -0,0 +1,3 @@
+module U::U {
+    public fun f() {}
+}
@@ -0,0 +1 @@
+// `Via` declares no modules of its own, and only provides its dependency `Transitive`
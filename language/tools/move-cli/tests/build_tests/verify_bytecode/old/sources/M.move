@@ -0,0 +1,5 @@
+module A::M {
+    public fun f(): u64 { 1 }
+    public fun g(x: u64): u64 { x + 2 }
+    public fun removed() {}
+}
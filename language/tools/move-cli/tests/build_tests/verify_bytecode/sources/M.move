@@ -0,0 +1,4 @@
+module A::M {
+    public fun f(): u64 { 1 }
+    public fun g(x: u64): u64 { x + 1 }
+}
@@ -0,0 +1,3 @@
+module A::N {
+    public fun h(): u64 { A::M::f() }
+}
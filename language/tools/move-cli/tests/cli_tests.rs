@@ -69,6 +69,83 @@ fn cross_process_locking_git_deps() {
     handle.join().unwrap();
 }
 
+#[test]
+fn repl_evaluates_entries_against_storage() {
+    let package_path = fs::canonicalize("./tests/repl_tests/Counter").unwrap();
+    let work_dir = tempfile::tempdir().unwrap();
+    let mut child = Command::new(get_cli_binary_path())
+        .current_dir(work_dir.path())
+        .args([
+            "--path",
+            package_path.to_str().unwrap(),
+            "--install-dir",
+            work_dir.path().to_str().unwrap(),
+            "repl",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let input = [
+        "let x = 40",
+        "x + 2",
+        "let v = vector[1u8, 2]",
+        "use std::vector",
+        "vector::length(&v)",
+        ":publish",
+        ":signer alice 0x2",
+        "use 0x2::Counter",
+        "Counter::create(&alice)",
+        "Counter::increment(&alice)",
+        ":view 0x2 0x2::Counter::Counter",
+        ":rollback",
+        "Counter::value(@0x2)",
+        "1 / 0",
+        ":gas 1000",
+        "let y = {",
+        "    let i = 0;",
+        "    while (i < 3) i = i + 1;",
+        "    (i as u8)",
+        "}",
+        ":gas",
+        "(y, true)",
+        "&x",
+    ];
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.join("\n").as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    for expected in [
+        "x: u64 = 40",
+        "_: u64 = 42",
+        "_: u64 = 2",
+        "alice: signer = 0x2",
+        "_: u64 = 1",
+        "value: 1",
+        "Rolled back the last change",
+        "_: u64 = 0",
+        "Execution failed with ARITHMETIC_ERROR",
+        "y: u8 = 3u8",
+        "Gas budget: 1000",
+        "Gas used by the last entry",
+        "_: (u8, bool) = (3u8, true)",
+        "Error: The value of an entry cannot be a reference",
+    ] {
+        assert!(
+            stdout.contains(expected),
+            "expected `{}` in the output:\n{}",
+            expected,
+            stdout
+        );
+    }
+}
+
 const UPLOAD_PACKAGE_PATH: &str = "./tests/upload_tests";
 #[test]
 fn upload_package_to_movey_works() {
@@ -0,0 +1,21 @@
+module 0x2::Counter {
+    use std::signer;
+
+    struct Counter has key {
+        value: u64,
+    }
+
+    public fun create(account: &signer) {
+        move_to(account, Counter { value: 0 })
+    }
+
+    public fun increment(account: &signer): u64 acquires Counter {
+        let counter = borrow_global_mut<Counter>(signer::address_of(account));
+        counter.value = counter.value + 1;
+        counter.value
+    }
+
+    public fun value(addr: address): u64 acquires Counter {
+        borrow_global<Counter>(addr).value
+    }
+}
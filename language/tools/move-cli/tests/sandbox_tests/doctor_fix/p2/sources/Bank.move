@@ -0,0 +1,36 @@
+module 0x2::Bank {
+    use std::event::{Self, EventHandle};
+    use std::signer;
+
+    // `owner`, `history` and `limits` were appended: stored accounts are rewritten with defaults
+    struct Account has key {
+        balance: u64,
+        deposits: EventHandle<Deposited>,
+        owner: address,
+        history: vector<u64>,
+        limits: Limits,
+    }
+
+    struct Limits has store {
+        daily: u64,
+        frozen: bool,
+    }
+
+    // the type of `enabled` changed: stored configs are quarantined
+    struct Config has key {
+        enabled: u64,
+    }
+
+    // `Legacy` and `Flagged` were removed: stored legacy resources are quarantined, and flagged
+    // events are orphaned
+
+    struct Deposited has drop, store {
+        amount: u64,
+    }
+
+    public entry fun deposit(account: signer, amount: u64) acquires Account {
+        let account = borrow_global_mut<Account>(signer::address_of(&account));
+        account.balance = account.balance + amount;
+        event::emit_event(&mut account.deposits, Deposited { amount });
+    }
+}
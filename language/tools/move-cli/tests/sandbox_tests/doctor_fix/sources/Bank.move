@@ -0,0 +1,41 @@
+module 0x2::Bank {
+    use std::event::{Self, EventHandle};
+    use std::signer;
+
+    struct Account has key {
+        balance: u64,
+        deposits: EventHandle<Deposited>,
+    }
+
+    struct Config has key {
+        enabled: bool,
+    }
+
+    struct Legacy has key {
+        flags: EventHandle<Flagged>,
+    }
+
+    struct Deposited has drop, store {
+        amount: u64,
+    }
+
+    struct Flagged has drop, store {
+        amount: u64,
+    }
+
+    public entry fun open(account: signer) {
+        move_to(&account, Account { balance: 0, deposits: event::new_event_handle(&account) });
+        move_to(&account, Config { enabled: true });
+        move_to(&account, Legacy { flags: event::new_event_handle(&account) });
+    }
+
+    public entry fun deposit(account: signer, amount: u64) acquires Account, Legacy {
+        let addr = signer::address_of(&account);
+        let account = borrow_global_mut<Account>(addr);
+        account.balance = account.balance + amount;
+        event::emit_event(&mut account.deposits, Deposited { amount });
+        if (amount > 100) {
+            event::emit_event(&mut borrow_global_mut<Legacy>(addr).flags, Flagged { amount });
+        }
+    }
+}
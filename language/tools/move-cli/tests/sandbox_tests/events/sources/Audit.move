@@ -0,0 +1,22 @@
+module 0x2::Audit {
+    use std::event::{Self, EventHandle};
+
+    struct Log has key {
+        notes: EventHandle<Note>,
+        codes: EventHandle<u64>,
+    }
+
+    struct Note has drop, store {
+        code: u64,
+    }
+
+    public entry fun note(account: signer, code: u64) {
+        let log = Log {
+            notes: event::new_event_handle(&account),
+            codes: event::new_event_handle(&account),
+        };
+        event::emit_event(&mut log.notes, Note { code });
+        event::emit_event(&mut log.codes, code);
+        move_to(&account, log)
+    }
+}
@@ -0,0 +1,39 @@
+module 0x2::Bank {
+    use std::event::{Self, EventHandle};
+    use std::signer;
+
+    struct Account has key {
+        balance: u64,
+        deposits: EventHandle<Deposited>,
+        withdrawals: EventHandle<Withdrawn>,
+    }
+
+    struct Deposited has drop, store {
+        amount: u64,
+    }
+
+    struct Withdrawn has drop, store {
+        amount: u64,
+        remaining: u64,
+    }
+
+    public entry fun open(account: signer) {
+        move_to(&account, Account {
+            balance: 0,
+            deposits: event::new_event_handle(&account),
+            withdrawals: event::new_event_handle(&account),
+        })
+    }
+
+    public entry fun deposit(account: signer, amount: u64) acquires Account {
+        let account = borrow_global_mut<Account>(signer::address_of(&account));
+        account.balance = account.balance + amount;
+        event::emit_event(&mut account.deposits, Deposited { amount });
+    }
+
+    public entry fun withdraw(account: signer, amount: u64) acquires Account {
+        let account = borrow_global_mut<Account>(signer::address_of(&account));
+        account.balance = account.balance - amount;
+        event::emit_event(&mut account.withdrawals, Withdrawn { amount, remaining: account.balance });
+    }
+}
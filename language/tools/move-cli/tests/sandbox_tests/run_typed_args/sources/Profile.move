@@ -0,0 +1,38 @@
+module 0x2::Profile {
+    use std::option::{Self, Option};
+    use std::string::String;
+    use std::signer;
+
+    struct Limits has copy, drop, store {
+        max_items: u64,
+        enabled: bool,
+    }
+
+    struct Profile has key {
+        name: String,
+        friends: vector<address>,
+        age: Option<u8>,
+        limits: Limits,
+        tags: vector<vector<u8>>,
+    }
+
+    public entry fun create(
+        account: signer,
+        name: String,
+        friends: vector<address>,
+        age: Option<u8>,
+        limits: Limits,
+        tags: vector<vector<u8>>,
+    ) {
+        move_to(&account, Profile { name, friends, age, limits, tags })
+    }
+
+    public entry fun rename(account: &signer, name: String) acquires Profile {
+        borrow_global_mut<Profile>(signer::address_of(account)).name = name
+    }
+
+    public entry fun check_age<T: drop>(account: &signer, age: Option<T>) {
+        assert!(option::is_some(&age), 0);
+        let _ = account;
+    }
+}
@@ -0,0 +1,29 @@
+module 0x2::Counter {
+    use std::event::{Self, EventHandle};
+    use std::signer;
+
+    struct Counter has key {
+        value: u64,
+        increments: EventHandle<Incremented>,
+    }
+
+    struct Incremented has drop, store {
+        value: u64,
+    }
+
+    public entry fun create(account: signer) {
+        let increments = event::new_event_handle(&account);
+        move_to(&account, Counter { value: 0, increments })
+    }
+
+    public entry fun increment(account: signer) acquires Counter {
+        let counter = borrow_global_mut<Counter>(signer::address_of(&account));
+        counter.value = counter.value + 1;
+        event::emit_event(&mut counter.increments, Incremented { value: counter.value });
+    }
+
+    public entry fun destroy(account: signer) acquires Counter {
+        let Counter { value: _, increments } = move_from<Counter>(signer::address_of(&account));
+        event::destroy_handle(increments);
+    }
+}
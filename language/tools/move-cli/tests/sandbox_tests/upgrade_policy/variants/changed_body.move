@@ -0,0 +1,5 @@
+module 0x42::M {
+    public fun value(): u64 {
+        2
+    }
+}
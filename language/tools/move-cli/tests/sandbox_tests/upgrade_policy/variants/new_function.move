@@ -0,0 +1,9 @@
+module 0x42::M {
+    public fun value(): u64 {
+        1
+    }
+
+    public fun other_value(): u64 {
+        2
+    }
+}
@@ -0,0 +1,55 @@
+module 0x2::Vault {
+    use std::event::{Self, EventHandle};
+    use std::signer;
+
+    struct Coin<phantom T> has store {
+        value: u64,
+    }
+
+    struct Gold {}
+    struct Silver {}
+
+    struct Vault<phantom T> has key {
+        owner: address,
+        coin: Coin<T>,
+        small: u8,
+        large: u128,
+        huge: u256,
+        memo: vector<u8>,
+        history: vector<u64>,
+        deposits: EventHandle<Deposited>,
+    }
+
+    struct Deposited has drop, store {
+        amount: u64,
+        depositor: address,
+    }
+
+    fun create<T>(account: &signer, value: u64, memo: vector<u8>) {
+        move_to(account, Vault<T> {
+            owner: signer::address_of(account),
+            coin: Coin { value },
+            small: 255,
+            large: 340282366920938463463374607431768211455,
+            huge: 1,
+            memo,
+            history: vector[value],
+            deposits: event::new_event_handle(account),
+        })
+    }
+
+    public entry fun create_gold(account: signer, value: u64, memo: vector<u8>) {
+        create<Gold>(&account, value, memo)
+    }
+
+    public entry fun create_silver(account: signer, value: u64) {
+        create<Silver>(&account, value, vector[])
+    }
+
+    public entry fun deposit(account: signer, amount: u64) acquires Vault {
+        let addr = signer::address_of(&account);
+        let vault = borrow_global_mut<Vault<Gold>>(addr);
+        vault.coin.value = vault.coin.value + amount;
+        event::emit_event(&mut vault.deposits, Deposited { amount, depositor: addr });
+    }
+}
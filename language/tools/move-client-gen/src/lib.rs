@@ -0,0 +1,240 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generates typed client code for calling Move entry functions and transaction scripts, and for
+//! reading Move resources.
+//!
+//! The inputs are the ABIs produced by the Move prover's ABI generator (`ScriptABI`) and struct
+//! layouts built with `TypeLayoutBuilder::build_with_types` (`MoveStructLayout::WithTypes`).
+//! Call builders serialize their arguments with BCS and attach the type arguments as type tags, so
+//! the result can be submitted as-is. Structs are emitted with BCS (de)serialization and their
+//! `StructTag`.
+
+pub mod rust;
+pub mod typescript;
+
+use anyhow::{bail, Context, Result};
+use move_binary_format::{access::ModuleAccess, file_format::Ability, CompiledModule};
+use move_bytecode_utils::{layout::TypeLayoutBuilder, Modules};
+use move_command_line_common::files::{extension_equals, find_filenames};
+use move_core_types::{
+    abi::ScriptABI,
+    account_address::AccountAddress,
+    identifier::Identifier,
+    language_storage::{StructTag, TypeTag},
+    value::{MoveFieldLayout, MoveStructLayout, MoveTypeLayout},
+};
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// Extension of the BCS-serialized ABI files produced by the ABI generator
+pub const ABI_EXTENSION: &str = "abi";
+
+/// Read the ABIs in all the `.abi` files found under `paths`, sorted by module and name.
+pub fn read_abis(paths: &[impl AsRef<Path>]) -> Result<Vec<ScriptABI>> {
+    let mut abis = vec![];
+    for file in find_filenames(paths, |path| extension_equals(path, ABI_EXTENSION))? {
+        let bytes = fs::read(&file).with_context(|| format!("Unable to read {}", file))?;
+        let abi = bcs::from_bytes::<ScriptABI>(&bytes)
+            .with_context(|| format!("Unable to deserialize ABI in {}", file))?;
+        abis.push(abi);
+    }
+    abis.sort_by_key(|abi| match abi {
+        ScriptABI::ScriptFunction(abi) => (Some(abi.module_name().clone()), abi.name().to_string()),
+        ScriptABI::TransactionScript(abi) => (None, abi.name().to_string()),
+    });
+    Ok(abis)
+}
+
+/// Return the tags of the resources declared in `modules`: all structs with the `key` ability and
+/// no type parameters.
+pub fn resource_tags(modules: &[CompiledModule]) -> Vec<StructTag> {
+    let mut tags = vec![];
+    for module in modules {
+        for def in module.struct_defs() {
+            let handle = module.struct_handle_at(def.struct_handle);
+            if handle.abilities.has_ability(Ability::Key) && handle.type_parameters.is_empty() {
+                tags.push(StructTag {
+                    address: *module.address(),
+                    module: module.name().to_owned(),
+                    name: module.identifier_at(handle.name).to_owned(),
+                    type_params: vec![],
+                })
+            }
+        }
+    }
+    tags
+}
+
+/// Build the layouts of the structs `tags`, resolving their declarations in `modules`.
+pub fn struct_layouts(
+    tags: &[StructTag],
+    modules: &[CompiledModule],
+) -> Result<Vec<MoveStructLayout>> {
+    let resolver = Modules::new(modules);
+    tags.iter()
+        .map(|tag| {
+            match TypeLayoutBuilder::build_with_types(
+                &TypeTag::Struct(Box::new(tag.clone())),
+                &resolver,
+            )
+            .with_context(|| format!("Unable to build the layout of {}", tag))?
+            {
+                MoveTypeLayout::Struct(layout) => Ok(layout),
+                _ => unreachable!("the layout of a struct is a struct layout"),
+            }
+        })
+        .collect()
+}
+
+/// Returns true if `tag` is `std::string::String` or `std::ascii::String`, which clients represent
+/// as native strings.
+pub(crate) fn is_string(tag: &StructTag) -> bool {
+    tag.address == AccountAddress::ONE
+        && (tag.module.as_str() == "string" || tag.module.as_str() == "ascii")
+        && tag.name.as_str() == "String"
+}
+
+/// If `tag` is `std::option::Option<T>`, which clients represent as native optional values,
+/// return `T`.
+pub(crate) fn option_type(tag: &StructTag) -> Option<&TypeTag> {
+    if tag.address == AccountAddress::ONE
+        && tag.module.as_str() == "option"
+        && tag.name.as_str() == "Option"
+    {
+        tag.type_params.first()
+    } else {
+        None
+    }
+}
+
+/// The name of the client type generated for `tag`: the struct name followed by the names of its
+/// type arguments, e.g. `CoinXUS` for `0x1::Coin::Coin<0x1::XUS::XUS>`.
+pub(crate) fn struct_name(tag: &StructTag) -> String {
+    fn type_name(ty: &TypeTag) -> String {
+        match ty {
+            TypeTag::Bool => "Bool".to_string(),
+            TypeTag::U8 => "U8".to_string(),
+            TypeTag::U16 => "U16".to_string(),
+            TypeTag::U32 => "U32".to_string(),
+            TypeTag::U64 => "U64".to_string(),
+            TypeTag::U128 => "U128".to_string(),
+            TypeTag::U256 => "U256".to_string(),
+            TypeTag::Address => "Address".to_string(),
+            TypeTag::Signer => "Signer".to_string(),
+            TypeTag::Vector(ty) => format!("Vector{}", type_name(ty)),
+            TypeTag::Struct(tag) => struct_name(tag),
+        }
+    }
+    let mut name = tag.name.to_string();
+    for ty in &tag.type_params {
+        name.push_str(&type_name(ty));
+    }
+    name
+}
+
+/// A struct to generate a client type for, with the types of its fields.
+pub(crate) struct StructDecl {
+    pub tag: StructTag,
+    pub name: String,
+    pub fields: Vec<(Identifier, TypeTag)>,
+}
+
+/// Collect the structs to generate for `layouts`, including the structs used by their fields,
+/// each struct after the structs it uses. Strings and options are skipped since they map to native
+/// client types.
+pub(crate) fn struct_decls(layouts: &[MoveStructLayout]) -> Result<Vec<StructDecl>> {
+    fn visit_struct(
+        layout: &MoveStructLayout,
+        names: &mut BTreeMap<String, StructTag>,
+        decls: &mut Vec<StructDecl>,
+    ) -> Result<()> {
+        let (tag, fields) = match layout {
+            MoveStructLayout::WithTypes { type_, fields } => (type_, fields),
+            _ => bail!("Struct layouts must be built with types"),
+        };
+        if decls.iter().any(|decl| &decl.tag == tag) || is_string(tag) {
+            return Ok(());
+        }
+        for field in fields {
+            visit_type(&field.layout, names, decls)?;
+        }
+        if option_type(tag).is_some() {
+            return Ok(());
+        }
+        let name = struct_name(tag);
+        if let Some(other) = names.insert(name.clone(), tag.clone()) {
+            bail!(
+                "Structs {} and {} would both be generated as {}",
+                other,
+                tag,
+                name
+            )
+        }
+        decls.push(StructDecl {
+            tag: tag.clone(),
+            name,
+            fields: fields
+                .iter()
+                .map(|MoveFieldLayout { name, layout }| Ok((name.clone(), layout_type(layout)?)))
+                .collect::<Result<_>>()?,
+        });
+        Ok(())
+    }
+
+    fn visit_type(
+        layout: &MoveTypeLayout,
+        names: &mut BTreeMap<String, StructTag>,
+        decls: &mut Vec<StructDecl>,
+    ) -> Result<()> {
+        match layout {
+            MoveTypeLayout::Vector(layout) => visit_type(layout, names, decls),
+            MoveTypeLayout::Struct(layout) => visit_struct(layout, names, decls),
+            _ => Ok(()),
+        }
+    }
+
+    let mut names = BTreeMap::new();
+    let mut decls = vec![];
+    for layout in layouts {
+        visit_struct(layout, &mut names, &mut decls)?;
+    }
+    Ok(decls)
+}
+
+/// The type described by `layout`, which must have been built with types.
+fn layout_type(layout: &MoveTypeLayout) -> Result<TypeTag> {
+    Ok(match layout {
+        MoveTypeLayout::Bool => TypeTag::Bool,
+        MoveTypeLayout::U8 => TypeTag::U8,
+        MoveTypeLayout::U16 => TypeTag::U16,
+        MoveTypeLayout::U32 => TypeTag::U32,
+        MoveTypeLayout::U64 => TypeTag::U64,
+        MoveTypeLayout::U128 => TypeTag::U128,
+        MoveTypeLayout::U256 => TypeTag::U256,
+        MoveTypeLayout::Address => TypeTag::Address,
+        MoveTypeLayout::Signer => TypeTag::Signer,
+        MoveTypeLayout::Vector(layout) => TypeTag::Vector(Box::new(layout_type(layout)?)),
+        MoveTypeLayout::Struct(MoveStructLayout::WithTypes { type_, .. }) => {
+            TypeTag::Struct(Box::new(type_.clone()))
+        }
+        MoveTypeLayout::Struct(_) => bail!("Struct layouts must be built with types"),
+    })
+}
+
+/// Check that clients can pass arguments of type `ty`: signers are supplied by the transaction
+/// sender, and structs other than strings and options cannot be constructed outside of Move.
+pub(crate) fn check_abi_arg_type(ty: &TypeTag) -> Result<()> {
+    match ty {
+        TypeTag::Signer => bail!("Signer arguments cannot be passed by clients"),
+        TypeTag::Vector(ty) => check_abi_arg_type(ty),
+        TypeTag::Struct(tag) if is_string(tag) => Ok(()),
+        TypeTag::Struct(tag) => match option_type(tag) {
+            Some(ty) => check_abi_arg_type(ty),
+            None => bail!(
+                "Struct arguments of type {} cannot be passed by clients",
+                tag
+            ),
+        },
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,78 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result};
+use clap::{ArgEnum, Parser};
+use move_binary_format::{access::ModuleAccess, CompiledModule};
+use move_client_gen::{read_abis, resource_tags, rust, struct_layouts, typescript};
+use move_command_line_common::files::{extension_equals, find_filenames, MOVE_COMPILED_EXTENSION};
+use move_core_types::{language_storage::StructTag, parser};
+use std::{collections::BTreeMap, fs, io::Write, path::PathBuf};
+
+#[derive(Debug, Clone, Copy, ArgEnum)]
+enum Language {
+    Rust,
+    #[clap(name = "typescript")]
+    TypeScript,
+}
+
+#[derive(Parser)]
+#[clap(
+    name = "move-client-gen",
+    about = "Generate typed Rust or TypeScript clients from Move ABIs and struct layouts"
+)]
+struct Args {
+    /// Language of the generated client.
+    #[clap(long = "language", arg_enum)]
+    language: Language,
+    /// Files, or directories containing `.abi` files, to generate call builders for.
+    #[clap(long = "abis", multiple_occurrences(true))]
+    abis: Vec<PathBuf>,
+    /// Files, or directories containing `.mv` files, declaring the structs to generate types for.
+    #[clap(long = "modules", multiple_occurrences(true))]
+    modules: Vec<PathBuf>,
+    /// Structs to generate types for, e.g. `0x1::Coin::Coin<0x1::XUS::XUS>`. Defaults to all the
+    /// resources (structs with `key` and no type parameters) declared in `--modules`.
+    #[clap(
+        long = "struct",
+        parse(try_from_str = parser::parse_struct_tag),
+        multiple_occurrences(true)
+    )]
+    structs: Vec<StructTag>,
+    /// File to write the client to. Defaults to stdout.
+    #[clap(long = "output", short = 'o')]
+    output: Option<PathBuf>,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+
+    let abis = read_abis(&args.abis)?;
+    // The same module may be found several times, e.g. when passing both a package's modules and
+    // its dependencies, which are nested in the same directory.
+    let mut modules = BTreeMap::new();
+    for file in find_filenames(&args.modules, |path| {
+        extension_equals(path, MOVE_COMPILED_EXTENSION)
+    })? {
+        let bytes = fs::read(&file).with_context(|| format!("Unable to read {}", file))?;
+        let module = CompiledModule::deserialize(&bytes)
+            .with_context(|| format!("Unable to deserialize module in {}", file))?;
+        modules.insert(module.self_id(), module);
+    }
+    let modules: Vec<_> = modules.into_values().collect();
+    let tags = if args.structs.is_empty() {
+        resource_tags(&modules)
+    } else {
+        args.structs
+    };
+    let layouts = struct_layouts(&tags, &modules)?;
+
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(fs::File::create(path)?),
+        None => Box::new(std::io::stdout()),
+    };
+    match args.language {
+        Language::Rust => rust::output(&mut out, &abis, &layouts),
+        Language::TypeScript => typescript::output(&mut out, &abis, &layouts),
+    }
+}
@@ -0,0 +1,321 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Rust client generation. The generated code depends on `move-core-types`, `bcs` and `serde`.
+
+use crate::{check_abi_arg_type, is_string, option_type, struct_decls, struct_name, StructDecl};
+use anyhow::Result;
+use heck::{ShoutySnakeCase, SnakeCase};
+use move_core_types::{
+    abi::{ArgumentABI, ScriptABI, TypeArgumentABI},
+    language_storage::{StructTag, TypeTag},
+    value::MoveStructLayout,
+};
+use std::io::Write;
+
+const PRELUDE: &str = r#"// Generated by move-client-gen. Do not edit.
+
+#[allow(unused_imports)]
+use move_core_types::{
+    account_address::AccountAddress,
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag, TypeTag},
+    u256::U256,
+};
+#[allow(unused_imports)]
+use serde::{Deserialize, Serialize};
+
+/// A call to an entry function, with its arguments serialized with BCS.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntryFunctionCall {
+    pub module: ModuleId,
+    pub function: Identifier,
+    pub ty_args: Vec<TypeTag>,
+    pub args: Vec<Vec<u8>>,
+}
+
+/// A call to a transaction script, with its arguments serialized with BCS.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScriptCall {
+    pub code: Vec<u8>,
+    pub ty_args: Vec<TypeTag>,
+    pub args: Vec<Vec<u8>>,
+}
+"#;
+
+/// Maximum length of the lines of generated code, as enforced by rustfmt
+const MAX_LINE_LENGTH: usize = 100;
+
+/// Rust keywords which cannot be used as identifiers without the `r#` prefix
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "crate", "do", "dyn", "else",
+    "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let", "loop", "macro",
+    "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "static", "struct",
+    "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual", "where",
+    "while", "yield",
+];
+
+/// Write a Rust module with a builder for each of `abis` and a struct for each of `layouts` (and
+/// the structs they contain) to `out`.
+pub fn output(out: &mut dyn Write, abis: &[ScriptABI], layouts: &[MoveStructLayout]) -> Result<()> {
+    let structs = struct_decls(layouts)?;
+    write!(out, "{}", PRELUDE)?;
+    for abi in abis {
+        writeln!(out)?;
+        output_builder(out, abi)?;
+    }
+    for decl in &structs {
+        writeln!(out)?;
+        output_struct(out, decl)?;
+    }
+    Ok(())
+}
+
+fn output_builder(out: &mut dyn Write, abi: &ScriptABI) -> Result<()> {
+    let (name, doc, ty_args, args) = match abi {
+        ScriptABI::ScriptFunction(abi) => (
+            format!(
+                "encode_{}_{}",
+                abi.module_name().name().as_str().to_snake_case(),
+                abi.name().to_snake_case()
+            ),
+            abi.doc(),
+            abi.ty_args(),
+            abi.args(),
+        ),
+        ScriptABI::TransactionScript(abi) => (
+            format!("encode_{}_script", abi.name().to_snake_case()),
+            abi.doc(),
+            abi.ty_args(),
+            abi.args(),
+        ),
+    };
+    for arg in args {
+        check_abi_arg_type(arg.type_tag())?;
+    }
+
+    if let ScriptABI::TransactionScript(abi) = abi {
+        writeln!(
+            out,
+            "/// The code of the `{}` transaction script.",
+            abi.name()
+        )?;
+        writeln!(
+            out,
+            "pub const {}_CODE: &[u8] = &[",
+            abi.name().to_shouty_snake_case()
+        )?;
+        for chunk in abi.code().chunks(16) {
+            let bytes: Vec<_> = chunk.iter().map(|b| b.to_string()).collect();
+            writeln!(out, "    {},", bytes.join(", "))?;
+        }
+        writeln!(out, "];")?;
+        writeln!(out)?;
+    }
+
+    output_doc(out, doc)?;
+    let params: Vec<_> = ty_args
+        .iter()
+        .map(|ty_arg| format!("{}: TypeTag", ty_arg_name(ty_arg)))
+        .chain(
+            args.iter()
+                .map(|arg| format!("{}: {}", arg_name(arg), rust_type(arg.type_tag()))),
+        )
+        .collect();
+    let ty_arg_names: Vec<_> = ty_args.iter().map(ty_arg_name).collect();
+    let arg_exprs: Vec<_> = args
+        .iter()
+        .map(|arg| format!("bcs::to_bytes(&{}).unwrap()", arg_name(arg)))
+        .collect();
+    match abi {
+        ScriptABI::ScriptFunction(abi) => {
+            output_signature(out, &name, &params, "EntryFunctionCall")?;
+            writeln!(out, "    EntryFunctionCall {{")?;
+            writeln!(out, "        module: ModuleId::new(")?;
+            writeln!(
+                out,
+                "            AccountAddress::from_hex_literal(\"0x{}\").unwrap(),",
+                abi.module_name().address().short_str_lossless()
+            )?;
+            writeln!(
+                out,
+                "            Identifier::new(\"{}\").unwrap(),",
+                abi.module_name().name()
+            )?;
+            writeln!(out, "        ),")?;
+            writeln!(
+                out,
+                "        function: Identifier::new(\"{}\").unwrap(),",
+                abi.name()
+            )?;
+        }
+        ScriptABI::TransactionScript(abi) => {
+            output_signature(out, &name, &params, "ScriptCall")?;
+            writeln!(out, "    ScriptCall {{")?;
+            writeln!(
+                out,
+                "        code: {}_CODE.to_vec(),",
+                abi.name().to_shouty_snake_case()
+            )?;
+        }
+    }
+    writeln!(out, "        ty_args: vec![{}],", ty_arg_names.join(", "))?;
+    if arg_exprs.is_empty() {
+        writeln!(out, "        args: vec![],")?;
+    } else {
+        writeln!(out, "        args: vec![")?;
+        for expr in arg_exprs {
+            writeln!(out, "            {},", expr)?;
+        }
+        writeln!(out, "        ],")?;
+    }
+    writeln!(out, "    }}")?;
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+fn output_struct(out: &mut dyn Write, decl: &StructDecl) -> Result<()> {
+    writeln!(out, "/// The `{}` struct.", decl.tag)?;
+    writeln!(
+        out,
+        "#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]"
+    )?;
+    writeln!(out, "pub struct {} {{", decl.name)?;
+    for (name, ty) in &decl.fields {
+        writeln!(out, "    pub {}: {},", ident(name.as_str()), rust_type(ty))?;
+    }
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+    writeln!(out, "impl {} {{", decl.name)?;
+    writeln!(out, "    /// The tag of the Move type of this struct.")?;
+    writeln!(out, "    pub fn struct_tag() -> StructTag {{")?;
+    let tag = &decl.tag;
+    let type_params: Vec<_> = tag.type_params.iter().map(type_tag_expr).collect();
+    writeln!(out, "        StructTag {{")?;
+    writeln!(
+        out,
+        "            address: AccountAddress::from_hex_literal(\"0x{}\").unwrap(),",
+        tag.address.short_str_lossless()
+    )?;
+    writeln!(
+        out,
+        "            module: Identifier::new(\"{}\").unwrap(),",
+        tag.module
+    )?;
+    writeln!(
+        out,
+        "            name: Identifier::new(\"{}\").unwrap(),",
+        tag.name
+    )?;
+    writeln!(
+        out,
+        "            type_params: vec![{}],",
+        type_params.join(", ")
+    )?;
+    writeln!(out, "        }}")?;
+    writeln!(out, "    }}")?;
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+/// Write the signature of a builder, with one parameter per line if it would not fit in one line.
+fn output_signature(
+    out: &mut dyn Write,
+    name: &str,
+    params: &[String],
+    return_type: &str,
+) -> Result<()> {
+    let line = format!(
+        "pub fn {}({}) -> {} {{",
+        name,
+        params.join(", "),
+        return_type
+    );
+    if line.len() <= MAX_LINE_LENGTH {
+        writeln!(out, "{}", line)?;
+    } else {
+        writeln!(out, "pub fn {}(", name)?;
+        for param in params {
+            writeln!(out, "    {},", param)?;
+        }
+        writeln!(out, ") -> {} {{", return_type)?;
+    }
+    Ok(())
+}
+
+fn output_doc(out: &mut dyn Write, doc: &str) -> Result<()> {
+    for line in doc.trim().lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            writeln!(out, "///")?;
+        } else {
+            writeln!(out, "/// {}", line)?;
+        }
+    }
+    Ok(())
+}
+
+fn ident(name: &str) -> String {
+    if KEYWORDS.contains(&name) {
+        format!("r#{}", name)
+    } else {
+        name.to_string()
+    }
+}
+
+fn arg_name(arg: &ArgumentABI) -> String {
+    ident(&arg.name().to_snake_case())
+}
+
+fn ty_arg_name(ty_arg: &TypeArgumentABI) -> String {
+    ident(&ty_arg.name().to_snake_case())
+}
+
+/// The Rust type whose BCS serialization matches that of the Move type `ty`
+fn rust_type(ty: &TypeTag) -> String {
+    match ty {
+        TypeTag::Bool => "bool".to_string(),
+        TypeTag::U8 => "u8".to_string(),
+        TypeTag::U16 => "u16".to_string(),
+        TypeTag::U32 => "u32".to_string(),
+        TypeTag::U64 => "u64".to_string(),
+        TypeTag::U128 => "u128".to_string(),
+        TypeTag::U256 => "U256".to_string(),
+        TypeTag::Address | TypeTag::Signer => "AccountAddress".to_string(),
+        TypeTag::Vector(ty) => format!("Vec<{}>", rust_type(ty)),
+        TypeTag::Struct(tag) if is_string(tag) => "String".to_string(),
+        TypeTag::Struct(tag) => match option_type(tag) {
+            Some(ty) => format!("Option<{}>", rust_type(ty)),
+            None => struct_name(tag),
+        },
+    }
+}
+
+fn type_tag_expr(ty: &TypeTag) -> String {
+    match ty {
+        TypeTag::Bool => "TypeTag::Bool".to_string(),
+        TypeTag::U8 => "TypeTag::U8".to_string(),
+        TypeTag::U16 => "TypeTag::U16".to_string(),
+        TypeTag::U32 => "TypeTag::U32".to_string(),
+        TypeTag::U64 => "TypeTag::U64".to_string(),
+        TypeTag::U128 => "TypeTag::U128".to_string(),
+        TypeTag::U256 => "TypeTag::U256".to_string(),
+        TypeTag::Address => "TypeTag::Address".to_string(),
+        TypeTag::Signer => "TypeTag::Signer".to_string(),
+        TypeTag::Vector(ty) => format!("TypeTag::Vector(Box::new({}))", type_tag_expr(ty)),
+        TypeTag::Struct(tag) => format!("TypeTag::Struct(Box::new({}))", struct_tag_expr(tag)),
+    }
+}
+
+fn struct_tag_expr(tag: &StructTag) -> String {
+    let type_params: Vec<_> = tag.type_params.iter().map(type_tag_expr).collect();
+    format!(
+        "StructTag {{ address: AccountAddress::from_hex_literal(\"0x{}\").unwrap(), \
+         module: Identifier::new(\"{}\").unwrap(), name: Identifier::new(\"{}\").unwrap(), \
+         type_params: vec![{}] }}",
+        tag.address.short_str_lossless(),
+        tag.module,
+        tag.name,
+        type_params.join(", ")
+    )
+}
@@ -0,0 +1,642 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! TypeScript client generation. The generated module is self-contained: it includes a BCS
+//! serializer and deserializer, and represents addresses as hex strings and 64 bit or larger
+//! integers as `bigint`s.
+
+use crate::{check_abi_arg_type, is_string, option_type, struct_decls, struct_name, StructDecl};
+use anyhow::Result;
+use heck::{CamelCase, MixedCase, ShoutySnakeCase};
+use move_core_types::{
+    abi::{ArgumentABI, ScriptABI, TypeArgumentABI},
+    account_address::AccountAddress,
+    language_storage::{StructTag, TypeTag},
+    value::MoveStructLayout,
+};
+use std::io::Write;
+
+const PRELUDE: &str = r#"// Generated by move-client-gen. Do not edit.
+
+export const ADDRESS_LENGTH = {ADDRESS_LENGTH};
+
+export type TypeTag =
+  | "bool"
+  | "u8"
+  | "u16"
+  | "u32"
+  | "u64"
+  | "u128"
+  | "u256"
+  | "address"
+  | "signer"
+  | { vector: TypeTag }
+  | { struct: StructTag };
+
+export interface StructTag {
+  address: string;
+  module: string;
+  name: string;
+  typeParams: TypeTag[];
+}
+
+/** A call to an entry function, with its arguments serialized with BCS. */
+export interface EntryFunctionCall {
+  module: { address: string; name: string };
+  function: string;
+  tyArgs: TypeTag[];
+  args: Uint8Array[];
+}
+
+/** A call to a transaction script, with its arguments serialized with BCS. */
+export interface ScriptCall {
+  code: Uint8Array;
+  tyArgs: TypeTag[];
+  args: Uint8Array[];
+}
+
+export class BcsSerializer {
+  private bytes: number[] = [];
+
+  serializeBool(value: boolean): void {
+    this.bytes.push(value ? 1 : 0);
+  }
+
+  serializeU8(value: number): void {
+    this.serializeUint(BigInt(value), 1);
+  }
+
+  serializeU16(value: number): void {
+    this.serializeUint(BigInt(value), 2);
+  }
+
+  serializeU32(value: number): void {
+    this.serializeUint(BigInt(value), 4);
+  }
+
+  serializeU64(value: bigint): void {
+    this.serializeUint(value, 8);
+  }
+
+  serializeU128(value: bigint): void {
+    this.serializeUint(value, 16);
+  }
+
+  serializeU256(value: bigint): void {
+    this.serializeUint(value, 32);
+  }
+
+  serializeLen(value: number): void {
+    while (value >= 0x80) {
+      this.bytes.push((value & 0x7f) | 0x80);
+      value >>>= 7;
+    }
+    this.bytes.push(value);
+  }
+
+  serializeBytes(value: Uint8Array): void {
+    this.serializeLen(value.length);
+    value.forEach((byte) => this.bytes.push(byte));
+  }
+
+  serializeStr(value: string): void {
+    this.serializeBytes(new TextEncoder().encode(value));
+  }
+
+  serializeAddress(value: string): void {
+    const hex = value.startsWith("0x") ? value.slice(2) : value;
+    if (hex.length > ADDRESS_LENGTH * 2 || !/^[0-9a-fA-F]*$/.test(hex)) {
+      throw new Error(`Invalid address: ${value}`);
+    }
+    const padded = hex.padStart(ADDRESS_LENGTH * 2, "0");
+    for (let i = 0; i < padded.length; i += 2) {
+      this.bytes.push(parseInt(padded.slice(i, i + 2), 16));
+    }
+  }
+
+  serializeVector<T>(values: T[], serializeElement: (s: BcsSerializer, value: T) => void): void {
+    this.serializeLen(values.length);
+    values.forEach((value) => serializeElement(this, value));
+  }
+
+  serializeOption<T>(value: T | null, serializeElement: (s: BcsSerializer, value: T) => void): void {
+    if (value === null) {
+      this.serializeLen(0);
+    } else {
+      this.serializeLen(1);
+      serializeElement(this, value);
+    }
+  }
+
+  getBytes(): Uint8Array {
+    return new Uint8Array(this.bytes);
+  }
+
+  private serializeUint(value: bigint, numBytes: number): void {
+    if (value < 0n || value >= 1n << BigInt(numBytes * 8)) {
+      throw new Error(`Value out of range for a ${numBytes * 8} bit integer: ${value}`);
+    }
+    for (let i = 0; i < numBytes; i++) {
+      this.bytes.push(Number(value & 0xffn));
+      value >>= 8n;
+    }
+  }
+}
+
+export class BcsDeserializer {
+  private offset = 0;
+
+  constructor(private bytes: Uint8Array) {}
+
+  deserializeBool(): boolean {
+    const value = this.readByte();
+    if (value > 1) {
+      throw new Error(`Invalid bool: ${value}`);
+    }
+    return value === 1;
+  }
+
+  deserializeU8(): number {
+    return Number(this.deserializeUint(1));
+  }
+
+  deserializeU16(): number {
+    return Number(this.deserializeUint(2));
+  }
+
+  deserializeU32(): number {
+    return Number(this.deserializeUint(4));
+  }
+
+  deserializeU64(): bigint {
+    return this.deserializeUint(8);
+  }
+
+  deserializeU128(): bigint {
+    return this.deserializeUint(16);
+  }
+
+  deserializeU256(): bigint {
+    return this.deserializeUint(32);
+  }
+
+  deserializeLen(): number {
+    let value = 0;
+    for (let shift = 0; shift < 32; shift += 7) {
+      const byte = this.readByte();
+      value += (byte & 0x7f) * 2 ** shift;
+      if ((byte & 0x80) === 0) {
+        return value;
+      }
+    }
+    throw new Error("Invalid length");
+  }
+
+  deserializeBytes(): Uint8Array {
+    const len = this.deserializeLen();
+    if (this.offset + len > this.bytes.length) {
+      throw new Error("Unexpected end of input");
+    }
+    const value = this.bytes.slice(this.offset, this.offset + len);
+    this.offset += len;
+    return value;
+  }
+
+  deserializeStr(): string {
+    return new TextDecoder().decode(this.deserializeBytes());
+  }
+
+  deserializeAddress(): string {
+    let hex = "0x";
+    for (let i = 0; i < ADDRESS_LENGTH; i++) {
+      hex += this.readByte().toString(16).padStart(2, "0");
+    }
+    return hex;
+  }
+
+  deserializeVector<T>(deserializeElement: (d: BcsDeserializer) => T): T[] {
+    const len = this.deserializeLen();
+    const values: T[] = [];
+    for (let i = 0; i < len; i++) {
+      values.push(deserializeElement(this));
+    }
+    return values;
+  }
+
+  deserializeOption<T>(deserializeElement: (d: BcsDeserializer) => T): T | null {
+    switch (this.deserializeLen()) {
+      case 0:
+        return null;
+      case 1:
+        return deserializeElement(this);
+      default:
+        throw new Error("Invalid option");
+    }
+  }
+
+  isEmpty(): boolean {
+    return this.offset === this.bytes.length;
+  }
+
+  private readByte(): number {
+    if (this.offset >= this.bytes.length) {
+      throw new Error("Unexpected end of input");
+    }
+    return this.bytes[this.offset++];
+  }
+
+  private deserializeUint(numBytes: number): bigint {
+    let value = 0n;
+    for (let i = 0; i < numBytes; i++) {
+      value |= BigInt(this.readByte()) << BigInt(i * 8);
+    }
+    return value;
+  }
+}
+
+const TYPE_TAG_VARIANTS = {
+  bool: 0,
+  u8: 1,
+  u64: 2,
+  u128: 3,
+  address: 4,
+  signer: 5,
+  u16: 8,
+  u32: 9,
+  u256: 10,
+};
+
+export function serializeTypeTag(s: BcsSerializer, tag: TypeTag): void {
+  if (typeof tag === "string") {
+    s.serializeLen(TYPE_TAG_VARIANTS[tag]);
+  } else if ("vector" in tag) {
+    s.serializeLen(6);
+    serializeTypeTag(s, tag.vector);
+  } else {
+    s.serializeLen(7);
+    serializeStructTag(s, tag.struct);
+  }
+}
+
+export function serializeStructTag(s: BcsSerializer, tag: StructTag): void {
+  s.serializeAddress(tag.address);
+  s.serializeStr(tag.module);
+  s.serializeStr(tag.name);
+  s.serializeVector(tag.typeParams, serializeTypeTag);
+}
+
+function bcsBytes(serialize: (s: BcsSerializer) => void): Uint8Array {
+  const s = new BcsSerializer();
+  serialize(s);
+  return s.getBytes();
+}
+"#;
+
+/// Maximum length of the lines of generated code
+const MAX_LINE_LENGTH: usize = 100;
+
+/// Reserved words which cannot be used as parameter names
+const KEYWORDS: &[&str] = &[
+    "arguments",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "eval",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+/// Write a TypeScript module with a builder for each of `abis` and an interface, with BCS
+/// (de)serialization functions, for each of `layouts` (and the structs they contain) to `out`.
+pub fn output(out: &mut dyn Write, abis: &[ScriptABI], layouts: &[MoveStructLayout]) -> Result<()> {
+    let structs = struct_decls(layouts)?;
+    write!(
+        out,
+        "{}",
+        PRELUDE.replace("{ADDRESS_LENGTH}", &AccountAddress::LENGTH.to_string())
+    )?;
+    for abi in abis {
+        writeln!(out)?;
+        output_builder(out, abi)?;
+    }
+    for decl in &structs {
+        writeln!(out)?;
+        output_struct(out, decl)?;
+    }
+    Ok(())
+}
+
+fn output_builder(out: &mut dyn Write, abi: &ScriptABI) -> Result<()> {
+    let (name, doc, ty_args, args) = match abi {
+        ScriptABI::ScriptFunction(abi) => (
+            format!(
+                "encode{}{}",
+                abi.module_name().name().as_str().to_camel_case(),
+                abi.name().to_camel_case()
+            ),
+            abi.doc(),
+            abi.ty_args(),
+            abi.args(),
+        ),
+        ScriptABI::TransactionScript(abi) => (
+            format!("encode{}Script", abi.name().to_camel_case()),
+            abi.doc(),
+            abi.ty_args(),
+            abi.args(),
+        ),
+    };
+    for arg in args {
+        check_abi_arg_type(arg.type_tag())?;
+    }
+
+    if let ScriptABI::TransactionScript(abi) = abi {
+        writeln!(
+            out,
+            "/** The code of the `{}` transaction script. */",
+            abi.name()
+        )?;
+        writeln!(
+            out,
+            "export const {}_CODE = new Uint8Array([",
+            abi.name().to_shouty_snake_case()
+        )?;
+        for chunk in abi.code().chunks(16) {
+            let bytes: Vec<_> = chunk.iter().map(|b| b.to_string()).collect();
+            writeln!(out, "  {},", bytes.join(", "))?;
+        }
+        writeln!(out, "]);")?;
+        writeln!(out)?;
+    }
+
+    output_doc(out, doc)?;
+    let params: Vec<_> = ty_args
+        .iter()
+        .map(|ty_arg| format!("{}: TypeTag", ty_arg_name(ty_arg)))
+        .chain(
+            args.iter()
+                .map(|arg| format!("{}: {}", arg_name(arg), ts_type(arg.type_tag()))),
+        )
+        .collect();
+    let ty_arg_names: Vec<_> = ty_args.iter().map(ty_arg_name).collect();
+    match abi {
+        ScriptABI::ScriptFunction(abi) => {
+            output_signature(out, &name, &params, "EntryFunctionCall")?;
+            writeln!(out, "  return {{")?;
+            writeln!(
+                out,
+                "    module: {{ address: \"0x{}\", name: \"{}\" }},",
+                abi.module_name().address().short_str_lossless(),
+                abi.module_name().name()
+            )?;
+            writeln!(out, "    function: \"{}\",", abi.name())?;
+        }
+        ScriptABI::TransactionScript(abi) => {
+            output_signature(out, &name, &params, "ScriptCall")?;
+            writeln!(out, "  return {{")?;
+            writeln!(out, "    code: {}_CODE,", abi.name().to_shouty_snake_case())?;
+        }
+    }
+    writeln!(out, "    tyArgs: [{}],", ty_arg_names.join(", "))?;
+    writeln!(out, "    args: [")?;
+    for arg in args {
+        writeln!(
+            out,
+            "      bcsBytes((s) => {}),",
+            serialize_expr(arg.type_tag(), &arg_name(arg))
+        )?;
+    }
+    writeln!(out, "    ],")?;
+    writeln!(out, "  }};")?;
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+fn output_struct(out: &mut dyn Write, decl: &StructDecl) -> Result<()> {
+    let name = &decl.name;
+    writeln!(out, "/** The `{}` struct. */", decl.tag)?;
+    writeln!(out, "export interface {} {{", name)?;
+    for (field, ty) in &decl.fields {
+        writeln!(out, "  {}: {};", field, ts_type(ty))?;
+    }
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "export const {}_STRUCT_TAG: StructTag = {};",
+        name.to_shouty_snake_case(),
+        struct_tag_expr(&decl.tag)
+    )?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "export function serialize{}(s: BcsSerializer, value: {}): void {{",
+        name, name
+    )?;
+    for (field, ty) in &decl.fields {
+        writeln!(
+            out,
+            "  {};",
+            serialize_expr(ty, &format!("value.{}", field))
+        )?;
+    }
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "export function deserialize{}(d: BcsDeserializer): {} {{",
+        name, name
+    )?;
+    writeln!(out, "  return {{")?;
+    for (field, ty) in &decl.fields {
+        writeln!(out, "    {}: {},", field, deserialize_expr(ty))?;
+    }
+    writeln!(out, "  }};")?;
+    writeln!(out, "}}")?;
+    Ok(())
+}
+
+/// Write the signature of a builder, with one parameter per line if it would not fit in one line.
+fn output_signature(
+    out: &mut dyn Write,
+    name: &str,
+    params: &[String],
+    return_type: &str,
+) -> Result<()> {
+    let line = format!(
+        "export function {}({}): {} {{",
+        name,
+        params.join(", "),
+        return_type
+    );
+    if line.len() <= MAX_LINE_LENGTH {
+        writeln!(out, "{}", line)?;
+    } else {
+        writeln!(out, "export function {}(", name)?;
+        for param in params {
+            writeln!(out, "  {},", param)?;
+        }
+        writeln!(out, "): {} {{", return_type)?;
+    }
+    Ok(())
+}
+
+fn output_doc(out: &mut dyn Write, doc: &str) -> Result<()> {
+    let doc = doc.trim();
+    if doc.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "/**")?;
+    for line in doc.lines() {
+        let line = line.trim_end().replace("*/", "*\\/");
+        if line.is_empty() {
+            writeln!(out, " *")?;
+        } else {
+            writeln!(out, " * {}", line)?;
+        }
+    }
+    writeln!(out, " */")?;
+    Ok(())
+}
+
+fn ident(name: String) -> String {
+    if KEYWORDS.contains(&name.as_str()) {
+        format!("{}_", name)
+    } else {
+        name
+    }
+}
+
+fn arg_name(arg: &ArgumentABI) -> String {
+    ident(arg.name().to_mixed_case())
+}
+
+fn ty_arg_name(ty_arg: &TypeArgumentABI) -> String {
+    ident(ty_arg.name().to_mixed_case())
+}
+
+/// The TypeScript type used to represent values of the Move type `ty`
+fn ts_type(ty: &TypeTag) -> String {
+    match ty {
+        TypeTag::Bool => "boolean".to_string(),
+        TypeTag::U8 | TypeTag::U16 | TypeTag::U32 => "number".to_string(),
+        TypeTag::U64 | TypeTag::U128 | TypeTag::U256 => "bigint".to_string(),
+        TypeTag::Address | TypeTag::Signer => "string".to_string(),
+        TypeTag::Vector(ty) if **ty == TypeTag::U8 => "Uint8Array".to_string(),
+        TypeTag::Vector(ty) => format!("{}[]", ts_type(ty)),
+        TypeTag::Struct(tag) if is_string(tag) => "string".to_string(),
+        TypeTag::Struct(tag) => match option_type(tag) {
+            Some(ty) => format!("{} | null", ts_type(ty)),
+            None => struct_name(tag),
+        },
+    }
+}
+
+/// An expression serializing `value`, of Move type `ty`, with the serializer `s`
+fn serialize_expr(ty: &TypeTag, value: &str) -> String {
+    match ty {
+        TypeTag::Bool => format!("s.serializeBool({})", value),
+        TypeTag::U8 => format!("s.serializeU8({})", value),
+        TypeTag::U16 => format!("s.serializeU16({})", value),
+        TypeTag::U32 => format!("s.serializeU32({})", value),
+        TypeTag::U64 => format!("s.serializeU64({})", value),
+        TypeTag::U128 => format!("s.serializeU128({})", value),
+        TypeTag::U256 => format!("s.serializeU256({})", value),
+        TypeTag::Address | TypeTag::Signer => format!("s.serializeAddress({})", value),
+        TypeTag::Vector(ty) if **ty == TypeTag::U8 => format!("s.serializeBytes({})", value),
+        TypeTag::Vector(ty) => format!(
+            "s.serializeVector({}, (s, e) => {})",
+            value,
+            serialize_expr(ty, "e")
+        ),
+        TypeTag::Struct(tag) if is_string(tag) => format!("s.serializeStr({})", value),
+        TypeTag::Struct(tag) => match option_type(tag) {
+            Some(ty) => format!(
+                "s.serializeOption({}, (s, e) => {})",
+                value,
+                serialize_expr(ty, "e")
+            ),
+            None => format!("serialize{}(s, {})", struct_name(tag), value),
+        },
+    }
+}
+
+/// An expression deserializing a value of Move type `ty` with the deserializer `d`
+fn deserialize_expr(ty: &TypeTag) -> String {
+    match ty {
+        TypeTag::Bool => "d.deserializeBool()".to_string(),
+        TypeTag::U8 => "d.deserializeU8()".to_string(),
+        TypeTag::U16 => "d.deserializeU16()".to_string(),
+        TypeTag::U32 => "d.deserializeU32()".to_string(),
+        TypeTag::U64 => "d.deserializeU64()".to_string(),
+        TypeTag::U128 => "d.deserializeU128()".to_string(),
+        TypeTag::U256 => "d.deserializeU256()".to_string(),
+        TypeTag::Address | TypeTag::Signer => "d.deserializeAddress()".to_string(),
+        TypeTag::Vector(ty) if **ty == TypeTag::U8 => "d.deserializeBytes()".to_string(),
+        TypeTag::Vector(ty) => format!("d.deserializeVector((d) => {})", deserialize_expr(ty)),
+        TypeTag::Struct(tag) if is_string(tag) => "d.deserializeStr()".to_string(),
+        TypeTag::Struct(tag) => match option_type(tag) {
+            Some(ty) => format!("d.deserializeOption((d) => {})", deserialize_expr(ty)),
+            None => format!("deserialize{}(d)", struct_name(tag)),
+        },
+    }
+}
+
+fn type_tag_expr(ty: &TypeTag) -> String {
+    match ty {
+        TypeTag::Vector(ty) => format!("{{ vector: {} }}", type_tag_expr(ty)),
+        TypeTag::Struct(tag) => format!("{{ struct: {} }}", struct_tag_expr(tag)),
+        _ => format!("\"{}\"", ty),
+    }
+}
+
+fn struct_tag_expr(tag: &StructTag) -> String {
+    let type_params: Vec<_> = tag.type_params.iter().map(type_tag_expr).collect();
+    format!(
+        "{{ address: \"0x{}\", module: \"{}\", name: \"{}\", typeParams: [{}] }}",
+        tag.address.short_str_lossless(),
+        tag.module,
+        tag.name,
+        type_params.join(", ")
+    )
+}
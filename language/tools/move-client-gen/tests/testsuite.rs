@@ -0,0 +1,197 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_client_gen::{read_abis, rust, typescript};
+use move_core_types::{
+    abi::{ArgumentABI, ScriptABI, ScriptFunctionABI, TransactionScriptABI, TypeArgumentABI},
+    account_address::AccountAddress,
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag, TypeTag},
+    value::{MoveFieldLayout, MoveStructLayout, MoveTypeLayout},
+};
+use move_prover_test_utils::baseline_test::verify_or_update_baseline;
+use std::{fs, path::Path};
+use tempfile::tempdir;
+
+fn struct_tag(address: u8, module: &str, name: &str, type_params: Vec<TypeTag>) -> StructTag {
+    let mut bytes = [0; AccountAddress::LENGTH];
+    bytes[AccountAddress::LENGTH - 1] = address;
+    StructTag {
+        address: AccountAddress::new(bytes),
+        module: Identifier::new(module).unwrap(),
+        name: Identifier::new(name).unwrap(),
+        type_params,
+    }
+}
+
+fn field(name: &str, layout: MoveTypeLayout) -> MoveFieldLayout {
+    MoveFieldLayout::new(Identifier::new(name).unwrap(), layout)
+}
+
+fn string_layout() -> MoveTypeLayout {
+    MoveTypeLayout::Struct(MoveStructLayout::WithTypes {
+        type_: struct_tag(1, "string", "String", vec![]),
+        fields: vec![field(
+            "bytes",
+            MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U8)),
+        )],
+    })
+}
+
+fn option_layout(ty: TypeTag, layout: MoveTypeLayout) -> MoveTypeLayout {
+    MoveTypeLayout::Struct(MoveStructLayout::WithTypes {
+        type_: struct_tag(1, "option", "Option", vec![ty]),
+        fields: vec![field("vec", MoveTypeLayout::Vector(Box::new(layout)))],
+    })
+}
+
+fn abis() -> Vec<ScriptABI> {
+    let string = TypeTag::Struct(Box::new(struct_tag(1, "string", "String", vec![])));
+    let option_u64 = TypeTag::Struct(Box::new(struct_tag(
+        1,
+        "option",
+        "Option",
+        vec![TypeTag::U64],
+    )));
+    vec![ScriptABI::ScriptFunction(ScriptFunctionABI::new(
+        "transfer_with_memo".to_string(),
+        ModuleId::new(
+            AccountAddress::from_hex_literal("0x2").unwrap(),
+            Identifier::new("Payments").unwrap(),
+        ),
+        "Transfer `amount` coins of type `CoinType` to `payee`.\n\nThe memo is stored with the payment.".to_string(),
+        vec![TypeArgumentABI::new("CoinType".to_string())],
+        vec![
+            ArgumentABI::new("payee".to_string(), TypeTag::Address),
+            ArgumentABI::new("amount".to_string(), TypeTag::U64),
+            ArgumentABI::new("fee".to_string(), TypeTag::U256),
+            ArgumentABI::new("memo".to_string(), string),
+            ArgumentABI::new("metadata".to_string(), TypeTag::Vector(Box::new(TypeTag::U8))),
+            ArgumentABI::new("expiration".to_string(), option_u64),
+            ArgumentABI::new("type".to_string(), TypeTag::U8),
+            ArgumentABI::new(
+                "proofs".to_string(),
+                TypeTag::Vector(Box::new(TypeTag::Vector(Box::new(TypeTag::U8)))),
+            ),
+        ],
+    ))]
+}
+
+fn layouts() -> Vec<MoveStructLayout> {
+    let xus = struct_tag(1, "XUS", "XUS", vec![]);
+    let coin = MoveStructLayout::WithTypes {
+        type_: struct_tag(1, "Coin", "Coin", vec![TypeTag::Struct(Box::new(xus))]),
+        fields: vec![field("value", MoveTypeLayout::U64)],
+    };
+    let payment = MoveStructLayout::WithTypes {
+        type_: struct_tag(2, "Payments", "Payment", vec![]),
+        fields: vec![
+            field("payer", MoveTypeLayout::Address),
+            field("coin", MoveTypeLayout::Struct(coin.clone())),
+            field("memo", string_layout()),
+            field(
+                "expiration",
+                option_layout(TypeTag::U64, MoveTypeLayout::U64),
+            ),
+            field("flags", MoveTypeLayout::U16),
+        ],
+    };
+    let payments = MoveStructLayout::WithTypes {
+        type_: struct_tag(2, "Payments", "Payments", vec![]),
+        fields: vec![
+            field(
+                "sent",
+                MoveTypeLayout::Vector(Box::new(MoveTypeLayout::Struct(payment))),
+            ),
+            field("balance", MoveTypeLayout::Struct(coin)),
+            field("total", MoveTypeLayout::U128),
+            field("frozen", MoveTypeLayout::Bool),
+            field("type", MoveTypeLayout::U32),
+        ],
+    };
+    vec![payments]
+}
+
+fn check_baselines(name: &str, abis: &[ScriptABI], layouts: &[MoveStructLayout]) {
+    let mut out = vec![];
+    rust::output(&mut out, abis, layouts).unwrap();
+    verify_or_update_baseline(
+        Path::new(&format!("tests/{}.rs.exp", name)),
+        &String::from_utf8(out).unwrap(),
+    )
+    .unwrap();
+
+    let mut out = vec![];
+    typescript::output(&mut out, abis, layouts).unwrap();
+    verify_or_update_baseline(
+        Path::new(&format!("tests/{}.ts.exp", name)),
+        &String::from_utf8(out).unwrap(),
+    )
+    .unwrap();
+}
+
+#[test]
+fn generate_builders_and_structs() {
+    check_baselines("payments", &abis(), &layouts());
+}
+
+#[test]
+fn generate_builders_for_transaction_scripts() {
+    let abi = ScriptABI::TransactionScript(TransactionScriptABI::new(
+        "rotate_key".to_string(),
+        "Rotate the authentication key of the sender to `new_key`.".to_string(),
+        (0..40).collect(),
+        vec![],
+        vec![ArgumentABI::new(
+            "new_key".to_string(),
+            TypeTag::Vector(Box::new(TypeTag::U8)),
+        )],
+    ));
+    check_baselines("rotate_key", &[abi], &[]);
+}
+
+#[test]
+fn read_abi_files() {
+    let dir = tempdir().unwrap();
+    let abis = abis();
+    fs::create_dir(dir.path().join("Payments")).unwrap();
+    fs::write(
+        dir.path().join("Payments").join("transfer_with_memo.abi"),
+        bcs::to_bytes(&abis[0]).unwrap(),
+    )
+    .unwrap();
+    fs::write(dir.path().join("README"), "not an ABI").unwrap();
+    assert_eq!(read_abis(&[dir.path()]).unwrap(), abis);
+}
+
+#[test]
+fn reject_signer_arguments() {
+    let abi = ScriptABI::ScriptFunction(ScriptFunctionABI::new(
+        "f".to_string(),
+        ModuleId::new(AccountAddress::ONE, Identifier::new("M").unwrap()),
+        String::new(),
+        vec![],
+        vec![ArgumentABI::new("s".to_string(), TypeTag::Signer)],
+    ));
+    let err = rust::output(&mut vec![], &[abi], &[]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Signer arguments cannot be passed by clients"
+    );
+}
+
+#[test]
+fn reject_colliding_struct_names() {
+    let layouts: Vec<_> = [1, 2]
+        .into_iter()
+        .map(|address| MoveStructLayout::WithTypes {
+            type_: struct_tag(address, "M", "S", vec![]),
+            fields: vec![field("f", MoveTypeLayout::Bool)],
+        })
+        .collect();
+    let err = typescript::output(&mut vec![], &[], &layouts).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Structs 0x1::M::S and 0x2::M::S would both be generated as S"
+    );
+}
@@ -20,7 +20,7 @@ use move_bytecode_source_map::{
     source_map::{FunctionSourceMap, SourceName},
 };
 use move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule, NamedCompiledScript};
-use move_core_types::identifier::IdentStr;
+use move_core_types::identifier::{IdentStr, Identifier};
 use move_coverage::coverage_map::{ExecCoverageMap, FunctionCoverage};
 use move_ir_types::location::Loc;
 
@@ -1160,6 +1160,56 @@ impl<'a> Disassembler<'a> {
         ))
     }
 
+    /// Disassembles each function defined in the module (or the script's "main" function),
+    /// returning the function names along with their disassembled bytecode.
+    pub fn disassemble_function_defs(&self) -> Result<Vec<(Identifier, String)>> {
+        match self.source_mapper.bytecode {
+            BinaryIndexedView::Script(script) => {
+                let main = IdentStr::new("main")?;
+                Ok(vec![(
+                    main.to_owned(),
+                    self.disassemble_function_def(
+                        self.source_mapper
+                            .source_map
+                            .get_function_source_map(FunctionDefinitionIndex(0_u16))?,
+                        None,
+                        main,
+                        &script.type_parameters,
+                        script.parameters,
+                        Some(&script.code),
+                    )?,
+                )])
+            }
+            BinaryIndexedView::Module(module) => (0..module.function_defs.len())
+                .map(|i| {
+                    let function_definition_index = FunctionDefinitionIndex(i as TableIndex);
+                    let function_definition = self.get_function_def(function_definition_index)?;
+                    let function_handle = self
+                        .source_mapper
+                        .bytecode
+                        .function_handle_at(function_definition.function);
+                    let name = self
+                        .source_mapper
+                        .bytecode
+                        .identifier_at(function_handle.name);
+                    Ok((
+                        name.to_owned(),
+                        self.disassemble_function_def(
+                            self.source_mapper
+                                .source_map
+                                .get_function_source_map(function_definition_index)?,
+                            Some((function_definition, function_handle)),
+                            name,
+                            &function_handle.type_parameters,
+                            function_handle.parameters,
+                            function_definition.code.as_ref(),
+                        )?,
+                    ))
+                })
+                .collect(),
+        }
+    }
+
     pub fn disassemble(&self) -> Result<String> {
         let name_opt = self.source_mapper.source_map.module_name_opt.as_ref();
         let name = name_opt.map(|(addr, n)| format!("{}.{}", addr.short_str_lossless(), n));
@@ -1177,42 +1227,11 @@ impl<'a> Disassembler<'a> {
             .map(|i| self.disassemble_struct_def(StructDefinitionIndex(i as TableIndex)))
             .collect::<Result<Vec<String>>>()?;
 
-        let function_defs: Vec<String> = match self.source_mapper.bytecode {
-            BinaryIndexedView::Script(script) => {
-                vec![self.disassemble_function_def(
-                    self.source_mapper
-                        .source_map
-                        .get_function_source_map(FunctionDefinitionIndex(0_u16))?,
-                    None,
-                    IdentStr::new("main")?,
-                    &script.type_parameters,
-                    script.parameters,
-                    Some(&script.code),
-                )?]
-            }
-            BinaryIndexedView::Module(module) => (0..module.function_defs.len())
-                .map(|i| {
-                    let function_definition_index = FunctionDefinitionIndex(i as TableIndex);
-                    let function_definition = self.get_function_def(function_definition_index)?;
-                    let function_handle = self
-                        .source_mapper
-                        .bytecode
-                        .function_handle_at(function_definition.function);
-                    self.disassemble_function_def(
-                        self.source_mapper
-                            .source_map
-                            .get_function_source_map(function_definition_index)?,
-                        Some((function_definition, function_handle)),
-                        self.source_mapper
-                            .bytecode
-                            .identifier_at(function_handle.name),
-                        &function_handle.type_parameters,
-                        function_handle.parameters,
-                        function_definition.code.as_ref(),
-                    )
-                })
-                .collect::<Result<Vec<String>>>()?,
-        };
+        let function_defs: Vec<String> = self
+            .disassemble_function_defs()?
+            .into_iter()
+            .map(|(_, function_def)| function_def)
+            .collect();
 
         Ok(format!(
             "// Move bytecode v{version}\n{header} {{\n{struct_defs}\n\n{function_defs}\n}}",
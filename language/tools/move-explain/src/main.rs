@@ -3,11 +3,18 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use clap::Parser;
+use move_binary_format::{
+    errors::ExecutionState,
+    file_format::{CodeOffset, FunctionDefinitionIndex},
+};
 use move_command_line_common::files::MOVE_ERROR_DESC_EXTENSION;
 use move_core_types::{
     account_address::AccountAddress, errmap::ErrorMapping, identifier::Identifier,
     language_storage::ModuleId,
 };
+use move_package::BuildConfig;
+use std::path::PathBuf;
+
 #[derive(Debug, Parser)]
 #[clap(author, version, about)]
 struct Args {
@@ -20,24 +27,51 @@ struct Args {
     /// Path to the error code mapping file
     #[clap(long = MOVE_ERROR_DESC_EXTENSION, short = 'e')]
     errmap_path: String,
+    /// Frames of the stack trace of the error, innermost first, each as
+    /// `<address>::<module>::<function index>::<code offset>`
+    #[clap(
+        long = "stack-trace",
+        short = 's',
+        multiple_values = true,
+        requires = "package_path"
+    )]
+    stack_trace: Vec<String>,
+    /// Path to the package whose source maps are used to show the stack trace with source
+    /// locations
+    #[clap(long = "package-path", short = 'p')]
+    package_path: Option<PathBuf>,
 }
 
-fn main() {
-    let args = Args::parse();
-
-    let mut location = args.location.trim().split("::");
-    let mut address_literal = location.next().expect("Could not find address").to_string();
-    let module_name = location
-        .next()
-        .expect("Could not find module name")
-        .to_string();
+fn parse_module_id(address: &str, module_name: &str) -> ModuleId {
+    let mut address_literal = address.to_string();
     if !address_literal.starts_with("0x") {
         address_literal = format!("0x{}", address_literal);
     }
-    let module_id = ModuleId::new(
+    ModuleId::new(
         AccountAddress::from_hex_literal(&address_literal).expect("Unable to parse module address"),
         Identifier::new(module_name).expect("Invalid module name encountered"),
-    );
+    )
+}
+
+fn parse_frame(frame: &str) -> (Option<ModuleId>, FunctionDefinitionIndex, CodeOffset) {
+    let parts: Vec<_> = frame.trim().split("::").collect();
+    match parts.as_slice() {
+        [address, module_name, function_index, code_offset] => (
+            Some(parse_module_id(address, module_name)),
+            FunctionDefinitionIndex(function_index.parse().expect("Invalid function index")),
+            code_offset.parse().expect("Invalid code offset"),
+        ),
+        _ => panic!("Invalid stack frame {}", frame),
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let mut location = args.location.trim().split("::");
+    let address = location.next().expect("Could not find address");
+    let module_name = location.next().expect("Could not find module name");
+    let module_id = parse_module_id(address, module_name);
 
     let errmap_bytes = std::fs::read(&args.errmap_path).expect("Could not load errmap from file");
     let errmap: ErrorMapping =
@@ -53,4 +87,26 @@ fn main() {
             error_desc.code_name, error_desc.code_description,
         ),
     }
+
+    if let Some(package_path) = &args.package_path {
+        let package = BuildConfig::default()
+            .compile_package(package_path, &mut std::io::sink())
+            .expect("Failed to build package");
+        let frames = args
+            .stack_trace
+            .iter()
+            .map(String::as_str)
+            .map(parse_frame)
+            .collect();
+        let stack_trace = package
+            .stack_trace_symbolizer()
+            .symbolize(&ExecutionState::new(frames));
+        println!("Stack trace:");
+        for frame in stack_trace.frames() {
+            println!("\t{}", frame);
+            if !frame.locals.is_empty() {
+                println!("\t\tlocals: {}", frame.locals.join(", "));
+            }
+        }
+    }
 }
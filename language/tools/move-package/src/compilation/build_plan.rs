@@ -3,13 +3,20 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    compilation::compiled_package::CompiledPackage, resolution::resolution_graph::ResolvedGraph,
+    compilation::{
+        compiled_package::{CompiledPackage, CompilerDriverResult},
+        unused_declarations::{find_unused_declarations, UnusedDeclaration},
+    },
+    resolution::resolution_graph::ResolvedGraph,
     source_package::parsed_manifest::PackageName,
 };
 use anyhow::Result;
 use move_compiler::{
     compiled_unit::AnnotatedCompiledUnit,
-    diagnostics::{report_diagnostics_to_color_buffer, report_warnings, FilesSourceText},
+    diagnostics::{
+        report_diagnostics_to_color_buffer, report_warnings, unwrap_or_report_diagnostics,
+        FilesSourceText,
+    },
     Compiler,
 };
 use petgraph::algo::toposort;
@@ -102,19 +109,28 @@ impl BuildPlan {
         })
     }
 
-    /// Compilation results in the process exit upon warning/failure
+    /// Compilation results in the process exit upon warning/failure. Only the sources that
+    /// changed since the last compilation, and the sources depending on them, are recompiled.
     pub fn compile<W: Write>(&self, writer: &mut W) -> Result<CompiledPackage> {
-        self.compile_with_driver(writer, |compiler| compiler.build_and_report())
+        self.compile_with_driver_impl(writer, true, |compiler| {
+            let (files, units_res) = compiler.build()?;
+            let (units, warning_diags) = unwrap_or_report_diagnostics(&files, units_res);
+            let files_with_warnings = warning_diags.file_hashes();
+            report_warnings(&files, warning_diags);
+            Ok((files, units, files_with_warnings))
+        })
     }
 
-    /// Compilation process does not exit even if warnings/failures are encountered
+    /// Compilation process does not exit even if warnings/failures are encountered. Like
+    /// `compile`, compilation is incremental.
     pub fn compile_no_exit<W: Write>(&self, writer: &mut W) -> Result<CompiledPackage> {
-        self.compile_with_driver(writer, |compiler| {
+        self.compile_with_driver_impl(writer, true, |compiler| {
             let (files, units_res) = compiler.build()?;
             match units_res {
                 Ok((units, warning_diags)) => {
+                    let files_with_warnings = warning_diags.file_hashes();
                     report_warnings(&files, warning_diags);
-                    Ok((files, units))
+                    Ok((files, units, files_with_warnings))
                 }
                 Err(error_diags) => {
                     assert!(!error_diags.is_empty());
@@ -128,6 +144,8 @@ impl BuildPlan {
         })
     }
 
+    /// Compiles all the sources of the package and its dependencies in a single invocation of
+    /// `compiler_driver`
     pub fn compile_with_driver<W: Write>(
         &self,
         writer: &mut W,
@@ -135,6 +153,43 @@ impl BuildPlan {
             Compiler,
         )
             -> anyhow::Result<(FilesSourceText, Vec<AnnotatedCompiledUnit>)>,
+    ) -> Result<CompiledPackage> {
+        self.compile_with_driver_impl(writer, false, |compiler| {
+            let (files, units) = compiler_driver(compiler)?;
+            Ok((files, units, BTreeSet::new()))
+        })
+    }
+
+    /// Compiles the sources of the package and its dependencies which changed since the last
+    /// compilation, or which depend on modules whose interface changed. `compiler_driver` is
+    /// invoked with only these sources as targets, possibly several times. In test mode, all the
+    /// sources are recompiled, as the tests are compiled against the source of the
+    /// `std::unit_test` module.
+    ///
+    /// Unlike with `compile`, the warnings of the sources which are not recompiled are not
+    /// reported again.
+    pub fn compile_incrementally_with_driver<W: Write>(
+        &self,
+        writer: &mut W,
+        mut compiler_driver: impl FnMut(
+            Compiler,
+        )
+            -> anyhow::Result<(FilesSourceText, Vec<AnnotatedCompiledUnit>)>,
+    ) -> Result<CompiledPackage> {
+        self.compile_with_driver_impl(writer, true, |compiler| {
+            let (files, units) = compiler_driver(compiler)?;
+            Ok((files, units, BTreeSet::new()))
+        })
+    }
+
+    /// `compiler_driver` returns, along with the compilation results, the files for which it
+    /// reported warnings. These files are recompiled by the next incremental compilation, so that
+    /// their warnings are reported again.
+    fn compile_with_driver_impl<W: Write>(
+        &self,
+        writer: &mut W,
+        incremental: bool,
+        mut compiler_driver: impl FnMut(Compiler) -> anyhow::Result<CompilerDriverResult>,
     ) -> Result<CompiledPackage> {
         let root_package = &self.resolution_graph.package_table[&self.root];
         let project_root = match &self.resolution_graph.build_options.install_dir {
@@ -170,6 +225,7 @@ impl BuildPlan {
             root_package.clone(),
             transitive_dependencies,
             &self.resolution_graph,
+            incremental,
             &mut compiler_driver,
         )?;
 
@@ -180,6 +236,15 @@ impl BuildPlan {
         Ok(compiled)
     }
 
+    /// Returns the dependencies and named addresses declared by the root package that are not
+    /// used by `compiled_package`, the result of compiling this plan.
+    pub fn unused_declarations(
+        &self,
+        compiled_package: &CompiledPackage,
+    ) -> Result<Vec<UnusedDeclaration>> {
+        find_unused_declarations(&self.resolution_graph, compiled_package)
+    }
+
     #[cfg(feature = "evm-backend")]
     pub fn compile_evm<W: Write>(&self, writer: &mut W) -> Result<()> {
         let root_package = &self.resolution_graph.package_table[&self.root];
@@ -412,7 +477,9 @@ impl BuildPlan {
     fn clean(build_root: &Path, keep_paths: BTreeSet<PackageName>) -> Result<()> {
         for dir in std::fs::read_dir(build_root)? {
             let path = dir?.path();
-            if !keep_paths.iter().any(|name| path.ends_with(name.as_str())) {
+            if !keep_paths.iter().any(|name| path.ends_with(name.as_str()))
+                && !path.ends_with(CompiledPackageLayout::IncrementalCache.path())
+            {
                 std::fs::remove_dir_all(&path)?;
             }
         }
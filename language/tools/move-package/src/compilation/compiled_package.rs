@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    compilation::package_layout::CompiledPackageLayout,
+    compilation::{incremental::compile_incrementally, package_layout::CompiledPackageLayout},
     resolution::resolution_graph::{Renaming, ResolvedGraph, ResolvedPackage, ResolvedTable},
     source_package::{
         layout::{SourcePackageLayout, REFERENCE_TEMPLATE_FILENAME},
@@ -15,12 +15,12 @@ use anyhow::{ensure, Result};
 use colored::Colorize;
 use move_abigen::{Abigen, AbigenOptions};
 use move_binary_format::file_format::{CompiledModule, CompiledScript};
-use move_bytecode_source_map::utils::source_map_from_file;
+use move_bytecode_source_map::{stack_trace::StackTraceSymbolizer, utils::source_map_from_file};
 use move_bytecode_utils::Modules;
 use move_command_line_common::{
     env::get_bytecode_version_from_env,
     files::{
-        extension_equals, find_filenames, MOVE_COMPILED_EXTENSION, MOVE_EXTENSION,
+        extension_equals, find_filenames, FileHash, MOVE_COMPILED_EXTENSION, MOVE_EXTENSION,
         SOURCE_MAP_EXTENSION,
     },
 };
@@ -33,7 +33,10 @@ use move_compiler::{
     Compiler,
 };
 use move_docgen::{Docgen, DocgenOptions};
-use move_model::{model::GlobalEnv, options::ModelBuilderOptions, run_model_builder_with_options};
+use move_model::{
+    model::GlobalEnv, options::ModelBuilderOptions,
+    run_model_builder_with_options_and_compilation_flags,
+};
 use move_symbol_pool::Symbol;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -50,6 +53,14 @@ pub enum CompilationCachingStatus {
     Recompiled,
 }
 
+/// The result of a compiler driver: the source files, the compiled units, and the files for which
+/// warnings were reported
+pub(crate) type CompilerDriverResult = (
+    FilesSourceText,
+    Vec<AnnotatedCompiledUnit>,
+    BTreeSet<FileHash>,
+);
+
 #[derive(Debug, Clone)]
 pub struct CompiledUnitWithSource {
     pub unit: CompiledUnit,
@@ -505,6 +516,25 @@ impl CompiledPackage {
             .filter(|unit| matches!(unit.unit, CompiledUnit::Script(_)))
     }
 
+    /// Returns a symbolizer for stack traces through the modules of this package and its
+    /// transitive dependencies. Source files which cannot be read are skipped, leaving the frames
+    /// in them without a source location.
+    pub fn stack_trace_symbolizer(&self) -> StackTraceSymbolizer<'_> {
+        let mut symbolizer = StackTraceSymbolizer::new();
+        for compiled_unit in self.all_compiled_units_with_source() {
+            if let CompiledUnit::Module(NamedCompiledModule {
+                module, source_map, ..
+            }) = &compiled_unit.unit
+            {
+                symbolizer.add_module(module, source_map);
+            }
+            if let Ok(contents) = std::fs::read_to_string(&compiled_unit.source_path) {
+                symbolizer.add_file(compiled_unit.source_path.to_string_lossy(), contents);
+            }
+        }
+        symbolizer
+    }
+
     #[allow(unused)]
     fn can_load_cached(
         package: &OnDiskCompiledPackage,
@@ -537,10 +567,8 @@ impl CompiledPackage {
             /* address mapping */ &ResolvedTable,
         )>,
         resolution_graph: &ResolvedGraph,
-        mut compiler_driver: impl FnMut(
-            Compiler,
-        )
-            -> Result<(FilesSourceText, Vec<AnnotatedCompiledUnit>)>,
+        incremental: bool,
+        mut compiler_driver: impl FnMut(Compiler) -> Result<CompilerDriverResult>,
     ) -> Result<CompiledPackage> {
         let immediate_dependencies = transitive_dependencies
             .iter()
@@ -574,41 +602,74 @@ impl CompiledPackage {
             Flags::testing()
         } else {
             Flags::empty()
-        };
+        }
+        .set_package_features(resolution_graph.package_features());
         // invoke the compiler
         let mut paths = deps_package_paths.clone();
         paths.push(sources_package_paths.clone());
 
-        let compiler = Compiler::from_package_paths(paths, vec![]).set_flags(flags);
-        let (file_map, all_compiled_units) = compiler_driver(compiler)?;
+        // Test mode needs the source of the `std::unit_test` module, while the cached units of the
+        // dependencies are only passed to the compiler as bytecode
+        let all_compiled_units = if incremental && !resolution_graph.build_options.test_mode {
+            compile_incrementally(
+                &project_root.join(CompiledPackageLayout::Root.path()),
+                &paths,
+                &flags,
+                resolution_graph.build_options.force_recompilation,
+                &mut compiler_driver,
+            )?
+        } else {
+            let compiler = Compiler::from_package_paths(paths, vec![]).set_flags(flags);
+            let (file_map, all_compiled_units, _) = compiler_driver(compiler)?;
+            all_compiled_units
+                .into_iter()
+                .map(|annot_unit| {
+                    let source_path =
+                        PathBuf::from(file_map[&annot_unit.loc().file_hash()].0.as_str());
+                    let package_name = match &annot_unit {
+                        compiled_unit::CompiledUnitEnum::Module(m) => {
+                            m.named_module.package_name.unwrap()
+                        }
+                        compiled_unit::CompiledUnitEnum::Script(s) => {
+                            s.named_script.package_name.unwrap()
+                        }
+                    };
+                    let unit = CompiledUnitWithSource {
+                        unit: annot_unit.into_compiled_unit(),
+                        source_path,
+                    };
+                    (package_name, unit)
+                })
+                .collect()
+        };
         let mut root_compiled_units = vec![];
         let mut deps_compiled_units = vec![];
-        for annot_unit in all_compiled_units {
-            let source_path = PathBuf::from(file_map[&annot_unit.loc().file_hash()].0.as_str());
-            let package_name = match &annot_unit {
-                compiled_unit::CompiledUnitEnum::Module(m) => m.named_module.package_name.unwrap(),
-                compiled_unit::CompiledUnitEnum::Script(s) => s.named_script.package_name.unwrap(),
-            };
-            let unit = CompiledUnitWithSource {
-                unit: annot_unit.into_compiled_unit(),
-                source_path,
-            };
+        for (package_name, unit) in all_compiled_units {
             if package_name == root_package_name {
                 root_compiled_units.push(unit)
             } else {
                 deps_compiled_units.push((package_name, unit))
             }
         }
+        // Record the upgrade policy of the package in its modules, for the VM to enforce it
+        if let Some(policy) = resolved_package.source_package.package.upgrade_policy {
+            for compiled_unit in &mut root_compiled_units {
+                if let CompiledUnit::Module(named_module) = &mut compiled_unit.unit {
+                    named_module.module.metadata.push(policy.to_metadata());
+                }
+            }
+        }
 
         let mut compiled_docs = None;
         let mut compiled_abis = None;
         if resolution_graph.build_options.generate_docs
             || resolution_graph.build_options.generate_abis
         {
-            let model = run_model_builder_with_options(
+            let model = run_model_builder_with_options_and_compilation_flags(
                 vec![sources_package_paths],
                 deps_package_paths,
                 ModelBuilderOptions::default(),
+                Flags::verification().set_package_features(resolution_graph.package_features()),
             )?;
 
             if resolution_graph.build_options.generate_docs {
@@ -0,0 +1,416 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Incremental compilation of a package and its dependencies.
+//!
+//! The compiled units of each source file are cached under the build directory, along with the
+//! hash of the file and the interface hashes of the modules its units depend on. On the next
+//! compilation, only the files that changed are recompiled, against the cached bytecode of the
+//! other modules. A file whose dependencies end up with a different interface is recompiled in
+//! turn, until the interfaces of all modules are consistent with what their dependents were
+//! compiled against.
+
+use crate::{
+    compilation::{
+        compiled_package::{CompiledUnitWithSource, CompilerDriverResult},
+        package_layout::CompiledPackageLayout,
+    },
+    source_package::parsed_manifest::PackageName,
+};
+use anyhow::{bail, Result};
+use move_binary_format::{
+    access::{ModuleAccess, ScriptAccess},
+    file_format::{CompiledModule, CompiledScript},
+};
+use move_bytecode_source_map::source_map::SourceMap;
+use move_command_line_common::files::FileHash;
+use move_compiler::{
+    compiled_unit::{CompiledUnit, NamedCompiledModule, NamedCompiledScript},
+    interface_generator::write_module_to_string,
+    shared::{Flags, NumberFormat, NumericalAddress, PackagePaths},
+    Compiler,
+};
+use move_core_types::language_storage::ModuleId;
+use move_symbol_pool::Symbol;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+/// Bumped whenever the format of the cache, or the way its entries are computed, changes
+const CACHE_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IncrementalCache {
+    /// Digest of the compilation settings the cached units were compiled with
+    settings_digest: String,
+    /// The cache entry of each source file, by path
+    files: BTreeMap<Symbol, CachedFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedFile {
+    package: PackageName,
+    source_hash: FileHash,
+    /// Whether warnings were reported for the file. Such files are always recompiled, so that
+    /// their warnings are reported again.
+    has_warnings: bool,
+    /// The interface hashes of the modules the units of this file depend on, at the time the file
+    /// was compiled. Modules defined in the file itself are not included.
+    dependencies: BTreeMap<ModuleId, String>,
+    /// The interface hashes of the modules defined in this file
+    interfaces: BTreeMap<ModuleId, String>,
+    units: Vec<CachedUnit>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedUnit {
+    is_module: bool,
+    name: Symbol,
+    bytecode: Vec<u8>,
+    source_map: Vec<u8>,
+}
+
+/// Compiles the files of `package_paths`, reusing the units cached under `build_root` by a
+/// previous compilation with the same `flags` for the files that did not change. Every file is
+/// compiled regardless of the cache if `force_recompilation` is set. Each compilation is performed by `compiler_driver`, which may be
+/// called several times, with only the files to recompile as targets.
+pub(crate) fn compile_incrementally(
+    build_root: &Path,
+    package_paths: &[PackagePaths],
+    flags: &Flags,
+    force_recompilation: bool,
+    mut compiler_driver: impl FnMut(Compiler) -> Result<CompilerDriverResult>,
+) -> Result<Vec<(PackageName, CompiledUnitWithSource)>> {
+    let cache_path = build_root
+        .join(CompiledPackageLayout::IncrementalCache.path())
+        .join(if flags.is_testing() { "test" } else { "build" })
+        .with_extension("bcs");
+    let settings_digest = settings_digest(package_paths, flags);
+
+    let mut sources = BTreeMap::new();
+    for package in package_paths {
+        let package_name = package.name.expect("packages are named");
+        for path in &package.paths {
+            let contents = std::fs::read_to_string(path.as_str())?;
+            sources.insert(*path, (package_name, FileHash::new(&contents)));
+        }
+    }
+
+    let cache = if force_recompilation {
+        IncrementalCache::default()
+    } else {
+        // An unreadable or outdated cache is only a missed opportunity to save work
+        std::fs::read(&cache_path)
+            .ok()
+            .and_then(|bytes| bcs::from_bytes::<IncrementalCache>(&bytes).ok())
+            .filter(|cache| cache.settings_digest == settings_digest)
+            .unwrap_or_default()
+    };
+    let mut entries: BTreeMap<Symbol, CachedFile> = cache
+        .files
+        .into_iter()
+        .filter(|(path, entry)| {
+            sources.get(path) == Some(&(entry.package, entry.source_hash)) && !entry.has_warnings
+        })
+        .collect();
+    let mut dirty: BTreeSet<Symbol> = sources
+        .keys()
+        .filter(|path| !entries.contains_key(*path))
+        .copied()
+        .collect();
+
+    let mut compiled = BTreeMap::new();
+    let mut rounds = 0;
+    loop {
+        // Files whose dependencies changed interface since they were compiled, or disappeared.
+        // The modules of the files about to be recompiled are not known yet: their dependents are
+        // checked against them after this round.
+        let interfaces: BTreeMap<&ModuleId, &String> = entries
+            .values()
+            .flat_map(|entry| entry.interfaces.iter())
+            .collect();
+        let stale: Vec<Symbol> = entries
+            .iter()
+            .filter(|(_, entry)| {
+                entry
+                    .dependencies
+                    .iter()
+                    .any(|(id, hash)| match interfaces.get(id) {
+                        Some(current) => *current != hash,
+                        None => dirty.is_empty(),
+                    })
+            })
+            .map(|(path, _)| *path)
+            .collect();
+        for path in stale {
+            entries.remove(&path);
+            dirty.insert(path);
+        }
+        if dirty.is_empty() {
+            break;
+        }
+        rounds += 1;
+        if rounds > sources.len() + 1 {
+            bail!("IPE: incremental compilation did not reach a fixed point")
+        }
+
+        let (recompiled, files_with_warnings) =
+            compile_round(package_paths, &dirty, &entries, flags, &mut compiler_driver)?;
+        for path in std::mem::take(&mut dirty) {
+            let (package, source_hash) = sources[&path];
+            let units = recompiled.get(&path).map(Vec::as_slice).unwrap_or(&[]);
+            let entry = CachedFile {
+                package,
+                source_hash,
+                has_warnings: files_with_warnings.contains(&path),
+                dependencies: BTreeMap::new(),
+                interfaces: units
+                    .iter()
+                    .filter_map(|unit| match unit {
+                        CompiledUnit::Module(m) => Some(&m.module),
+                        CompiledUnit::Script(_) => None,
+                    })
+                    .map(|module| Ok((module.self_id(), interface_hash(module)?)))
+                    .collect::<Result<_>>()?,
+                units: units.iter().map(CachedUnit::new).collect::<Result<_>>()?,
+            };
+            entries.insert(path, entry);
+        }
+        let interfaces: BTreeMap<ModuleId, String> = entries
+            .values()
+            .flat_map(|entry| entry.interfaces.clone())
+            .collect();
+        for (path, units) in &recompiled {
+            let entry = entries.get_mut(path).unwrap();
+            entry.dependencies = unit_dependencies(units)
+                .into_iter()
+                .filter(|id| !entry.interfaces.contains_key(id))
+                .map(|id| {
+                    let hash = interfaces.get(&id).cloned().unwrap_or_default();
+                    (id, hash)
+                })
+                .collect();
+        }
+        compiled.extend(recompiled);
+    }
+
+    let mut result = vec![];
+    for (path, entry) in &entries {
+        let source_path = PathBuf::from(path.as_str());
+        let units = match compiled.remove(path) {
+            Some(units) => units,
+            None => entry
+                .units
+                .iter()
+                .map(|unit| unit.decode(entry.package))
+                .collect::<Result<_>>()?,
+        };
+        result.extend(units.into_iter().map(|unit| {
+            (
+                entry.package,
+                CompiledUnitWithSource {
+                    unit,
+                    source_path: source_path.clone(),
+                },
+            )
+        }));
+    }
+
+    let cache = IncrementalCache {
+        settings_digest,
+        files: entries,
+    };
+    std::fs::create_dir_all(cache_path.parent().unwrap())?;
+    std::fs::write(&cache_path, bcs::to_bytes(&cache)?)?;
+    Ok(in_dependency_order(result))
+}
+
+/// Orders `units` like the compiler does: modules after the modules they depend on, followed by
+/// scripts
+fn in_dependency_order(
+    units: Vec<(PackageName, CompiledUnitWithSource)>,
+) -> Vec<(PackageName, CompiledUnitWithSource)> {
+    fn visit(
+        id: &ModuleId,
+        modules: &mut BTreeMap<ModuleId, (PackageName, CompiledUnitWithSource)>,
+        sorted: &mut Vec<(PackageName, CompiledUnitWithSource)>,
+    ) {
+        if let Some(unit) = modules.remove(id) {
+            if let CompiledUnit::Module(m) = &unit.1.unit {
+                for dep in m.module.immediate_dependencies() {
+                    visit(&dep, modules, sorted)
+                }
+            }
+            sorted.push(unit)
+        }
+    }
+
+    let (modules, scripts): (Vec<_>, Vec<_>) = units
+        .into_iter()
+        .partition(|(_, unit)| matches!(unit.unit, CompiledUnit::Module(_)));
+    let mut modules: BTreeMap<_, _> = modules
+        .into_iter()
+        .map(|unit| match &unit.1.unit {
+            CompiledUnit::Module(m) => (m.module.self_id(), unit),
+            CompiledUnit::Script(_) => unreachable!(),
+        })
+        .collect();
+    let ids: Vec<_> = modules.keys().cloned().collect();
+    let mut sorted = vec![];
+    for id in &ids {
+        visit(id, &mut modules, &mut sorted)
+    }
+    sorted.extend(scripts);
+    sorted
+}
+
+/// Compiles the files in `dirty` against the cached modules of `entries`, returning the compiled
+/// units of each file, and the files for which warnings were reported
+fn compile_round(
+    package_paths: &[PackagePaths],
+    dirty: &BTreeSet<Symbol>,
+    entries: &BTreeMap<Symbol, CachedFile>,
+    flags: &Flags,
+    compiler_driver: &mut impl FnMut(Compiler) -> Result<CompilerDriverResult>,
+) -> Result<(BTreeMap<Symbol, Vec<CompiledUnit>>, BTreeSet<Symbol>)> {
+    let targets = package_paths
+        .iter()
+        .map(|package| PackagePaths {
+            name: package.name,
+            paths: package
+                .paths
+                .iter()
+                .filter(|path| dirty.contains(path))
+                .copied()
+                .collect(),
+            named_address_map: package.named_address_map.clone(),
+        })
+        .filter(|package| !package.paths.is_empty())
+        .collect();
+
+    // The cached modules are passed to the compiler as bytecode dependencies
+    let deps_dir = tempfile::tempdir()?;
+    let bytecode_dir = deps_dir.path().join("modules");
+    std::fs::create_dir_all(&bytecode_dir)?;
+    for unit in entries.values().flat_map(|entry| &entry.units) {
+        if unit.is_module {
+            let id = CompiledModule::deserialize(&unit.bytecode)?.self_id();
+            std::fs::write(
+                bytecode_dir.join(format!("{}_{}.mv", id.address().to_hex(), id.name())),
+                &unit.bytecode,
+            )?;
+        }
+    }
+    let deps = vec![PackagePaths {
+        name: None,
+        paths: vec![Symbol::from(bytecode_dir.to_string_lossy().as_ref())],
+        named_address_map: BTreeMap::new(),
+    }];
+
+    let compiler = Compiler::from_package_paths(targets, deps)
+        .set_flags(flags.clone())
+        .set_interface_files_dir(
+            deps_dir
+                .path()
+                .join("interfaces")
+                .to_string_lossy()
+                .to_string(),
+        );
+    let (file_map, units, files_with_warnings) = compiler_driver(compiler)?;
+    let mut compiled: BTreeMap<Symbol, Vec<CompiledUnit>> = BTreeMap::new();
+    for unit in units {
+        let path = file_map[&unit.loc().file_hash()].0;
+        compiled
+            .entry(path)
+            .or_default()
+            .push(unit.into_compiled_unit());
+    }
+    let files_with_warnings = files_with_warnings
+        .iter()
+        .filter_map(|hash| file_map.get(hash))
+        .map(|(path, _)| *path)
+        .collect();
+    Ok((compiled, files_with_warnings))
+}
+
+/// The digest of everything besides the sources that affects the compilation of a file: the
+/// compiler flags, and the packages with their named address assignments
+fn settings_digest(package_paths: &[PackagePaths], flags: &Flags) -> String {
+    let packages: Vec<_> = package_paths
+        .iter()
+        .map(|package| (package.name, &package.named_address_map))
+        .collect();
+    let settings = format!("{}:{:?}:{:?}", CACHE_FORMAT_VERSION, flags, packages);
+    format!("{:X}", Sha256::digest(settings.as_bytes()))
+}
+
+/// The hash of the interface of `module`, i.e., of everything about it that its dependents can
+/// rely on
+fn interface_hash(module: &CompiledModule) -> Result<String> {
+    let (_, interface) = write_module_to_string(&BTreeMap::<ModuleId, String>::new(), module)?;
+    Ok(format!("{:X}", Sha256::digest(interface.as_bytes())))
+}
+
+/// The modules used or befriended by `units`
+fn unit_dependencies(units: &[CompiledUnit]) -> BTreeSet<ModuleId> {
+    units
+        .iter()
+        .flat_map(|unit| match unit {
+            CompiledUnit::Module(m) => {
+                let mut deps = m.module.immediate_dependencies();
+                deps.extend(m.module.immediate_friends());
+                deps
+            }
+            CompiledUnit::Script(s) => s.script.immediate_dependencies(),
+        })
+        .collect()
+}
+
+impl CachedUnit {
+    fn new(unit: &CompiledUnit) -> Result<Self> {
+        let mut bytecode = vec![];
+        let (is_module, name, source_map) = match unit {
+            CompiledUnit::Module(m) => {
+                m.module.serialize(&mut bytecode)?;
+                (true, m.name, &m.source_map)
+            }
+            CompiledUnit::Script(s) => {
+                s.script.serialize(&mut bytecode)?;
+                (false, s.name, &s.source_map)
+            }
+        };
+        Ok(Self {
+            is_module,
+            name,
+            bytecode,
+            source_map: bcs::to_bytes(source_map)?,
+        })
+    }
+
+    fn decode(&self, package_name: PackageName) -> Result<CompiledUnit> {
+        let source_map: SourceMap = bcs::from_bytes(&self.source_map)?;
+        Ok(if self.is_module {
+            let module = CompiledModule::deserialize(&self.bytecode)?;
+            CompiledUnit::Module(NamedCompiledModule {
+                package_name: Some(package_name),
+                address: NumericalAddress::new(
+                    module.self_id().address().into_bytes(),
+                    NumberFormat::Hex,
+                ),
+                name: self.name,
+                module,
+                source_map,
+            })
+        } else {
+            CompiledUnit::Script(NamedCompiledScript {
+                package_name: Some(package_name),
+                name: self.name,
+                script: CompiledScript::deserialize(&self.bytecode)?,
+                source_map,
+            })
+        })
+    }
+}
@@ -4,5 +4,7 @@
 
 pub mod build_plan;
 pub mod compiled_package;
+mod incremental;
 pub mod model_builder;
 pub mod package_layout;
+pub mod unused_declarations;
@@ -7,8 +7,11 @@ use crate::{
     resolution::resolution_graph::ResolvedGraph, ModelConfig,
 };
 use anyhow::Result;
-use move_compiler::shared::PackagePaths;
-use move_model::{model::GlobalEnv, options::ModelBuilderOptions, run_model_builder_with_options};
+use move_compiler::shared::{Flags, PackagePaths};
+use move_model::{
+    model::GlobalEnv, options::ModelBuilderOptions,
+    run_model_builder_with_options_and_compilation_flags,
+};
 
 #[derive(Debug, Clone)]
 pub struct ModelBuilder {
@@ -103,6 +106,11 @@ impl ModelBuilder {
             None => (all_targets, all_deps),
         };
 
-        run_model_builder_with_options(all_targets, all_deps, ModelBuilderOptions::default())
+        run_model_builder_with_options_and_compilation_flags(
+            all_targets,
+            all_deps,
+            ModelBuilderOptions::default(),
+            Flags::verification().set_package_features(self.resolution_graph.package_features()),
+        )
     }
 }
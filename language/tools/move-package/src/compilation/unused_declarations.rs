@@ -0,0 +1,219 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Detection of the dependencies and named addresses declared in the manifest of the root package
+//! which the package does not use.
+
+use crate::{
+    compilation::compiled_package::CompiledPackage,
+    resolution::resolution_graph::ResolvedGraph,
+    source_package::{layout::SourcePackageLayout, parsed_manifest::PackageName},
+};
+use anyhow::Result;
+use move_binary_format::access::{ModuleAccess, ScriptAccess};
+use move_command_line_common::files::FileHash;
+use move_compiler::{
+    compiled_unit::CompiledUnitEnum,
+    parser::lexer::{Lexer, Tok},
+};
+use move_core_types::language_storage::ModuleId;
+use move_symbol_pool::Symbol;
+use std::{collections::BTreeSet, fmt, fs, path::PathBuf};
+
+const DEPENDENCIES_SECTION: &str = "dependencies";
+const DEV_DEPENDENCIES_SECTION: &str = "dev-dependencies";
+const ADDRESSES_SECTION: &str = "addresses";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnusedDeclarationKind {
+    /// A dependency none of whose modules (or modules of its own dependencies which are not
+    /// dependencies of the package) are referenced by the package
+    Dependency,
+    /// A named address which is not used in any source file of the package, and which does not
+    /// instantiate an address of a dependency either
+    NamedAddress,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnusedDeclaration {
+    pub kind: UnusedDeclarationKind,
+    pub name: Symbol,
+    pub manifest_path: PathBuf,
+    /// The (1-based) line of the declaration in the manifest, if it could be found
+    pub line: Option<usize>,
+}
+
+impl fmt::Display for UnusedDeclaration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = match self.kind {
+            UnusedDeclarationKind::Dependency => "dependency",
+            UnusedDeclarationKind::NamedAddress => "named address",
+        };
+        write!(f, "{}", self.manifest_path.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+        }
+        write!(f, ": unused {} '{}'", what, self.name)
+    }
+}
+
+/// Returns the dependencies and named addresses declared in the manifest of the root package of
+/// `resolved_graph` that are not used by `compiled_package`, its compilation. Dependencies that
+/// are only used by test code are reported unless `compiled_package` was compiled in test mode.
+/// Dev dependencies are only reported in test mode, as they are meant to be used by test code.
+pub fn find_unused_declarations(
+    resolved_graph: &ResolvedGraph,
+    compiled_package: &CompiledPackage,
+) -> Result<Vec<UnusedDeclaration>> {
+    let root_name = resolved_graph.root_package.package.name;
+    let root_package = resolved_graph.get_package(&root_name);
+    let manifest_path = resolved_graph
+        .root_package_path
+        .join(SourcePackageLayout::Manifest.path());
+    let manifest = fs::read_to_string(&manifest_path)?;
+    let mut unused = vec![];
+
+    let used_packages = referenced_packages(compiled_package);
+    let immediate_dependencies = root_package.immediate_dependencies(resolved_graph);
+    let mut sections = vec![(
+        DEPENDENCIES_SECTION,
+        &root_package.source_package.dependencies,
+    )];
+    if resolved_graph.build_options.test_mode {
+        sections.push((
+            DEV_DEPENDENCIES_SECTION,
+            &root_package.source_package.dev_dependencies,
+        ));
+    }
+    for (section, dep_name) in sections
+        .into_iter()
+        .flat_map(|(section, deps)| deps.keys().map(move |dep_name| (section, dep_name)))
+    {
+        if !immediate_dependencies.contains(dep_name) {
+            // A disabled optional dependency, or a dev dependency outside of dev mode
+            continue;
+        }
+        let dep_package = resolved_graph.get_package(dep_name);
+        let mut provided = dep_package.transitive_dependencies(resolved_graph);
+        // Packages the root package depends on directly are accounted to their own declaration
+        provided.retain(|name| !immediate_dependencies.contains(name));
+        provided.insert(*dep_name);
+        if provided.is_disjoint(&used_packages) {
+            unused.push(UnusedDeclaration {
+                kind: UnusedDeclarationKind::Dependency,
+                name: *dep_name,
+                manifest_path: manifest_path.clone(),
+                line: manifest_entry_line(&manifest, section, dep_name.as_str()),
+            });
+        }
+    }
+
+    let used_addresses = referenced_addresses(
+        &root_package
+            .get_sources(&resolved_graph.build_options)?
+            .into_iter()
+            .map(|file| fs::read_to_string(file.as_str()))
+            .collect::<std::io::Result<Vec<_>>>()?,
+    );
+    for name in root_package
+        .source_package
+        .addresses
+        .iter()
+        .flat_map(|addresses| addresses.keys())
+    {
+        let from_dependency = immediate_dependencies.iter().any(|dep| {
+            resolved_graph
+                .dependency_exports(
+                    *dep,
+                    resolved_graph.dependency_substitution(root_name, *dep),
+                )
+                .contains_key(name)
+        });
+        if !from_dependency && !used_addresses.contains(name) {
+            unused.push(UnusedDeclaration {
+                kind: UnusedDeclarationKind::NamedAddress,
+                name: *name,
+                manifest_path: manifest_path.clone(),
+                line: manifest_entry_line(&manifest, ADDRESSES_SECTION, name.as_str()),
+            });
+        }
+    }
+
+    Ok(unused)
+}
+
+/// The dependency packages defining the modules referenced by the root modules and scripts of
+/// `compiled_package`
+fn referenced_packages(compiled_package: &CompiledPackage) -> BTreeSet<PackageName> {
+    let referenced_modules: BTreeSet<ModuleId> = compiled_package
+        .root_compiled_units
+        .iter()
+        .flat_map(|unit| match &unit.unit {
+            CompiledUnitEnum::Module(m) => {
+                let mut deps = m.module.immediate_dependencies();
+                deps.extend(m.module.immediate_friends());
+                deps
+            }
+            CompiledUnitEnum::Script(s) => s.script.immediate_dependencies(),
+        })
+        .collect();
+    compiled_package
+        .deps_compiled_units
+        .iter()
+        .filter_map(|(package_name, unit)| match &unit.unit {
+            CompiledUnitEnum::Module(m) if referenced_modules.contains(&m.module.self_id()) => {
+                Some(*package_name)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The names that are used as named addresses in `sources`: in `@name`, `name::..` and
+/// `address name { .. }`
+fn referenced_addresses(sources: &[String]) -> BTreeSet<Symbol> {
+    let mut names = BTreeSet::new();
+    for source in sources {
+        let mut lexer = Lexer::new(source, FileHash::new(source));
+        let mut previous = (Tok::EOF, "");
+        // Files which do not lex are reported by the compiler
+        while lexer.advance().is_ok() && lexer.peek() != Tok::EOF {
+            if lexer.peek() == Tok::Identifier {
+                let is_address = match previous {
+                    (Tok::AtSign, _) => true,
+                    (Tok::Identifier, "address") => true,
+                    _ => matches!(lexer.lookahead(), Ok(Tok::ColonColon)),
+                };
+                if is_address {
+                    names.insert(Symbol::from(lexer.content()));
+                }
+            }
+            previous = (lexer.peek(), lexer.content());
+        }
+    }
+    names
+}
+
+/// Returns the (1-based) line of the entry for `key` in `section` of the manifest `manifest`,
+/// either as `key = ..` in `[section]`, or as a `[section.key]` table.
+fn manifest_entry_line(manifest: &str, section: &str, key: &str) -> Option<usize> {
+    let unquote = |s: &str| s.trim().trim_matches('"').to_string();
+    let mut current_section = String::new();
+    for (idx, line) in manifest.lines().enumerate() {
+        let line = line.trim();
+        if let Some(header) = line.strip_prefix('[') {
+            let header = header.split(']').next().unwrap_or_default();
+            if header.split('.').map(unquote).collect::<Vec<_>>() == [section, key] {
+                return Some(idx + 1);
+            }
+            current_section = unquote(header);
+        } else if current_section == section {
+            if let Some((entry, _)) = line.split_once('=') {
+                if unquote(entry) == key {
+                    return Some(idx + 1);
+                }
+            }
+        }
+    }
+    None
+}
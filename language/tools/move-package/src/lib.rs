@@ -25,6 +25,7 @@ use std::{
 use crate::{
     compilation::{
         build_plan::BuildPlan, compiled_package::CompiledPackage, model_builder::ModelBuilder,
+        unused_declarations::UnusedDeclaration,
     },
     package_lock::PackageLock,
     resolution::resolution_graph::{ResolutionGraph, ResolvedGraph},
@@ -134,6 +135,16 @@ pub struct BuildConfig {
     /// Skip fetching latest git dependencies
     #[clap(long = "skip-fetch-latest-git-deps", global = true)]
     pub skip_fetch_latest_git_deps: bool,
+
+    /// Comma separated list of features to enable in the root package
+    #[clap(long = "features", global = true, use_value_delimiter = true)]
+    #[serde(default)]
+    pub features: Vec<String>,
+
+    /// Do not enable the 'default' feature of the root package
+    #[clap(long = "no-default-features", global = true)]
+    #[serde(default)]
+    pub no_default_features: bool,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, PartialOrd)]
@@ -156,6 +167,24 @@ impl BuildConfig {
         ret
     }
 
+    /// Compile the package at `path` or the containing Move package like `compile_package`, and
+    /// return the dependencies and named addresses declared in its manifest which it does not use.
+    pub fn compile_package_and_find_unused<W: Write>(
+        self,
+        path: &Path,
+        writer: &mut W,
+    ) -> Result<(CompiledPackage, Vec<UnusedDeclaration>)> {
+        let resolved_graph = self.resolution_graph_for_package(path, writer)?;
+        let mutx = PackageLock::lock();
+        let build_plan = BuildPlan::create(resolved_graph)?;
+        let ret = build_plan.compile(writer).and_then(|compiled_package| {
+            let unused = build_plan.unused_declarations(&compiled_package)?;
+            Ok((compiled_package, unused))
+        });
+        mutx.unlock();
+        ret
+    }
+
     /// Compile the package at `path` or the containing Move package. Do not exit process on warning
     /// or failure.
     pub fn compile_package_no_exit<W: Write>(
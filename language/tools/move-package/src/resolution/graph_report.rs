@@ -0,0 +1,495 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reports over a resolved package graph: the dependency tree (optionally inverted) and where each
+//! named address in scope for a package comes from.
+
+use crate::{
+    resolution::resolution_graph::{ResolvedGraph, ResolvedPackage},
+    source_package::parsed_manifest::{
+        FeatureName, NamedAddress, PackageName, SubstOrRename, Substitution,
+    },
+};
+use anyhow::{bail, Result};
+use move_core_types::account_address::AccountAddress;
+use ptree::TreeBuilder;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    io::Write,
+};
+
+/// A node in the dependency tree of a package graph.
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyTreeNode {
+    pub package: PackageName,
+    pub version: String,
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub features: BTreeSet<FeatureName>,
+    /// The address renamings and assignments declared on the edge between this package and its
+    /// parent in the tree
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub substitutions: Vec<EdgeSubstitution>,
+    /// Set if the children of this package are already listed earlier in the tree
+    pub duplicate: bool,
+    pub children: Vec<DependencyTreeNode>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EdgeSubstitution {
+    /// `name = "from"`: the dependency's address `from` is known as `name` in the dependent
+    Rename {
+        name: NamedAddress,
+        from: NamedAddress,
+    },
+    /// `name = "0x.."`: the dependent assigns a value to the dependency's address `name`
+    Assign {
+        name: NamedAddress,
+        value: AccountAddress,
+    },
+}
+
+/// The named addresses in scope for a package.
+#[derive(Debug, Clone, Serialize)]
+pub struct PackageAddresses {
+    pub package: PackageName,
+    pub addresses: Vec<NamedAddressInfo>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NamedAddressInfo {
+    pub name: NamedAddress,
+    pub value: AccountAddress,
+    pub origin: NamedAddressOrigin,
+    /// Everywhere in the package graph a value is given to this address (or to an address it is
+    /// unified with)
+    pub assigned_by: Vec<AddressAssignment>,
+}
+
+/// How a named address comes to be in scope for a package.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NamedAddressOrigin {
+    /// Declared in the `[addresses]` section of the package
+    Declared,
+    /// Imported from a dependency under a new name
+    Renamed {
+        package: PackageName,
+        from: NamedAddress,
+    },
+    /// Inherited unchanged from one or more dependencies
+    Inherited { packages: Vec<PackageName> },
+    /// Added by the build configuration
+    BuildConfig,
+}
+
+/// Where a value is given to a named address.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AddressAssignment {
+    /// In the `[addresses]` section of `package`
+    Addresses { package: PackageName },
+    /// In the `[dev-addresses]` section of `package`
+    DevAddresses { package: PackageName },
+    /// In the substitution `package` declares for its dependency `dependency`
+    Substitution {
+        package: PackageName,
+        dependency: PackageName,
+    },
+    /// By the build configuration
+    BuildConfig,
+}
+
+impl ResolvedGraph {
+    /// Returns the dependency tree of the root package, cut off below `max_depth` if given.
+    pub fn dependency_tree(&self, max_depth: Option<usize>) -> DependencyTreeNode {
+        let root = self.root_package.package.name;
+        self.tree_node(
+            root,
+            None,
+            max_depth,
+            &mut BTreeSet::new(),
+            &|pkg: &ResolvedPackage| pkg.immediate_dependencies(self).into_iter().collect(),
+        )
+    }
+
+    /// Returns the tree of the packages depending on `package`, up to the root package, cut off
+    /// below `max_depth` if given.
+    pub fn reverse_dependency_tree(
+        &self,
+        package: PackageName,
+        max_depth: Option<usize>,
+    ) -> Result<DependencyTreeNode> {
+        if !self.package_table.contains_key(&package) {
+            bail!(
+                "Package '{}' is not part of the package graph of '{}'",
+                package,
+                self.root_package.package.name
+            )
+        }
+        Ok(self.tree_node(
+            package,
+            None,
+            max_depth,
+            &mut BTreeSet::new(),
+            &|pkg: &ResolvedPackage| self.immediate_dependents(pkg.source_package.package.name),
+        ))
+    }
+
+    /// Returns, for every package in the graph, the named addresses in scope for it and where they
+    /// come from.
+    pub fn named_address_report(&self) -> Vec<PackageAddresses> {
+        let assignments = self.address_assignments();
+        self.package_table
+            .iter()
+            .map(|(package_name, pkg)| PackageAddresses {
+                package: *package_name,
+                addresses: pkg
+                    .resolution_table
+                    .iter()
+                    .map(|(name, value)| NamedAddressInfo {
+                        name: *name,
+                        value: *value,
+                        origin: self.address_origin(pkg, *name),
+                        assigned_by: assignments
+                            .get(&(*package_name, *name))
+                            .cloned()
+                            .unwrap_or_default(),
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
+    fn tree_node(
+        &self,
+        package: PackageName,
+        parent: Option<PackageName>,
+        max_depth: Option<usize>,
+        seen: &mut BTreeSet<PackageName>,
+        children_of: &dyn Fn(&ResolvedPackage) -> Vec<PackageName>,
+    ) -> DependencyTreeNode {
+        let pkg = self.get_package(&package);
+        let (major, minor, patch) = pkg.source_package.package.version;
+        let substitutions = parent
+            .map(|parent| self.edge_substitutions(parent, package))
+            .unwrap_or_default();
+        let children = children_of(pkg);
+        let duplicate = !children.is_empty() && seen.contains(&package);
+        let children = if duplicate || max_depth == Some(0) {
+            vec![]
+        } else {
+            seen.insert(package);
+            children
+                .into_iter()
+                .map(|child| {
+                    self.tree_node(
+                        child,
+                        Some(package),
+                        max_depth.map(|depth| depth - 1),
+                        seen,
+                        children_of,
+                    )
+                })
+                .collect()
+        };
+        DependencyTreeNode {
+            package,
+            version: format!("{}.{}.{}", major, minor, patch),
+            features: pkg.enabled_features.clone(),
+            substitutions,
+            duplicate,
+            children,
+        }
+    }
+
+    /// The packages that directly depend on `package`
+    fn immediate_dependents(&self, package: PackageName) -> Vec<PackageName> {
+        self.package_table
+            .iter()
+            .filter(|(_, pkg)| pkg.immediate_dependencies(self).contains(&package))
+            .map(|(name, _)| *name)
+            .collect()
+    }
+
+    /// The substitution declared on the dependency edge between `a` and `b`, in whichever
+    /// direction it exists.
+    fn edge_substitutions(&self, a: PackageName, b: PackageName) -> Vec<EdgeSubstitution> {
+        let subst = self
+            .dependency_substitution(a, b)
+            .or_else(|| self.dependency_substitution(b, a));
+        subst
+            .into_iter()
+            .flatten()
+            .map(|(name, subst)| match subst {
+                SubstOrRename::RenameFrom(from) => EdgeSubstitution::Rename {
+                    name: *name,
+                    from: *from,
+                },
+                SubstOrRename::Assign(value) => EdgeSubstitution::Assign {
+                    name: *name,
+                    value: *value,
+                },
+            })
+            .collect()
+    }
+
+    pub(crate) fn dependency_substitution(
+        &self,
+        package: PackageName,
+        dependency: PackageName,
+    ) -> Option<&Substitution> {
+        let manifest = &self.get_package(&package).source_package;
+        manifest
+            .dependencies
+            .get(&dependency)
+            .or_else(|| {
+                if self.build_options.dev_mode {
+                    manifest.dev_dependencies.get(&dependency)
+                } else {
+                    None
+                }
+            })
+            .and_then(|dep| dep.subst.as_ref())
+    }
+
+    fn address_origin(&self, pkg: &ResolvedPackage, name: NamedAddress) -> NamedAddressOrigin {
+        let package = pkg.source_package.package.name;
+        if pkg
+            .source_package
+            .addresses
+            .as_ref()
+            .map_or(false, |addrs| addrs.contains_key(&name))
+        {
+            return NamedAddressOrigin::Declared;
+        }
+        if self
+            .build_options
+            .additional_named_addresses
+            .contains_key(name.as_str())
+        {
+            return NamedAddressOrigin::BuildConfig;
+        }
+        let mut inherited_from = vec![];
+        for dep in pkg.immediate_dependencies(self) {
+            let subst = self.dependency_substitution(package, dep);
+            if let Some(SubstOrRename::RenameFrom(from)) = subst.and_then(|s| s.get(&name)) {
+                return NamedAddressOrigin::Renamed {
+                    package: dep,
+                    from: *from,
+                };
+            }
+            if self.dependency_exports(dep, subst).contains_key(&name) {
+                inherited_from.push(dep);
+            }
+        }
+        NamedAddressOrigin::Inherited {
+            packages: inherited_from,
+        }
+    }
+
+    /// The names under which the addresses of `dependency` are in scope for a package using the
+    /// substitution `subst` for it, mapped to their names in `dependency`.
+    pub(crate) fn dependency_exports(
+        &self,
+        dependency: PackageName,
+        subst: Option<&Substitution>,
+    ) -> BTreeMap<NamedAddress, NamedAddress> {
+        let mut exports: BTreeMap<_, _> = self
+            .get_package(&dependency)
+            .resolution_table
+            .keys()
+            .map(|name| (*name, *name))
+            .collect();
+        for (name, subst) in subst.into_iter().flatten() {
+            if let SubstOrRename::RenameFrom(from) = subst {
+                exports.remove(from);
+                exports.insert(*name, *from);
+            }
+        }
+        exports
+    }
+
+    /// Computes, for every named address of every package, where values are given to it. Named
+    /// addresses are unified along dependency edges (taking renamings into account), so the
+    /// assignments of an address are those of all addresses it is connected to.
+    fn address_assignments(&self) -> BTreeMap<(PackageName, NamedAddress), Vec<AddressAssignment>> {
+        type Node = (PackageName, NamedAddress);
+        let root = self.root_package.package.name;
+        let mut edges: BTreeMap<Node, BTreeSet<Node>> = BTreeMap::new();
+        let mut direct: BTreeMap<Node, BTreeSet<AddressAssignment>> = BTreeMap::new();
+
+        for (package, pkg) in &self.package_table {
+            let manifest = &pkg.source_package;
+            for (name, value) in manifest.addresses.iter().flatten() {
+                if value.is_some() {
+                    direct
+                        .entry((*package, *name))
+                        .or_default()
+                        .insert(AddressAssignment::Addresses { package: *package });
+                }
+            }
+            if self.build_options.dev_mode && *package == root {
+                for name in manifest
+                    .dev_address_assignments
+                    .iter()
+                    .flatten()
+                    .map(|(n, _)| n)
+                {
+                    direct
+                        .entry((*package, *name))
+                        .or_default()
+                        .insert(AddressAssignment::DevAddresses { package: *package });
+                }
+            }
+            for name in self.build_options.additional_named_addresses.keys() {
+                direct
+                    .entry((*package, NamedAddress::from(name.as_str())))
+                    .or_default()
+                    .insert(AddressAssignment::BuildConfig);
+            }
+            for dep in pkg.immediate_dependencies(self) {
+                let subst = self.dependency_substitution(*package, dep);
+                for (name, subst) in subst.into_iter().flatten() {
+                    if let SubstOrRename::Assign(_) = subst {
+                        direct.entry((dep, *name)).or_default().insert(
+                            AddressAssignment::Substitution {
+                                package: *package,
+                                dependency: dep,
+                            },
+                        );
+                    }
+                }
+                for (name, dep_name) in self.dependency_exports(dep, subst) {
+                    edges
+                        .entry((*package, name))
+                        .or_default()
+                        .insert((dep, dep_name));
+                    edges
+                        .entry((dep, dep_name))
+                        .or_default()
+                        .insert((*package, name));
+                }
+            }
+        }
+
+        let mut result = BTreeMap::new();
+        for (package, pkg) in &self.package_table {
+            for name in pkg.resolution_table.keys() {
+                let start = (*package, *name);
+                if result.contains_key(&start) {
+                    continue;
+                }
+                // Collect the connected component of `start`, and the assignments within it
+                let mut component = BTreeSet::from([start]);
+                let mut queue = VecDeque::from([start]);
+                let mut assignments = BTreeSet::new();
+                while let Some(node) = queue.pop_front() {
+                    assignments.extend(direct.get(&node).into_iter().flatten().cloned());
+                    for next in edges.get(&node).into_iter().flatten() {
+                        if component.insert(*next) {
+                            queue.push_back(*next);
+                        }
+                    }
+                }
+                let assignments: Vec<_> = assignments.into_iter().collect();
+                for node in component {
+                    result.insert(node, assignments.clone());
+                }
+            }
+        }
+        result
+    }
+}
+
+impl DependencyTreeNode {
+    pub fn print<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let mut tree = TreeBuilder::new(self.label());
+        self.build_tree(&mut tree);
+        ptree::write_tree(&tree.build(), writer)?;
+        Ok(())
+    }
+
+    fn build_tree(&self, tree: &mut TreeBuilder) {
+        for child in &self.children {
+            tree.begin_child(child.label());
+            child.build_tree(tree);
+            tree.end_child();
+        }
+    }
+
+    fn label(&self) -> String {
+        let mut label = format!("{} v{}", self.package, self.version);
+        if !self.features.is_empty() {
+            let features: Vec<_> = self.features.iter().map(|f| f.as_str()).collect();
+            label.push_str(&format!(" [features: {}]", features.join(", ")));
+        }
+        if !self.substitutions.is_empty() {
+            let substitutions: Vec<_> = self
+                .substitutions
+                .iter()
+                .map(|subst| match subst {
+                    EdgeSubstitution::Rename { name, from } => format!("{} = {}", name, from),
+                    EdgeSubstitution::Assign { name, value } => {
+                        format!("{} = 0x{}", name, value.short_str_lossless())
+                    }
+                })
+                .collect();
+            label.push_str(&format!(" {{ {} }}", substitutions.join(", ")));
+        }
+        if self.duplicate {
+            label.push_str(" (*)");
+        }
+        label
+    }
+}
+
+impl PackageAddresses {
+    pub fn print<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writeln!(writer, "{}", self.package)?;
+        for info in &self.addresses {
+            let origin = match &info.origin {
+                NamedAddressOrigin::Declared => "declared".to_string(),
+                NamedAddressOrigin::Renamed { package, from } => {
+                    format!("renamed from '{}' in {}", from, package)
+                }
+                NamedAddressOrigin::Inherited { packages } => format!(
+                    "inherited from {}",
+                    packages
+                        .iter()
+                        .map(|p| p.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                NamedAddressOrigin::BuildConfig => "added by the build configuration".to_string(),
+            };
+            let assigned_by = info
+                .assigned_by
+                .iter()
+                .map(|assignment| match assignment {
+                    AddressAssignment::Addresses { package } => {
+                        format!("[addresses] of {}", package)
+                    }
+                    AddressAssignment::DevAddresses { package } => {
+                        format!("[dev-addresses] of {}", package)
+                    }
+                    AddressAssignment::Substitution {
+                        package,
+                        dependency,
+                    } => format!("substitution for {} in {}", dependency, package),
+                    AddressAssignment::BuildConfig => "build configuration".to_string(),
+                })
+                .collect::<Vec<_>>();
+            writeln!(
+                writer,
+                "  {} = 0x{} ({}; assigned by {})",
+                info.name,
+                info.value.short_str_lossless(),
+                origin,
+                assigned_by.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
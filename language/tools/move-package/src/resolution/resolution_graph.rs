@@ -9,8 +9,8 @@ use crate::{
         layout::SourcePackageLayout,
         manifest_parser::{parse_move_manifest_string, parse_source_manifest},
         parsed_manifest::{
-            Dependencies, Dependency, FileName, NamedAddress, PackageDigest, PackageName,
-            SourceManifest, SubstOrRename,
+            Dependencies, Dependency, FeatureName, FileName, NamedAddress, PackageDigest,
+            PackageName, SourceManifest, SubstOrRename, DEFAULT_FEATURE,
         },
     },
     BuildConfig,
@@ -50,6 +50,22 @@ pub struct ResolvingNamedAddress {
     value: Rc<RefCell<Option<AccountAddress>>>,
 }
 
+/// The features requested for a package, either by the build configuration (for the root package)
+/// or by the packages depending on it. Requests from multiple dependents are unified.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FeatureRequest {
+    pub features: BTreeSet<FeatureName>,
+    pub default_features: bool,
+}
+
+#[derive(Debug, Default)]
+struct FeatureRequests {
+    requests: BTreeMap<PackageName, FeatureRequest>,
+    /// Set if a request grew for a package that has already been resolved, in which case the
+    /// graph needs to be resolved again.
+    stale: bool,
+}
+
 /// A `ResolutionGraph` comes in two flavors:
 /// 1. a `ResolutionGraph` during resolution (some named addresses may yet be instantiated)
 /// 2. a `ResolvedGraph` which is a graph after resolution in which all named addresses have been
@@ -87,6 +103,8 @@ pub struct ResolutionPackage<T> {
     pub renaming: Renaming,
     /// The mapping of addresses for this package (and that are in scope for it)
     pub resolution_table: ResolutionTable<T>,
+    /// The features (including enabled optional dependencies) of this package enabled in this build
+    pub enabled_features: BTreeSet<FeatureName>,
     /// The digest of the contents of all source files and manifest under the package root
     pub source_digest: PackageDigest,
 }
@@ -103,23 +121,49 @@ impl ResolvingGraph {
                 build_options.architecture = info.architecture;
             }
         }
-        let mut resolution_graph = Self {
-            root_package_path: root_package_path.clone(),
-            build_options,
-            root_package: root_package.clone(),
-            graph: DiGraphMap::new(),
-            package_table: BTreeMap::new(),
-        };
-
-        resolution_graph
-            .build_resolution_graph(root_package.clone(), root_package_path, true, writer)
-            .with_context(|| {
-                format!(
-                    "Unable to resolve packages for package '{}'",
-                    root_package.package.name
+        let skip_fetch_latest_git_deps = build_options.skip_fetch_latest_git_deps;
+        let mut feature_requests = FeatureRequests::default();
+        feature_requests.requests.insert(
+            root_package.package.name,
+            FeatureRequest::for_root(&build_options),
+        );
+
+        // Features requested by a package can enable additional features (and optional
+        // dependencies) in packages which have already been resolved, so resolve until the
+        // feature requests reach a fixpoint.
+        loop {
+            let mut resolution_graph = Self {
+                root_package_path: root_package_path.clone(),
+                build_options: build_options.clone(),
+                root_package: root_package.clone(),
+                graph: DiGraphMap::new(),
+                package_table: BTreeMap::new(),
+            };
+
+            resolution_graph
+                .build_resolution_graph(
+                    root_package.clone(),
+                    root_package_path.clone(),
+                    true,
+                    &mut feature_requests,
+                    writer,
                 )
-            })?;
-        Ok(resolution_graph)
+                .with_context(|| {
+                    format!(
+                        "Unable to resolve packages for package '{}'",
+                        root_package.package.name
+                    )
+                })?;
+
+            if !feature_requests.stale {
+                resolution_graph.build_options.skip_fetch_latest_git_deps =
+                    skip_fetch_latest_git_deps;
+                return Ok(resolution_graph);
+            }
+            feature_requests.stale = false;
+            // Dependencies have already been fetched in the first round
+            build_options.skip_fetch_latest_git_deps = true;
+        }
     }
 
     pub fn resolve(self) -> Result<ResolvedGraph> {
@@ -142,6 +186,7 @@ impl ResolvingGraph {
                     package_path,
                     renaming,
                     resolution_table,
+                    enabled_features,
                     source_digest,
                 } = package;
 
@@ -166,6 +211,7 @@ impl ResolvingGraph {
                     package_path,
                     renaming,
                     resolution_table: resolved_table,
+                    enabled_features,
                     source_digest,
                 };
                 (name, resolved_pkg)
@@ -197,6 +243,7 @@ impl ResolvingGraph {
         package: SourceManifest,
         package_path: PathBuf,
         is_root_package: bool,
+        feature_requests: &mut FeatureRequests,
         writer: &mut W,
     ) -> Result<()> {
         let package_name = package.package.name;
@@ -228,6 +275,13 @@ impl ResolvingGraph {
             })
             .collect();
 
+        let enabled_features = feature_requests
+            .requests
+            .get(&package_name)
+            .cloned()
+            .unwrap_or_else(FeatureRequest::default_only)
+            .enabled_features(&package)?;
+
         // include dev dependencies if in dev mode
         let additional_deps = if self.build_options.dev_mode {
             package.dev_dependencies.clone()
@@ -240,7 +294,14 @@ impl ResolvingGraph {
             .clone()
             .into_iter()
             .chain(additional_deps.into_iter())
+            .filter(|(dep_name, dep)| !dep.optional || enabled_features.contains(dep_name))
         {
+            feature_requests.request(
+                dep_name,
+                FeatureRequest::for_dependency(&dep_name, &dep, &package, &enabled_features),
+                self.package_table.contains_key(&dep_name),
+            );
+
             let dep_node_id = self.get_or_add_node(dep_name).with_context(|| {
                 format!(
                     "Cycle between packages {} and {} found",
@@ -250,7 +311,13 @@ impl ResolvingGraph {
             self.graph.add_edge(package_node_id, dep_node_id, ());
 
             let (dep_renaming, dep_resolution_table) = self
-                .process_dependency(dep_name, dep, package_path.clone(), writer)
+                .process_dependency(
+                    dep_name,
+                    dep,
+                    package_path.clone(),
+                    feature_requests,
+                    writer,
+                )
                 .with_context(|| {
                     format!(
                         "While resolving dependency '{}' in package '{}'",
@@ -291,6 +358,7 @@ impl ResolvingGraph {
             package_path,
             renaming,
             resolution_table,
+            enabled_features,
             source_digest,
         };
 
@@ -391,6 +459,7 @@ impl ResolvingGraph {
         dep_name_in_pkg: PackageName,
         dep: Dependency,
         root_path: PathBuf,
+        feature_requests: &mut FeatureRequests,
         writer: &mut W,
     ) -> Result<(Renaming, ResolvingTable)> {
         Self::download_and_update_if_remote(
@@ -402,10 +471,14 @@ impl ResolvingGraph {
         let (dep_package, dep_package_dir) =
             Self::parse_package_manifest(&dep, &dep_name_in_pkg, root_path)
                 .with_context(|| format!("While processing dependency '{}'", dep_name_in_pkg))?;
-        self.build_resolution_graph(dep_package.clone(), dep_package_dir, false, writer)
-            .with_context(|| {
-                format!("Unable to resolve package dependency '{}'", dep_name_in_pkg)
-            })?;
+        self.build_resolution_graph(
+            dep_package.clone(),
+            dep_package_dir,
+            false,
+            feature_requests,
+            writer,
+        )
+        .with_context(|| format!("Unable to resolve package dependency '{}'", dep_name_in_pkg))?;
 
         if dep_name_in_pkg != dep_package.package.name {
             bail!("Name of dependency declared in package '{}' does not match dependency's package name '{}'",
@@ -782,6 +855,122 @@ impl ResolvingPackage {
     }
 }
 
+impl FeatureRequest {
+    /// The features requested for the root package by the build configuration
+    pub fn for_root(build_options: &BuildConfig) -> Self {
+        Self {
+            features: build_options
+                .features
+                .iter()
+                .map(|f| FeatureName::from(f.as_str()))
+                .collect(),
+            default_features: !build_options.no_default_features,
+        }
+    }
+
+    /// The features requested for `dep_name` by `package`, given the features enabled in
+    /// `package`
+    pub fn for_dependency(
+        dep_name: &PackageName,
+        dep: &Dependency,
+        package: &SourceManifest,
+        enabled_features: &BTreeSet<FeatureName>,
+    ) -> Self {
+        let mut features = dep.features.clone();
+        for entry in enabled_features
+            .iter()
+            .filter_map(|feature| package.features.get(feature))
+            .flatten()
+        {
+            if let Some((name, feature)) = entry.as_str().split_once('/') {
+                if name == dep_name.as_str() {
+                    features.insert(FeatureName::from(feature));
+                }
+            }
+        }
+        Self {
+            features,
+            default_features: dep.default_features,
+        }
+    }
+
+    pub fn default_only() -> Self {
+        Self {
+            features: BTreeSet::new(),
+            default_features: true,
+        }
+    }
+
+    /// Computes the transitive closure of the requested features in `package`. Enabled optional
+    /// dependencies are included in the result under the name of the dependency.
+    pub fn enabled_features(&self, package: &SourceManifest) -> Result<BTreeSet<FeatureName>> {
+        let default = FeatureName::from(DEFAULT_FEATURE);
+        let is_optional_dep = |name: &FeatureName| {
+            package
+                .dependencies
+                .get(name)
+                .or_else(|| package.dev_dependencies.get(name))
+                .map(|dep| dep.optional)
+                .unwrap_or(false)
+        };
+
+        let mut worklist = self.features.iter().copied().collect::<Vec<_>>();
+        if self.default_features && package.features.contains_key(&default) {
+            worklist.push(default);
+        }
+        let mut enabled = BTreeSet::new();
+        while let Some(feature) = worklist.pop() {
+            if !enabled.insert(feature) {
+                continue;
+            }
+            match package.features.get(&feature) {
+                Some(entries) => {
+                    for entry in entries {
+                        match entry.as_str().split_once('/') {
+                            Some((dep_name, _)) => {
+                                let dep_name = FeatureName::from(dep_name);
+                                if is_optional_dep(&dep_name) {
+                                    worklist.push(dep_name)
+                                }
+                            }
+                            None => worklist.push(*entry),
+                        }
+                    }
+                }
+                None if is_optional_dep(&feature) => (),
+                None => bail!(
+                    "Package '{}' does not have a feature named '{}'",
+                    package.package.name,
+                    feature
+                ),
+            }
+        }
+        Ok(enabled)
+    }
+
+    fn merge(&mut self, other: FeatureRequest) -> bool {
+        let old_len = self.features.len();
+        self.features.extend(other.features);
+        let changed =
+            self.features.len() != old_len || other.default_features && !self.default_features;
+        self.default_features |= other.default_features;
+        changed
+    }
+}
+
+impl FeatureRequests {
+    fn request(&mut self, package_name: PackageName, request: FeatureRequest, resolved: bool) {
+        let changed = match self.requests.get_mut(&package_name) {
+            Some(current) => current.merge(request),
+            None => {
+                self.requests.insert(package_name, request);
+                true
+            }
+        };
+        self.stale |= changed && resolved;
+    }
+}
+
 impl ResolvingNamedAddress {
     pub fn new(address_opt: Option<AccountAddress>) -> Self {
         Self {
@@ -836,6 +1025,14 @@ impl ResolvedGraph {
         Ok(())
     }
 
+    /// Returns the features enabled in each package of the graph
+    pub fn package_features(&self) -> BTreeMap<PackageName, BTreeSet<FeatureName>> {
+        self.package_table
+            .iter()
+            .map(|(name, pkg)| (*name, pkg.enabled_features.clone()))
+            .collect()
+    }
+
     pub fn extract_named_address_mapping(
         &self,
     ) -> impl Iterator<Item = (Symbol, AccountAddress)> + '_ {
@@ -915,15 +1112,19 @@ impl ResolvedPackage {
     }
 
     pub fn immediate_dependencies(&self, resolved_graph: &ResolvedGraph) -> BTreeSet<PackageName> {
-        if resolved_graph.build_options.dev_mode {
-            self.source_package
-                .dependencies
-                .keys()
-                .chain(self.source_package.dev_dependencies.keys())
-                .copied()
-                .collect()
+        let empty_deps;
+        let dev_dependencies = if resolved_graph.build_options.dev_mode {
+            &self.source_package.dev_dependencies
         } else {
-            self.source_package.dependencies.keys().copied().collect()
-        }
+            empty_deps = Dependencies::new();
+            &empty_deps
+        };
+        self.source_package
+            .dependencies
+            .iter()
+            .chain(dev_dependencies.iter())
+            .filter(|(name, dep)| !dep.optional || self.enabled_features.contains(name))
+            .map(|(name, _)| *name)
+            .collect()
     }
 }
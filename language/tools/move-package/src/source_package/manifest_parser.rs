@@ -23,6 +23,7 @@ const ADDRESSES_NAME: &str = "addresses";
 const DEV_ADDRESSES_NAME: &str = "dev-addresses";
 const DEPENDENCY_NAME: &str = "dependencies";
 const DEV_DEPENDENCY_NAME: &str = "dev-dependencies";
+const FEATURES_NAME: &str = "features";
 
 const KNOWN_NAMES: &[&str] = &[
     PACKAGE_NAME,
@@ -31,6 +32,7 @@ const KNOWN_NAMES: &[&str] = &[
     DEV_ADDRESSES_NAME,
     DEPENDENCY_NAME,
     DEV_DEPENDENCY_NAME,
+    FEATURES_NAME,
 ];
 
 const REQUIRED_FIELDS: &[&str] = &[PACKAGE_NAME];
@@ -87,6 +89,12 @@ pub fn parse_source_manifest(tval: TV) -> Result<PM::SourceManifest> {
                 .transpose()
                 .context("Error parsing '[dev-dependencies]' section of manifest")?
                 .unwrap_or_default();
+            let features = table
+                .remove(FEATURES_NAME)
+                .map(|tval| parse_features(tval, &dependencies, &dev_dependencies))
+                .transpose()
+                .context("Error parsing '[features]' section of manifest")?
+                .unwrap_or_default();
             Ok(PM::SourceManifest {
                 package,
                 addresses,
@@ -94,6 +102,7 @@ pub fn parse_source_manifest(tval: TV) -> Result<PM::SourceManifest> {
                 build,
                 dependencies,
                 dev_dependencies,
+                features,
             })
         }
         x => {
@@ -111,7 +120,7 @@ pub fn parse_package_info(tval: TV) -> Result<PM::PackageInfo> {
         TV::Table(mut table) => {
             check_for_required_field_names(&table, &["name", "version"])?;
             let hook_names = package_hooks::custom_package_info_fields();
-            let known_names = ["name", "version", "authors", "license"]
+            let known_names = ["name", "version", "authors", "license", "upgrade-policy"]
                 .into_iter()
                 .chain(hook_names.iter().map(|s| s.as_str()))
                 .collect::<Vec<_>>();
@@ -128,6 +137,16 @@ pub fn parse_package_info(tval: TV) -> Result<PM::PackageInfo> {
             let name = PM::PackageName::from(name);
             let version = parse_version(version)?;
             let license = table.remove("license").map(|x| Symbol::from(x.to_string()));
+            let upgrade_policy = match table.remove("upgrade-policy") {
+                None => None,
+                Some(policy) => Some(
+                    policy
+                        .as_str()
+                        .ok_or_else(|| format_err!("Upgrade policy must be a string"))?
+                        .parse()
+                        .map_err(|msg: String| format_err!(msg))?,
+                ),
+            };
             let authors = match table.remove("authors") {
                 None => Vec::new(),
                 Some(arr) => {
@@ -165,6 +184,7 @@ pub fn parse_package_info(tval: TV) -> Result<PM::PackageInfo> {
                 version,
                 authors,
                 license,
+                upgrade_policy,
                 custom_properties,
             })
         }
@@ -195,6 +215,77 @@ pub fn parse_dependencies(tval: TV) -> Result<PM::Dependencies> {
     }
 }
 
+pub fn parse_features(
+    tval: TV,
+    dependencies: &PM::Dependencies,
+    dev_dependencies: &PM::Dependencies,
+) -> Result<PM::Features> {
+    let table = match tval {
+        TV::Table(table) => table,
+        x => bail!(
+            "Malformed section in manifest {}. Expected a table, but encountered a {}",
+            x,
+            x.type_str()
+        ),
+    };
+    let mut features = BTreeMap::new();
+    for (feature_name, entries) in table.iter() {
+        let entries = entries
+            .as_array()
+            .ok_or_else(|| format_err!("Feature '{}' must be a list of strings", feature_name))?
+            .iter()
+            .map(|entry| {
+                entry.as_str().map(Symbol::from).ok_or_else(|| {
+                    format_err!(
+                        "Invalid entry {} of type {} found in feature '{}'. Expected a string.",
+                        entry,
+                        entry.type_str(),
+                        feature_name
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        features.insert(PM::FeatureName::from(feature_name.as_str()), entries);
+    }
+
+    // Every entry must either name a feature of this package, an optional dependency, or a
+    // feature of a dependency
+    let find_dependency = |name: &str| {
+        dependencies
+            .get(&Symbol::from(name))
+            .or_else(|| dev_dependencies.get(&Symbol::from(name)))
+    };
+    for (feature_name, entries) in &features {
+        for entry in entries {
+            match entry.as_str().split_once('/') {
+                Some((dep_name, _)) => {
+                    if find_dependency(dep_name).is_none() {
+                        bail!(
+                            "Feature '{}' enables a feature of '{}', which is not a dependency",
+                            feature_name,
+                            dep_name
+                        )
+                    }
+                }
+                None => {
+                    let is_optional_dep = find_dependency(entry.as_str())
+                        .map(|dep| dep.optional)
+                        .unwrap_or(false);
+                    if !features.contains_key(entry) && !is_optional_dep {
+                        bail!(
+                            "Feature '{}' enables '{}', which is neither a feature nor an optional \
+                             dependency",
+                            feature_name,
+                            entry
+                        )
+                    }
+                }
+            }
+        }
+    }
+    Ok(features)
+}
+
 pub fn parse_build_info(tval: TV) -> Result<PM::BuildInfo> {
     match tval {
         TV::Table(mut table) => {
@@ -318,6 +409,9 @@ fn parse_dependency(dep_name: &str, tval: TV) -> Result<PM::Dependency> {
                 "rev",
                 "subdir",
                 "address",
+                "optional",
+                "features",
+                "default-features",
             ];
             let custom_key_opt = &package_hooks::custom_dependency_key();
             if let Some(key) = custom_key_opt {
@@ -330,6 +424,21 @@ fn parse_dependency(dep_name: &str, tval: TV) -> Result<PM::Dependency> {
                 .transpose()?;
             let version = table.remove("version").map(parse_version).transpose()?;
             let digest = table.remove("digest").map(parse_digest).transpose()?;
+            let optional = table
+                .remove("optional")
+                .map(|tval| parse_bool(tval, "optional"))
+                .transpose()?
+                .unwrap_or(false);
+            let features = table
+                .remove("features")
+                .map(parse_dependency_features)
+                .transpose()?
+                .unwrap_or_default();
+            let default_features = table
+                .remove("default-features")
+                .map(|tval| parse_bool(tval, "default-features"))
+                .transpose()?
+                .unwrap_or(true);
             let mut git_info = None;
             let mut node_info = None;
             match (
@@ -353,6 +462,9 @@ fn parse_dependency(dep_name: &str, tval: TV) -> Result<PM::Dependency> {
                         local: local_path,
                         git_info,
                         node_info,
+                        optional,
+                        features,
+                        default_features,
                     })
                 }
                 (None, Some(git), None) => {
@@ -394,6 +506,9 @@ fn parse_dependency(dep_name: &str, tval: TV) -> Result<PM::Dependency> {
                         local: local_path.join(subdir),
                         git_info,
                         node_info,
+                        optional,
+                        features,
+                        default_features,
                     })
                 }
                 (None, None, Some(custom_key)) => {
@@ -428,6 +543,9 @@ fn parse_dependency(dep_name: &str, tval: TV) -> Result<PM::Dependency> {
                         local: local_path,
                         git_info,
                         node_info,
+                        optional,
+                        features,
+                        default_features,
                     })
                 }
                 _ => {
@@ -516,6 +634,27 @@ fn parse_architecture(tval: TV) -> Result<Architecture> {
     Architecture::try_parse_from_str(tval.as_str().unwrap())
 }
 
+fn parse_bool(tval: TV, field_name: &str) -> Result<bool> {
+    tval.as_bool()
+        .ok_or_else(|| format_err!("'{}' must be a boolean", field_name))
+}
+
+fn parse_dependency_features(tval: TV) -> Result<BTreeSet<PM::FeatureName>> {
+    tval.as_array()
+        .ok_or_else(|| format_err!("Dependency features must be a list of strings"))?
+        .iter()
+        .map(|feature| {
+            feature.as_str().map(PM::FeatureName::from).ok_or_else(|| {
+                format_err!(
+                    "Invalid feature {} of type {} found. Expected a string.",
+                    feature,
+                    feature.type_str()
+                )
+            })
+        })
+        .collect()
+}
+
 fn parse_digest(tval: TV) -> Result<PM::PackageDigest> {
     let digest_str = tval
         .as_str()
@@ -3,20 +3,29 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::Architecture;
-use move_core_types::account_address::AccountAddress;
+use move_core_types::{account_address::AccountAddress, metadata::UpgradePolicy};
 use move_symbol_pool::symbol::Symbol;
-use std::{collections::BTreeMap, path::PathBuf};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
 
 pub type NamedAddress = Symbol;
 pub type PackageName = Symbol;
 pub type FileName = Symbol;
 pub type PackageDigest = Symbol;
+pub type FeatureName = Symbol;
 
 pub type AddressDeclarations = BTreeMap<NamedAddress, Option<AccountAddress>>;
 pub type DevAddressDeclarations = BTreeMap<NamedAddress, AccountAddress>;
 pub type Version = (u64, u64, u64);
 pub type Dependencies = BTreeMap<PackageName, Dependency>;
 pub type Substitution = BTreeMap<NamedAddress, SubstOrRename>;
+/// Maps each feature to the features, optional dependencies and dependency features
+/// ('<dependency>/<feature>') it enables.
+pub type Features = BTreeMap<FeatureName, Vec<Symbol>>;
+
+pub const DEFAULT_FEATURE: &str = "default";
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct SourceManifest {
@@ -26,6 +35,7 @@ pub struct SourceManifest {
     pub build: Option<BuildInfo>,
     pub dependencies: Dependencies,
     pub dev_dependencies: Dependencies,
+    pub features: Features,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -34,6 +44,7 @@ pub struct PackageInfo {
     pub version: Version,
     pub authors: Vec<Symbol>,
     pub license: Option<Symbol>,
+    pub upgrade_policy: Option<UpgradePolicy>,
     pub custom_properties: BTreeMap<Symbol, String>,
 }
 
@@ -45,6 +56,12 @@ pub struct Dependency {
     pub digest: Option<PackageDigest>,
     pub git_info: Option<GitInfo>,
     pub node_info: Option<CustomDepInfo>,
+    /// Whether this dependency is only included when enabled through a feature
+    pub optional: bool,
+    /// The features to enable in the dependency
+    pub features: BTreeSet<FeatureName>,
+    /// Whether the 'default' feature of the dependency is enabled
+    pub default_features: bool,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
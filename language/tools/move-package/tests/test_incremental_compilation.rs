@@ -0,0 +1,134 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_compiler::diagnostics::unwrap_or_report_diagnostics;
+use move_package::{compilation::build_plan::BuildPlan, BuildConfig};
+use std::{collections::BTreeSet, path::Path};
+use tempfile::tempdir;
+
+const MANIFEST: &str = r#"
+[package]
+name = "Incremental"
+version = "0.0.0"
+"#;
+
+const A: &str = "module 0x42::A { public fun value(): u64 { 1 } }";
+const B: &str = "module 0x42::B { use 0x42::A; public fun double(): u64 { A::value() * 2 } }";
+const C: &str = "module 0x42::C { public fun value(): u64 { 3 } }";
+
+const TEST_MANIFEST: &str = r#"
+[package]
+name = "Incremental"
+version = "0.0.0"
+
+[dev-addresses]
+std = "0x1"
+
+[dev-dependencies]
+MoveStdlib = { local = "{{STDLIB}}" }
+"#;
+
+const T: &str = "module 0x42::T { #[test] fun nop() {} }";
+
+/// Builds the package at `path`, returning the names of the units passed through the compiler
+fn recompiled_units(path: &Path, install_dir: &Path) -> BTreeSet<String> {
+    recompiled_units_in_mode(path, install_dir, false)
+}
+
+fn recompiled_units_in_mode(path: &Path, install_dir: &Path, test_mode: bool) -> BTreeSet<String> {
+    let resolution_graph = BuildConfig {
+        dev_mode: test_mode,
+        test_mode,
+        install_dir: Some(install_dir.to_path_buf()),
+        ..Default::default()
+    }
+    .resolution_graph_for_package(path, &mut Vec::new())
+    .unwrap();
+    let mut recompiled = BTreeSet::new();
+    BuildPlan::create(resolution_graph)
+        .unwrap()
+        .compile_incrementally_with_driver(&mut Vec::new(), |compiler| {
+            let (files, units_res) = compiler.build()?;
+            let (units, _) = unwrap_or_report_diagnostics(&files, units_res);
+            recompiled.extend(
+                units
+                    .iter()
+                    .map(|unit| unit.clone().into_compiled_unit().name().to_string()),
+            );
+            Ok((files, units))
+        })
+        .unwrap();
+    recompiled
+}
+
+fn names(names: &[&str]) -> BTreeSet<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+#[test]
+fn test_incremental_compilation_recompiles_changed_files() {
+    let package = tempdir().unwrap();
+    let install_dir = tempdir().unwrap();
+    let path = package.path();
+    let sources = path.join("sources");
+    std::fs::create_dir_all(&sources).unwrap();
+    std::fs::write(path.join("Move.toml"), MANIFEST).unwrap();
+    std::fs::write(sources.join("A.move"), A).unwrap();
+    std::fs::write(sources.join("B.move"), B).unwrap();
+    std::fs::write(sources.join("C.move"), C).unwrap();
+
+    // Everything is compiled the first time
+    assert_eq!(
+        recompiled_units(path, install_dir.path()),
+        names(&["A", "B", "C"])
+    );
+
+    // Nothing changed
+    assert_eq!(recompiled_units(path, install_dir.path()), names(&[]));
+
+    // Only the body of A changes: its dependent B keeps using the cached bytecode
+    std::fs::write(sources.join("A.move"), A.replace("{ 1 }", "{ 2 }")).unwrap();
+    assert_eq!(recompiled_units(path, install_dir.path()), names(&["A"]));
+
+    // The interface of A changes: B is recompiled against it, but not the unrelated C
+    std::fs::write(
+        sources.join("A.move"),
+        A.replace("{ 1 }", "{ 2 } public fun other(): u64 { 4 }"),
+    )
+    .unwrap();
+    assert_eq!(
+        recompiled_units(path, install_dir.path()),
+        names(&["A", "B"])
+    );
+
+    // C changes, without any dependents
+    std::fs::write(sources.join("C.move"), C.replace("{ 3 }", "{ 5 }")).unwrap();
+    assert_eq!(recompiled_units(path, install_dir.path()), names(&["C"]));
+}
+
+#[test]
+fn test_incremental_compilation_recompiles_everything_in_test_mode() {
+    let package = tempdir().unwrap();
+    let install_dir = tempdir().unwrap();
+    let path = package.path();
+    let sources = path.join("sources");
+    std::fs::create_dir_all(&sources).unwrap();
+    let stdlib = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../../move-stdlib")
+        .canonicalize()
+        .unwrap();
+    std::fs::write(
+        path.join("Move.toml"),
+        TEST_MANIFEST.replace("{{STDLIB}}", &stdlib.to_string_lossy()),
+    )
+    .unwrap();
+    std::fs::write(sources.join("T.move"), T).unwrap();
+
+    // The tests need the source of `std::unit_test`, so the cached units are not reused
+    let recompiled = recompiled_units_in_mode(path, install_dir.path(), true);
+    assert!(recompiled.contains("T") && recompiled.contains("unit_test"));
+    assert_eq!(
+        recompiled_units_in_mode(path, install_dir.path(), true),
+        recompiled
+    );
+}
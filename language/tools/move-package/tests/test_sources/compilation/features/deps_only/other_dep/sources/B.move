@@ -0,0 +1,7 @@
+module B::B {
+    #[cfg(feature = fast)]
+    public fun fast(): u64 { 1 }
+
+    #[cfg(not(feature = fast))]
+    public fun slow(): u64 { 0 }
+}
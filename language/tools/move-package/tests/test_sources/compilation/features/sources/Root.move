@@ -0,0 +1,13 @@
+module A::Root {
+    use B::B;
+
+    #[cfg(feature = fast)]
+    public fun run(): u64 {
+        B::fast()
+    }
+
+    #[cfg(not(feature = fast))]
+    public fun run(): u64 {
+        B::slow()
+    }
+}
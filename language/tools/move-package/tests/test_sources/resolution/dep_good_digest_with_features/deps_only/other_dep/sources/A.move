@@ -0,0 +1 @@
+module B::A { }
@@ -0,0 +1,45 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_package::{compilation::unused_declarations::UnusedDeclarationKind, BuildConfig};
+use std::path::Path;
+use tempfile::tempdir;
+
+/// The unused dependencies and named addresses reported when building the test package
+fn unused_declarations(test_mode: bool) -> Vec<(String, Option<usize>, UnusedDeclarationKind)> {
+    let (_, unused) = BuildConfig {
+        dev_mode: test_mode,
+        test_mode,
+        install_dir: Some(tempdir().unwrap().path().to_path_buf()),
+        ..Default::default()
+    }
+    .compile_package_and_find_unused(
+        Path::new("tests/unused_declarations_test_sources/Package"),
+        &mut Vec::new(),
+    )
+    .unwrap();
+    unused
+        .iter()
+        .map(|declaration| {
+            (
+                declaration.name.to_string(),
+                declaration.line,
+                declaration.kind,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn dev_dependencies_are_only_reported_in_test_mode() {
+    assert!(unused_declarations(false).is_empty());
+    // `DevUnused` only provides the standard library, on which test code depends directly
+    assert_eq!(
+        unused_declarations(true),
+        vec![(
+            "DevUnused".to_string(),
+            Some(14),
+            UnusedDeclarationKind::Dependency
+        )]
+    );
+}
@@ -0,0 +1 @@
+module DN::DN {}
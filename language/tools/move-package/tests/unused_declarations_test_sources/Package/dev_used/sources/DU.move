@@ -0,0 +1,3 @@
+module DU::DU {
+    public fun f() {}
+}
@@ -0,0 +1,6 @@
+#[test_only]
+module A::T {
+    fun f() {
+        DU::DU::f();
+    }
+}
@@ -0,0 +1,71 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A stable JSON representation of annotated values, for tools consuming the output of the
+//! viewer. Unlike the `Serialize` implementations of the annotated values, the shape of the
+//! output only depends on the types of the values:
+//! - `u8`, `u16` and `u32` integers are numbers; `u64`, `u128` and `u256` integers are strings,
+//!   since they do not fit in the numbers of most JSON parsers
+//! - addresses are canonical hex strings, e.g. `"0x0000000000000000000000000000000a"`
+//! - `vector<u8>` values are hex strings, e.g. `"0x0102"`
+//! - other vectors are arrays
+//! - structs are objects with their `type` and their `fields`, in declaration order
+
+use crate::{AnnotatedMoveStruct, AnnotatedMoveValue};
+use serde::{
+    ser::{SerializeMap, SerializeSeq, SerializeStruct},
+    Serialize, Serializer,
+};
+
+/// The JSON representation of an `AnnotatedMoveValue`
+pub struct JsonValue<'a>(pub &'a AnnotatedMoveValue);
+
+/// The JSON representation of an `AnnotatedMoveStruct`
+pub struct JsonStruct<'a>(pub &'a AnnotatedMoveStruct);
+
+/// The fields of a struct, as an object preserving their declaration order
+struct JsonFields<'a>(&'a AnnotatedMoveStruct);
+
+impl Serialize for JsonValue<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use AnnotatedMoveValue::*;
+        match self.0 {
+            U8(n) => serializer.serialize_u8(*n),
+            U16(n) => serializer.serialize_u16(*n),
+            U32(n) => serializer.serialize_u32(*n),
+            U64(n) => serializer.serialize_str(&n.to_string()),
+            U128(n) => serializer.serialize_str(&n.to_string()),
+            U256(n) => serializer.serialize_str(&n.to_string()),
+            Bool(b) => serializer.serialize_bool(*b),
+            Address(a) => serializer.serialize_str(&format!("0x{}", a.to_canonical_string())),
+            Bytes(v) => serializer.serialize_str(&format!("0x{}", hex::encode(v))),
+            Vector(_, vals) => {
+                let mut vec = serializer.serialize_seq(Some(vals.len()))?;
+                for v in vals {
+                    vec.serialize_element(&JsonValue(v))?;
+                }
+                vec.end()
+            }
+            Struct(s) => JsonStruct(s).serialize(serializer),
+        }
+    }
+}
+
+impl Serialize for JsonStruct<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("Struct", 2)?;
+        s.serialize_field("type", &self.0.type_.to_string())?;
+        s.serialize_field("fields", &JsonFields(self.0))?;
+        s.end()
+    }
+}
+
+impl Serialize for JsonFields<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_map(Some(self.0.value.len()))?;
+        for (f, v) in &self.0.value {
+            s.serialize_entry(f.as_str(), &JsonValue(v))?
+        }
+        s.end()
+    }
+}
@@ -30,6 +30,7 @@ use std::{
 };
 
 mod fat_type;
+pub mod json;
 mod module_cache;
 mod resolver;
 
@@ -60,7 +61,19 @@ pub enum AnnotatedMoveValue {
     U256(u256::U256),
 }
 
+impl AnnotatedMoveStruct {
+    /// The stable JSON representation of the struct, see `json`
+    pub fn json(&self) -> json::JsonStruct<'_> {
+        json::JsonStruct(self)
+    }
+}
+
 impl AnnotatedMoveValue {
+    /// The stable JSON representation of the value, see `json`
+    pub fn json(&self) -> json::JsonValue<'_> {
+        json::JsonValue(self)
+    }
+
     pub fn get_type(&self) -> TypeTag {
         use AnnotatedMoveValue::*;
         match self {
@@ -18,13 +18,16 @@ use move_compiler::{
     Compiler, Flags, PASS_CFGIR,
 };
 use move_core_types::language_storage::ModuleId;
-use move_vm_runtime::native_functions::NativeFunctionTable;
+use move_vm_runtime::{
+    native_functions::NativeFunctionTable, verified_module_cache::VerifiedModuleCache,
+};
 use move_vm_test_utils::gas_schedule::CostTable;
 use std::{
     collections::BTreeMap,
     io::{Result, Write},
     marker::Send,
-    sync::Mutex,
+    path::PathBuf,
+    sync::{Arc, Mutex},
 };
 
 /// The default value bounding the amount of gas consumed in a test.
@@ -115,6 +118,12 @@ pub struct UnitTestingConfig {
     #[clap(short = 'v', long = "verbose")]
     pub report_writeset: bool,
 
+    /// Persist the modules which passed bytecode verification to this file, and skip verifying
+    /// them again in later runs. The file is trusted: modules recorded in a tampered file are
+    /// not verified.
+    #[clap(name = "verified_module_cache", long = "verified_module_cache")]
+    pub verified_module_cache: Option<PathBuf>,
+
     /// Use the EVM-based execution backend.
     /// Does not work with --stackless.
     #[cfg(feature = "evm-backend")]
@@ -148,6 +157,7 @@ impl UnitTestingConfig {
             list: false,
             named_address_values: vec![],
             report_writeset: false,
+            verified_module_cache: None,
 
             #[cfg(feature = "evm-backend")]
             evm: false,
@@ -240,6 +250,11 @@ impl UnitTestingConfig {
         }
 
         writeln!(shared_writer.lock().unwrap(), "Running Move unit tests")?;
+        let verified_module_cache = self
+            .verified_module_cache
+            .as_ref()
+            .map(|path| VerifiedModuleCache::load(path).map(Arc::new))
+            .transpose()?;
         let mut test_runner = TestRunner::new(
             self.gas_limit.unwrap_or(DEFAULT_EXECUTION_BOUND),
             self.num_threads,
@@ -252,6 +267,7 @@ impl UnitTestingConfig {
             cost_table,
             verify_and_create_named_address_mapping(self.named_address_values.clone()).unwrap(),
             self.report_writeset,
+            verified_module_cache.clone(),
             #[cfg(feature = "evm-backend")]
             self.evm,
         )
@@ -271,6 +287,9 @@ impl UnitTestingConfig {
         }
 
         let ok = test_results.summarize(&shared_writer)?;
+        if let Some(verified_module_cache) = verified_module_cache {
+            verified_module_cache.save()?;
+        }
 
         let writer = shared_writer.into_inner().unwrap();
         Ok((writer, ok))
@@ -3,22 +3,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::format_module_id;
-use codespan_reporting::files::{Files, SimpleFiles};
 use colored::{control, Colorize};
-use move_binary_format::{
-    access::ModuleAccess,
-    errors::{ExecutionState, Location, VMError, VMResult},
-};
-use move_command_line_common::files::FileHash;
+use move_binary_format::errors::{Location, VMError, VMResult};
+use move_bytecode_source_map::stack_trace::StackTraceSymbolizer;
 use move_compiler::{
     diagnostics::{self, Diagnostic, Diagnostics},
     unit_test::{ModuleTestPlan, TestName, TestPlan},
 };
 use move_core_types::{effects::ChangeSet, language_storage::ModuleId, vm_status::StatusType};
-use move_ir_types::location::Loc;
-use move_symbol_pool::Symbol;
 use std::{
-    collections::{BTreeMap, BTreeSet, HashMap},
+    collections::{BTreeMap, BTreeSet},
     io::{Result, Write},
     sync::Mutex,
     time::Duration,
@@ -251,82 +245,20 @@ impl TestFailure {
         }
     }
 
-    fn get_line_number(
-        loc: &Loc,
-        files: &SimpleFiles<Symbol, &str>,
-        file_mapping: &HashMap<FileHash, usize>,
-    ) -> String {
-        Self::get_line_number_internal(loc, files, file_mapping)
-            .unwrap_or_else(|_| "no_source_line".to_string())
-    }
-
-    fn get_line_number_internal(
-        loc: &Loc,
-        files: &SimpleFiles<Symbol, &str>,
-        file_mapping: &HashMap<FileHash, usize>,
-    ) -> std::result::Result<String, codespan_reporting::files::Error> {
-        let id = file_mapping
-            .get(&loc.file_hash())
-            .ok_or(codespan_reporting::files::Error::FileMissing)?;
-        let start_line_index = files.line_index(*id, loc.start() as usize)?;
-        let start_line_number = files.line_number(*id, start_line_index)?;
-        let end_line_index = files.line_index(*id, loc.end() as usize)?;
-        let end_line_number = files.line_number(*id, end_line_index)?;
-        if start_line_number == end_line_number {
-            Ok(start_line_number.to_string())
-        } else {
-            Ok(format!("{}-{}", start_line_number, end_line_number))
+    fn report_stack_trace(test_plan: &TestPlan, vm_error: &VMError) -> String {
+        let mut symbolizer = StackTraceSymbolizer::new();
+        for named_module in test_plan.module_info.values() {
+            symbolizer.add_module(&named_module.module, &named_module.source_map);
         }
-    }
-
-    fn report_exec_state(test_plan: &TestPlan, exec_state: &ExecutionState) -> String {
-        let stack_trace = exec_state.stack_trace();
-        let mut buf = String::new();
-        if !stack_trace.is_empty() {
-            buf.push_str("stack trace\n");
-            let mut files = SimpleFiles::new();
-            let mut file_mapping = HashMap::new();
-            for (fhash, (fname, source)) in &test_plan.files {
-                let id = files.add(*fname, source.as_str());
-                file_mapping.insert(*fhash, id);
-            }
-
-            for frame in stack_trace {
-                let module_id = match &frame.0 {
-                    Some(v) => v,
-                    None => return "\tmalformed stack trace (no module ID)".to_string(),
-                };
-                let named_module = match test_plan.module_info.get(module_id) {
-                    Some(v) => v,
-                    None => return "\tmalformed stack trace (no module)".to_string(),
-                };
-                let function_source_map =
-                    match named_module.source_map.get_function_source_map(frame.1) {
-                        Ok(v) => v,
-                        Err(_) => return "\tmalformed stack trace (no source map)".to_string(),
-                    };
-                // unwrap here is a mirror of the same unwrap in report_error_with_location
-                let loc = function_source_map.get_code_location(frame.2).unwrap();
-                let fn_handle_idx = named_module.module.function_def_at(frame.1).function;
-                let fn_id_idx = named_module.module.function_handle_at(fn_handle_idx).name;
-                let fn_name = named_module.module.identifier_at(fn_id_idx).as_str();
-                let file_name = match test_plan.files.get(&loc.file_hash()) {
-                    Some(v) => format!("{}", v.0),
-                    None => "unknown_source".to_string(),
-                };
-                buf.push_str(
-                    &format!(
-                        "\t{}::{}({}:{})\n",
-                        module_id.name(),
-                        fn_name,
-                        file_name,
-                        Self::get_line_number(&loc, &files, &file_mapping)
-                    )
-                    .to_string(),
-                );
-            }
+        for (fname, source) in test_plan.files.values() {
+            symbolizer.add_file(fname.as_str(), source.as_str());
+        }
+        let stack_trace = symbolizer.symbolize_error(vm_error);
+        if stack_trace.is_empty() {
+            String::new()
+        } else {
+            format!("stack trace\n{}", stack_trace)
         }
-        buf
     }
 
     fn report_error_with_location(
@@ -376,16 +308,15 @@ impl TestFailure {
             _ => base_message,
         };
 
-        match vm_error.exec_state() {
-            None => diags,
-            Some(exec_state) => {
-                let exec_state_str = Self::report_exec_state(test_plan, exec_state);
-                if exec_state_str.is_empty() {
-                    diags
-                } else {
-                    format!("{}\n{}", diags, exec_state_str)
-                }
-            }
+        // only errors raised during execution carry a stack trace
+        if vm_error.exec_state().is_none() {
+            return diags;
+        }
+        let stack_trace_str = Self::report_stack_trace(test_plan, vm_error);
+        if stack_trace_str.is_empty() {
+            diags
+        } else {
+            format!("{}\n{}", diags, stack_trace_str)
         }
     }
 }
@@ -38,13 +38,22 @@ use move_stackless_bytecode_interpreter::{
     shared::bridge::{adapt_move_vm_change_set, adapt_move_vm_result},
     StacklessBytecodeInterpreter,
 };
-use move_vm_runtime::{move_vm::MoveVM, native_functions::NativeFunctionTable};
+use move_vm_runtime::{
+    config::VMConfig, move_vm::MoveVM, native_functions::NativeFunctionTable,
+    verified_module_cache::VerifiedModuleCache,
+};
 use move_vm_test_utils::{
     gas_schedule::{zero_cost_schedule, CostTable, Gas, GasCost, GasStatus},
     InMemoryStorage,
 };
 use rayon::prelude::*;
-use std::{collections::BTreeMap, io::Write, marker::Send, sync::Mutex, time::Instant};
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    marker::Send,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
 
 use move_vm_runtime::native_extensions::NativeContextExtensions;
 #[cfg(feature = "evm-backend")]
@@ -70,6 +79,7 @@ pub struct SharedTestingConfig {
     check_stackless_vm: bool,
     verbose: bool,
     record_writeset: bool,
+    verified_module_cache: Option<Arc<VerifiedModuleCache>>,
 
     #[cfg(feature = "evm-backend")]
     evm: bool,
@@ -153,6 +163,7 @@ impl TestRunner {
         cost_table: Option<CostTable>,
         named_address_values: BTreeMap<String, NumericalAddress>,
         record_writeset: bool,
+        verified_module_cache: Option<Arc<VerifiedModuleCache>>,
         #[cfg(feature = "evm-backend")] evm: bool,
     ) -> Result<Self> {
         let source_files = tests
@@ -186,6 +197,7 @@ impl TestRunner {
                 verbose,
                 named_address_values,
                 record_writeset,
+                verified_module_cache,
                 #[cfg(feature = "evm-backend")]
                 evm,
             },
@@ -283,7 +295,14 @@ impl SharedTestingConfig {
         VMResult<Vec<Vec<u8>>>,
         TestRunInfo,
     ) {
-        let move_vm = MoveVM::new(self.native_function_table.clone()).unwrap();
+        let move_vm = MoveVM::new_with_config(
+            self.native_function_table.clone(),
+            VMConfig {
+                verified_module_cache: self.verified_module_cache.clone(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
         let extensions = extensions::new_extensions();
         let mut session =
             move_vm.new_session_with_extensions(&self.starting_storage_state, extensions);